@@ -0,0 +1,118 @@
+//! Benchmarks for the hot paths of drawing a frame: lines, triangle and polygon filling, clearing, compositing layers,
+//! averaging supersamples, and writing the image out, measured with [Criterion]. Run them with `--features parallel`
+//! too, to see what filling triangles on several threads gains.
+//!
+//! Run them with `cargo bench`, or only the ones with a name containing a filter with `cargo bench -- fill`. Criterion
+//! compares every run with the previous one, and says whether the change is significant. To compare with a fixed
+//! point instead, save a baseline before a change with `cargo bench -- --save-baseline before`, and compare with it
+//! after the change with `cargo bench -- --baseline before`.
+//!
+//! [Criterion]: https://crates.io/crates/criterion
+
+use std::{hint::black_box, io};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use graphics::{blend::BlendMode, prelude::*, tile::TileRenderer};
+
+fn hot_paths(c: &mut Criterion) {
+    let mut img = Ppm::new();
+
+    c.bench_function("draw_line", |b| {
+        b.iter(|| {
+            for i in 0..100 {
+                let t = i as f64 * 5.;
+                img.draw_line((0., t, 0.), (499., 499. - t, 0.), Rgb::WHITE);
+                img.draw_line((t, 0., 0.), (499. - t, 499., 0.), Rgb::WHITE);
+            }
+        })
+    });
+
+    c.bench_function("fill_triangle", |b| {
+        b.iter(|| {
+            img.fill_triangle(
+                (10., 10., 0.),
+                (490., 60., 5.),
+                (200., 480., -5.),
+                Rgb::new(0, 150, 0),
+            );
+        })
+    });
+
+    // a star, concave with many crossings per scanline
+    let star: Vec<(f64, f64, f64)> = (0..10)
+        .map(|i| {
+            let r = if i % 2 == 0 { 240. } else { 100. };
+            let angle = i as f64 * std::f64::consts::PI / 5.;
+            (250. + r * angle.cos(), 250. + r * angle.sin(), 0.)
+        })
+        .collect();
+    c.bench_function("fill_polygon", |b| {
+        b.iter(|| img.fill_polygon(&star, Rgb::new(0, 0, 255)))
+    });
+
+    // a mesh of small triangles over the whole image, like a rendered model
+    let triangles: Vec<_> = (0..50 * 50)
+        .map(|i| {
+            let (x, y) = ((i % 50) as f64 * 10., (i / 50) as f64 * 10.);
+            (
+                [(x, y, 0.), (x + 10., y, 1.), (x, y + 10., 2.)],
+                Rgb::new(i as u8, 100, 200),
+            )
+        })
+        .collect();
+    c.bench_function("fill_triangles", |b| {
+        b.iter(|| img.fill_triangles(&triangles))
+    });
+
+    // the same mesh scaled up to a 4K image, where tiles help the most
+    let mut big = PpmBuilder::new(2160, 3840, 255).build();
+    let mut tiles = TileRenderer::new();
+    for i in 0..192 * 108 {
+        let (x, y) = ((i % 192) as f64 * 20., (i / 192) as f64 * 20.);
+        tiles.fill_triangle(
+            (x, y, 0.),
+            (x + 20., y, 1.),
+            (x, y + 20., 2.),
+            Rgb::new(i as u8, 100, 200),
+        );
+    }
+    c.bench_function("tile_renderer", |b| b.iter(|| tiles.render(&mut big)));
+    black_box(&big);
+
+    c.bench_function("clear", |b| {
+        b.iter(|| img.clear(black_box(Rgb::new(10, 20, 30))))
+    });
+
+    let mut layer = PpmBuilder::new(500, 500, 255)
+        .bg_color(Rgb::new(100, 50, 200))
+        .build();
+    layer.draw_circle(250., 250., 0., 200., Rgb::WHITE);
+    c.bench_function("composite", |b| {
+        b.iter(|| img.composite(&layer, 0, 0, BlendMode::Screen))
+    });
+
+    // writing a supersampled image averages every block of samples into a pixel
+    let mut supersampled = PpmBuilder::new(500, 500, 255).supersample(4).build();
+    supersampled.fill_triangle(
+        (0., 0., 0.),
+        (499., 100., 0.),
+        (200., 499., 0.),
+        Rgb::new(0, 150, 0),
+    );
+    c.bench_function("downscale", |b| {
+        b.iter(|| {
+            supersampled.write_binary_to_buf(&mut io::sink()).unwrap();
+        })
+    });
+
+    c.bench_function("write_binary", |b| {
+        b.iter(|| {
+            img.write_binary_to_buf(&mut io::sink()).unwrap();
+        })
+    });
+
+    black_box(&img);
+}
+
+criterion_group!(benches, hot_paths);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+#![no_main]
+
+use graphics::font::Font;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Font::from_bdf(data);
+    let _ = Font::from_psf(data);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use graphics::{color::Rgb16, prelude::*};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Ppm::<Rgb>::from_reader(data);
+    // maxvals above 255 go through a different path
+    let _ = Ppm::<Rgb16>::from_reader(data);
+});
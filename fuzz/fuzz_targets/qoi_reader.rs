@@ -0,0 +1,8 @@
+#![no_main]
+
+use graphics::prelude::*;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Ppm::<Rgb>::from_qoi_reader(data);
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use graphics::{prelude::*, record::Recording};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(recording) = Recording::<Rgb>::from_reader(data) {
+        // replaying checks the commands too, but huge sizes would only test the allocator
+        if recording.width <= 256 && recording.height <= 256 {
+            let mut img = PpmBuilder::new(recording.height, recording.width, 255).build();
+            recording.replay(&mut img);
+        }
+    }
+});
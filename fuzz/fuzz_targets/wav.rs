@@ -0,0 +1,8 @@
+#![no_main]
+
+use graphics::audio::Wav;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Wav::from_reader(data);
+});
@@ -0,0 +1,416 @@
+//! Read WAV files and turn them into per-frame values for music-synchronized animations.
+//!
+//! A [`Wav`] is decoded into mono samples, then [`Wav::analyze`] splits it into animation frames and computes, for each frame:
+//! - the loudness (RMS amplitude) of the frame, and
+//! - the energy of a few frequency bands, computed with an FFT.
+//!
+//! All values in an [`AudioAnalysis`] are normalized to `[0, 1]` over the whole track, so they can be used directly as knob values
+//! (e.g. to scale a shape by how loud the bass is at that moment).
+//!
+//! Only uncompressed PCM (8, 16, 24 and 32 bit integer) and 32 bit float WAV files are supported. Use a tool like
+//! `ffmpeg` to convert anything else: `ffmpeg -i song.mp3 song.wav`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use graphics::audio::Wav;
+//!
+//! let wav = Wav::open("song.wav").expect("error reading song.wav");
+//! let analysis = wav.analyze(30., 8);
+//!
+//! for frame in 0..analysis.len() {
+//!     let loudness = analysis.knob("amplitude", frame).unwrap();
+//!     let bass = analysis.knob("band0", frame).unwrap();
+//!     // draw something with `loudness` and `bass`...
+//! }
+//! ```
+//!
+//! [`Wav`]: ./struct.Wav.html
+//! [`Wav::analyze`]: ./struct.Wav.html#method.analyze
+//! [`AudioAnalysis`]: ./struct.AudioAnalysis.html
+
+use std::{
+    convert::TryInto,
+    f64::consts::PI,
+    fs::File,
+    io::{prelude::*, BufReader},
+    path::Path,
+};
+
+use crate::error::{self, GraphicsError};
+
+/// Number of samples fed to the FFT for every frame. Must be a power of 2.
+const FFT_SIZE: usize = 1024;
+
+/// Decoded audio, mixed down to a single channel.
+#[derive(Clone, Debug)]
+pub struct Wav {
+    /// Samples per second.
+    pub sample_rate: u32,
+    /// Mono samples in `[-1, 1]`.
+    pub samples: Vec<f64>,
+}
+
+impl Wav {
+    /// Read a WAV file from `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> error::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Read a WAV file from `reader`.
+    pub fn from_reader<R: Read>(mut reader: R) -> error::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(GraphicsError::parse("not a RIFF/WAVE file"));
+        }
+
+        let mut format = None;
+        let mut data = None;
+
+        // walk through the chunks, only "fmt " and "data" are interesting
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let id = &bytes[pos..pos + 4];
+            let size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            // `size` comes from the file, so it may run past the end (or past usize::MAX on 32 bit targets)
+            let end = (pos + 8)
+                .checked_add(size)
+                .map_or(bytes.len(), |end| end.min(bytes.len()));
+            let body = &bytes[pos + 8..end];
+            match id {
+                b"fmt " if body.len() >= 16 => format = Some(Format::parse(body)?),
+                b"data" => data = Some(body),
+                _ => {}
+            }
+            // chunks are padded to an even size
+            pos = match size
+                .checked_add(size % 2)
+                .and_then(|padded| (pos + 8).checked_add(padded))
+            {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        let format = format.ok_or_else(|| GraphicsError::parse("missing fmt chunk"))?;
+        let data = data.ok_or_else(|| GraphicsError::parse("missing data chunk"))?;
+
+        let sample_width = format.bits_per_sample as usize / 8;
+        let frame_width = sample_width * format.channels as usize;
+        let samples = data
+            .chunks_exact(frame_width)
+            .map(|frame| {
+                let sum: f64 = frame
+                    .chunks_exact(sample_width)
+                    .map(|s| format.decode(s))
+                    .sum();
+                sum / format.channels as f64
+            })
+            .collect();
+
+        Ok(Wav {
+            sample_rate: format.sample_rate,
+            samples,
+        })
+    }
+
+    /// Duration of the audio in seconds.
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate as f64
+    }
+
+    /// Split the audio into frames at `fps` and compute amplitude and `bands` frequency bands for every frame.
+    ///
+    /// Bands are spaced logarithmically from 20Hz to half the sample rate, so `band0` is the bass and the last band is the treble.
+    pub fn analyze(&self, fps: f64, bands: usize) -> AudioAnalysis {
+        let frame_count = (self.duration() * fps).ceil() as usize;
+        let samples_per_frame = self.sample_rate as f64 / fps;
+
+        let window: Vec<f64> = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2. * PI * i as f64 / (FFT_SIZE - 1) as f64).cos())
+            .collect();
+        let edges = band_edges(bands, self.sample_rate);
+
+        let mut amplitude = Vec::with_capacity(frame_count);
+        let mut band_levels = Vec::with_capacity(frame_count);
+
+        for frame in 0..frame_count {
+            let start = (frame as f64 * samples_per_frame) as usize;
+            let end = (((frame + 1) as f64 * samples_per_frame) as usize).min(self.samples.len());
+            let chunk = &self.samples[start.min(end)..end];
+
+            let rms = if chunk.is_empty() {
+                0.
+            } else {
+                (chunk.iter().map(|s| s * s).sum::<f64>() / chunk.len() as f64).sqrt()
+            };
+            amplitude.push(rms);
+
+            let mut re: Vec<f64> = (0..FFT_SIZE)
+                .map(|i| self.samples.get(start + i).copied().unwrap_or(0.) * window[i])
+                .collect();
+            let mut im = vec![0.; FFT_SIZE];
+            fft(&mut re, &mut im);
+
+            band_levels.push(
+                edges
+                    .windows(2)
+                    .map(|edge| {
+                        let bins = edge[0]..edge[1].max(edge[0] + 1);
+                        let count = bins.len() as f64;
+                        bins.map(|k| (re[k] * re[k] + im[k] * im[k]).sqrt())
+                            .sum::<f64>()
+                            / count
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        normalize(&mut amplitude);
+        for band in 0..bands {
+            let mut levels: Vec<f64> = band_levels.iter().map(|b| b[band]).collect();
+            normalize(&mut levels);
+            for (frame, level) in band_levels.iter_mut().zip(levels) {
+                frame[band] = level;
+            }
+        }
+
+        AudioAnalysis {
+            fps,
+            amplitude,
+            bands: band_levels,
+        }
+    }
+}
+
+/// Per-frame audio features, all normalized to `[0, 1]`.
+#[derive(Clone, Debug)]
+pub struct AudioAnalysis {
+    /// Frames per second used for the analysis.
+    pub fps: f64,
+    /// Loudness of each frame.
+    pub amplitude: Vec<f64>,
+    /// Frequency band levels of each frame, from low to high frequency.
+    pub bands: Vec<Vec<f64>>,
+}
+
+impl AudioAnalysis {
+    /// Number of frames.
+    pub fn len(&self) -> usize {
+        self.amplitude.len()
+    }
+
+    /// Whether there are no frames at all.
+    pub fn is_empty(&self) -> bool {
+        self.amplitude.is_empty()
+    }
+
+    /// Look up a knob value by `name` at `frame`.
+    ///
+    /// Valid names are `"amplitude"` and `"band0"`, `"band1"`, ... up to the number of bands. Returns `None` for unknown names
+    /// and frames past the end of the track.
+    pub fn knob(&self, name: &str, frame: usize) -> Option<f64> {
+        if name == "amplitude" {
+            return self.amplitude.get(frame).copied();
+        }
+        let band: usize = name.strip_prefix("band")?.parse().ok()?;
+        self.bands.get(frame)?.get(band).copied()
+    }
+}
+
+/// The interesting parts of a "fmt " chunk.
+struct Format {
+    /// 1 for integer PCM, 3 for IEEE float.
+    tag: u16,
+    channels: u16,
+    sample_rate: u32,
+    bits_per_sample: u16,
+}
+
+impl Format {
+    fn parse(body: &[u8]) -> error::Result<Self> {
+        let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+        let mut tag = u16_at(0);
+        // WAVE_FORMAT_EXTENSIBLE stores the actual format in the sub-format GUID
+        if tag == 0xFFFE && body.len() >= 26 {
+            tag = u16_at(24);
+        }
+        let format = Format {
+            tag,
+            channels: u16_at(2),
+            sample_rate: u32::from_le_bytes(body[4..8].try_into().unwrap()),
+            bits_per_sample: u16_at(14),
+        };
+
+        match (format.tag, format.bits_per_sample) {
+            // without a sample rate, the duration and the number of frames would be infinite
+            _ if format.sample_rate == 0 => Err(GraphicsError::parse("WAV sample rate is 0")),
+            (1, 8) | (1, 16) | (1, 24) | (1, 32) | (3, 32) if format.channels > 0 => Ok(format),
+            _ => Err(GraphicsError::parse("unsupported WAV sample format")),
+        }
+    }
+
+    /// Decode a single little endian sample to `[-1, 1]`.
+    fn decode(&self, sample: &[u8]) -> f64 {
+        match (self.tag, sample.len()) {
+            (3, _) => f32::from_le_bytes(sample.try_into().unwrap()) as f64,
+            // 8 bit samples are unsigned
+            (_, 1) => (sample[0] as f64 - 128.) / 128.,
+            (_, 2) => i16::from_le_bytes([sample[0], sample[1]]) as f64 / 32768.,
+            (_, 3) => {
+                i32::from_le_bytes([0, sample[0], sample[1], sample[2]]) as f64 / 2_147_483_648.
+            }
+            _ => i32::from_le_bytes(sample.try_into().unwrap()) as f64 / 2_147_483_648.,
+        }
+    }
+}
+
+/// FFT bin boundaries for `bands` logarithmically spaced bands.
+fn band_edges(bands: usize, sample_rate: u32) -> Vec<usize> {
+    let nyquist = sample_rate as f64 / 2.;
+    let low: f64 = 20f64.min(nyquist);
+    let bin_width = sample_rate as f64 / FFT_SIZE as f64;
+    (0..=bands)
+        .map(|i| {
+            let freq = low * (nyquist / low).powf(i as f64 / bands as f64);
+            ((freq / bin_width) as usize).min(FFT_SIZE / 2)
+        })
+        .collect()
+}
+
+/// Scale `values` so that the largest becomes 1.
+fn normalize(values: &mut [f64]) {
+    let max = values.iter().cloned().fold(0., f64::max);
+    if max > 0. {
+        for v in values.iter_mut() {
+            *v /= max;
+        }
+    }
+}
+
+/// In-place iterative radix-2 FFT. `re.len()` must be a power of 2.
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let (w_im, w_re) = (-2. * PI / len as f64).sin_cos();
+        for start in (0..n).step_by(len) {
+            let (mut cur_re, mut cur_im) = (1., 0.);
+            for k in 0..len / 2 {
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Make a 16 bit mono WAV file in memory.
+    fn wav_bytes(sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data_len = samples.len() as u32 * 2;
+        let mut bytes = vec![];
+        bytes.extend(b"RIFF");
+        bytes.extend(&(36 + data_len).to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(&16u32.to_le_bytes());
+        bytes.extend(&1u16.to_le_bytes());
+        bytes.extend(&1u16.to_le_bytes());
+        bytes.extend(&sample_rate.to_le_bytes());
+        bytes.extend(&(sample_rate * 2).to_le_bytes());
+        bytes.extend(&2u16.to_le_bytes());
+        bytes.extend(&16u16.to_le_bytes());
+        bytes.extend(b"data");
+        bytes.extend(&data_len.to_le_bytes());
+        for s in samples {
+            bytes.extend(&s.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_read_wav() {
+        let wav = Wav::from_reader(&wav_bytes(8000, &[0, 16384, -32768])[..]).unwrap();
+        assert_eq!(8000, wav.sample_rate);
+        assert_eq!(vec![0., 0.5, -1.], wav.samples);
+        assert!(Wav::from_reader(&b"RIFF0000WAVX"[..]).is_err());
+    }
+
+    #[test]
+    fn test_read_wav_bad_header() {
+        assert!(Wav::from_reader(&wav_bytes(0, &[0, 1, 2])[..]).is_err());
+
+        // a data chunk claiming to be way longer than the file
+        let mut bytes = wav_bytes(8000, &[0, 16384]);
+        let len = bytes.len();
+        bytes[len - 8..len - 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let wav = Wav::from_reader(&bytes[..]).unwrap();
+        assert_eq!(vec![0., 0.5], wav.samples);
+    }
+
+    #[test]
+    fn test_fft_finds_frequency() {
+        let mut re: Vec<f64> = (0..64)
+            .map(|i| (2. * PI * 4. * i as f64 / 64.).cos())
+            .collect();
+        let mut im = vec![0.; 64];
+        fft(&mut re, &mut im);
+        let peak = (0..32)
+            .max_by(|&a, &b| re[a].hypot(im[a]).partial_cmp(&re[b].hypot(im[b])).unwrap())
+            .unwrap();
+        assert_eq!(4, peak);
+    }
+
+    #[test]
+    fn test_analyze_loud_and_quiet() {
+        // one second of silence followed by one second of a loud low tone
+        let rate = 8000;
+        let samples: Vec<i16> = (0..rate * 2)
+            .map(|i| {
+                if i < rate {
+                    0
+                } else {
+                    ((2. * PI * 100. * i as f64 / rate as f64).sin() * 20000.) as i16
+                }
+            })
+            .collect();
+        let analysis = Wav::from_reader(&wav_bytes(rate, &samples)[..])
+            .unwrap()
+            .analyze(10., 4);
+        assert_eq!(20, analysis.len());
+        assert_eq!(Some(0.), analysis.knob("amplitude", 0));
+        assert!(analysis.knob("amplitude", 15).unwrap() > 0.9);
+        assert!(analysis.knob("band0", 15).unwrap() > analysis.knob("band3", 15).unwrap());
+        assert_eq!(None, analysis.knob("band4", 15));
+        assert_eq!(None, analysis.knob("treble", 15));
+    }
+}
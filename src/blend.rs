@@ -0,0 +1,233 @@
+//! Drawing with translucent colors, and blend modes to combine layers.
+//!
+//! [`Blended`] wraps a screen, and turns every plot into a [`plot_blended`]: every drawing method takes an [`Rgba`], and
+//! composites it over what's already drawn.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{blend::Blended, color::Rgba, prelude::*};
+//!
+//! let mut img = Ppm::new();
+//! img.clear(Rgb::WHITE);
+//!
+//! // 3 overlapping translucent discs
+//! let discs = [
+//!     (200., 200., Rgba::new(255, 0, 0, 100)),
+//!     (300., 200., Rgba::new(0, 255, 0, 100)),
+//!     (250., 290., Rgba::new(0, 0, 255, 100)),
+//! ];
+//! for (z, &(x, y, color)) in discs.iter().enumerate() {
+//!     let mut blended = Blended::new(&mut img);
+//!     let disc: Vec<(f64, f64, f64)> = (0..64)
+//!         .map(|i| {
+//!             let angle = (i as f64 * 360. / 64.).to_radians();
+//!             (x + 100. * angle.cos(), y + 100. * angle.sin(), z as f64)
+//!         })
+//!         .collect();
+//!     blended.fill_polygon(&disc, color);
+//! }
+//!
+//! // overlap of the red and green discs
+//! let overlap = img.get_pixel(250, 180).unwrap();
+//! assert!(overlap.red > 0 && overlap.green > 0 && overlap.blue > 0);
+//! ```
+//!
+//! [`Blended`]: ./struct.Blended.html
+//! [`plot_blended`]: ../screen/trait.Screen.html#method.plot_blended
+//! [`Rgba`]: ../color/struct.Rgba.html
+
+use std::{io, ops::Range};
+
+use crate::{
+    color::{Rgb, Rgba},
+    error,
+    ppm::Origin,
+    raster::Rasterizer,
+    screen::{SaveOptions, Screen},
+    util::Float,
+};
+
+/// A [`Screen`] of [`Rgba`] colors, blending everything drawn on it over the screen it wraps.
+///
+/// Reading a pixel gives the (opaque) color of the wrapped screen. Saving and displaying show the wrapped screen.
+///
+/// [`Screen`]: ../screen/trait.Screen.html
+/// [`Rgba`]: ../color/struct.Rgba.html
+pub struct Blended<'a, S: Screen> {
+    screen: &'a mut S,
+}
+
+impl<'a, S: Screen> Blended<'a, S> {
+    /// Draw translucent colors over `screen`.
+    pub fn new(screen: &'a mut S) -> Self {
+        Blended { screen }
+    }
+}
+
+impl<S: Screen> Screen<Rgba> for Blended<'_, S> {
+    fn plot(&mut self, x: i64, y: i64, z: Float, color: Rgba) {
+        self.screen.plot_blended(x, y, z, color);
+    }
+
+    fn save(&self, file_path: &str) -> error::Result<()> {
+        self.screen.save(file_path)
+    }
+
+    fn save_with(&self, file_path: &str, options: &SaveOptions) -> error::Result<()> {
+        self.screen.save_with(file_path, options)
+    }
+
+    fn width(&self) -> usize {
+        self.screen.width()
+    }
+
+    fn height(&self) -> usize {
+        self.screen.height()
+    }
+
+    fn write_to_buf<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+        self.screen.write_to_buf(writer)
+    }
+
+    fn display(&self) -> error::Result<()> {
+        self.screen.display()
+    }
+
+    /// Clear the wrapped screen to `color` composited over black.
+    fn clear(&mut self, color: Rgba) {
+        self.screen.clear(color.over(Rgb::BLACK));
+    }
+
+    fn get_pixel(&self, x: i64, y: i64) -> Option<Rgba> {
+        self.screen.get_pixel(x, y).map(Rgba::from)
+    }
+
+    fn get_depth(&self, x: i64, y: i64) -> Option<Float> {
+        self.screen.get_depth(x, y)
+    }
+
+    fn rasterizer(&self) -> &'static dyn Rasterizer {
+        self.screen.rasterizer()
+    }
+
+    fn clip_bounds(&self) -> (Range<i64>, Range<i64>) {
+        self.screen.clip_bounds()
+    }
+
+    fn origin(&self) -> Origin {
+        self.screen.origin()
+    }
+}
+
+/// How to combine a layer with the image under it. See [`Ppm::composite`].
+///
+/// [`Ppm::composite`]: ../ppm/struct.Ppm.html#method.composite
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BlendMode {
+    /// The layer replaces the image.
+    Normal,
+    /// Channels are added, clipping at white. Good for lights and glows.
+    Add,
+    /// Channels are multiplied, which can only darken. Good for shadows and tinting.
+    Multiply,
+    /// The inverse of multiplying the inverted channels, which can only lighten.
+    Screen,
+}
+
+impl BlendMode {
+    /// Blend a `top` channel over a `bottom` one, both from `0` to `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::blend::BlendMode;
+    ///
+    /// assert_eq!(0.25, BlendMode::Multiply.apply(0.5, 0.5));
+    /// assert_eq!(0.75, BlendMode::Screen.apply(0.5, 0.5));
+    /// assert_eq!(1., BlendMode::Add.apply(0.5, 0.75));
+    /// ```
+    pub fn apply(self, bottom: f64, top: f64) -> f64 {
+        match self {
+            BlendMode::Normal => top,
+            BlendMode::Add => (bottom + top).min(1.),
+            BlendMode::Multiply => bottom * top,
+            BlendMode::Screen => 1. - (1. - bottom) * (1. - top),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppm::{Ppm, PpmBuilder};
+
+    fn img() -> Ppm {
+        let mut img = PpmBuilder::new(10, 10, 255).build();
+        img.clear(Rgb::WHITE);
+        img
+    }
+
+    #[test]
+    fn test_blended_plots() {
+        let mut img = img();
+        let mut blended = Blended::new(&mut img);
+        blended.plot(1, 1, 0., Rgba::new(0, 0, 0, 255));
+        blended.plot(2, 2, 0., Rgba::new(0, 0, 0, 128));
+        blended.plot(3, 3, 0., Rgba::TRANSPARENT);
+        assert_eq!(Some(Rgba::new(0, 0, 0, 255)), blended.get_pixel(1, 1));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(1, 1));
+        assert_eq!(Some(Rgb::gray(127)), img.get_pixel(2, 2));
+        assert_eq!(Some(Rgb::WHITE), img.get_pixel(3, 3));
+    }
+
+    #[test]
+    fn test_layers_stack() {
+        let mut img = img();
+        let half = Rgba::new(0, 0, 0, 128);
+        Blended::new(&mut img).plot(5, 5, 1., half);
+        Blended::new(&mut img).plot(5, 5, 2., half);
+        assert_eq!(Some(Rgb::gray(63)), img.get_pixel(5, 5));
+    }
+
+    #[test]
+    fn test_clipped() {
+        // off the image and degenerate shapes are fine
+        let mut img = img();
+        let mut blended = Blended::new(&mut img);
+        blended.plot(-1, 5, 0., Rgba::new(255, 0, 0, 128));
+        blended.plot(5, 10, 0., Rgba::new(255, 0, 0, 128));
+        blended.fill_rect((-5., -5., 0.), 10., 10., Rgba::new(255, 0, 0, 128));
+        blended.fill_rect((5., 5., 0.), 0., 10., Rgba::new(255, 0, 0, 128));
+        blended.draw_circle(5., 5., 0., f64::NAN, Rgba::new(255, 0, 0, 128));
+        assert_eq!((0..10, 0..10), blended.clip_bounds());
+        assert_eq!(Some(Rgb::new(255, 127, 127)), img.get_pixel(0, 0));
+        assert_eq!(Some(Rgb::WHITE), img.get_pixel(4, 5));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut img = img();
+        Blended::new(&mut img).clear(Rgba::new(255, 255, 255, 128));
+        assert_eq!(Some(Rgb::gray(128)), img.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let modes = [
+            BlendMode::Normal,
+            BlendMode::Add,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+        ];
+        let expected = [0.25, 0.75, 0.125, 0.625];
+        for (&mode, &expected) in modes.iter().zip(expected.iter()) {
+            assert_eq!(expected, mode.apply(0.5, 0.25), "{:?}", mode);
+        }
+        // black and white
+        assert_eq!(1., BlendMode::Add.apply(1., 1.));
+        assert_eq!(0., BlendMode::Multiply.apply(1., 0.));
+        assert_eq!(1., BlendMode::Screen.apply(0., 1.));
+        assert!(BlendMode::Screen.apply(f64::NAN, 0.5).is_nan());
+    }
+}
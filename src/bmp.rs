@@ -0,0 +1,81 @@
+//! Dependency-free encoder for the [BMP] format, which every platform can open without ImageMagick.
+//!
+//! Use it through [`Ppm::save_bmp`] and [`Ppm::write_bmp_to_buf`].
+//!
+//! [BMP]: https://en.wikipedia.org/wiki/BMP_file_format
+//! [`Ppm::save_bmp`]: ../ppm/struct.Ppm.html#method.save_bmp
+//! [`Ppm::write_bmp_to_buf`]: ../ppm/struct.Ppm.html#method.write_bmp_to_buf
+
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+};
+
+use crate::{color::PpmColor, ppm::Ppm, screen::Screen};
+
+/// Size of the file header and the BITMAPINFOHEADER together.
+const HEADER_SIZE: u32 = 14 + 40;
+
+/// Pixels per meter, at 72 dpi.
+const RESOLUTION: i32 = 2835;
+
+/// Write `img` as an uncompressed 24 bit BMP.
+pub(crate) fn write_bmp<C: PpmColor, W: Write>(img: &Ppm<C>, writer: &mut W) -> io::Result<()> {
+    let (width, height) = (img.width(), img.height());
+    // every row is padded to a multiple of 4 bytes
+    let row_size = (width * 3).div_ceil(4) * 4;
+    let image_size = u32::try_from(row_size * height)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "image too large for BMP"))?;
+
+    // file header
+    writer.write_all(b"BM")?;
+    writer.write_all(&(HEADER_SIZE + image_size).to_le_bytes())?;
+    writer.write_all(&[0; 4])?;
+    writer.write_all(&HEADER_SIZE.to_le_bytes())?;
+
+    // BITMAPINFOHEADER
+    writer.write_all(&40u32.to_le_bytes())?;
+    writer.write_all(&(width as i32).to_le_bytes())?;
+    // positive height: rows go from the bottom up
+    writer.write_all(&(height as i32).to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&24u16.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.write_all(&image_size.to_le_bytes())?;
+    writer.write_all(&RESOLUTION.to_le_bytes())?;
+    writer.write_all(&RESOLUTION.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+
+    let pixels = img.to_rgba8();
+    let mut row = Vec::with_capacity(row_size);
+    for line in pixels.chunks(width.max(1)).rev() {
+        row.clear();
+        for &[r, g, b, _] in line {
+            row.extend_from_slice(&[b, g, r]);
+        }
+        row.resize(row_size, 0);
+        writer.write_all(&row)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Rgb, ppm::PpmBuilder};
+
+    #[test]
+    fn test_write_bmp() {
+        let mut img = PpmBuilder::new(2, 2, 255).build();
+        img.plot(0, 0, 0., Rgb::new(1, 2, 3));
+        let mut buf = vec![];
+        img.write_bmp_to_buf(&mut buf).unwrap();
+
+        // 2 rows of 6 bytes, padded to 8
+        assert_eq!(54 + 16, buf.len());
+        assert_eq!(&(buf.len() as u32).to_le_bytes(), &buf[2..6]);
+        // the bottom row comes first, in BGR order
+        assert_eq!(&[3, 2, 1, 0, 0, 0, 0, 0], &buf[54..62]);
+    }
+}
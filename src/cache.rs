@@ -0,0 +1,313 @@
+//! A persistent, content-addressed cache for rendered images.
+//!
+//! Rendering the same scene twice is a waste of time, especially when a Makefile regenerates a whole folder of images
+//! every time one of them changes. A [`RenderCache`] remembers the output of a render under a hash of everything that
+//! went into it (the script, scene description, settings, ...), and simply copies the old output when nothing changed.
+//!
+//! The key can be anything that implements [`Hash`]. It is hashed with FNV-1a, so keys stay valid across runs of the
+//! program, and the cache survives on disk between runs. The bytes that get hashed come from the key's [`Hash`]
+//! implementation though, and the standard library doesn't promise to keep those the same between Rust versions. Upgrading
+//! the compiler or moving to another platform may turn old entries into misses, so treat the cache as disposable.
+//!
+//! [`Screen::save_cached`] goes one step further and uses the image itself as the key, which only skips encoding the file,
+//! but needs no key at all.
+//!
+//! Make sure to include everything that affects the output in the key. If the render depends on something that isn't in
+//! the key, stale images will come out of the cache.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use graphics::{cache::RenderCache, prelude::*};
+//!
+//! let script = std::fs::read_to_string("scene.txt").expect("error reading scene.txt");
+//! let size = 500;
+//!
+//! let cache = RenderCache::new("target/render-cache").expect("error creating cache");
+//! let cached = cache
+//!     .save_or_render(&(&script, size), "scene.png", || {
+//!         let mut img = PpmBuilder::new(size, size, 255).build();
+//!         // parse `script` and draw...
+//!         img
+//!     })
+//!     .expect("error saving scene.png");
+//!
+//! if cached {
+//!     println!("scene.png is up to date");
+//! }
+//! ```
+//!
+//! [`RenderCache`]: ./struct.RenderCache.html
+//! [`Screen::save_cached`]: ../screen/trait.Screen.html#method.save_cached
+//! [`Hash`]: https://doc.rust-lang.org/std/hash/trait.Hash.html
+
+use std::{
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{color::Color, error, screen::Screen};
+
+/// A directory of rendered images, named by the hash of the scene they came from.
+#[derive(Clone, Debug)]
+pub struct RenderCache {
+    dir: PathBuf,
+}
+
+impl RenderCache {
+    /// Use `dir` as the cache directory, creating it if needed.
+    pub fn new<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(RenderCache {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// FNV-1a hash of `key`.
+    pub fn hash<K: Hash + ?Sized>(key: &K) -> u64 {
+        let mut hasher = StableHasher::default();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Path of the cached file for `key`, if it was saved as `file_path`.
+    ///
+    /// The extension of `file_path` is part of the cache entry, since the same scene saved as png and jpg gives different files.
+    pub fn entry_path<K: Hash + ?Sized>(&self, key: &K, file_path: &str) -> PathBuf {
+        let extension = Path::new(file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let name = format!("{:016x}", Self::hash(&(key, extension)));
+        self.dir.join(name).with_extension(extension)
+    }
+
+    /// Whether there is a cached output for `key` saved as `file_path`.
+    pub fn contains<K: Hash + ?Sized>(&self, key: &K, file_path: &str) -> bool {
+        self.entry_path(key, file_path).is_file()
+    }
+
+    /// Make sure `file_path` holds the render of the scene identified by `key`.
+    ///
+    /// On a cache hit, the cached file is copied to `file_path` and `render` is never called. Otherwise, `render` is called,
+    /// its result is [`save`]d to `file_path`, and a copy is stored in the cache.
+    ///
+    /// Returns `true` if the output came from the cache.
+    ///
+    /// [`save`]: ../screen/trait.Screen.html#tymethod.save
+    pub fn save_or_render<K, S, C, F>(
+        &self,
+        key: &K,
+        file_path: &str,
+        render: F,
+    ) -> error::Result<bool>
+    where
+        K: Hash + ?Sized,
+        S: Screen<C>,
+        C: Color,
+        F: FnOnce() -> S,
+    {
+        self.fetch_or_save(&self.entry_path(key, file_path), file_path, || {
+            render().save(file_path)
+        })
+    }
+
+    /// Save `img` to `file_path`, reusing an earlier file if the exact same image was saved the same way before.
+    ///
+    /// The key is the image data itself, as written by [`write_to_buf`], so this only saves the time spent encoding (and
+    /// possibly converting with ImageMagick), not the time spent drawing. See [`save_or_render`] to skip drawing too.
+    ///
+    /// Returns `true` if the output came from the cache.
+    ///
+    /// [`save_or_render`]: #method.save_or_render
+    /// [`write_to_buf`]: ../screen/trait.Screen.html#tymethod.write_to_buf
+    pub fn save<S, C>(&self, img: &S, file_path: &str) -> error::Result<bool>
+    where
+        S: Screen<C> + ?Sized,
+        C: Color,
+    {
+        let mut data = Vec::new();
+        img.write_to_buf(&mut data)?;
+        self.fetch_or_save(&self.entry_path(&data[..], file_path), file_path, || {
+            img.save(file_path)
+        })
+    }
+
+    /// Copy `entry` to `file_path` if it exists, otherwise call `save` to write `file_path`, and store a copy as `entry`.
+    fn fetch_or_save<F>(&self, entry: &Path, file_path: &str, save: F) -> error::Result<bool>
+    where
+        F: FnOnce() -> error::Result<()>,
+    {
+        if entry.is_file() {
+            fs::copy(entry, file_path)?;
+            return Ok(true);
+        }
+
+        save()?;
+        store(Path::new(file_path), entry)?;
+        Ok(false)
+    }
+
+    /// Delete all cached files.
+    pub fn clear(&self) -> io::Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copy `file` into the cache as `entry`.
+///
+/// The copy goes to a temporary file that is renamed into place, so a crash, or another program reading the cache at the
+/// same time, never sees a half written entry.
+fn store(file: &Path, entry: &Path) -> io::Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let temp = entry.with_extension(format!(
+        "{}-{}.tmp",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::copy(file, &temp)?;
+    fs::rename(&temp, entry).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+/// 64 bit FNV-1a hasher.
+///
+/// Unlike the standard library's `DefaultHasher`, the hash function itself is fixed, and it isn't randomly seeded, so the
+/// same bytes always give the same hash. `usize`s (like the length of a slice) are hashed as 8 little endian bytes, so
+/// they come out the same on every platform.
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write(&(i as u64).to_le_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Rgb, ppm::PpmBuilder};
+    use std::{cell::Cell, env};
+
+    fn bytes<S: Screen>(img: &S) -> Vec<u8> {
+        let mut data = Vec::new();
+        img.write_to_buf(&mut data).unwrap();
+        data
+    }
+
+    /// An empty cache in a directory of its own.
+    fn cache(name: &str) -> RenderCache {
+        let dir = env::temp_dir().join(format!("graphics-cache-{}-{}", name, process::id()));
+        let cache = RenderCache::new(dir).unwrap();
+        cache.clear().unwrap();
+        cache
+    }
+
+    #[test]
+    fn test_hash_is_stable() {
+        // FNV-1a test vectors
+        assert_eq!(0xcbf2_9ce4_8422_2325, StableHasher::default().finish());
+        let mut hasher = StableHasher::default();
+        hasher.write(b"a");
+        assert_eq!(0xaf63_dc4c_8601_ec8c, hasher.finish());
+
+        let mut a = StableHasher::default();
+        a.write_usize(3);
+        let mut b = StableHasher::default();
+        b.write(&[3, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(a.finish(), b.finish());
+    }
+
+    #[test]
+    fn test_hit_miss_and_invalidation() {
+        let cache = cache("render");
+        let output = cache.dir.join("out.ppm");
+        let output = output.to_str().unwrap();
+        let renders = Cell::new(0);
+        let render = |color| {
+            renders.set(renders.get() + 1);
+            PpmBuilder::new(4, 4, 255).bg_color(color).build()
+        };
+
+        // miss: render and store
+        assert!(!cache
+            .save_or_render("scene", output, || render(Rgb::WHITE))
+            .unwrap());
+        assert!(cache.contains("scene", output));
+        assert!(!cache.contains("scene", "out.png"));
+        assert_eq!(1, renders.get());
+
+        // hit: copy back without rendering, even if the output is gone
+        fs::remove_file(output).unwrap();
+        assert!(cache
+            .save_or_render("scene", output, || render(Rgb::BLACK))
+            .unwrap());
+        assert_eq!(1, renders.get());
+        assert_eq!(bytes(&render(Rgb::WHITE)), fs::read(output).unwrap());
+
+        // a different key is a different scene
+        assert!(!cache
+            .save_or_render("scene 2", output, || render(Rgb::BLACK))
+            .unwrap());
+        assert_eq!(3, renders.get());
+
+        // clearing invalidates everything, and leaves no temporary files behind
+        let names: Vec<_> = fs::read_dir(&cache.dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(3, names.len());
+        assert!(names
+            .iter()
+            .all(|name| !name.to_string_lossy().ends_with(".tmp")));
+        cache.clear().unwrap();
+        assert!(!cache.contains("scene", output));
+        assert!(!cache
+            .save_or_render("scene", output, || render(Rgb::WHITE))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_save_by_content() {
+        let cache = cache("save");
+        let output = cache.dir.join("out.ppm");
+        let output = output.to_str().unwrap();
+        let mut img = PpmBuilder::new(4, 4, 255).build();
+
+        assert!(!cache.save(&img, output).unwrap());
+        assert!(cache.save(&img, output).unwrap());
+
+        // any change to the image is a miss
+        img.plot(1, 1, 0., Rgb::WHITE);
+        assert!(!cache.save(&img, output).unwrap());
+        assert_eq!(bytes(&img), fs::read(output).unwrap());
+    }
+}
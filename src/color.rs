@@ -1,9 +1,49 @@
 //! Module to work with colors.
+//!
+//! [`Rgb`] is the color of a [`Ppm`]. Other [`Screen`]s can store other kinds of pixels, like grayscale values or
+//! floating point intensities, as long as they implement [`Color`].
+//!
+//! [`Rgb`]: ./struct.Rgb.html
+//! [`Ppm`]: ../ppm/struct.Ppm.html
+//! [`Screen`]: ../screen/trait.Screen.html
+//! [`Color`]: ./trait.Color.html
+
+use std::{
+    fmt::Debug,
+    ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
+};
+
+/// A pixel value that a [`Screen`] can store and draw with.
+///
+/// The drawing algorithms only need to compare colors (for fills), [`mix`] them (for anti-aliasing), and turn an [`Rgb`]
+/// into one (for gradients and patterns). Implement this trait to use those algorithms on another kind of pixel, like
+/// palette indices.
+///
+/// Implemented for [`Rgb`], [`Rgb16`], [`Rgba`], `u8` (8 bit grayscale) and `f32`/`f64` (intensity, `1.0` being white, not clamped).
+///
+/// [`Screen`]: ../screen/trait.Screen.html
+/// [`mix`]: #tymethod.mix
+/// [`Rgb`]: ./struct.Rgb.html
+/// [`Rgb16`]: ./struct.Rgb16.html
+/// [`Rgba`]: ./struct.Rgba.html
+pub trait Color: Copy + PartialEq + Debug {
+    /// Convert an [`Rgb`] to this color type.
+    ///
+    /// [`Rgb`]: ./struct.Rgb.html
+    fn from_rgb(rgb: Rgb) -> Self;
+
+    /// Mix `self` with `other`, going from `self` at `t = 0` to `other` at `t = 1`.
+    ///
+    /// Color types without in-between values (like palette indices) can pick whichever is closest.
+    fn mix(self, other: Self, t: f64) -> Self;
+}
 
 /// Represent an rgb triple.
 ///
 /// The fields are of type `u8`, since that's the standard RGB space. But the actual upper bound of color defined by PPM file spec is 
-/// 2^16. Just change all instance of u8 here if you want that instead. You would also need to change the fns that write a PPM to file.
+/// 2^16. Use [`Rgb16`] if you want that instead.
+///
+/// [`Rgb16`]: ./struct.Rgb16.html
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Rgb {
     pub red: u8,
@@ -16,6 +56,79 @@ impl Rgb {
 
     pub const BLACK: Rgb = Rgb::gray(0);
 
+    // the 16 basic CSS colors
+    pub const SILVER: Rgb = Rgb::gray(192);
+    pub const GRAY: Rgb = Rgb::gray(128);
+    pub const MAROON: Rgb = Rgb::new(128, 0, 0);
+    pub const RED: Rgb = Rgb::new(255, 0, 0);
+    pub const PURPLE: Rgb = Rgb::new(128, 0, 128);
+    pub const FUCHSIA: Rgb = Rgb::new(255, 0, 255);
+    pub const GREEN: Rgb = Rgb::new(0, 128, 0);
+    pub const LIME: Rgb = Rgb::new(0, 255, 0);
+    pub const OLIVE: Rgb = Rgb::new(128, 128, 0);
+    pub const YELLOW: Rgb = Rgb::new(255, 255, 0);
+    pub const NAVY: Rgb = Rgb::new(0, 0, 128);
+    pub const BLUE: Rgb = Rgb::new(0, 0, 255);
+    pub const TEAL: Rgb = Rgb::new(0, 128, 128);
+    pub const AQUA: Rgb = Rgb::new(0, 255, 255);
+
+    // common extras, with their CSS values
+    pub const CYAN: Rgb = Rgb::AQUA;
+    pub const MAGENTA: Rgb = Rgb::FUCHSIA;
+    pub const ORANGE: Rgb = Rgb::new(255, 165, 0);
+    pub const PINK: Rgb = Rgb::new(255, 192, 203);
+    pub const BROWN: Rgb = Rgb::new(165, 42, 42);
+
+    /// All the named colors, with their lowercase names. See [`from_name`].
+    ///
+    /// [`from_name`]: #method.from_name
+    pub const NAMED: [(&'static str, Rgb); 22] = [
+        ("black", Rgb::BLACK),
+        ("silver", Rgb::SILVER),
+        ("gray", Rgb::GRAY),
+        ("grey", Rgb::GRAY),
+        ("white", Rgb::WHITE),
+        ("maroon", Rgb::MAROON),
+        ("red", Rgb::RED),
+        ("purple", Rgb::PURPLE),
+        ("fuchsia", Rgb::FUCHSIA),
+        ("green", Rgb::GREEN),
+        ("lime", Rgb::LIME),
+        ("olive", Rgb::OLIVE),
+        ("yellow", Rgb::YELLOW),
+        ("navy", Rgb::NAVY),
+        ("blue", Rgb::BLUE),
+        ("teal", Rgb::TEAL),
+        ("aqua", Rgb::AQUA),
+        ("cyan", Rgb::CYAN),
+        ("magenta", Rgb::MAGENTA),
+        ("orange", Rgb::ORANGE),
+        ("pink", Rgb::PINK),
+        ("brown", Rgb::BROWN),
+    ];
+
+    /// Look up a named color, ignoring case. Useful for parsing colors out of a script.
+    ///
+    /// The names are the ones in [`NAMED`]: the 16 basic CSS colors, plus a few common ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// assert_eq!(Some(Rgb::ORANGE), Rgb::from_name("orange"));
+    /// assert_eq!(Some(Rgb::new(0, 255, 255)), Rgb::from_name("Cyan"));
+    /// assert_eq!(None, Rgb::from_name("not a color"));
+    /// ```
+    ///
+    /// [`NAMED`]: #associatedconstant.NAMED
+    pub fn from_name(name: &str) -> Option<Rgb> {
+        Self::NAMED
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, color)| color)
+    }
+
     /// Make an [`Rgb`] on grayscale.
     ///
     /// `depth` of `0` will give black, and the upper bound of color depth will give white.
@@ -50,4 +163,755 @@ impl Rgb {
     pub const fn new(red: u8, green: u8, blue: u8) -> Self {
         Rgb { red, green, blue }
     }
+
+    /// Interpolate from `a` (at `t = 0`) to `b` (at `t = 1`), channel by channel.
+    ///
+    /// `t` outside of `[0, 1]` extrapolates, with channels saturating at `0` and `255`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// assert_eq!(Rgb::new(128, 0, 128), Rgb::lerp(Rgb::BLUE, Rgb::RED, 0.5));
+    /// assert_eq!(Rgb::WHITE, Rgb::lerp(Rgb::BLACK, Rgb::GRAY, 3.));
+    /// ```
+    pub fn lerp(a: Rgb, b: Rgb, t: f64) -> Rgb {
+        a.mix(b, t)
+    }
+
+    /// Make an [`Rgb`] from [HSV] (hue, saturation, value).
+    ///
+    /// `hue` is in degrees, and wraps around, so any value works. `saturation` and `value` go from `0` to `1`, and are
+    /// clamped to that range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// assert_eq!(Rgb::new(255, 0, 0), Rgb::from_hsv(0., 1., 1.));
+    /// assert_eq!(Rgb::new(0, 0, 255), Rgb::from_hsv(240., 1., 1.));
+    /// assert_eq!(Rgb::new(0, 0, 255), Rgb::from_hsv(-120., 1., 1.));
+    /// assert_eq!(Rgb::gray(128), Rgb::from_hsv(123., 0., 0.5));
+    ///
+    /// // a rainbow, by cycling the hue
+    /// let rainbow: Vec<Rgb> = (0..12).map(|i| Rgb::from_hsv(i as f64 * 30., 1., 1.)).collect();
+    /// ```
+    ///
+    /// [`Rgb`]: ./struct.Rgb.html
+    /// [HSV]: https://en.wikipedia.org/wiki/HSL_and_HSV
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64) -> Self {
+        let (s, v) = (saturation.clamp(0., 1.), value.clamp(0., 1.));
+        let chroma = v * s;
+        Self::from_hue(hue, chroma, v - chroma)
+    }
+
+    /// Hue (in degrees, from `0` to `360`), saturation and value of this color. See [`from_hsv`].
+    ///
+    /// Grays have a hue of `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// assert_eq!((120., 1., 1.), Rgb::new(0, 255, 0).to_hsv());
+    /// assert_eq!((0., 0., 1.), Rgb::WHITE.to_hsv());
+    ///
+    /// // round trip
+    /// let orange = Rgb::new(255, 128, 0);
+    /// let (h, s, v) = orange.to_hsv();
+    /// assert_eq!(orange, Rgb::from_hsv(h, s, v));
+    /// ```
+    ///
+    /// [`from_hsv`]: #method.from_hsv
+    pub fn to_hsv(self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue();
+        let saturation = if max == 0. { 0. } else { (max - min) / max };
+        (hue, saturation, max)
+    }
+
+    /// Make an [`Rgb`] from [HSL] (hue, saturation, lightness).
+    ///
+    /// `hue` is in degrees, and wraps around, so any value works. `saturation` and `lightness` go from `0` to `1`, and are
+    /// clamped to that range. A `lightness` of `0.5` gives the most colorful colors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// assert_eq!(Rgb::new(255, 0, 0), Rgb::from_hsl(0., 1., 0.5));
+    /// assert_eq!(Rgb::new(255, 128, 128), Rgb::from_hsl(0., 1., 0.75));
+    /// assert_eq!(Rgb::WHITE, Rgb::from_hsl(0., 1., 1.));
+    /// ```
+    ///
+    /// [`Rgb`]: ./struct.Rgb.html
+    /// [HSL]: https://en.wikipedia.org/wiki/HSL_and_HSV
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        let (s, l) = (saturation.clamp(0., 1.), lightness.clamp(0., 1.));
+        let chroma = (1. - (2. * l - 1.).abs()) * s;
+        Self::from_hue(hue, chroma, l - chroma / 2.)
+    }
+
+    /// Hue (in degrees, from `0` to `360`), saturation and lightness of this color. See [`from_hsl`].
+    ///
+    /// Grays have a hue of `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let pink = Rgb::new(255, 128, 200);
+    /// let (h, s, l) = pink.to_hsl();
+    /// assert_eq!(pink, Rgb::from_hsl(h, s, l));
+    /// ```
+    ///
+    /// [`from_hsl`]: #method.from_hsl
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue();
+        let lightness = (max + min) / 2.;
+        let saturation = if max == min {
+            0.
+        } else {
+            (max - min) / (1. - (2. * lightness - 1.).abs())
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// Color with the given `hue` (degrees) and `chroma`, with `offset` added to all channels. Channels are from `0` to `1`.
+    fn from_hue(hue: f64, chroma: f64, offset: f64) -> Self {
+        let h = hue.rem_euclid(360.) / 60.;
+        let x = chroma * (1. - (h % 2. - 1.).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (chroma, x, 0.),
+            1 => (x, chroma, 0.),
+            2 => (0., chroma, x),
+            3 => (0., x, chroma),
+            4 => (x, 0., chroma),
+            _ => (chroma, 0., x),
+        };
+        let channel = |c: f64| ((c + offset) * 255.).round().clamp(0., 255.) as u8;
+        Rgb::new(channel(r), channel(g), channel(b))
+    }
+
+    /// Hue in degrees, and the largest and smallest channels, from `0` to `1`.
+    fn hue(self) -> (f64, f64, f64) {
+        let (r, g, b) = (
+            self.red as f64 / 255.,
+            self.green as f64 / 255.,
+            self.blue as f64 / 255.,
+        );
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+        let chroma = max - min;
+        let hue = if chroma == 0. {
+            0.
+        } else if max == r {
+            60. * ((g - b) / chroma).rem_euclid(6.)
+        } else if max == g {
+            60. * ((b - r) / chroma + 2.)
+        } else {
+            60. * ((r - g) / chroma + 4.)
+        };
+        (hue, max, min)
+    }
+}
+
+/// An rgb color with 16 bits per channel, for images with a color depth above 255.
+///
+/// Use it with [`PpmBuilder::with_color_type`] to make a 16 bit [`Ppm`]. Channels go up to the color depth of the image,
+/// usually `65535`.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::{color::Rgb16, prelude::*};
+///
+/// let mut img = PpmBuilder::<Rgb16>::with_color_type(500, 500, 65535).build();
+/// img.draw_line((0., 0., 0.), (499., 499., 0.), Rgb16::new(65535, 1000, 0));
+///
+/// // 8 bit colors convert to the same color at full 16 bit depth
+/// assert_eq!(Rgb16::WHITE, Rgb16::from(Rgb::WHITE));
+/// assert_eq!(Rgb::WHITE, Rgb16::WHITE.to_rgb());
+/// ```
+///
+/// [`PpmBuilder::with_color_type`]: ../ppm/struct.PpmBuilder.html#method.with_color_type
+/// [`Ppm`]: ../ppm/struct.Ppm.html
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Rgb16 {
+    pub red: u16,
+    pub green: u16,
+    pub blue: u16,
+}
+
+impl Rgb16 {
+    pub const WHITE: Rgb16 = Rgb16::gray(65535);
+
+    pub const BLACK: Rgb16 = Rgb16::gray(0);
+
+    /// Function constructor for [`Rgb16`].
+    ///
+    /// [`Rgb16`]: ./struct.Rgb16.html
+    pub const fn new(red: u16, green: u16, blue: u16) -> Self {
+        Rgb16 { red, green, blue }
+    }
+
+    /// Make an [`Rgb16`] on grayscale.
+    ///
+    /// [`Rgb16`]: ./struct.Rgb16.html
+    pub const fn gray(depth: u16) -> Self {
+        Rgb16::new(depth, depth, depth)
+    }
+
+    /// Convert to 8 bits per channel, rounding to the closest color.
+    pub fn to_rgb(self) -> Rgb {
+        let channel = |c: u16| ((c as u32 + 128) / 257) as u8;
+        Rgb::new(channel(self.red), channel(self.green), channel(self.blue))
+    }
+}
+
+impl From<Rgb> for Rgb16 {
+    /// Scale the channels from `[0, 255]` to `[0, 65535]`.
+    fn from(rgb: Rgb) -> Self {
+        let channel = |c: u8| c as u16 * 257;
+        Rgb16::new(channel(rgb.red), channel(rgb.green), channel(rgb.blue))
+    }
+}
+
+/// An rgb color with an alpha (opacity) channel. An `alpha` of `0` is fully transparent, `255` is opaque.
+///
+/// Draw with it through [`Screen::plot_blended`], or on a [`Blended`] screen to use all the drawing methods with
+/// translucent colors.
+///
+/// [`Screen::plot_blended`]: ../screen/trait.Screen.html#method.plot_blended
+/// [`Blended`]: ../blend/struct.Blended.html
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Rgba {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl Rgba {
+    pub const TRANSPARENT: Rgba = Rgba::new(0, 0, 0, 0);
+
+    /// Function constructor for [`Rgba`].
+    ///
+    /// [`Rgba`]: ./struct.Rgba.html
+    pub const fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Rgba {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// The color without its alpha channel.
+    pub const fn rgb(self) -> Rgb {
+        Rgb::new(self.red, self.green, self.blue)
+    }
+
+    /// Composite this color over an opaque `background` ("source-over").
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{color::Rgba, prelude::*};
+    ///
+    /// let half_red = Rgba::new(255, 0, 0, 128);
+    /// assert_eq!(Rgb::new(128, 0, 127), half_red.over(Rgb::new(0, 0, 255)));
+    /// assert_eq!(Rgb::WHITE, Rgba::TRANSPARENT.over(Rgb::WHITE));
+    /// ```
+    pub fn over(self, background: Rgb) -> Rgb {
+        background.mix(self.rgb(), self.alpha as f64 / 255.)
+    }
+}
+
+/// Transfer function between the linear light values colors are drawn and blended with, and the values written to image
+/// files.
+///
+/// Mixing and averaging colors (in gradients, anti-aliasing, or lighting) only looks right on linear values. But image
+/// files are expected to be gamma encoded, so a linear `128` written as is shows up much darker than half as bright as
+/// white. Set [`PpmBuilder::gamma`] to draw in linear space and encode only on output.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::{color::Gamma, prelude::*};
+///
+/// assert_eq!(Rgb::gray(188), Gamma::Srgb.encode_rgb(Rgb::gray(128)));
+/// assert_eq!(Rgb::gray(128), Gamma::Srgb.decode_rgb(Rgb::gray(188)));
+/// assert_eq!(Rgb::gray(128), Gamma::Linear.encode_rgb(Rgb::gray(128)));
+/// ```
+///
+/// [`PpmBuilder::gamma`]: ../ppm/struct.PpmBuilder.html#method.gamma
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Gamma {
+    /// Values are written as they are.
+    Linear,
+    /// The sRGB transfer function, which is what most images and screens use.
+    Srgb,
+    /// A plain power curve: values are raised to `1 / gamma` when encoding. `Power(2.2)` is close to sRGB.
+    Power(f64),
+}
+
+impl Gamma {
+    /// Encode a linear value from `0` to `1`.
+    pub fn encode(self, v: f64) -> f64 {
+        let v = v.clamp(0., 1.);
+        match self {
+            Gamma::Linear => v,
+            Gamma::Srgb if v <= 0.003_130_8 => v * 12.92,
+            Gamma::Srgb => 1.055 * v.powf(1. / 2.4) - 0.055,
+            Gamma::Power(gamma) => v.powf(1. / gamma),
+        }
+    }
+
+    /// Decode an encoded value from `0` to `1` back to linear. The inverse of [`encode`].
+    ///
+    /// [`encode`]: #method.encode
+    pub fn decode(self, v: f64) -> f64 {
+        let v = v.clamp(0., 1.);
+        match self {
+            Gamma::Linear => v,
+            Gamma::Srgb if v <= 0.040_45 => v / 12.92,
+            Gamma::Srgb => ((v + 0.055) / 1.055).powf(2.4),
+            Gamma::Power(gamma) => v.powf(gamma),
+        }
+    }
+
+    /// [`encode`] every channel of a color.
+    ///
+    /// [`encode`]: #method.encode
+    pub fn encode_rgb(self, color: Rgb) -> Rgb {
+        let [red, green, blue] = self.encode_channels(color.channels(), 255);
+        Rgb::new(red as u8, green as u8, blue as u8)
+    }
+
+    /// [`decode`] every channel of a color. Useful to convert colors picked from an image or a color picker, which are
+    /// encoded, to the linear values to draw with.
+    ///
+    /// [`decode`]: #method.decode
+    pub fn decode_rgb(self, color: Rgb) -> Rgb {
+        let decode = |c: u8| (self.decode(c as f64 / 255.) * 255.).round() as u8;
+        Rgb::new(decode(color.red), decode(color.green), decode(color.blue))
+    }
+
+    /// [`encode`] channels with a max value of `max`.
+    ///
+    /// [`encode`]: #method.encode
+    pub(crate) fn encode_channels(self, channels: [u16; 3], max: u16) -> [u16; 3] {
+        let max = max as f64;
+        let encode = |c: u16| (self.encode(c as f64 / max) * max).round() as u16;
+        [
+            encode(channels[0]),
+            encode(channels[1]),
+            encode(channels[2]),
+        ]
+    }
+}
+
+/// Channel by channel, saturating at `255`.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::prelude::*;
+///
+/// let ambient = Rgb::new(50, 50, 50);
+/// let diffuse = Rgb::new(100, 220, 0);
+/// assert_eq!(Rgb::new(150, 255, 50), ambient + diffuse);
+/// ```
+impl Add for Rgb {
+    type Output = Rgb;
+
+    fn add(self, other: Rgb) -> Rgb {
+        Rgb::new(
+            self.red.saturating_add(other.red),
+            self.green.saturating_add(other.green),
+            self.blue.saturating_add(other.blue),
+        )
+    }
+}
+
+impl AddAssign for Rgb {
+    fn add_assign(&mut self, other: Rgb) {
+        *self = *self + other;
+    }
+}
+
+/// Channel by channel, saturating at `0`.
+impl Sub for Rgb {
+    type Output = Rgb;
+
+    fn sub(self, other: Rgb) -> Rgb {
+        Rgb::new(
+            self.red.saturating_sub(other.red),
+            self.green.saturating_sub(other.green),
+            self.blue.saturating_sub(other.blue),
+        )
+    }
+}
+
+impl SubAssign for Rgb {
+    fn sub_assign(&mut self, other: Rgb) {
+        *self = *self - other;
+    }
+}
+
+/// Scale every channel, rounding and saturating at `0` and `255`.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::prelude::*;
+///
+/// assert_eq!(Rgb::new(50, 100, 128), Rgb::new(100, 200, 255) * 0.5);
+/// assert_eq!(Rgb::new(255, 255, 0), Rgb::new(200, 130, 0) * 2.);
+/// ```
+impl Mul<f64> for Rgb {
+    type Output = Rgb;
+
+    fn mul(self, scale: f64) -> Rgb {
+        let channel = |c: u8| (c as f64 * scale).round() as u8;
+        Rgb::new(channel(self.red), channel(self.green), channel(self.blue))
+    }
+}
+
+impl Mul<Rgb> for f64 {
+    type Output = Rgb;
+
+    fn mul(self, color: Rgb) -> Rgb {
+        color * self
+    }
+}
+
+impl MulAssign<f64> for Rgb {
+    fn mul_assign(&mut self, scale: f64) {
+        *self = *self * scale;
+    }
+}
+
+/// Modulate two colors channel by channel, with `255` as `1`, like light of one color reflecting off a surface of the
+/// other.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::prelude::*;
+///
+/// assert_eq!(Rgb::new(255, 0, 0), Rgb::WHITE * Rgb::RED);
+/// assert_eq!(Rgb::new(128, 0, 0), Rgb::GRAY * Rgb::RED);
+/// ```
+impl Mul for Rgb {
+    type Output = Rgb;
+
+    fn mul(self, other: Rgb) -> Rgb {
+        let channel = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+        Rgb::new(
+            channel(self.red, other.red),
+            channel(self.green, other.green),
+            channel(self.blue, other.blue),
+        )
+    }
+}
+
+impl From<Rgb> for Rgba {
+    /// An opaque [`Rgba`].
+    ///
+    /// [`Rgba`]: ./struct.Rgba.html
+    fn from(rgb: Rgb) -> Self {
+        Rgba::new(rgb.red, rgb.green, rgb.blue, 255)
+    }
+}
+
+impl Color for Rgb {
+    fn from_rgb(rgb: Rgb) -> Self {
+        rgb
+    }
+
+    fn mix(self, other: Self, t: f64) -> Self {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgb::new(
+            mix(self.red, other.red),
+            mix(self.green, other.green),
+            mix(self.blue, other.blue),
+        )
+    }
+}
+
+/// 8 bit grayscale, converted from [`Rgb`] with Rec. 601 luma.
+///
+/// [`Rgb`]: ./struct.Rgb.html
+impl Color for u8 {
+    fn from_rgb(rgb: Rgb) -> Self {
+        luma(rgb).round() as u8
+    }
+
+    fn mix(self, other: Self, t: f64) -> Self {
+        (self as f64 + (other as f64 - self as f64) * t).round() as u8
+    }
+}
+
+/// A color with red, green and blue channels of up to 16 bits, which is what a [`Ppm`] stores.
+///
+/// [`Ppm`]: ../ppm/struct.Ppm.html
+pub trait PpmColor: Color {
+    /// Whether this color type has an alpha channel, which formats that support it (like PAM) write.
+    const HAS_ALPHA: bool = false;
+
+    /// The red, green and blue channels.
+    fn channels(self) -> [u16; 3];
+
+    /// Make a color out of red, green and blue channels. Channels too large for the color type are clamped.
+    fn from_channels(channels: [u16; 3]) -> Self;
+
+    /// Opacity, from `0` (transparent) to `255` (opaque). Always opaque for color types without an alpha channel.
+    fn alpha(self) -> u8 {
+        255
+    }
+
+    /// This color with an opacity of `alpha`. Ignored for color types without an alpha channel.
+    fn with_alpha(self, _alpha: u8) -> Self {
+        self
+    }
+}
+
+impl PpmColor for Rgb {
+    fn channels(self) -> [u16; 3] {
+        [self.red as u16, self.green as u16, self.blue as u16]
+    }
+
+    fn from_channels([red, green, blue]: [u16; 3]) -> Self {
+        let channel = |c: u16| c.min(255) as u8;
+        Rgb::new(channel(red), channel(green), channel(blue))
+    }
+}
+
+impl PpmColor for Rgb16 {
+    fn channels(self) -> [u16; 3] {
+        [self.red, self.green, self.blue]
+    }
+
+    fn from_channels([red, green, blue]: [u16; 3]) -> Self {
+        Rgb16::new(red, green, blue)
+    }
+}
+
+/// Written as [`Rgb`] to formats without transparency. [`from_channels`] makes an opaque color.
+///
+/// [`Rgb`]: ./struct.Rgb.html
+/// [`from_channels`]: ./trait.PpmColor.html#tymethod.from_channels
+impl PpmColor for Rgba {
+    const HAS_ALPHA: bool = true;
+
+    fn channels(self) -> [u16; 3] {
+        self.rgb().channels()
+    }
+
+    fn from_channels(channels: [u16; 3]) -> Self {
+        Rgb::from_channels(channels).into()
+    }
+
+    fn alpha(self) -> u8 {
+        self.alpha
+    }
+
+    fn with_alpha(self, alpha: u8) -> Self {
+        Rgba { alpha, ..self }
+    }
+}
+
+/// Converted from [`Rgb`] by scaling channels to 16 bits.
+///
+/// [`Rgb`]: ./struct.Rgb.html
+impl Color for Rgb16 {
+    fn from_rgb(rgb: Rgb) -> Self {
+        rgb.into()
+    }
+
+    fn mix(self, other: Self, t: f64) -> Self {
+        let mix = |a: u16, b: u16| (a as f64 + (b as f64 - a as f64) * t).round() as u16;
+        Rgb16::new(
+            mix(self.red, other.red),
+            mix(self.green, other.green),
+            mix(self.blue, other.blue),
+        )
+    }
+}
+
+/// Opaque when converted from [`Rgb`]. Mixing interpolates all 4 channels.
+///
+/// [`Rgb`]: ./struct.Rgb.html
+impl Color for Rgba {
+    fn from_rgb(rgb: Rgb) -> Self {
+        rgb.into()
+    }
+
+    fn mix(self, other: Self, t: f64) -> Self {
+        let rgb = self.rgb().mix(other.rgb(), t);
+        let alpha = self.alpha.mix(other.alpha, t);
+        Rgba::new(rgb.red, rgb.green, rgb.blue, alpha)
+    }
+}
+
+/// Intensity, converted from [`Rgb`] with Rec. 601 luma scaled to `[0, 1]`.
+///
+/// [`Rgb`]: ./struct.Rgb.html
+impl Color for f32 {
+    fn from_rgb(rgb: Rgb) -> Self {
+        (luma(rgb) / 255.) as f32
+    }
+
+    fn mix(self, other: Self, t: f64) -> Self {
+        self + (other - self) * t as f32
+    }
+}
+
+/// Intensity, converted from [`Rgb`] with Rec. 601 luma scaled to `[0, 1]`.
+///
+/// [`Rgb`]: ./struct.Rgb.html
+impl Color for f64 {
+    fn from_rgb(rgb: Rgb) -> Self {
+        luma(rgb) / 255.
+    }
+
+    fn mix(self, other: Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+/// Rec. 601 luma, from `0` to `255`.
+fn luma(c: Rgb) -> f64 {
+    0.299 * c.red as f64 + 0.587 * c.green as f64 + 0.114 * c.blue as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hsv_round_trip() {
+        for &color in Rgb::NAMED.iter().map(|(_, c)| c) {
+            let (h, s, v) = color.to_hsv();
+            assert_eq!(color, Rgb::from_hsv(h, s, v), "{:?}", color);
+            let (h, s, l) = color.to_hsl();
+            assert_eq!(color, Rgb::from_hsl(h, s, l), "{:?}", color);
+        }
+    }
+
+    #[test]
+    fn test_hsv_out_of_range() {
+        // hue wraps around, the rest is clamped
+        assert_eq!(
+            Rgb::from_hsv(120., 1., 1.),
+            Rgb::from_hsv(120. + 360. * 5., 2., 7.)
+        );
+        assert_eq!(Rgb::from_hsv(240., 0., 0.), Rgb::from_hsv(-120., -1., -1.));
+        assert_eq!(Rgb::WHITE, Rgb::from_hsl(0., 1., 3.));
+        assert_eq!(Rgb::BLACK, Rgb::from_hsl(0., 1., -3.));
+        assert_eq!(Rgb::from_hsv(359.999, 1., 1.), Rgb::RED);
+    }
+
+    #[test]
+    fn test_hsv_nan() {
+        // garbage, but no panic
+        Rgb::from_hsv(f64::NAN, 1., 1.);
+        Rgb::from_hsv(0., f64::NAN, f64::NAN);
+        Rgb::from_hsl(f64::NAN, f64::NAN, f64::NAN);
+        assert_eq!(Rgb::BLACK, Rgb::from_hsv(f64::INFINITY, 1., 0.));
+    }
+
+    #[test]
+    fn test_grays_have_no_hue() {
+        for &depth in [0, 1, 128, 254, 255].iter() {
+            let (h, s, _) = Rgb::gray(depth).to_hsv();
+            assert_eq!((0., 0.), (h, s));
+            let (h, s, _) = Rgb::gray(depth).to_hsl();
+            assert_eq!((0., 0.), (h, s));
+        }
+    }
+
+    #[test]
+    fn test_lerp() {
+        let (a, b) = (Rgb::new(10, 100, 200), Rgb::new(20, 50, 0));
+        assert_eq!(a, Rgb::lerp(a, b, 0.));
+        assert_eq!(b, Rgb::lerp(a, b, 1.));
+        // saturating when extrapolating
+        assert_eq!(Rgb::new(0, 255, 255), Rgb::lerp(a, b, -10.));
+        assert_eq!(Rgb::new(255, 0, 0), Rgb::lerp(a, b, 100.));
+        assert_eq!(Rgb::BLACK, Rgb::lerp(a, b, f64::NAN));
+    }
+
+    #[test]
+    fn test_arithmetic_saturates() {
+        assert_eq!(Rgb::WHITE, Rgb::GRAY + Rgb::SILVER);
+        assert_eq!(Rgb::BLACK, Rgb::GRAY - Rgb::SILVER);
+        assert_eq!(Rgb::WHITE, Rgb::GRAY * 1e10);
+        assert_eq!(Rgb::BLACK, Rgb::GRAY * -1.);
+        assert_eq!(Rgb::BLACK, Rgb::GRAY * f64::NAN);
+        assert_eq!(Rgb::GRAY, Rgb::GRAY * Rgb::WHITE);
+        assert_eq!(Rgb::BLACK, Rgb::GRAY * Rgb::BLACK);
+    }
+
+    #[test]
+    fn test_over() {
+        let color = Rgba::new(10, 20, 30, 255);
+        assert_eq!(color.rgb(), color.over(Rgb::WHITE));
+        assert_eq!(Rgb::WHITE, color.with_alpha(0).over(Rgb::WHITE));
+        assert_eq!(Rgba::new(1, 2, 3, 255), Rgba::from_channels([1, 2, 3]));
+        assert_eq!(Rgb::WHITE, Rgb::from_channels([300, 65535, 255]));
+    }
+
+    #[test]
+    fn test_gamma() {
+        for &gamma in [Gamma::Linear, Gamma::Srgb, Gamma::Power(2.2)].iter() {
+            let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+            assert!(close(0., gamma.encode(0.)));
+            assert!(close(1., gamma.encode(1.)));
+            // clamped
+            assert!(close(0., gamma.encode(-1.)));
+            assert!(close(1., gamma.decode(2.)));
+            for i in 0..=255 {
+                let v = i as f64 / 255.;
+                assert!(
+                    (v - gamma.decode(gamma.encode(v))).abs() < 1e-9,
+                    "{:?} {}",
+                    gamma,
+                    v
+                );
+            }
+            assert_eq!(Rgb::WHITE, gamma.encode_rgb(Rgb::WHITE));
+            assert_eq!(Rgb::BLACK, gamma.decode_rgb(Rgb::BLACK));
+        }
+        // the sRGB curve is continuous where its 2 pieces meet
+        let (below, above) = (
+            Gamma::Srgb.encode(0.003_130_7),
+            Gamma::Srgb.encode(0.003_130_9),
+        );
+        assert!((above - below).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_grayscale_colors() {
+        assert_eq!(255, u8::from_rgb(Rgb::WHITE));
+        assert_eq!(0, u8::from_rgb(Rgb::BLACK));
+        assert_eq!(1., f64::from_rgb(Rgb::WHITE));
+        assert_eq!(Rgb16::new(257, 0, 65535), Rgb16::from(Rgb::new(1, 0, 255)));
+        assert_eq!(Rgb::new(1, 0, 255), Rgb16::new(257, 0, 65535).to_rgb());
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Some(Rgb::RED), Rgb::from_name("RED"));
+        assert_eq!(None, Rgb::from_name(""));
+        assert_eq!(None, Rgb::from_name("red "));
+        for (name, color) in Rgb::NAMED.iter() {
+            assert_eq!(Some(*color), Rgb::from_name(name));
+        }
+    }
 }
@@ -0,0 +1,261 @@
+//! Color maps, to turn numbers into colors.
+//!
+//! A [`ColorMap`] maps a value `t` from `0` to `1` to an [`Rgb`], by interpolating between color stops. This is how
+//! fractals, height maps and depth buffers are usually visualized: compute a number for each pixel, scale it to `[0, 1]`,
+//! and look up its color.
+//!
+//! A few standard maps are built in. [`viridis`] and [`plasma`] are perceptually uniform (equal steps in `t` look like
+//! equal steps in color) and stay readable in grayscale, which makes them a better default than [`rainbow`].
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{colormap::ColorMap, prelude::*};
+//!
+//! let mut img = PpmBuilder::new(256, 50, 255).build();
+//! let map = ColorMap::viridis();
+//! for x in 0..256 {
+//!     let color = map.get(x as f64 / 255.);
+//!     img.draw_line((x as f64, 0., 0.), (x as f64, 49., 0.), color);
+//! }
+//!
+//! // or your own map
+//! let fire = ColorMap::new(&[(0., Rgb::BLACK), (0.4, Rgb::RED), (0.8, Rgb::YELLOW), (1., Rgb::WHITE)]);
+//! assert_eq!(Rgb::RED, fire.get(0.4));
+//! ```
+//!
+//! [`ColorMap`]: ./struct.ColorMap.html
+//! [`Rgb`]: ../color/struct.Rgb.html
+//! [`viridis`]: ./struct.ColorMap.html#method.viridis
+//! [`plasma`]: ./struct.ColorMap.html#method.plasma
+//! [`rainbow`]: ./struct.ColorMap.html#method.rainbow
+
+use crate::color::{Color, Rgb};
+
+/// A map from `[0, 1]` to colors, made of color stops.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColorMap {
+    /// Color stops, sorted by position.
+    stops: Vec<(f64, Rgb)>,
+}
+
+impl ColorMap {
+    /// A color map with the given stops, each a position from `0` to `1` and the color at that position.
+    ///
+    /// Stops can be in any order. Values before the first stop or after the last one get the color of that stop. Stops
+    /// at a NaN position are ignored.
+    pub fn new(stops: &[(f64, Rgb)]) -> Self {
+        let mut stops: Vec<_> = stops
+            .iter()
+            .copied()
+            .filter(|(position, _)| !position.is_nan())
+            .collect();
+        stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        ColorMap { stops }
+    }
+
+    /// A color map going through `colors`, evenly spaced from `0` to `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{colormap::ColorMap, prelude::*};
+    ///
+    /// let map = ColorMap::from_colors(&[Rgb::BLUE, Rgb::WHITE, Rgb::RED]);
+    /// assert_eq!(Rgb::WHITE, map.get(0.5));
+    /// ```
+    pub fn from_colors(colors: &[Rgb]) -> Self {
+        let last = colors.len().saturating_sub(1).max(1) as f64;
+        let stops = colors
+            .iter()
+            .enumerate()
+            .map(|(i, &color)| (i as f64 / last, color))
+            .collect();
+        ColorMap { stops }
+    }
+
+    /// From black to white.
+    pub fn grayscale() -> Self {
+        Self::from_colors(&[Rgb::BLACK, Rgb::WHITE])
+    }
+
+    /// Hue going around the color wheel, from red through yellow, green, cyan and blue to magenta.
+    ///
+    /// Stops before getting back to red, so that `0` and `1` are different.
+    pub fn rainbow() -> Self {
+        Self::from_colors(&[
+            Rgb::RED,
+            Rgb::YELLOW,
+            Rgb::LIME,
+            Rgb::CYAN,
+            Rgb::BLUE,
+            Rgb::MAGENTA,
+        ])
+    }
+
+    /// Matplotlib's default color map, from dark purple through teal to yellow.
+    pub fn viridis() -> Self {
+        Self::from_colors(&[
+            Rgb::new(68, 1, 84),
+            Rgb::new(71, 45, 123),
+            Rgb::new(59, 82, 139),
+            Rgb::new(44, 114, 142),
+            Rgb::new(33, 145, 140),
+            Rgb::new(40, 174, 128),
+            Rgb::new(94, 201, 98),
+            Rgb::new(173, 220, 48),
+            Rgb::new(253, 231, 37),
+        ])
+    }
+
+    /// From dark blue through magenta and orange to yellow.
+    pub fn plasma() -> Self {
+        Self::from_colors(&[
+            Rgb::new(13, 8, 135),
+            Rgb::new(75, 3, 161),
+            Rgb::new(125, 3, 168),
+            Rgb::new(168, 34, 150),
+            Rgb::new(203, 70, 121),
+            Rgb::new(229, 107, 93),
+            Rgb::new(248, 148, 65),
+            Rgb::new(253, 195, 40),
+            Rgb::new(240, 249, 33),
+        ])
+    }
+
+    /// The same map, going the other way.
+    pub fn reversed(&self) -> Self {
+        let stops = self
+            .stops
+            .iter()
+            .rev()
+            .map(|&(position, color)| (1. - position, color))
+            .collect();
+        ColorMap { stops }
+    }
+
+    /// The color stops, sorted by position.
+    pub fn stops(&self) -> &[(f64, Rgb)] {
+        &self.stops
+    }
+
+    /// Color at `t`, interpolated between the stops around it. A map without stops is black everywhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{colormap::ColorMap, prelude::*};
+    ///
+    /// let map = ColorMap::grayscale();
+    /// assert_eq!(Rgb::BLACK, map.get(-1.));
+    /// assert_eq!(Rgb::gray(64), map.get(0.25));
+    /// assert_eq!(Rgb::WHITE, map.get(f64::INFINITY));
+    /// ```
+    pub fn get(&self, t: f64) -> Rgb {
+        let next = self.stops.partition_point(|&(position, _)| position <= t);
+        match (self.stops.get(next.wrapping_sub(1)), self.stops.get(next)) {
+            (Some(&(p0, c0)), Some(&(p1, c1))) => c0.mix(c1, (t - p0) / (p1 - p0)),
+            (Some(&(_, c)), None) | (None, Some(&(_, c))) => c,
+            (None, None) => Rgb::BLACK,
+        }
+    }
+
+    /// Color of `value` in the range from `min` to `max`, which is scaled to `[0, 1]` before looking it up with [`get`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{colormap::ColorMap, prelude::*};
+    ///
+    /// // depth buffer visualization
+    /// let map = ColorMap::plasma();
+    /// let (near, far) = (100., -100.);
+    /// assert_eq!(map.get(0.5), map.get_in_range(0., near, far));
+    /// ```
+    ///
+    /// [`get`]: #method.get
+    pub fn get_in_range(&self, value: f64, min: f64, max: f64) -> Rgb {
+        if min == max {
+            return self.get(0.);
+        }
+        self.get((value - min) / (max - min))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::PpmColor;
+
+    #[test]
+    fn test_get() {
+        let map = ColorMap::new(&[(1., Rgb::WHITE), (0., Rgb::BLACK), (0.5, Rgb::RED)]);
+        assert_eq!(Rgb::BLACK, map.get(0.));
+        assert_eq!(Rgb::RED, map.get(0.5));
+        assert_eq!(Rgb::WHITE, map.get(1.));
+        assert_eq!(Rgb::new(128, 0, 0), map.get(0.25));
+        // clamped to the ends
+        assert_eq!(Rgb::BLACK, map.get(-5.));
+        assert_eq!(Rgb::WHITE, map.get(5.));
+        assert_eq!(Rgb::WHITE, map.get(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_degenerate_maps() {
+        assert_eq!(Rgb::BLACK, ColorMap::new(&[]).get(0.5));
+        assert_eq!(Rgb::BLACK, ColorMap::from_colors(&[]).get(0.5));
+
+        let single = ColorMap::from_colors(&[Rgb::RED]);
+        assert_eq!(&[(0., Rgb::RED)], single.stops());
+        assert_eq!(Rgb::RED, single.get(0.));
+        assert_eq!(Rgb::RED, single.get(1.));
+
+        // 2 stops at the same position are a hard edge
+        let edge = ColorMap::new(&[(0.5, Rgb::BLACK), (0.5, Rgb::WHITE)]);
+        assert_eq!(Rgb::BLACK, edge.get(0.49));
+        assert_eq!(Rgb::WHITE, edge.get(0.5));
+    }
+
+    #[test]
+    fn test_nan() {
+        let map = ColorMap::grayscale();
+        assert_eq!(Rgb::BLACK, map.get(f64::NAN));
+        assert_eq!(Rgb::BLACK, map.get_in_range(f64::NAN, 0., 1.));
+
+        // NaN stops are ignored
+        let map = ColorMap::new(&[(f64::NAN, Rgb::RED), (0., Rgb::BLACK), (1., Rgb::WHITE)]);
+        assert_eq!(Rgb::BLACK, map.get(0.));
+        assert_eq!(Rgb::WHITE, map.get(1.));
+    }
+
+    #[test]
+    fn test_get_in_range() {
+        let map = ColorMap::grayscale();
+        assert_eq!(Rgb::gray(128), map.get_in_range(15., 10., 20.));
+        // reversed ranges work too
+        assert_eq!(Rgb::WHITE, map.get_in_range(10., 20., 10.));
+        // an empty range is the start of the map
+        assert_eq!(Rgb::BLACK, map.get_in_range(7., 3., 3.));
+    }
+
+    #[test]
+    fn test_reversed() {
+        for map in [ColorMap::viridis(), ColorMap::plasma(), ColorMap::rainbow()].iter() {
+            let reversed = map.reversed();
+            for i in 0..=10 {
+                let t = i as f64 / 10.;
+                let (a, b) = (map.get(t), reversed.get(1. - t));
+                // up to rounding
+                let difference = (a - b) + (b - a);
+                assert!(
+                    difference.channels().iter().all(|&c| c <= 1),
+                    "{:?} {:?}",
+                    a,
+                    b
+                );
+            }
+            let colors = |map: &ColorMap| map.stops().iter().map(|&(_, c)| c).collect::<Vec<_>>();
+            assert_eq!(colors(map), colors(&reversed.reversed()));
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Sun position and color over the course of a day, for day-night cycle animations.
+//!
+//! Time of day is a normalized parameter `t`, where `0.` is midnight, `0.25` is sunrise, `0.5` is noon and `0.75` is sunset.
+//! Values outside of `[0, 1)` wrap around, so `t` can simply be `frame as f64 / frames_per_day as f64`.
+//!
+//! The sun rises on the left (-x), peaks above the scene at noon, and sets on the right (+x). Its path is tilted towards the
+//! viewer (+z), so that faces pointing at the camera are lit around noon.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::daylight::Sun;
+//!
+//! let frames = 120;
+//! for frame in 0..frames {
+//!     let sun = Sun::at(frame as f64 / frames as f64);
+//!     // use sun.direction and sun.light_color() for a directional light...
+//! }
+//!
+//! let noon = Sun::at(0.5);
+//! assert!(noon.elevation > 45.);
+//! assert_eq!(1., noon.intensity);
+//!
+//! let midnight = Sun::at(0.);
+//! assert_eq!(0., midnight.intensity);
+//! ```
+
+use std::f64::consts::TAU;
+
+use crate::{
+    color::Rgb,
+    util::{Float, Vec3},
+};
+
+/// How much the sun's path leans towards the viewer, in degrees.
+const TILT_DEGREES: f64 = 30.;
+
+/// Color temperature of the sun right at the horizon, in Kelvin.
+const HORIZON_KELVIN: f64 = 2000.;
+
+/// Color temperature of the sun at noon, in Kelvin.
+const NOON_KELVIN: f64 = 6500.;
+
+/// State of the sun at some time of day.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Sun {
+    /// Unit vector pointing from the scene towards the sun.
+    pub direction: Vec3,
+    /// Elevation above the horizon, in degrees. Negative at night.
+    pub elevation: f64,
+    /// Color temperature, in Kelvin.
+    pub temperature: f64,
+    /// Color of the sunlight at full intensity.
+    pub color: Rgb,
+    /// Brightness in `[0, 1]`. Fades out around sunrise and sunset and is `0` at night.
+    pub intensity: f64,
+}
+
+impl Sun {
+    /// Compute the sun at normalized time of day `t`.
+    pub fn at(t: f64) -> Sun {
+        let t = t.rem_euclid(1.);
+        // angle along the sun's path, 0 at sunrise and PI at sunset
+        let (sin, cos) = ((t - 0.25) * TAU).sin_cos();
+        let (tilt_sin, tilt_cos) = TILT_DEGREES.to_radians().sin_cos();
+
+        let (x, y, z) = (-cos, sin * tilt_cos, sin * tilt_sin);
+        let direction = Vec3::new(x as Float, y as Float, z as Float);
+        let elevation = y.asin().to_degrees();
+
+        // 0 at the horizon and 1 at noon
+        let height = (y / tilt_cos).max(0.);
+        let temperature = HORIZON_KELVIN + (NOON_KELVIN - HORIZON_KELVIN) * height.sqrt();
+
+        Sun {
+            direction,
+            elevation,
+            temperature,
+            color: color_temperature(temperature),
+            intensity: smoothstep(-0.05, 0.2, y),
+        }
+    }
+
+    /// The sun's [`color`] scaled by its [`intensity`].
+    ///
+    /// [`color`]: #structfield.color
+    /// [`intensity`]: #structfield.intensity
+    pub fn light_color(&self) -> Rgb {
+        let scale = |c: u8| (c as f64 * self.intensity).round() as u8;
+        Rgb::new(
+            scale(self.color.red),
+            scale(self.color.green),
+            scale(self.color.blue),
+        )
+    }
+}
+
+/// Approximate the color of a black body at `kelvin` degrees.
+///
+/// Uses Tanner Helland's curve fit, which is good enough for lighting between 1000K and 40000K.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::{daylight::color_temperature, prelude::*};
+///
+/// // candle light is orange
+/// let candle = color_temperature(1900.);
+/// assert!(candle.red > candle.green && candle.green > candle.blue);
+///
+/// // 6600K daylight is white
+/// assert_eq!(Rgb::WHITE, color_temperature(6600.));
+/// ```
+pub fn color_temperature(kelvin: f64) -> Rgb {
+    let t = kelvin.clamp(1000., 40000.) / 100.;
+    let clamp = |c: f64| c.round().clamp(0., 255.) as u8;
+
+    let red = if t <= 66. {
+        255.
+    } else {
+        329.698_727_446 * (t - 60.).powf(-0.133_204_759_2)
+    };
+    let green = if t <= 66. {
+        99.470_802_586_1 * t.ln() - 161.119_568_166_1
+    } else {
+        288.122_169_528_3 * (t - 60.).powf(-0.075_514_849_2)
+    };
+    let blue = if t >= 66. {
+        255.
+    } else if t <= 19. {
+        0.
+    } else {
+        138.517_731_223_1 * (t - 10.).ln() - 305.044_792_730_7
+    };
+
+    Rgb::new(clamp(red), clamp(green), clamp(blue))
+}
+
+/// Hermite interpolation from 0 to 1 as `x` goes from `edge0` to `edge1`.
+fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        for i in 0..100 {
+            let sun = Sun::at(i as f64 / 100.);
+            assert!((sun.direction.length() - 1.).abs() < 1e-5);
+            assert!((0. ..=1.).contains(&sun.intensity));
+            assert!((HORIZON_KELVIN..=NOON_KELVIN).contains(&sun.temperature));
+        }
+
+        let (sunrise, noon, sunset) = (Sun::at(0.25), Sun::at(0.5), Sun::at(0.75));
+        assert!(sunrise.elevation.abs() < 1e-9 && sunset.elevation.abs() < 1e-9);
+        assert!(sunrise.direction.x < -0.99 && sunset.direction.x > 0.99);
+        assert!((noon.elevation - (90. - TILT_DEGREES)).abs() < 1e-9);
+        assert_eq!(NOON_KELVIN, noon.temperature);
+        assert_eq!(HORIZON_KELVIN, sunrise.temperature);
+        assert!(Sun::at(0.).elevation < -45.);
+    }
+
+    #[test]
+    fn test_wraps_around() {
+        for &t in [0., 0.3, 0.5, 0.9].iter() {
+            for &other in [Sun::at(t + 3.), Sun::at(t - 1.)].iter() {
+                assert!((Sun::at(t).elevation - other.elevation).abs() < 1e-9);
+                assert_eq!(Sun::at(t).color, other.color);
+            }
+        }
+    }
+
+    #[test]
+    fn test_light_color() {
+        assert_eq!(Rgb::BLACK, Sun::at(0.).light_color());
+        let noon = Sun::at(0.5);
+        assert_eq!(noon.color, noon.light_color());
+        // dimmer, and redder, close to the horizon
+        let dawn = Sun::at(0.26);
+        assert!(0. < dawn.intensity && dawn.intensity < 1.);
+        assert!(dawn.light_color().red < dawn.color.red);
+        assert!(dawn.color.blue < noon.color.blue);
+    }
+
+    #[test]
+    fn test_color_temperature() {
+        // warmer to cooler: blue goes up, red goes down
+        let colors: Vec<Rgb> = (10..=100)
+            .map(|k| color_temperature(k as f64 * 100.))
+            .collect();
+        for pair in colors.windows(2) {
+            assert!(
+                pair[0].blue <= pair[1].blue && pair[0].red >= pair[1].red,
+                "{:?}",
+                pair
+            );
+        }
+        // clamped to the range the fit works for
+        assert_eq!(color_temperature(1000.), color_temperature(0.));
+        assert_eq!(color_temperature(1000.), color_temperature(-500.));
+        assert_eq!(color_temperature(40000.), color_temperature(1e9));
+    }
+
+    #[test]
+    fn test_nan() {
+        // garbage, but no panic
+        Sun::at(f64::NAN).light_color();
+        Sun::at(f64::INFINITY).light_color();
+        color_temperature(f64::NAN);
+    }
+}
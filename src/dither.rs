@@ -0,0 +1,169 @@
+//! Color quantization, with optional Floyd–Steinberg dithering.
+//!
+//! Writing an image with fewer colors than it was drawn with, like a lower color depth or a fixed palette for a gif,
+//! turns smooth gradients into visible bands. Dithering spreads the rounding error of each pixel to its neighbours, so
+//! that on average the colors come out right, and bands turn into a fine noise instead.
+//!
+//! Both functions return a new [`Ppm`], ready to be written or saved.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{dither::{self, Dither}, gradient::Gradient, prelude::*};
+//!
+//! let mut img = PpmBuilder::new(256, 256, 255).build();
+//! img.fill_rect(
+//!     (0., 0., 0.),
+//!     256.,
+//!     256.,
+//!     &Gradient::linear((0., 0.), (256., 0.)).stop(0., Rgb::BLACK).stop(1., Rgb::WHITE),
+//! );
+//!
+//! // 8 levels per channel
+//! let reduced = dither::reduce_depth(&img, 7, Dither::FloydSteinberg);
+//! assert_eq!(7, reduced.color_depth());
+//!
+//! // black and white only
+//! let bw = dither::to_palette(&img, &[Rgb::BLACK, Rgb::WHITE], Dither::FloydSteinberg);
+//! assert!(bw.get_pixel(128, 50).is_some_and(|c| c == Rgb::BLACK || c == Rgb::WHITE));
+//! ```
+//!
+//! [`Ppm`]: ../ppm/struct.Ppm.html
+
+use crate::{
+    color::{Gamma, Rgb},
+    ppm::{Ppm, PpmBuilder},
+    screen::Screen,
+};
+
+/// How to handle the error between a pixel and the closest color that can be written.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Dither {
+    /// Just round every pixel to the closest color.
+    None,
+    /// Spread the error to the pixels to the right and below, with weights 7/16, 3/16, 5/16 and 1/16.
+    FloydSteinberg,
+}
+
+/// Reduce `img` to a color depth of `depth` (the max value of a channel), scaling the channels down from `255`.
+///
+/// The result is written as is: [`gamma`] is applied before quantizing, and not again.
+///
+/// [`gamma`]: ../ppm/struct.Ppm.html#structfield.gamma
+pub fn reduce_depth(img: &Ppm, depth: u16, dither: Dither) -> Ppm {
+    let depth = depth.clamp(1, 255);
+    let step = 255. / depth as f32;
+    quantize(img, depth, dither, |[r, g, b]| {
+        let level = |c: f32| (c / step).round().clamp(0., depth as f32);
+        let (r, g, b) = (level(r), level(g), level(b));
+        (
+            Rgb::new(r as u8, g as u8, b as u8),
+            [r * step, g * step, b * step],
+        )
+    })
+}
+
+/// Map every pixel of `img` to the closest color (by euclidean distance) in `palette`. An empty palette gives a black
+/// image.
+///
+/// The result is written as is: [`gamma`] is applied before quantizing, and not again.
+///
+/// [`gamma`]: ../ppm/struct.Ppm.html#structfield.gamma
+pub fn to_palette(img: &Ppm, palette: &[Rgb], dither: Dither) -> Ppm {
+    quantize(img, img.color_depth(), dither, |[r, g, b]| {
+        let distance = |c: &&Rgb| {
+            let (dr, dg, db) = (c.red as f32 - r, c.green as f32 - g, c.blue as f32 - b);
+            dr * dr + dg * dg + db * db
+        };
+        let closest = palette
+            .iter()
+            .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap())
+            .copied()
+            .unwrap_or(Rgb::BLACK);
+        (
+            closest,
+            [
+                closest.red as f32,
+                closest.green as f32,
+                closest.blue as f32,
+            ],
+        )
+    })
+}
+
+/// Quantize every pixel of `img` with `closest`, which returns the color to write and its value on the original
+/// `[0, 255]` scale, to compute the error.
+fn quantize<F>(img: &Ppm, depth: u16, dither: Dither, closest: F) -> Ppm
+where
+    F: Fn([f32; 3]) -> (Rgb, [f32; 3]),
+{
+    let (width, height) = (img.width(), img.height());
+    let mut values: Vec<[f32; 3]> = img
+        .output_data()
+        .iter()
+        .map(|c| [c.red as f32, c.green as f32, c.blue as f32])
+        .collect();
+    let mut data = Vec::with_capacity(values.len());
+
+    // rows in data go from the top of the image, which is the usual scanning order for dithering
+    for y in 0..height {
+        for x in 0..width {
+            let value = values[y * width + x];
+            let (color, quantized) = closest(value);
+            data.push(color);
+
+            if dither == Dither::None {
+                continue;
+            }
+            let error = [
+                value[0] - quantized[0],
+                value[1] - quantized[1],
+                value[2] - quantized[2],
+            ];
+            let neighbours = [(1, 0, 7.), (-1, 1, 3.), (0, 1, 5.), (1, 1, 1.)];
+            for &(dx, dy, weight) in neighbours.iter() {
+                let (nx, ny) = (x as i64 + dx, y + dy);
+                if nx < 0 || nx >= width as i64 || ny >= height {
+                    continue;
+                }
+                let neighbour = &mut values[ny * width + nx as usize];
+                for (channel, error) in neighbour.iter_mut().zip(error.iter()) {
+                    *channel += error * weight / 16.;
+                }
+            }
+        }
+    }
+
+    PpmBuilder::new(height, width, depth)
+        .wrap_x(img.wrap_x)
+        .wrap_y(img.wrap_y)
+        .origin(img.origin)
+        .rasterizer(img.rasterizer)
+        .gamma(Gamma::Linear)
+        .with_data(data)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dither_keeps_average() {
+        // 50% gray, which can only be written as black or white
+        let img = PpmBuilder::new(16, 16, 255)
+            .bg_color(Rgb::gray(128))
+            .build();
+
+        let plain = to_palette(&img, &[Rgb::BLACK, Rgb::WHITE], Dither::None);
+        assert!(plain.output_data().iter().all(|&c| c == Rgb::WHITE));
+
+        let dithered = to_palette(&img, &[Rgb::BLACK, Rgb::WHITE], Dither::FloydSteinberg);
+        let white = dithered
+            .output_data()
+            .iter()
+            .filter(|&&c| c == Rgb::WHITE)
+            .count();
+        assert!((120..=136).contains(&white), "{} white pixels", white);
+    }
+}
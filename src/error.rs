@@ -0,0 +1,166 @@
+//! The error type of this crate.
+//!
+//! Saving, displaying, and reading images return a [`GraphicsError`], which says what went wrong in a way that can be
+//! acted on: ImageMagick missing from the `PATH` is different from ImageMagick failing, and both are different from a
+//! corrupt file. Methods that only write to a writer, like [`write_binary_to_buf`], still return an [`io::Result`].
+//!
+//! A [`GraphicsError`] converts to an [`io::Error`], so `?` still works in functions returning [`io::Result`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use graphics::{error::GraphicsError, prelude::*};
+//!
+//! let img = Ppm::new();
+//! match img.save("img.png") {
+//!     Ok(()) => {}
+//!     Err(GraphicsError::MagickFailed { stderr, .. }) => eprintln!("ImageMagick said: {}", stderr),
+//!     Err(e) => eprintln!("error saving img.png: {}", e),
+//! }
+//! ```
+//!
+//! [`GraphicsError`]: ./enum.GraphicsError.html
+//! [`write_binary_to_buf`]: ../ppm/struct.Ppm.html#method.write_binary_to_buf
+//! [`io::Result`]: https://doc.rust-lang.org/std/io/type.Result.html
+//! [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
+
+use std::{error::Error, fmt, io};
+
+/// Everything that can go wrong when saving, displaying, or reading images. See the [module documentation].
+///
+/// [module documentation]: ./index.html
+#[derive(Debug)]
+pub enum GraphicsError {
+    /// Reading or writing a file or stream failed.
+    Io(io::Error),
+    /// An ImageMagick `command` couldn't be started, usually because ImageMagick isn't installed, or isn't on the
+    /// `PATH`.
+    MagickNotFound { command: String },
+    /// ImageMagick ran, but exited with a non-zero `status` (`None` if it was killed by a signal). `stderr` has its
+    /// error messages.
+    MagickFailed { status: Option<i32>, stderr: String },
+    /// ImageMagick is needed, but this build doesn't support it, because the `magick` feature is disabled.
+    MagickDisabled,
+    /// A file couldn't be read, because it isn't in the expected format or is corrupt.
+    Parse(String),
+    /// Image dimensions or settings that don't make an image, like a width of `0`.
+    InvalidDimensions(String),
+    /// A window couldn't be opened, usually because there is no display.
+    Window(String),
+}
+
+/// A `Result` with a [`GraphicsError`].
+///
+/// [`GraphicsError`]: ./enum.GraphicsError.html
+pub type Result<T> = std::result::Result<T, GraphicsError>;
+
+impl GraphicsError {
+    /// A [`GraphicsError::Parse`] with `message`.
+    ///
+    /// [`GraphicsError::Parse`]: #variant.Parse
+    pub(crate) fn parse(message: &str) -> Self {
+        GraphicsError::Parse(message.to_string())
+    }
+}
+
+impl fmt::Display for GraphicsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphicsError::Io(e) => write!(f, "{}", e),
+            GraphicsError::MagickNotFound { command } => write!(
+                f,
+                "ImageMagick not found (could not run `{}`); install it and add it to the PATH, \
+                 or use save_png to save images without it",
+                command
+            ),
+            GraphicsError::MagickFailed { status, stderr } => {
+                match status {
+                    Some(code) => write!(f, "ImageMagick exited with status {}", code)?,
+                    None => write!(f, "ImageMagick was terminated by a signal")?,
+                }
+                if stderr.trim().is_empty() {
+                    Ok(())
+                } else {
+                    write!(f, ": {}", stderr.trim())
+                }
+            }
+            GraphicsError::MagickDisabled => write!(
+                f,
+                "this needs ImageMagick, but the `magick` feature is disabled; \
+                 use save_png or another built-in format"
+            ),
+            GraphicsError::Parse(message) => write!(f, "invalid file: {}", message),
+            GraphicsError::InvalidDimensions(message) => write!(f, "{}", message),
+            GraphicsError::Window(message) => write!(f, "error opening window: {}", message),
+        }
+    }
+}
+
+impl Error for GraphicsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GraphicsError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GraphicsError {
+    fn from(e: io::Error) -> Self {
+        GraphicsError::Io(e)
+    }
+}
+
+/// Keeps the original error for [`GraphicsError::Io`], and wraps other errors with the closest [`io::ErrorKind`].
+///
+/// [`GraphicsError::Io`]: ./enum.GraphicsError.html#variant.Io
+/// [`io::ErrorKind`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html
+impl From<GraphicsError> for io::Error {
+    fn from(e: GraphicsError) -> Self {
+        let kind = match e {
+            GraphicsError::Io(e) => return e,
+            GraphicsError::MagickNotFound { .. } => io::ErrorKind::NotFound,
+            GraphicsError::MagickFailed { .. } => io::ErrorKind::Other,
+            GraphicsError::MagickDisabled => io::ErrorKind::Unsupported,
+            GraphicsError::Parse(_) => io::ErrorKind::InvalidData,
+            GraphicsError::InvalidDimensions(_) => io::ErrorKind::InvalidInput,
+            GraphicsError::Window(_) => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_conversion() {
+        let e: io::Error = GraphicsError::Parse("not a ppm file".to_string()).into();
+        assert_eq!(io::ErrorKind::InvalidData, e.kind());
+        assert_eq!("invalid file: not a ppm file", e.to_string());
+
+        let original = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+        let e: io::Error = GraphicsError::from(original).into();
+        assert_eq!(io::ErrorKind::PermissionDenied, e.kind());
+
+        let failed = GraphicsError::MagickFailed {
+            status: Some(1),
+            stderr: "convert: no decode delegate\n".to_string(),
+        };
+        assert_eq!(
+            "ImageMagick exited with status 1: convert: no decode delegate",
+            failed.to_string()
+        );
+
+        let window: io::Error = GraphicsError::Window("no display".to_string()).into();
+        assert_eq!("error opening window: no display", window.to_string());
+
+        let not_found = GraphicsError::MagickNotFound {
+            command: "convert".to_string(),
+        };
+        assert!(not_found
+            .to_string()
+            .starts_with("ImageMagick not found (could not run `convert`)"));
+    }
+}
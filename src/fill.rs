@@ -0,0 +1,154 @@
+//! What to fill 2D shapes with: a solid color, a gradient, or a repeating pattern.
+//!
+//! [`fill_rect`] and [`fill_polygon`] accept anything that converts into a [`Fill`], so a plain [`Rgb`], a
+//! [`&Gradient`] or a [`&Ppm`] can be passed directly.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{gradient::Gradient, prelude::*};
+//!
+//! // a 2x2 checkerboard tile
+//! let mut tile = PpmBuilder::new(2, 2, 255).bg_color(Rgb::WHITE).build();
+//! tile.plot(0, 0, 0., Rgb::BLACK);
+//! tile.plot(1, 1, 0., Rgb::BLACK);
+//!
+//! let mut img = Ppm::new();
+//! img.fill_rect((0., 0., 0.), 500., 100., Rgb::new(0, 0, 255));
+//! img.fill_rect((0., 100., 0.), 500., 100., &tile);
+//! img.fill_rect(
+//!     (0., 200., 0.),
+//!     500.,
+//!     100.,
+//!     &Gradient::linear((0., 0.), (500., 0.))
+//!         .stop(0., Rgb::BLACK)
+//!         .stop(1., Rgb::WHITE),
+//! );
+//!
+//! assert_eq!(Some(Rgb::BLACK), img.get_pixel(10, 110));
+//! assert_eq!(Some(Rgb::WHITE), img.get_pixel(11, 110));
+//! ```
+//!
+//! [`fill_rect`]: ../screen/trait.Screen.html#method.fill_rect
+//! [`fill_polygon`]: ../screen/trait.Screen.html#method.fill_polygon
+//! [`Fill`]: ./enum.Fill.html
+//! [`Rgb`]: ../color/struct.Rgb.html
+//! [`&Gradient`]: ../gradient/struct.Gradient.html
+//! [`&Ppm`]: ../ppm/struct.Ppm.html
+
+use crate::{
+    color::{Color, Rgb},
+    gradient::Gradient,
+    ppm::Ppm,
+    screen::Screen,
+};
+
+/// Source of the color of each pixel of a filled shape, on a screen with pixels of color type `C`.
+///
+/// Gradients and patterns are made of [`Rgb`] colors, converted with [`Color::from_rgb`].
+///
+/// [`Rgb`]: ../color/struct.Rgb.html
+/// [`Color::from_rgb`]: ../color/trait.Color.html#tymethod.from_rgb
+#[derive(Copy, Clone)]
+pub enum Fill<'a, C = Rgb> {
+    /// The same color everywhere.
+    Solid(C),
+    /// A [`Gradient`], positioned in screen coordinates.
+    ///
+    /// [`Gradient`]: ../gradient/struct.Gradient.html
+    Gradient(&'a Gradient),
+    /// An image repeated over the whole screen, with its pixel (0, 0) at the screen's (0, 0).
+    ///
+    /// Since the pattern is anchored to the screen and not to the shape, neighbouring shapes filled with the same pattern
+    /// line up seamlessly.
+    Pattern(&'a Ppm),
+}
+
+impl<C: Color> Fill<'_, C> {
+    /// Color of the fill at pixel (`x`, `y`).
+    ///
+    /// An empty pattern is black everywhere.
+    pub fn color_at(&self, x: i64, y: i64) -> C {
+        match self {
+            Fill::Solid(color) => *color,
+            Fill::Gradient(gradient) => C::from_rgb(gradient.color_at(x as f64, y as f64)),
+            Fill::Pattern(pattern) => {
+                let (width, height) = (pattern.width() as i64, pattern.height() as i64);
+                if width == 0 || height == 0 {
+                    return C::from_rgb(Rgb::BLACK);
+                }
+                C::from_rgb(
+                    pattern
+                        .get_pixel(x.rem_euclid(width), y.rem_euclid(height))
+                        .unwrap_or(Rgb::BLACK),
+                )
+            }
+        }
+    }
+}
+
+impl<C: Color> From<C> for Fill<'_, C> {
+    fn from(color: C) -> Self {
+        Fill::Solid(color)
+    }
+}
+
+impl<'a, C> From<&'a Gradient> for Fill<'a, C> {
+    fn from(gradient: &'a Gradient) -> Self {
+        Fill::Gradient(gradient)
+    }
+}
+
+impl<'a, C> From<&'a Ppm> for Fill<'a, C> {
+    fn from(pattern: &'a Ppm) -> Self {
+        Fill::Pattern(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppm::PpmBuilder;
+
+    #[test]
+    fn test_solid() {
+        let fill = Fill::from(Rgb::RED);
+        assert_eq!(Rgb::RED, fill.color_at(0, 0));
+        assert_eq!(Rgb::RED, fill.color_at(i64::MIN, i64::MAX));
+        assert_eq!(0.5, Fill::from(0.5).color_at(3, 4));
+    }
+
+    #[test]
+    fn test_gradient() {
+        let gradient = Gradient::linear((0., 0.), (10., 0.))
+            .stop(0., Rgb::BLACK)
+            .stop(1., Rgb::WHITE);
+        let fill = Fill::from(&gradient);
+        assert_eq!(Rgb::gray(128), fill.color_at(5, 100));
+        assert_eq!(Rgb::WHITE, fill.color_at(100, 0));
+        assert_eq!(255u8, Fill::<u8>::from(&gradient).color_at(10, 0));
+    }
+
+    #[test]
+    fn test_pattern_repeats() {
+        let mut pattern = PpmBuilder::new(2, 3, 255).build();
+        pattern.set(1, 0, Rgb::RED);
+        let fill: Fill = Fill::from(&pattern);
+        assert_eq!(Rgb::RED, fill.color_at(1, 0));
+        assert_eq!(Rgb::RED, fill.color_at(4, 2));
+        assert_eq!(Rgb::RED, fill.color_at(-2, -2));
+        assert_eq!(Rgb::BLACK, fill.color_at(0, 0));
+        // no overflow at the far ends
+        fill.color_at(i64::MIN, i64::MIN);
+        fill.color_at(i64::MAX, i64::MAX);
+    }
+
+    #[test]
+    fn test_single_pixel_pattern() {
+        let mut pattern = PpmBuilder::new(1, 1, 255).build();
+        pattern.set(0, 0, Rgb::RED);
+        let fill: Fill = Fill::from(&pattern);
+        assert_eq!(Rgb::RED, fill.color_at(-7, 12));
+        assert_eq!(Rgb::RED, fill.color_at(i64::MIN, 0));
+    }
+}
@@ -0,0 +1,508 @@
+//! Bitmap fonts for drawing text with [`draw_text`].
+//!
+//! A [`Font`] maps characters to monochrome bitmaps (glyphs) that all fit in the same `width` by `height` cell. A classic
+//! 5x7 font covering printable ASCII is built in, see [`Font::builtin`].
+//!
+//! Other fonts can be loaded from [BDF] or [PSF] (Linux console font) files with [`Font::open`], and drawn with
+//! [`draw_text_with`]. Glyphs can be at most 32 pixels wide.
+//!
+//! [BDF]: https://en.wikipedia.org/wiki/Glyph_Bitmap_Distribution_Format
+//! [PSF]: https://en.wikipedia.org/wiki/PC_Screen_Font
+//! [`Font::open`]: ./struct.Font.html#method.open
+//! [`draw_text_with`]: ../screen/trait.Screen.html#method.draw_text_with
+//! [`draw_text`]: ../screen/trait.Screen.html#method.draw_text
+//! [`Font`]: ./struct.Font.html
+//! [`Font::builtin`]: ./struct.Font.html#method.builtin
+
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    sync::OnceLock,
+};
+
+use crate::error::{self, GraphicsError};
+
+/// A fixed-size bitmap font.
+#[derive(Clone, Debug)]
+pub struct Font {
+    /// Width of a glyph, in pixels. At most 32.
+    width: usize,
+    /// Height of a glyph, in pixels.
+    height: usize,
+    /// Glyph bitmaps, one `u32` per row from top to bottom. The leftmost pixel of a row is bit `width - 1`.
+    glyphs: HashMap<char, Vec<u32>>,
+}
+
+impl Font {
+    /// Characters without a glyph are drawn as this one instead, if the font has it.
+    pub const REPLACEMENT: char = '?';
+
+    /// The built-in 5x7 font, covering printable ASCII (`' '` to `'~'`).
+    pub fn builtin() -> &'static Font {
+        static FONT: OnceLock<Font> = OnceLock::new();
+        FONT.get_or_init(|| {
+            let glyphs = BUILTIN_5X7
+                .iter()
+                .enumerate()
+                .map(|(i, columns)| {
+                    let rows = (0..7)
+                        .map(|row| {
+                            columns
+                                .iter()
+                                .fold(0, |bits, column| (bits << 1) | ((column >> row) & 1) as u32)
+                        })
+                        .collect();
+                    ((b' ' + i as u8) as char, rows)
+                })
+                .collect();
+            Font {
+                width: 5,
+                height: 7,
+                glyphs,
+            }
+        })
+    }
+
+    /// Width of a glyph, in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of a glyph, in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Rows of the glyph for `c`, top to bottom, falling back to [`REPLACEMENT`]. The leftmost pixel of a row is bit
+    /// `width - 1`.
+    ///
+    /// [`REPLACEMENT`]: #associatedconstant.REPLACEMENT
+    pub fn glyph(&self, c: char) -> Option<&[u32]> {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.glyphs.get(&Self::REPLACEMENT))
+            .map(|rows| &rows[..])
+    }
+
+    /// Whether pixel (`col`, `row`) of a glyph is set, counting from the top left.
+    pub(crate) fn is_set(&self, rows: &[u32], col: usize, row: usize) -> bool {
+        rows.get(row)
+            .is_some_and(|bits| bits >> (self.width - 1 - col) & 1 == 1)
+    }
+
+    /// Size (width, height) in pixels of `text` drawn at `scale`, including the 1 pixel gaps between characters and lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::font::Font;
+    ///
+    /// let font = Font::builtin();
+    /// assert_eq!((17, 7), font.text_size("abc", 1));
+    /// assert_eq!((34, 30), font.text_size("abc\nd", 2));
+    /// ```
+    pub fn text_size(&self, text: &str, scale: usize) -> (usize, usize) {
+        let lines = text.split('\n');
+        let (mut columns, mut rows) = (0, 0);
+        for line in lines {
+            columns = columns.max(line.chars().count());
+            rows += 1;
+        }
+        let width = (columns * (self.width + 1)).saturating_sub(1);
+        let height = (rows * (self.height + 1)).saturating_sub(1);
+        (width * scale, height * scale)
+    }
+
+    /// Load a font file, in BDF format if the extension is `.bdf`, or PSF (version 1 or 2) otherwise.
+    ///
+    /// Compressed fonts (`.psf.gz`) have to be decompressed first.
+    pub fn open<P: AsRef<Path>>(path: P) -> error::Result<Font> {
+        let path = path.as_ref();
+        let reader = BufReader::new(File::open(path)?);
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("bdf") => Self::from_bdf(reader),
+            _ => Self::from_psf(reader),
+        }
+    }
+
+    /// Parse a font in the [BDF] text format.
+    ///
+    /// The glyph cell is the `FONTBOUNDINGBOX` of the font, and glyphs are placed in it according to their own `BBX`.
+    /// Glyphs with a negative `ENCODING` are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::font::Font;
+    ///
+    /// let bdf = "\
+    /// STARTFONT 2.1
+    /// FONTBOUNDINGBOX 2 2 0 0
+    /// CHARS 1
+    /// STARTCHAR slash
+    /// ENCODING 47
+    /// BBX 2 2 0 0
+    /// BITMAP
+    /// 40
+    /// 80
+    /// ENDCHAR
+    /// ENDFONT
+    /// ";
+    /// let font = Font::from_bdf(bdf.as_bytes()).unwrap();
+    /// assert_eq!((2, 2), (font.width(), font.height()));
+    /// assert_eq!(Some(&[0b01, 0b10][..]), font.glyph('/'));
+    /// ```
+    ///
+    /// [BDF]: https://en.wikipedia.org/wiki/Glyph_Bitmap_Distribution_Format
+    pub fn from_bdf<R: BufRead>(reader: R) -> error::Result<Font> {
+        let mut bounds = None;
+        let mut glyphs = HashMap::new();
+
+        // state of the glyph being parsed
+        let mut encoding = None;
+        let mut bbx = (0, 0, 0, 0);
+        let mut bitmap: Option<Vec<u32>> = None;
+
+        for line in reader.lines() {
+            let line = line?;
+            let mut words = line.split_whitespace();
+            let keyword = match words.next() {
+                Some(keyword) => keyword,
+                None => continue,
+            };
+            let numbers = || -> error::Result<Vec<i64>> {
+                line.split_whitespace()
+                    .skip(1)
+                    .map(|w| {
+                        w.parse()
+                            .map_err(|_| GraphicsError::parse("invalid number in BDF font"))
+                    })
+                    .collect()
+            };
+
+            if let Some(rows) = bitmap.as_mut() {
+                if keyword == "ENDCHAR" {
+                    let (width, height, x_off, y_off): (i64, i64, i64, i64) =
+                        bounds.ok_or_else(|| {
+                            GraphicsError::parse("missing FONTBOUNDINGBOX in BDF font")
+                        })?;
+                    let (glyph_width, glyph_height, glyph_x, glyph_y) = bbx;
+                    let mut cell = vec![0; height as usize];
+                    // top row of the glyph, counting from the top of the cell
+                    let top = (y_off + height) - (glyph_y + glyph_height);
+                    for (i, bits) in rows.iter().enumerate() {
+                        let row = top + i as i64;
+                        if row < 0 || row >= height {
+                            continue;
+                        }
+                        for col in 0..glyph_width {
+                            let x = glyph_x - x_off + col;
+                            if bits >> (31 - col) & 1 == 1 && x >= 0 && x < width {
+                                cell[row as usize] |= 1 << (width - 1 - x);
+                            }
+                        }
+                    }
+                    if let Some(c) = encoding.take() {
+                        glyphs.insert(c, cell);
+                    }
+                    bitmap = None;
+                } else {
+                    // hex digits, left aligned: the leftmost pixel is the most significant bit
+                    let digits = &keyword[..keyword.len().min(8)];
+                    let bits = u32::from_str_radix(digits, 16)
+                        .map_err(|_| GraphicsError::parse("invalid BITMAP row in BDF font"))?;
+                    rows.push(bits << (32 - 4 * digits.len() as u32));
+                }
+                continue;
+            }
+
+            match keyword {
+                "FONTBOUNDINGBOX" => {
+                    if let [width, height, x_off, y_off] = numbers()?[..] {
+                        if width <= 0 || width > 32 || height <= 0 {
+                            return Err(GraphicsError::parse("unsupported glyph size in BDF font"));
+                        }
+                        bounds = Some((width, height, x_off, y_off));
+                    } else {
+                        return Err(GraphicsError::parse("invalid FONTBOUNDINGBOX in BDF font"));
+                    }
+                }
+                "STARTCHAR" => {
+                    encoding = None;
+                    bbx = bounds.unwrap_or_default();
+                }
+                "ENCODING" => {
+                    encoding = numbers()?
+                        .first()
+                        .and_then(|&code| u32::try_from(code).ok())
+                        .and_then(std::char::from_u32);
+                }
+                "BBX" => {
+                    if let [width, height, x_off, y_off] = numbers()?[..] {
+                        bbx = (width.min(32), height, x_off, y_off);
+                    } else {
+                        return Err(GraphicsError::parse("invalid BBX in BDF font"));
+                    }
+                }
+                "BITMAP" => bitmap = Some(vec![]),
+                _ => {}
+            }
+        }
+
+        let (width, height, _, _) =
+            bounds.ok_or_else(|| GraphicsError::parse("missing FONTBOUNDINGBOX in BDF font"))?;
+        Ok(Font {
+            width: width as usize,
+            height: height as usize,
+            glyphs,
+        })
+    }
+
+    /// Parse a font in the binary [PSF] format, version 1 or 2, as used by the Linux console.
+    ///
+    /// If the font has a unicode table, glyphs are mapped to the characters listed in it. Otherwise, glyph `i` is mapped
+    /// to the character with code point `i`, which is right for most Latin-1 fonts.
+    ///
+    /// [PSF]: https://en.wikipedia.org/wiki/PC_Screen_Font
+    pub fn from_psf<R: Read>(mut reader: R) -> error::Result<Font> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        let truncated = || GraphicsError::parse("truncated PSF font");
+        let u32_at = |i: usize| -> error::Result<usize> {
+            let b = bytes.get(i..i + 4).ok_or_else(truncated)?;
+            Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        };
+
+        // (width, height, glyph count, bytes per glyph, header size, has unicode table, is version 1)
+        let (width, height, count, glyph_size, header_size, has_table, psf1) =
+            if bytes.starts_with(&[0x36, 0x04]) {
+                let mode = *bytes.get(2).ok_or_else(truncated)?;
+                let size = *bytes.get(3).ok_or_else(truncated)? as usize;
+                let count = if mode & 0x01 != 0 { 512 } else { 256 };
+                (8, size, count, size, 4, mode & 0x06 != 0, true)
+            } else if bytes.starts_with(&[0x72, 0xb5, 0x4a, 0x86]) {
+                let (header_size, flags) = (u32_at(8)?, u32_at(12)?);
+                let (count, glyph_size) = (u32_at(16)?, u32_at(20)?);
+                let (height, width) = (u32_at(24)?, u32_at(28)?);
+                (
+                    width,
+                    height,
+                    count,
+                    glyph_size,
+                    header_size,
+                    flags & 0x01 != 0,
+                    false,
+                )
+            } else {
+                return Err(GraphicsError::parse("not a PSF font"));
+            };
+
+        if width == 0 || width > 32 || height == 0 {
+            return Err(GraphicsError::parse("unsupported glyph size in PSF font"));
+        }
+        let row_size = width.div_ceil(8);
+        if glyph_size < row_size * height {
+            return Err(GraphicsError::parse("invalid glyph size in PSF font"));
+        }
+
+        let table_start = header_size + count * glyph_size;
+        let data = bytes.get(header_size..table_start).ok_or_else(truncated)?;
+        let rows: Vec<Vec<u32>> = data
+            .chunks(glyph_size)
+            .map(|glyph| {
+                glyph
+                    .chunks(row_size)
+                    .take(height)
+                    .map(|row| {
+                        let bits = row.iter().fold(0u64, |bits, &b| (bits << 8) | b as u64);
+                        (bits >> (row_size * 8 - width)) as u32
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut glyphs = HashMap::new();
+        if has_table {
+            let table = &bytes[table_start..];
+            if psf1 {
+                // little endian u16 code points, 0xFFFE starts combining sequences, 0xFFFF ends a glyph's entry
+                let mut codes = table
+                    .chunks_exact(2)
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]));
+                for glyph in rows {
+                    let mut in_sequence = false;
+                    for code in codes.by_ref() {
+                        match code {
+                            0xFFFF => break,
+                            0xFFFE => in_sequence = true,
+                            _ if !in_sequence => {
+                                if let Some(c) = std::char::from_u32(code as u32) {
+                                    glyphs.insert(c, glyph.clone());
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            } else {
+                // UTF-8 strings, 0xFE starts combining sequences, 0xFF ends a glyph's entry
+                let mut entries = table.split(|&b| b == 0xFF);
+                for glyph in rows {
+                    let entry = entries.next().unwrap_or(&[]);
+                    let singles = entry.split(|&b| b == 0xFE).next().unwrap_or(&[]);
+                    for c in String::from_utf8_lossy(singles).chars() {
+                        if c != std::char::REPLACEMENT_CHARACTER {
+                            glyphs.insert(c, glyph.clone());
+                        }
+                    }
+                }
+            }
+        } else {
+            for (i, glyph) in rows.into_iter().enumerate() {
+                if let Some(c) = std::char::from_u32(i as u32) {
+                    glyphs.insert(c, glyph);
+                }
+            }
+        }
+
+        Ok(Font {
+            width,
+            height,
+            glyphs,
+        })
+    }
+}
+
+/// The built-in 5x7 font, for characters `' '` to `'~'`.
+///
+/// Each glyph is stored column by column, from left to right, with the top row in the least significant bit.
+#[rustfmt::skip]
+const BUILTIN_5X7: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // )
+    [0x08, 0x2A, 0x1C, 0x2A, 0x08], // *
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // @
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // D
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7F, 0x09, 0x09, 0x01, 0x01], // F
+    [0x3E, 0x41, 0x41, 0x51, 0x32], // G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // J
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7F, 0x02, 0x04, 0x02, 0x7F], // M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // T
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+    [0x7F, 0x20, 0x18, 0x20, 0x7F], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x03, 0x04, 0x78, 0x04, 0x03], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7F, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // backslash
+    [0x00, 0x41, 0x41, 0x7F, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // f
+    [0x08, 0x54, 0x54, 0x54, 0x3C], // g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // j
+    [0x00, 0x7F, 0x10, 0x28, 0x44], // k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // l
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // t
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_psf2_with_unicode_table() {
+        let mut bytes = vec![0x72, 0xb5, 0x4a, 0x86];
+        // version, header size, flags, glyph count, bytes per glyph, height, width
+        for n in [0, 32, 1, 2, 4, 2, 10].iter() {
+            bytes.extend_from_slice(&(*n as u32).to_le_bytes());
+        }
+        // 10 pixel wide rows take 2 bytes each
+        bytes.extend_from_slice(&[0xFF, 0xC0, 0x80, 0x40]);
+        bytes.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        // glyph 0 is 'A' and 'Á', glyph 1 is ' ' and a sequence that gets ignored
+        bytes.extend_from_slice("AÁ".as_bytes());
+        bytes.push(0xFF);
+        bytes.extend_from_slice(b" \xFEab\xFF");
+
+        let font = Font::from_psf(&bytes[..]).unwrap();
+        assert_eq!((10, 2), (font.width(), font.height()));
+        assert_eq!(Some(&[0b11_1111_1111, 0b10_0000_0001][..]), font.glyph('A'));
+        assert_eq!(font.glyph('A'), font.glyph('Á'));
+        assert_eq!(Some(&[0, 0][..]), font.glyph(' '));
+        assert_eq!(None, font.glyph('a'));
+    }
+}
@@ -0,0 +1,357 @@
+//! Classic fractal curves, as lists of points.
+//!
+//! Every curve here is made by replacing each line with smaller lines, `depth` times. The results are plain points,
+//! to draw with [`draw_polyline`], follow with a [`Turtle`], or transform before drawing. They are also good stress
+//! tests for a `draw_line` implementation: lots of short lines, in every direction.
+//!
+//! | Curve | Points |
+//! |-------|--------|
+//! | [`koch`] | `4^depth + 1` |
+//! | [`koch_snowflake`] | `3 * 4^depth` (closed) |
+//! | [`sierpinski_arrowhead`] | `3^depth + 1` |
+//! | [`dragon`] | `2^depth + 1` |
+//! | [`sierpinski_triangles`] | `3^depth` triangles |
+//!
+//! Curves are in the plane through their end points, with `z` going from the `z` of the first point to the `z` of
+//! the last.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{fractal, prelude::*};
+//!
+//! let mut img = Ppm::new();
+//! let snowflake = fractal::koch_snowflake((250., 250., 0.), 200., 4);
+//! img.draw_polyline(&snowflake, true, Rgb::WHITE);
+//!
+//! let dragon = fractal::dragon((150., 250., 0.), (350., 250., 0.), 10);
+//! img.draw_polyline(&dragon, false, Rgb::new(255, 0, 0));
+//! ```
+//!
+//! [`draw_polyline`]: ../screen/trait.Screen.html#method.draw_polyline
+//! [`Turtle`]: ../turtle/struct.Turtle.html
+//! [`koch`]: ./fn.koch.html
+//! [`koch_snowflake`]: ./fn.koch_snowflake.html
+//! [`sierpinski_arrowhead`]: ./fn.sierpinski_arrowhead.html
+//! [`dragon`]: ./fn.dragon.html
+//! [`sierpinski_triangles`]: ./fn.sierpinski_triangles.html
+
+use crate::util::{Float, Point, Vec3};
+
+/// The Koch curve from `p0` to `p1`: every line is split in thirds, and the middle third is replaced by the two other
+/// sides of an equilateral triangle, on the left.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::{fractal, prelude::*};
+///
+/// let curve = fractal::koch((0., 0., 0.), (270., 0., 0.), 1);
+/// assert_eq!(5, curve.len());
+/// // the bump is in the middle
+/// assert_eq!(Point::new(90., 0., 0.), curve[1]);
+/// assert!((curve[2].y - 77.94).abs() < 0.01);
+/// ```
+pub fn koch<P0: Into<Point>, P1: Into<Point>>(p0: P0, p1: P1, depth: usize) -> Vec<Point> {
+    let mut points = vec![(0., 0.)];
+    koch_local((0., 0.), (1., 0.), depth, &mut points);
+    to_world(p0.into(), p1.into(), &points)
+}
+
+/// A Koch snowflake: 3 Koch curves on the sides of an equilateral triangle, with the bumps outside. The corners
+/// of the triangle are `radius` from `center`, with one at the top.
+///
+/// The outline is closed, but the first point isn't repeated at the end: draw it with [`draw_polyline`] with
+/// `closed` set, or fill it with [`fill_polygon`].
+///
+/// [`draw_polyline`]: ../screen/trait.Screen.html#method.draw_polyline
+/// [`fill_polygon`]: ../screen/trait.Screen.html#method.fill_polygon
+pub fn koch_snowflake<P: Into<Point>>(center: P, radius: Float, depth: usize) -> Vec<Point> {
+    let center = center.into();
+    // clockwise, so the bumps on the left of every side point outside
+    let corners: Vec<Point> = [90., -30., 210.]
+        .iter()
+        .map(|&angle: &Float| {
+            let (sin, cos) = angle.to_radians().sin_cos();
+            center + Vec3::new(cos, sin, 0.) * radius
+        })
+        .collect();
+    let mut points = Vec::new();
+    for i in 0..3 {
+        let mut side = koch(corners[i], corners[(i + 1) % 3], depth);
+        side.pop();
+        points.append(&mut side);
+    }
+    points
+}
+
+/// The Sierpinski arrowhead curve from `p0` to `p1`, which goes through the points of a Sierpinski triangle
+/// standing on the line between them.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::{fractal, prelude::*};
+///
+/// let mut img = Ppm::new();
+/// let curve = fractal::sierpinski_arrowhead((50., 50., 0.), (450., 50., 0.), 6);
+/// assert_eq!(3usize.pow(6) + 1, curve.len());
+/// img.draw_polyline(&curve, false, Rgb::WHITE);
+/// ```
+pub fn sierpinski_arrowhead<P0: Into<Point>, P1: Into<Point>>(
+    p0: P0,
+    p1: P1,
+    depth: usize,
+) -> Vec<Point> {
+    // a turtle walking lines of length 1, scaled down to end at (1, 0) at the end
+    let mut walk = Walk {
+        points: vec![(0., 0.)],
+        heading: 0.,
+    };
+    let mut angle = 60.;
+    // with an odd depth, the curve would end up on the other side, at an angle
+    if depth % 2 == 1 {
+        walk.heading = 60.;
+        angle = -60.;
+    }
+    arrowhead_local(&mut walk, depth, angle);
+
+    let scale = 1. / (1u64 << depth.min(63)) as Float;
+    let points: Vec<_> = walk
+        .points
+        .iter()
+        .map(|&(x, y)| (x * scale, y * scale))
+        .collect();
+    to_world(p0.into(), p1.into(), &points)
+}
+
+/// The Heighway dragon curve from `p0` to `p1`: every line is replaced by the two other sides of a right isosceles
+/// triangle, alternating between the left and the right.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::{fractal, prelude::*};
+///
+/// let curve = fractal::dragon((0., 0., 0.), (100., 0., 0.), 2);
+/// assert_eq!(5, curve.len());
+/// assert_eq!(Point::new(0., 50., 0.), curve[1]);
+/// assert_eq!(Point::new(50., 50., 0.), curve[2]);
+/// ```
+pub fn dragon<P0: Into<Point>, P1: Into<Point>>(p0: P0, p1: P1, depth: usize) -> Vec<Point> {
+    let mut points = vec![(0., 0.)];
+    dragon_local((0., 0.), (1., 0.), depth, true, &mut points);
+    to_world(p0.into(), p1.into(), &points)
+}
+
+/// The filled triangles of a Sierpinski triangle with corners `p0`, `p1` and `p2`: each triangle is split into 4 by
+/// the midpoints of its sides, and the middle one is left out.
+///
+/// The triangles are in the same order as their corners are given, ready for [`fill_triangle`].
+///
+/// # Examples
+///
+/// ```
+/// use graphics::{fractal, prelude::*};
+///
+/// let mut img = Ppm::new();
+/// let triangles = fractal::sierpinski_triangles((50., 50., 0.), (450., 50., 0.), (250., 400., 0.), 5);
+/// assert_eq!(3usize.pow(5), triangles.len());
+/// for [p0, p1, p2] in triangles {
+///     img.fill_triangle(p0, p1, p2, Rgb::WHITE);
+/// }
+/// // the middle is empty
+/// assert_eq!(Some(Rgb::BLACK), img.get(250, 100));
+/// ```
+///
+/// [`fill_triangle`]: ../screen/trait.Screen.html#method.fill_triangle
+pub fn sierpinski_triangles<P0, P1, P2>(p0: P0, p1: P1, p2: P2, depth: usize) -> Vec<[Point; 3]>
+where
+    P0: Into<Point>,
+    P1: Into<Point>,
+    P2: Into<Point>,
+{
+    let mut triangles = Vec::new();
+    sierpinski_split([p0.into(), p1.into(), p2.into()], depth, &mut triangles);
+    triangles
+}
+
+/// The lines between consecutive `points`, as `(start, end)` pairs, for code that draws a list of edges. With
+/// `closed`, the last point is joined to the first.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::{fractal, prelude::*};
+///
+/// let curve = fractal::koch((0., 0., 0.), (300., 0., 0.), 2);
+/// let edges = fractal::edges(&curve, false);
+/// assert_eq!(16, edges.len());
+/// assert_eq!(curve[0], edges[0].0);
+/// ```
+pub fn edges(points: &[Point], closed: bool) -> Vec<(Point, Point)> {
+    let mut edges: Vec<_> = points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    if closed && points.len() > 2 {
+        edges.push((points[points.len() - 1], points[0]));
+    }
+    edges
+}
+
+/// Add the Koch curve from `a` to `b` to `points`, except for `a`, which is already there.
+fn koch_local(
+    a: (Float, Float),
+    b: (Float, Float),
+    depth: usize,
+    points: &mut Vec<(Float, Float)>,
+) {
+    if depth == 0 {
+        points.push(b);
+        return;
+    }
+    let third = ((b.0 - a.0) / 3., (b.1 - a.1) / 3.);
+    let m1 = (a.0 + third.0, a.1 + third.1);
+    let m2 = (a.0 + 2. * third.0, a.1 + 2. * third.1);
+    // the third turned 60 degrees to the left
+    let (sin, cos) = (60 as Float).to_radians().sin_cos();
+    let apex = (
+        m1.0 + third.0 * cos - third.1 * sin,
+        m1.1 + third.0 * sin + third.1 * cos,
+    );
+    for &(start, end) in [(a, m1), (m1, apex), (apex, m2), (m2, b)].iter() {
+        koch_local(start, end, depth - 1, points);
+    }
+}
+
+/// Add the dragon curve from `a` to `b` to `points`, except for `a`. The corner goes on the left if `left`.
+fn dragon_local(
+    a: (Float, Float),
+    b: (Float, Float),
+    depth: usize,
+    left: bool,
+    points: &mut Vec<(Float, Float)>,
+) {
+    if depth == 0 {
+        points.push(b);
+        return;
+    }
+    let half = ((b.0 - a.0) / 2., (b.1 - a.1) / 2.);
+    // the half turned 90 degrees to the side of the corner
+    let side = if left {
+        (-half.1, half.0)
+    } else {
+        (half.1, -half.0)
+    };
+    let corner = (a.0 + half.0 + side.0, a.1 + half.1 + side.1);
+    dragon_local(a, corner, depth - 1, true, points);
+    dragon_local(corner, b, depth - 1, false, points);
+}
+
+/// A turtle for the arrowhead curve, which is easiest to describe with turns.
+struct Walk {
+    points: Vec<(Float, Float)>,
+    heading: Float,
+}
+
+impl Walk {
+    fn forward(&mut self) {
+        let &(x, y) = self.points.last().unwrap();
+        let (sin, cos) = self.heading.to_radians().sin_cos();
+        self.points.push((x + cos, y + sin));
+    }
+}
+
+fn arrowhead_local(walk: &mut Walk, depth: usize, angle: Float) {
+    if depth == 0 {
+        walk.forward();
+        return;
+    }
+    arrowhead_local(walk, depth - 1, -angle);
+    walk.heading += angle;
+    arrowhead_local(walk, depth - 1, angle);
+    walk.heading += angle;
+    arrowhead_local(walk, depth - 1, -angle);
+}
+
+fn sierpinski_split(corners: [Point; 3], depth: usize, triangles: &mut Vec<[Point; 3]>) {
+    if depth == 0 {
+        triangles.push(corners);
+        return;
+    }
+    let [a, b, c] = corners;
+    let (ab, bc, ca) = (a.lerp(b, 0.5), b.lerp(c, 0.5), c.lerp(a, 0.5));
+    sierpinski_split([a, ab, ca], depth - 1, triangles);
+    sierpinski_split([ab, b, bc], depth - 1, triangles);
+    sierpinski_split([ca, bc, c], depth - 1, triangles);
+}
+
+/// Map points from a frame where the curve goes from (0, 0) to (1, 0), to one where it goes from `p0` to `p1`.
+fn to_world(p0: Point, p1: Point, points: &[(Float, Float)]) -> Vec<Point> {
+    let along = p1 - p0;
+    // to the left of the line, in the xy plane
+    let left = Vec3::new(-along.y, along.x, 0.);
+    points
+        .iter()
+        .map(|&(u, v)| p0 + along * u + left * v)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: Point, actual: Point) {
+        assert!(
+            expected.distance(actual) < 1e-3,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_curves_end_at_their_end_points() {
+        let (p0, p1) = (Point::new(10., 20., 0.), Point::new(-30., 50., 4.));
+        for depth in 0..6 {
+            let curves = [
+                (koch(p0, p1, depth), 4usize.pow(depth as u32) + 1),
+                (
+                    sierpinski_arrowhead(p0, p1, depth),
+                    3usize.pow(depth as u32) + 1,
+                ),
+                (dragon(p0, p1, depth), 2usize.pow(depth as u32) + 1),
+            ];
+            for (curve, len) in curves.iter() {
+                assert_eq!(*len, curve.len());
+                assert_close(p0, curve[0]);
+                assert_close(p1, curve[curve.len() - 1]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_koch_snowflake() {
+        let center = Point::new(100., 100., 0.);
+        let snowflake = koch_snowflake(center, 50., 3);
+        assert_eq!(3 * 64, snowflake.len());
+        assert_close(Point::new(100., 150., 0.), snowflake[0]);
+        // bumps point outside: no point is closer to the center than the middle of a side
+        for p in snowflake {
+            assert!(center.distance(p) >= 25. - 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_dragon_never_crosses_itself() {
+        let curve = dragon((0., 0., 0.), (64., 0., 0.), 10);
+        let mut seen = std::collections::HashSet::new();
+        for pair in curve.windows(2) {
+            let middle = pair[0].lerp(pair[1], 0.5);
+            // lines of the dragon can touch at corners, but never overlap
+            let key = (
+                (middle.x * 1000.).round() as i64,
+                (middle.y * 1000.).round() as i64,
+            );
+            assert!(seen.insert(key));
+        }
+    }
+}
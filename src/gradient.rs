@@ -0,0 +1,223 @@
+//! Color gradients, for filling shapes with smoothly changing colors.
+//!
+//! A [`Gradient`] maps every point on the screen to a color. Its shape is either linear (the color changes along a line)
+//! or radial (the color changes with the distance to a center). Along that shape, colors are interpolated between two or
+//! more stops, each a color at an offset from `0` to `1`. Before the first stop and after the last one, the color stays
+//! the same.
+//!
+//! Gradients are positioned in screen coordinates, not relative to the shape they fill, so several shapes can share a
+//! single gradient. Pass a `&Gradient` to [`fill_rect`] or [`fill_polygon`] to use it.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{gradient::Gradient, prelude::*};
+//!
+//! let mut img = Ppm::new();
+//!
+//! // a sunset sky
+//! let sky = Gradient::linear((0., 500.), (0., 0.))
+//!     .stop(0., Rgb::new(20, 20, 80))
+//!     .stop(0.7, Rgb::new(255, 120, 50))
+//!     .stop(1., Rgb::new(255, 220, 100));
+//! img.fill_rect((0., 0., 0.), 500., 500., &sky);
+//!
+//! let sun = Gradient::radial((250., 100.), 60.)
+//!     .stop(0., Rgb::new(255, 255, 200))
+//!     .stop(1., Rgb::new(255, 180, 0));
+//! img.fill_polygon(&[(190., 40., 1.), (310., 40., 1.), (310., 160., 1.), (190., 160., 1.)], &sun);
+//!
+//! assert_eq!(Some(Rgb::new(255, 255, 200)), img.get_pixel(250, 100));
+//! ```
+//!
+//! [`Gradient`]: ./struct.Gradient.html
+//! [`fill_rect`]: ../screen/trait.Screen.html#method.fill_rect
+//! [`fill_polygon`]: ../screen/trait.Screen.html#method.fill_polygon
+
+use crate::color::{Color, Rgb};
+
+/// Shape of a [`Gradient`].
+///
+/// [`Gradient`]: ./struct.Gradient.html
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GradientKind {
+    /// Offset `0` at `start` and `1` at `end`, constant on lines perpendicular to `start -> end`.
+    Linear { start: (f64, f64), end: (f64, f64) },
+    /// Offset `0` at `center` and `1` on the circle of `radius` around it.
+    Radial { center: (f64, f64), radius: f64 },
+}
+
+/// A color gradient: a [`GradientKind`] and a list of color stops.
+///
+/// [`GradientKind`]: ./enum.GradientKind.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    /// Color stops, sorted by offset.
+    stops: Vec<(f64, Rgb)>,
+}
+
+impl Gradient {
+    /// A gradient of `kind`, without any stop yet. Add them with [`stop`].
+    ///
+    /// [`stop`]: #method.stop
+    pub fn new(kind: GradientKind) -> Self {
+        Gradient {
+            kind,
+            stops: vec![],
+        }
+    }
+
+    /// A linear gradient from `start` to `end`. Add stops with [`stop`].
+    ///
+    /// [`stop`]: #method.stop
+    pub fn linear(start: (f64, f64), end: (f64, f64)) -> Self {
+        Self::new(GradientKind::Linear { start, end })
+    }
+
+    /// A radial gradient around `center`, reaching offset `1` at `radius`. Add stops with [`stop`].
+    ///
+    /// [`stop`]: #method.stop
+    pub fn radial(center: (f64, f64), radius: f64) -> Self {
+        Self::new(GradientKind::Radial { center, radius })
+    }
+
+    /// Add a color stop at `offset`, usually from `0` to `1`.
+    ///
+    /// Stops can be added in any order. Two stops at the same offset make a hard edge. A stop at a NaN offset is
+    /// ignored.
+    pub fn stop(mut self, offset: f64, color: Rgb) -> Self {
+        if offset.is_nan() {
+            return self;
+        }
+        let index = self.stops.partition_point(|&(o, _)| o <= offset);
+        self.stops.insert(index, (offset, color));
+        self
+    }
+
+    /// The color stops, sorted by offset.
+    pub fn stops(&self) -> &[(f64, Rgb)] {
+        &self.stops
+    }
+
+    /// Offset of the point (`x`, `y`) along the gradient, `0` at the start and `1` at the end. Not clamped.
+    pub fn offset_at(&self, x: f64, y: f64) -> f64 {
+        match self.kind {
+            GradientKind::Linear { start, end } => {
+                let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+                let length_squared = dx * dx + dy * dy;
+                if length_squared == 0. {
+                    return 0.;
+                }
+                ((x - start.0) * dx + (y - start.1) * dy) / length_squared
+            }
+            GradientKind::Radial { center, radius } => {
+                if radius == 0. {
+                    return 0.;
+                }
+                (x - center.0).hypot(y - center.1) / radius
+            }
+        }
+    }
+
+    /// Color of the gradient at the point (`x`, `y`).
+    ///
+    /// A gradient without stops is black everywhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{gradient::Gradient, prelude::*};
+    ///
+    /// let gradient = Gradient::linear((0., 0.), (100., 0.))
+    ///     .stop(0., Rgb::BLACK)
+    ///     .stop(1., Rgb::WHITE);
+    /// assert_eq!(Rgb::BLACK, gradient.color_at(-50., 20.));
+    /// assert_eq!(Rgb::gray(128), gradient.color_at(50., 20.));
+    /// assert_eq!(Rgb::WHITE, gradient.color_at(150., 20.));
+    /// ```
+    pub fn color_at(&self, x: f64, y: f64) -> Rgb {
+        let offset = self.offset_at(x, y);
+        let next = self.stops.partition_point(|&(o, _)| o <= offset);
+        match (self.stops.get(next.wrapping_sub(1)), self.stops.get(next)) {
+            (Some(&(o0, c0)), Some(&(o1, c1))) => c0.mix(c1, (offset - o0) / (o1 - o0)),
+            (Some(&(_, c)), None) | (None, Some(&(_, c))) => c,
+            (None, None) => Rgb::BLACK,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear() {
+        let gradient = Gradient::linear((10., 0.), (20., 0.))
+            .stop(1., Rgb::WHITE)
+            .stop(0., Rgb::BLACK);
+        assert_eq!(0.5, gradient.offset_at(15., 100.));
+        assert_eq!(-1., gradient.offset_at(0., 0.));
+        assert_eq!(Rgb::BLACK, gradient.color_at(10., 0.));
+        assert_eq!(Rgb::gray(128), gradient.color_at(15., -3.));
+        assert_eq!(Rgb::WHITE, gradient.color_at(20., 0.));
+        // past the ends
+        assert_eq!(Rgb::BLACK, gradient.color_at(-100., 0.));
+        assert_eq!(Rgb::WHITE, gradient.color_at(100., 0.));
+    }
+
+    #[test]
+    fn test_radial() {
+        let gradient = Gradient::radial((0., 0.), 10.)
+            .stop(0., Rgb::RED)
+            .stop(1., Rgb::BLUE);
+        assert_eq!(0., gradient.offset_at(0., 0.));
+        assert_eq!(0.5, gradient.offset_at(3., 4.));
+        assert_eq!(Rgb::BLUE, gradient.color_at(-30., 40.));
+    }
+
+    #[test]
+    fn test_degenerate() {
+        // no length, no radius
+        let linear = Gradient::linear((5., 5.), (5., 5.)).stop(0., Rgb::RED);
+        assert_eq!(0., linear.offset_at(100., 100.));
+        let radial = Gradient::radial((5., 5.), 0.).stop(0., Rgb::RED);
+        assert_eq!(0., radial.offset_at(100., 100.));
+        assert_eq!(Rgb::RED, radial.color_at(100., 100.));
+
+        // no stops
+        assert_eq!(
+            Rgb::BLACK,
+            Gradient::linear((0., 0.), (1., 0.)).color_at(0.5, 0.)
+        );
+    }
+
+    #[test]
+    fn test_stops() {
+        let gradient = Gradient::linear((0., 0.), (1., 0.))
+            .stop(0.5, Rgb::RED)
+            .stop(0., Rgb::BLACK)
+            .stop(0.5, Rgb::BLUE);
+        let offsets: Vec<_> = gradient.stops().iter().map(|&(o, _)| o).collect();
+        assert_eq!(vec![0., 0.5, 0.5], offsets);
+        // stops at the same offset make a hard edge, in the order they were added
+        assert_eq!(
+            Rgb::new(0, 0, 0).mix(Rgb::RED, 0.98),
+            gradient.color_at(0.49, 0.)
+        );
+        assert_eq!(Rgb::BLUE, gradient.color_at(0.5, 0.));
+    }
+
+    #[test]
+    fn test_nan() {
+        let gradient = Gradient::linear((0., 0.), (1., 0.))
+            .stop(0., Rgb::BLACK)
+            .stop(1., Rgb::WHITE);
+        assert_eq!(Rgb::BLACK, gradient.color_at(f64::NAN, 0.));
+        let gradient = gradient.stop(f64::NAN, Rgb::RED);
+        assert_eq!(2, gradient.stops().len());
+        assert_eq!(Rgb::WHITE, gradient.color_at(1., 0.));
+        let gradient = Gradient::radial((0., 0.), f64::NAN).stop(0., Rgb::RED);
+        assert_eq!(Rgb::RED, gradient.color_at(1., 1.));
+    }
+}
@@ -0,0 +1,333 @@
+//! High dynamic range rendering, with a floating point framebuffer and tone mapping.
+//!
+//! With 8 bit colors, every channel is clamped at `255` as soon as it is plotted. A scene lit by several bright lights
+//! just turns flat white where they overlap. An [`HdrScreen`] stores [`RgbF`] colors instead, which go above `1.0`
+//! (white) without clipping, and adds light with [`add_light`]. Only when the image is written out does a [`ToneMap`]
+//! squeeze the whole range back into `[0, 255]`, keeping the detail in the bright parts.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use graphics::{hdr::{HdrScreen, RgbF, ToneMap}, prelude::*};
+//!
+//! let mut img = HdrScreen::new(500, 500);
+//! img.tone_map = ToneMap::Aces;
+//! img.exposure = 0.8;
+//!
+//! // 3 overlapping spot lights
+//! for &(cx, cy) in [(200., 200.), (300., 200.), (250., 290.)].iter() {
+//!     for y in 0..500 {
+//!         for x in 0..500 {
+//!             let d = ((x as f64 - cx).powi(2) + (y as f64 - cy).powi(2)).sqrt();
+//!             let intensity = (2. / (1. + d / 50.).powi(2)) as f32;
+//!             img.add_light(x, y, RgbF::new(1., 0.9, 0.7) * intensity);
+//!         }
+//!     }
+//! }
+//!
+//! img.save("lights.png").expect("error saving lights.png");
+//! // or convert it, to keep drawing in 8 bit
+//! let ppm: Ppm = img.to_ppm();
+//! ```
+//!
+//! [`HdrScreen`]: ./struct.HdrScreen.html
+//! [`RgbF`]: ./struct.RgbF.html
+//! [`add_light`]: ./struct.HdrScreen.html#method.add_light
+//! [`ToneMap`]: ./enum.ToneMap.html
+
+use std::{
+    io,
+    ops::{Add, AddAssign, Mul},
+};
+
+use crate::{
+    color::{Color, Gamma, Rgb},
+    error,
+    ppm::{Ppm, PpmBuilder},
+    screen::Screen,
+    util::Float,
+};
+
+/// Linear floating point rgb color. `1.0` is the brightest an output pixel can be, but values can go above that.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct RgbF {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+}
+
+impl RgbF {
+    pub const BLACK: RgbF = RgbF::new(0., 0., 0.);
+
+    pub const WHITE: RgbF = RgbF::new(1., 1., 1.);
+
+    /// Function constructor for [`RgbF`].
+    ///
+    /// [`RgbF`]: ./struct.RgbF.html
+    pub const fn new(red: f32, green: f32, blue: f32) -> Self {
+        RgbF { red, green, blue }
+    }
+
+    /// Luminance (Rec. 709 weights).
+    pub fn luminance(self) -> f32 {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
+
+    /// Apply `f` to every channel.
+    pub fn map<F: Fn(f32) -> f32>(self, f: F) -> Self {
+        RgbF::new(f(self.red), f(self.green), f(self.blue))
+    }
+}
+
+/// `255` is converted to `1.0`.
+impl From<Rgb> for RgbF {
+    fn from(rgb: Rgb) -> Self {
+        RgbF::new(
+            rgb.red as f32 / 255.,
+            rgb.green as f32 / 255.,
+            rgb.blue as f32 / 255.,
+        )
+    }
+}
+
+impl Color for RgbF {
+    fn from_rgb(rgb: Rgb) -> Self {
+        rgb.into()
+    }
+
+    fn mix(self, other: Self, t: f64) -> Self {
+        let t = t as f32;
+        RgbF::new(
+            self.red + (other.red - self.red) * t,
+            self.green + (other.green - self.green) * t,
+            self.blue + (other.blue - self.blue) * t,
+        )
+    }
+}
+
+impl Add for RgbF {
+    type Output = RgbF;
+
+    fn add(self, other: RgbF) -> RgbF {
+        RgbF::new(
+            self.red + other.red,
+            self.green + other.green,
+            self.blue + other.blue,
+        )
+    }
+}
+
+impl AddAssign for RgbF {
+    fn add_assign(&mut self, other: RgbF) {
+        *self = *self + other;
+    }
+}
+
+impl Mul<f32> for RgbF {
+    type Output = RgbF;
+
+    fn mul(self, scale: f32) -> RgbF {
+        self.map(|c| c * scale)
+    }
+}
+
+/// How to map the unbounded colors of an [`HdrScreen`] to `[0, 1]` for output.
+///
+/// [`HdrScreen`]: ./struct.HdrScreen.html
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ToneMap {
+    /// Clip every channel at `1`. What an 8 bit framebuffer would give.
+    Clamp,
+    /// `c / (1 + c)`: never clips, but washes out colors a bit.
+    Reinhard,
+    /// An approximation of the ACES filmic curve, with more contrast than Reinhard.
+    Aces,
+}
+
+impl ToneMap {
+    /// Map one channel (already multiplied by the exposure) to `[0, 1]`.
+    pub fn apply(self, c: f32) -> f32 {
+        let c = c.max(0.);
+        let mapped = match self {
+            ToneMap::Clamp => c,
+            ToneMap::Reinhard => c / (1. + c),
+            // Krzysztof Narkowicz's fit
+            ToneMap::Aces => (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14),
+        };
+        mapped.clamp(0., 1.)
+    }
+}
+
+/// A floating point framebuffer, with a depth buffer. See the [module documentation] for an example.
+///
+/// Like a [`Ppm`] built with default settings, the origin is on the bottom left.
+///
+/// [module documentation]: ./index.html
+/// [`Ppm`]: ../ppm/struct.Ppm.html
+pub struct HdrScreen {
+    height: usize,
+    width: usize,
+    /// Pixels, row by row, starting from the bottom.
+    data: Vec<RgbF>,
+    zbuf: Vec<Float>,
+    /// Tone mapping used when converting to 8 bit for output. Defaults to [`ToneMap::Reinhard`].
+    ///
+    /// [`ToneMap::Reinhard`]: ./enum.ToneMap.html#variant.Reinhard
+    pub tone_map: ToneMap,
+    /// Colors are multiplied by `exposure` before tone mapping. Defaults to `1`.
+    pub exposure: f32,
+    /// Transfer function applied after tone mapping. Defaults to [`Gamma::Linear`]; [`Gamma::Srgb`] is usually what
+    /// a physically based scene needs.
+    ///
+    /// [`Gamma::Linear`]: ../color/enum.Gamma.html#variant.Linear
+    /// [`Gamma::Srgb`]: ../color/enum.Gamma.html#variant.Srgb
+    pub gamma: Gamma,
+}
+
+impl HdrScreen {
+    /// Make a black `width` by `height` screen. The arguments are in the same order as [`PpmBuilder::new`].
+    ///
+    /// [`PpmBuilder::new`]: ../ppm/struct.PpmBuilder.html#method.new
+    pub fn new(height: usize, width: usize) -> Self {
+        HdrScreen {
+            height,
+            width,
+            data: vec![RgbF::BLACK; width * height],
+            zbuf: vec![Float::NEG_INFINITY; width * height],
+            tone_map: ToneMap::Reinhard,
+            exposure: 1.,
+            gamma: Gamma::Linear,
+        }
+    }
+
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            Some(y as usize * self.width + x as usize)
+        }
+    }
+
+    /// Add `light` to the pixel at (`x`, `y`), without any depth test. Useful to accumulate the contribution of several
+    /// lights.
+    pub fn add_light(&mut self, x: i64, y: i64, light: RgbF) {
+        if let Some(index) = self.index(x, y) {
+            self.data[index] += light;
+        }
+    }
+
+    /// Reset the z-buffer, without touching the pixels. See [`Ppm::clear_zbuf_only`].
+    ///
+    /// [`Ppm::clear_zbuf_only`]: ../ppm/struct.Ppm.html#method.clear_zbuf_only
+    pub fn clear_zbuf_only(&mut self) {
+        self.zbuf.fill(Float::NEG_INFINITY);
+    }
+
+    /// Tone map this screen into an 8 bit [`Ppm`], using [`tone_map`], [`exposure`] and [`gamma`].
+    ///
+    /// [`Ppm`]: ../ppm/struct.Ppm.html
+    /// [`tone_map`]: #structfield.tone_map
+    /// [`exposure`]: #structfield.exposure
+    /// [`gamma`]: #structfield.gamma
+    pub fn to_ppm(&self) -> Ppm {
+        let to_u8 = |c: f32| {
+            let mapped = self.tone_map.apply(c * self.exposure) as f64;
+            (self.gamma.encode(mapped) * 255.).round() as u8
+        };
+        // Ppm data starts from the top row
+        let data = self
+            .data
+            .chunks(self.width.max(1))
+            .rev()
+            .flatten()
+            .map(|c| Rgb::new(to_u8(c.red), to_u8(c.green), to_u8(c.blue)))
+            .collect();
+        PpmBuilder::new(self.height, self.width, 255)
+            .with_data(data)
+            .build()
+    }
+}
+
+impl Screen<RgbF> for HdrScreen {
+    fn plot(&mut self, x: i64, y: i64, z: Float, color: RgbF) {
+        if let Some(index) = self.index(x, y) {
+            if self.zbuf[index] < z {
+                self.data[index] = color;
+                self.zbuf[index] = z;
+            }
+        }
+    }
+
+    fn set_pixel(&mut self, x: i64, y: i64, color: RgbF) {
+        if let Some(index) = self.index(x, y) {
+            self.data[index] = color;
+        }
+    }
+
+    /// Tone map to 8 bit with [`to_ppm`], and save that.
+    ///
+    /// [`to_ppm`]: #method.to_ppm
+    fn save(&self, file_path: &str) -> error::Result<()> {
+        self.to_ppm().save(file_path)
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Tone map to 8 bit with [`to_ppm`], and write that.
+    ///
+    /// [`to_ppm`]: #method.to_ppm
+    fn write_to_buf<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+        self.to_ppm().write_to_buf(writer)
+    }
+
+    fn display(&self) -> error::Result<()> {
+        self.to_ppm().display()
+    }
+
+    fn clear(&mut self, color: RgbF) {
+        self.data.fill(color);
+        self.clear_zbuf_only();
+    }
+
+    fn get_pixel(&self, x: i64, y: i64) -> Option<RgbF> {
+        self.index(x, y).map(|i| self.data[i])
+    }
+
+    fn get_depth(&self, x: i64, y: i64) -> Option<Float> {
+        self.index(x, y).map(|i| self.zbuf[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tone_mapping() {
+        let mut img = HdrScreen::new(2, 2);
+        img.plot(0, 0, 0., RgbF::new(0.5, 1., 8.));
+        img.add_light(0, 0, RgbF::new(0.5, 0., 0.));
+
+        img.tone_map = ToneMap::Clamp;
+        assert_eq!(Some(Rgb::new(255, 255, 255)), img.to_ppm().get_pixel(0, 0));
+
+        img.tone_map = ToneMap::Reinhard;
+        let pixel = img.to_ppm().get_pixel(0, 0).unwrap();
+        assert_eq!((128, 128), (pixel.red, pixel.green));
+        assert!(pixel.blue < 255);
+
+        img.tone_map = ToneMap::Aces;
+        assert!(img.to_ppm().get_pixel(1, 1) == Some(Rgb::BLACK));
+
+        img.tone_map = ToneMap::Clamp;
+        img.gamma = Gamma::Srgb;
+        img.plot(1, 1, 0., RgbF::new(0.5, 0.5, 0.5));
+        assert_eq!(Some(Rgb::gray(188)), img.to_ppm().get_pixel(1, 1));
+    }
+}
@@ -0,0 +1,314 @@
+//! Export drawing as a standalone web page, which replays it on an HTML canvas.
+//!
+//! [`HtmlCanvas`] is a [`Screen`] that draws into a [`Ppm`] and logs every pixel that changes, in order. The log is
+//! written as a single HTML file with a bit of JavaScript that redraws the image, so a gallery submission can be
+//! shared as a web page. With the step-through slider, viewers can scrub through the drawing and watch how it was
+//! made.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use graphics::{html::HtmlCanvas, prelude::*};
+//!
+//! let mut canvas = HtmlCanvas::new(Ppm::new());
+//! canvas.draw_line((0., 0., 0.), (499., 499., 0.), Rgb::WHITE);
+//! canvas.draw_circle(250., 250., 0., 100., Rgb::new(255, 0, 0));
+//! canvas.save_html("gallery.html", true).expect("error saving gallery.html");
+//! ```
+//!
+//! [`HtmlCanvas`]: ./struct.HtmlCanvas.html
+//! [`Screen`]: ../screen/trait.Screen.html
+//! [`Ppm`]: ../ppm/struct.Ppm.html
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::Range,
+    path::Path,
+};
+
+use crate::{
+    color::{PpmColor, Rgb},
+    error,
+    ppm::{Origin, Ppm},
+    raster::Rasterizer,
+    screen::{SaveOptions, Screen},
+    util::Float,
+};
+
+/// A [`Ppm`] that logs its drawing, to replay it in a web page. See the [module documentation].
+///
+/// [`Ppm`]: ../ppm/struct.Ppm.html
+/// [module documentation]: ./index.html
+pub struct HtmlCanvas<C: PpmColor = Rgb> {
+    img: Ppm<C>,
+    /// The image before anything was drawn, as a PNG.
+    start: Vec<u8>,
+    /// Column, row (from the top) and `0xRRGGBB` color of every pixel changed, in order. A column of `-1` clears the
+    /// canvas to the color.
+    log: Vec<[i64; 3]>,
+}
+
+impl<C: PpmColor> HtmlCanvas<C> {
+    /// Log drawing on `img`, starting from what's already on it.
+    pub fn new(img: Ppm<C>) -> Self {
+        let mut start = Vec::new();
+        // writing to memory can't fail
+        img.write_png_to_buf(&mut start).unwrap();
+        HtmlCanvas {
+            img,
+            start,
+            log: Vec::new(),
+        }
+    }
+
+    /// How many pixel changes were logged.
+    pub fn len(&self) -> usize {
+        self.log.len()
+    }
+
+    /// Whether nothing was drawn yet.
+    pub fn is_empty(&self) -> bool {
+        self.log.is_empty()
+    }
+
+    /// The image drawn so far.
+    pub fn image(&self) -> &Ppm<C> {
+        &self.img
+    }
+
+    /// Stop logging, and keep the image.
+    pub fn into_image(self) -> Ppm<C> {
+        self.img
+    }
+
+    /// A color as `0xRRGGBB`, scaled to 8 bits.
+    fn rgb(&self, color: C) -> i64 {
+        let max = self.img.color_depth().max(1) as i64;
+        let [r, g, b] = color.channels();
+        let scale = |c: u16| (c as i64).min(max) * 255 / max;
+        scale(r) << 16 | scale(g) << 8 | scale(b)
+    }
+
+    /// Log the pixel at (`x`, `y`) if it is no longer `before`.
+    fn log_change(&mut self, x: i64, y: i64, before: Option<C>) {
+        let after = self.img.get_pixel(x, y);
+        if let (Some(after), Some((column, row))) = (after, self.img.pixel_position(x, y)) {
+            if before != Some(after) {
+                let rgb = self.rgb(after);
+                self.log.push([column as i64, row as i64, rgb]);
+            }
+        }
+    }
+
+    /// Write the web page to `writer`. With `slider`, the page has a slider and a play button to step through the
+    /// drawing; otherwise it shows the finished image.
+    ///
+    /// The `writer` will be buffered before being written to.
+    pub fn write_html<T: Write>(&self, writer: &mut T, slider: bool) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        let (width, height) = (self.img.width(), self.img.height());
+        write!(
+            buf,
+            r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>graphics</title>
+<style>
+body {{ font-family: sans-serif; background: #333; color: #eee; }}
+canvas {{ image-rendering: pixelated; border: 1px solid #888; }}
+</style>
+</head>
+<body>
+<canvas id="canvas" width="{w}" height="{h}"></canvas>
+<div{hidden}>
+<input id="step" type="range" min="0" max="{n}" value="{n}" style="width: {w}px">
+<button id="play">Play</button> <span id="count"></span>
+</div>
+<script>
+const log = ["##,
+            w = width,
+            h = height,
+            n = self.log.len(),
+            hidden = if slider { "" } else { " hidden" },
+        )?;
+        for (i, [x, y, color]) in self.log.iter().enumerate() {
+            if i % 1000 == 0 {
+                writeln!(buf)?;
+            }
+            write!(buf, "{},{},{},", x, y, color)?;
+        }
+        write!(
+            buf,
+            r##"];
+const ctx = document.getElementById("canvas").getContext("2d");
+const step = document.getElementById("step");
+const count = document.getElementById("count");
+const start = new Image();
+start.src = "data:image/png;base64,{png}";
+
+// redraw the first n pixel changes
+function draw(n) {{
+    ctx.drawImage(start, 0, 0);
+    for (let i = 0; i < 3 * n; i += 3) {{
+        ctx.fillStyle = "#" + log[i + 2].toString(16).padStart(6, "0");
+        if (log[i] < 0) {{
+            ctx.fillRect(0, 0, {w}, {h});
+        }} else {{
+            ctx.fillRect(log[i], log[i + 1], 1, 1);
+        }}
+    }}
+    count.textContent = n + " / " + log.length / 3;
+}}
+
+step.oninput = () => draw(+step.value);
+document.getElementById("play").onclick = () => {{
+    const total = log.length / 3;
+    const begin = performance.now();
+    // the whole drawing plays in about 5 seconds
+    function frame(now) {{
+        const n = Math.min(total, Math.round((now - begin) / 5000 * total));
+        step.value = n;
+        draw(n);
+        if (n < total) requestAnimationFrame(frame);
+    }}
+    requestAnimationFrame(frame);
+}};
+start.onload = () => draw(+step.value);
+</script>
+</body>
+</html>
+"##,
+            png = base64(&self.start),
+            w = width,
+            h = height,
+        )?;
+        buf.flush()
+    }
+
+    /// Save the web page to `path`. See [`write_html`].
+    ///
+    /// [`write_html`]: #method.write_html
+    pub fn save_html<P: AsRef<Path>>(&self, path: P, slider: bool) -> error::Result<()> {
+        Ok(self.write_html(&mut File::create(path)?, slider)?)
+    }
+}
+
+impl<C: PpmColor> Screen<C> for HtmlCanvas<C> {
+    /// Plot on the image, and log the pixel if it changed.
+    fn plot(&mut self, x: i64, y: i64, z: Float, color: C) {
+        let before = self.img.get_pixel(x, y);
+        self.img.plot(x, y, z, color);
+        self.log_change(x, y, before);
+    }
+
+    /// Overwrite the pixel on the image, and log it if it changed.
+    fn set_pixel(&mut self, x: i64, y: i64, color: C) {
+        let before = self.img.get_pixel(x, y);
+        self.img.set(x, y, color);
+        self.log_change(x, y, before);
+    }
+
+    fn save(&self, file_path: &str) -> error::Result<()> {
+        self.img.save(file_path)
+    }
+
+    fn save_with(&self, file_path: &str, options: &SaveOptions) -> error::Result<()> {
+        self.img.save_with(file_path, options)
+    }
+
+    fn width(&self) -> usize {
+        self.img.width()
+    }
+
+    fn height(&self) -> usize {
+        self.img.height()
+    }
+
+    fn write_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        self.img.write_to_buf(writer)
+    }
+
+    fn display(&self) -> error::Result<()> {
+        self.img.display()
+    }
+
+    fn clear(&mut self, color: C) {
+        self.img.clear(color);
+        let rgb = self.rgb(color);
+        self.log.push([-1, 0, rgb]);
+    }
+
+    fn get_pixel(&self, x: i64, y: i64) -> Option<C> {
+        self.img.get_pixel(x, y)
+    }
+
+    fn get_depth(&self, x: i64, y: i64) -> Option<Float> {
+        self.img.get_depth(x, y)
+    }
+
+    fn rasterizer(&self) -> &'static dyn Rasterizer {
+        self.img.rasterizer()
+    }
+
+    fn clip_bounds(&self) -> (Range<i64>, Range<i64>) {
+        self.img.clip_bounds()
+    }
+
+    fn origin(&self) -> Origin {
+        self.img.origin()
+    }
+}
+
+/// Encode `bytes` as base64, with padding.
+fn base64(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(DIGITS[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppm::PpmBuilder;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYmE=", base64(b"fooba"));
+    }
+
+    #[test]
+    fn test_log() {
+        // 3 wide and 2 tall, with y going up
+        let mut canvas = HtmlCanvas::new(PpmBuilder::new(2, 3, 255).build());
+        canvas.plot(2, 0, 0., Rgb::new(255, 0, 0));
+        // already black, so not logged
+        canvas.plot(0, 0, 0., Rgb::BLACK);
+        canvas.clear(Rgb::WHITE);
+        assert_eq!(vec![[2, 1, 0xff0000], [-1, 0, 0xffffff]], canvas.log);
+
+        let mut html = Vec::new();
+        canvas.write_html(&mut html, true).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("2,1,16711680,-1,0,16777215,"));
+        assert!(html.contains(r#"max="2""#));
+    }
+}
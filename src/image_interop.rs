@@ -0,0 +1,142 @@
+//! Conversions between [`Ppm`] and the buffers of the [`image`] crate, and loading textures with it.
+//!
+//! [`image`] reads PNG and JPEG without ImageMagick, so photos can be used as textures or backgrounds. Use it through
+//! [`Ppm::load_texture`], or convert between a [`Ppm`] and an [`RgbImage`] or [`RgbaImage`] with `From`.
+//!
+//! [`Ppm`]: ../ppm/struct.Ppm.html
+//! [`Ppm::load_texture`]: ../ppm/struct.Ppm.html#method.load_texture
+//! [`image`]: https://crates.io/crates/image
+//! [`RgbImage`]: https://docs.rs/image/0.25/image/type.RgbImage.html
+//! [`RgbaImage`]: https://docs.rs/image/0.25/image/type.RgbaImage.html
+
+use std::{convert::TryFrom, path::Path};
+
+use image::{ImageError, RgbImage, RgbaImage};
+
+use crate::{
+    color::PpmColor,
+    error::{self, GraphicsError},
+    ppm::{Ppm, PpmBuilder},
+    screen::Screen,
+};
+
+/// Decode a PNG or JPEG file into an image with a color depth of `255`.
+pub(crate) fn load_texture<C: PpmColor>(path: &Path) -> error::Result<Ppm<C>> {
+    let texture = image::open(path)
+        .map_err(|e| match e {
+            ImageError::IoError(e) => GraphicsError::Io(e),
+            e => GraphicsError::Parse(e.to_string()),
+        })?
+        .to_rgba8();
+    Ok(texture.into())
+}
+
+/// The size of `img` for the image crate.
+///
+/// # Panics
+///
+/// Panics if `img` is wider or taller than `u32::MAX` pixels.
+fn size<C: PpmColor>(img: &Ppm<C>) -> (u32, u32) {
+    let dimension = |n: usize| u32::try_from(n).expect("image too big for the image crate");
+    (dimension(img.width()), dimension(img.height()))
+}
+
+/// Channels are scaled to 8 bits, and the alpha is dropped.
+///
+/// # Panics
+///
+/// Panics if the image is wider or taller than `u32::MAX` pixels.
+impl<C: PpmColor> From<Ppm<C>> for RgbImage {
+    fn from(img: Ppm<C>) -> Self {
+        let (width, height) = size(&img);
+        let bytes = img
+            .to_rgba8()
+            .into_iter()
+            .flat_map(|[r, g, b, _]| [r, g, b])
+            .collect();
+        RgbImage::from_raw(width, height, bytes).expect("wrong number of pixels")
+    }
+}
+
+/// Channels are scaled to 8 bits.
+///
+/// # Panics
+///
+/// Panics if the image is wider or taller than `u32::MAX` pixels.
+impl<C: PpmColor> From<Ppm<C>> for RgbaImage {
+    fn from(img: Ppm<C>) -> Self {
+        let (width, height) = size(&img);
+        RgbaImage::from_raw(width, height, img.to_rgba_bytes()).expect("wrong number of pixels")
+    }
+}
+
+/// The image is built with default settings, and a color depth of `255`.
+impl<C: PpmColor> From<RgbImage> for Ppm<C> {
+    fn from(img: RgbImage) -> Self {
+        let data = img
+            .pixels()
+            .map(|p| C::from_channels([p[0] as u16, p[1] as u16, p[2] as u16]))
+            .collect();
+        PpmBuilder::with_color_type(img.height() as usize, img.width() as usize, 255)
+            .with_data(data)
+            .build()
+    }
+}
+
+/// The image is built with default settings, and a color depth of `255`. The alpha is dropped for color types without
+/// it.
+impl<C: PpmColor> From<RgbaImage> for Ppm<C> {
+    fn from(img: RgbaImage) -> Self {
+        PpmBuilder::from_rgba_bytes(img.width() as usize, img.height() as usize, img.as_raw())
+            .expect("wrong number of pixels")
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Rgb;
+
+    #[test]
+    fn test_conversions() {
+        let make = || {
+            let mut img = PpmBuilder::new(2, 3, 255).build();
+            img.plot(0, 1, 0., Rgb::new(255, 128, 0));
+            img.plot(2, 0, 0., Rgb::new(1, 2, 3));
+            img
+        };
+        let img = make();
+
+        let rgb = RgbImage::from(make());
+        assert_eq!((3, 2), rgb.dimensions());
+        // y goes up in a Ppm, and down in the image crate
+        assert_eq!([255, 128, 0], rgb.get_pixel(0, 0).0);
+        assert_eq!([1, 2, 3], rgb.get_pixel(2, 1).0);
+        let back: Ppm = rgb.into();
+        assert_eq!(img.data(), back.data());
+
+        let rgba = RgbaImage::from(make());
+        assert_eq!([255, 128, 0, 255], rgba.get_pixel(0, 0).0);
+        let back: Ppm = rgba.into();
+        assert_eq!(img.data(), back.data());
+    }
+
+    #[test]
+    fn test_load_texture() {
+        let mut img = PpmBuilder::new(4, 5, 255).build();
+        img.draw_line((0., 0., 0.), (4., 3., 0.), Rgb::new(10, 200, 30));
+        let path =
+            std::env::temp_dir().join(format!("graphics-texture-{}.png", std::process::id()));
+        img.save_png(&path).unwrap();
+
+        let texture = Ppm::<Rgb>::load_texture(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(img.data(), texture.unwrap().data());
+
+        match Ppm::<Rgb>::load_texture("does-not-exist.png") {
+            Err(GraphicsError::Io(_)) => {}
+            other => panic!("expected an io error, got {:?}", other.err()),
+        }
+    }
+}
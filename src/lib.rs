@@ -26,12 +26,16 @@
 //! # ImageMagick
 //! You should have ImageMagick installed on your computer as a command.
 //!
-//! On *nix, they are `convert` and `display`.
+//! On *nix, they are `convert` and `display`, or `magick` with ImageMagick 7.
 //! On Windows, they are `magick` and `imdisplay`.
 //!
 //! Both of these commands should be available in the shell that you run your Rust program.
-//! For Windows, if your ImageMagick commands are invoked by a different set of names, you should set the program to use
-//! the appropriate values in the [`magick`] module (magick.rs file).
+//! If your ImageMagick commands are invoked by a different set of names, set them with environment variables or in your
+//! code, as explained in the [`magick`] module.
+//!
+//! ImageMagick support can be turned off by disabling the default `magick` feature, for example to compile to
+//! WebAssembly (`wasm32-unknown-unknown`), where programs can't be run. Saving PNG, QOI, BMP, TGA and PPM files still
+//! works without it, and [`to_rgba_bytes`] gives the pixels in the layout a JavaScript canvas `ImageData` expects.
 //!
 //!
 //! # Structure Overview
@@ -50,11 +54,11 @@
 //! 2. Construct a [`Ppm`] through [`Ppm::new()`] or [`PpmBuilder`].
 //! 3. Call various drawing functions on [`Ppm`].
 //!     - Most of those functions are actually (or will be) implemented on the [`Screen`] trait, and [`Ppm`] implements the trait, so you can use them
-//!         on [`Ppm`] when [`Screen`] is in scope. Importing from [`prelude`] gives you all of that as a convenience.
+//!       on [`Ppm`] when [`Screen`] is in scope. Importing from [`prelude`] gives you all of that as a convenience.
 //! 4. Call [`display`] or [`save`] on your [`Ppm`] to see the final image. (Provided as part of [`Screen`] trait)
 //!     - Alternatively, you can call [`write_ascii_to_buf`] or [`write_binary_to_buf`] on [`Ppm`] and pass in a [`Write`]r.
-//!         - The [`Write`]r passed will be wrapped in a [`BufWriter`], so you should pass in a [`File`] directly.
-//!         - There is a variant that does not buffer the output. Check the [`Ppm`] struct for more information.
+//!       - The [`Write`]r passed will be wrapped in a [`BufWriter`], so you should pass in a [`File`] directly.
+//!       - There is a variant that does not buffer the output. Check the [`Ppm`] struct for more information.
 //!
 //! - Eventually, we will have other ways of doing things, but for now, this is the process.
 //!
@@ -66,11 +70,15 @@
 //! Look at main.rs to see how to draw stuff. Or go straight into work 1.
 //!
 //! # Work 1
-//! Head over to "screen.rs", ([`screen`] module), and implement the [`draw_line`] method. Read the docs for more info.
+//! Head over to "raster.rs", ([`raster`] module), and write your own line algorithm: implement [`Rasterizer`] on a struct of your own,
+//! and plug it into your image with [`PpmBuilder::rasterizer`]. Read the docs for more info.
+//!
+//! [`draw_line`] uses [`Bresenham`] unless told otherwise. That is the reference implementation, so you can draw the same lines
+//! with both and compare them pixel by pixel. Try not to peek at its source before you have your own working!
 //!
 //! After you implement that, run main.rs to see an example. For your creative work (gallery submission), feel free to explore
 //! other functionalities ([`draw_line_degrees`] and [`Turtle`]) provided to you for free! They are not part of the class material, but
-//! they depend on a correct implementation of `draw_line`.
+//! they all draw through the rasterizer, so they work with yours too.
 //!
 //! # Running Binary
 //! If you haven't figured out by now, `cargo run` will compile and run "main.rs". Subsequent `cargo run` won't compile again
@@ -78,6 +86,17 @@
 //!
 //! Use `cargo run --release` to run the optimized binary. Optimization isn't necessary now, but it will make the program a lot faster later on.
 //!
+//! # Features
+//! Besides `magick` (on by default), there are optional features for `audio` analysis, `parallel` filling, a live
+//! `window` opened with minifb, and loading PNG and JPEG textures with the `image` crate. They only add things, so any
+//! crate may turn them on.
+//!
+//! **`f32` is different.** It changes [`Float`] from `f64` to `f32`, which changes the types of many functions. Cargo turns
+//! a feature on for everyone as soon as a single crate asks for it, so a library that depends on this crate and passes an
+//! `f64` where a [`Float`] is expected stops compiling when some other crate turns on `f32`. Only ever enable `f32` in
+//! the `Cargo.toml` of the final binary, never in a library. Code that should work either way can write [`Float`]
+//! instead of `f64`.
+//!
 //! [module chapter]: https://doc.rust-lang.org/book/ch07-02-defining-modules-to-control-scope-and-privacy.html
 //! [Rust]: https://www.rust-lang.org/
 //! [the book]: https://doc.rust-lang.org/book/
@@ -94,20 +113,60 @@
 //! [`Screen`]: ./screen/trait.Screen.html
 //! [`screen`]: ./screen/index.html
 //! [`draw_line`]: ./screen/trait.Screen.html#method.draw_line
+//! [`raster`]: ./raster/index.html
+//! [`Rasterizer`]: ./raster/trait.Rasterizer.html
+//! [`Bresenham`]: ./raster/struct.Bresenham.html
+//! [`PpmBuilder::rasterizer`]: ./ppm/struct.PpmBuilder.html#method.rasterizer
 //! [`display`]: ./screen/trait.Screen.html#tymethod.display
 //! [`save`]: ./screen/trait.Screen.html#tymethod.save
 //! [`write_ascii_to_buf`]: ./ppm/struct.Ppm.html#method.write_ascii_to_buf
 //! [`write_binary_to_buf`]: ./ppm/struct.Ppm.html#method.write_binary_to_buf
 //! [`magick`]: ./magick/index.html
+//! [`to_rgba_bytes`]: ./ppm/struct.Ppm.html#method.to_rgba_bytes
 //! [`draw_line_degrees`]: ./screen/trait.Screen.html#method.draw_line_degrees
 //! [`Turtle`]: ./turtle/struct.Turtle.html
+//! [`Float`]: ./util/type.Float.html
 
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod blend;
+pub mod cache;
 pub mod color;
+pub mod colormap;
+pub mod daylight;
+pub mod dither;
+pub mod error;
+pub mod fill;
+pub mod font;
+pub mod fractal;
+pub mod gradient;
+pub mod hdr;
+pub mod html;
+pub mod lsystem;
+#[cfg(feature = "magick")]
 pub mod magick;
+#[cfg(not(feature = "magick"))]
+#[path = "no_magick.rs"]
+mod magick;
+pub mod metrics;
 pub mod ppm;
+pub mod random;
+pub mod raster;
+pub mod record;
 pub mod screen;
+pub mod testing;
+pub mod tile;
 pub mod turtle;
 pub mod util;
+#[cfg(feature = "window")]
+pub mod window;
+
+mod bmp;
+#[cfg(feature = "image")]
+mod image_interop;
+mod png;
+mod qoi;
+mod tga;
 
 /// Re-export common structs and traits for drawing.
 pub mod prelude {
@@ -115,5 +174,6 @@ pub mod prelude {
         color::Rgb,
         ppm::{Ppm, PpmBuilder},
         screen::Screen,
+        util::{Point, Vec3},
     };
 }
@@ -0,0 +1,232 @@
+//! L-systems: grow strings with rewriting rules, and draw them with a [`Turtle`].
+//!
+//! An [`LSystem`] starts from an axiom, and replaces every symbol that has a rule with the rule's replacement, all at
+//! the same time, once per level of depth. The result is read as commands for a turtle:
+//!
+//! | Symbol | Command |
+//! |--------|---------|
+//! | `F`, `G` | move forward one step, drawing a line |
+//! | `f` | move forward one step without drawing |
+//! | `+` | turn left by the angle |
+//! | `-` | turn right by the angle |
+//! | `\|` | turn around |
+//! | `[` | save the turtle's state (see [`push`]) |
+//! | `]` | go back to the last saved state (see [`pop`]) |
+//!
+//! Other symbols, like `X` in the plant below, are only there to be rewritten, and are ignored when drawing.
+//!
+//! Strings grow exponentially with depth, so a depth around 4 to 8 is usually plenty.
+//!
+//! # Examples
+//!
+//! A fractal plant:
+//!
+//! ```
+//! use graphics::{lsystem::LSystem, prelude::*, turtle::Turtle};
+//!
+//! let plant = LSystem::new("X")
+//!     .rule('X', "F+[[X]-X]-F[-FX]+X")
+//!     .rule('F', "FF")
+//!     .angle(25.);
+//!
+//! let mut turtle = Turtle::new(Ppm::new(), 250., 0., Rgb::new(0, 150, 0));
+//! turtle.set_heading(90.);
+//! plant.draw(&mut turtle, 5, 2.);
+//!
+//! let img = turtle.get_screen();
+//! // img.save("plant.png").expect("error saving plant.png");
+//! ```
+//!
+//! [`LSystem`]: ./struct.LSystem.html
+//! [`Turtle`]: ../turtle/struct.Turtle.html
+//! [`push`]: ../turtle/struct.Turtle.html#method.push
+//! [`pop`]: ../turtle/struct.Turtle.html#method.pop
+
+use std::collections::HashMap;
+
+use crate::{screen::Screen, turtle::Turtle};
+
+/// An axiom and rewriting rules, with the angle to turn by when drawing. See the [module documentation].
+///
+/// [module documentation]: ./index.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct LSystem {
+    axiom: String,
+    rules: HashMap<char, String>,
+    angle: f64,
+}
+
+impl LSystem {
+    /// An L-system starting from `axiom`, without rules yet, turning by 90 degrees.
+    pub fn new(axiom: &str) -> Self {
+        LSystem {
+            axiom: axiom.to_string(),
+            rules: HashMap::new(),
+            angle: 90.,
+        }
+    }
+
+    /// Replace `symbol` with `replacement` at every level. A later rule for the same symbol replaces the earlier one.
+    pub fn rule(mut self, symbol: char, replacement: &str) -> Self {
+        self.rules.insert(symbol, replacement.to_string());
+        self
+    }
+
+    /// Turn by `angle` degrees for `+` and `-`.
+    pub fn angle(mut self, angle: f64) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    /// The string after rewriting the axiom `depth` times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::lsystem::LSystem;
+    ///
+    /// // Lindenmayer's algae
+    /// let algae = LSystem::new("A").rule('A', "AB").rule('B', "A");
+    /// assert_eq!("A", algae.expand(0));
+    /// assert_eq!("ABAAB", algae.expand(3));
+    /// ```
+    pub fn expand(&self, depth: usize) -> String {
+        let mut current = self.axiom.clone();
+        for _ in 0..depth {
+            let mut next = String::with_capacity(current.len() * 2);
+            for symbol in current.chars() {
+                match self.rules.get(&symbol) {
+                    Some(replacement) => next.push_str(replacement),
+                    None => next.push(symbol),
+                }
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// Expand the L-system to `depth`, and draw it with `turtle`, moving `step` pixels at a time. See [`interpret`].
+    ///
+    /// [`interpret`]: #method.interpret
+    pub fn draw<T: Screen>(&self, turtle: &mut Turtle<T>, depth: usize, step: f64) {
+        self.interpret(&self.expand(depth), turtle, step);
+    }
+
+    /// Follow the commands in `commands` with `turtle`, from where it is, moving `step` pixels at a time and turning
+    /// by this L-system's angle. See the [module documentation] for the commands.
+    ///
+    /// The turtle's pen is put down for `F` and `G`, and lifted for `f`. It's left the way it was at the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{lsystem::LSystem, prelude::*, turtle::Turtle};
+    ///
+    /// let mut turtle = Turtle::new(Ppm::new(), 100., 100., Rgb::WHITE);
+    /// turtle.record_path = true;
+    ///
+    /// // a square, then a line after a gap
+    /// LSystem::new("").interpret("F+F+F+FfF", &mut turtle, 50.);
+    ///
+    /// assert_eq!(2, turtle.path().len());
+    /// assert_eq!(5, turtle.path()[0].points.len());
+    /// ```
+    ///
+    /// [module documentation]: ./index.html
+    pub fn interpret<T: Screen>(&self, commands: &str, turtle: &mut Turtle<T>, step: f64) {
+        let pen_down = turtle.pen_down;
+        for command in commands.chars() {
+            match command {
+                'F' | 'G' => {
+                    turtle.pen_down = true;
+                    turtle.forward(step);
+                }
+                'f' => {
+                    turtle.pen_down = false;
+                    turtle.forward(step);
+                }
+                '+' => turtle.turn_left(self.angle),
+                '-' => turtle.turn_right(self.angle),
+                '|' => turtle.turn_right(180.),
+                '[' => turtle.push(),
+                ']' => {
+                    turtle.pop();
+                }
+                _ => {}
+            }
+        }
+        turtle.pen_down = pen_down;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Rgb, testing::MockScreen};
+
+    fn turtle() -> Turtle<MockScreen> {
+        Turtle::new(MockScreen::new(100, 100), 50., 50., Rgb::WHITE)
+    }
+
+    #[test]
+    fn test_expand() {
+        let algae = LSystem::new("A").rule('A', "AB").rule('B', "A");
+        let expanded: Vec<String> = (0..5).map(|depth| algae.expand(depth)).collect();
+        assert_eq!(vec!["A", "AB", "ABA", "ABAAB", "ABAABABA"], expanded);
+
+        // symbols without rules stay as they are
+        let koch = LSystem::new("F--F").rule('F', "F+F");
+        assert_eq!("F+F--F+F", koch.expand(1));
+
+        // a later rule for the same symbol replaces the first
+        assert_eq!(
+            "BB",
+            LSystem::new("A").rule('A', "C").rule('A', "BB").expand(1)
+        );
+    }
+
+    #[test]
+    fn test_expand_degenerate() {
+        assert_eq!("", LSystem::new("").rule('F', "FF").expand(10));
+        assert_eq!("", LSystem::new("F").rule('F', "").expand(3));
+        assert_eq!("F", LSystem::new("F").rule('F', "FF").expand(0));
+        assert_eq!(1 << 10, LSystem::new("F").rule('F', "FF").expand(10).len());
+    }
+
+    #[test]
+    fn test_interpret() {
+        let square = LSystem::new("F+F+F+F");
+        let mut turtle = turtle();
+        square.draw(&mut turtle, 0, 10.);
+        let (x, y) = turtle.position();
+        assert!((x - 50.).abs() < 1e-9 && (y - 50.).abs() < 1e-9);
+        assert_eq!(Some((50, 40, 60, 50)), turtle.get_screen().bounding_box());
+    }
+
+    #[test]
+    fn test_moves_without_drawing() {
+        let system = LSystem::new("").angle(45.);
+        let mut turtle = turtle();
+        turtle.pen_down = false;
+        // 'f' moves without drawing, brackets come back, unknown symbols are skipped
+        system.interpret("fX[+F]|", &mut turtle, 10.);
+        assert!(!turtle.pen_down);
+        assert_eq!((60., 50.), turtle.position());
+        assert_eq!(180., turtle.heading());
+        let screen = turtle.get_screen();
+        assert!(!screen.contains_point(55, 50));
+        assert!(screen.contains_point(60, 50) && screen.contains_point(67, 43));
+    }
+
+    #[test]
+    fn test_interpret_degenerate() {
+        let mut turtle = turtle();
+        turtle.pen_down = true;
+        let system = LSystem::new("F").angle(f64::NAN);
+        // unbalanced brackets, NaN angles and steps
+        system.interpret("]]F[[", &mut turtle, 0.);
+        system.interpret("+F", &mut turtle, f64::NAN);
+        system.interpret("", &mut turtle, 10.);
+        assert!(turtle.pen_down);
+    }
+}
@@ -1,47 +1,644 @@
 //! Functions to help run ImageMagick commands as subprocesses.
+//!
+//! # Configuration
+//!
+//! By default, the commands are [detected] the first time they're needed. ImageMagick 7 is used through its `magick`
+//! front-end, as `magick` and `magick display` (`imdisplay` on Windows). Otherwise, images are converted with
+//! `magick` on Windows and `convert` elsewhere, and displayed with `imdisplay` on Windows and `display` elsewhere.
+//! For a nonstandard install, or to use [GraphicsMagick] instead, either set
+//! environment variables before running the program:
+//!
+//! - `GRAPHICS_MAGICK_CMD` for the command converting images, like `gm convert`,
+//! - `GRAPHICS_DISPLAY_CMD` for the command displaying images, like `gm display`,
+//!
+//! or call [`set_commands`] at the start of `main`:
+//!
+//! ```no_run
+//! use graphics::magick::{self, MagickCommands};
+//!
+//! magick::set_commands(MagickCommands {
+//!     convert: vec!["gm".to_string(), "convert".to_string()],
+//!     display: vec![r"C:\Program Files\ImageMagick\imdisplay.exe".to_string()],
+//! });
+//! ```
+//!
+//! If the display command can't be found, like on WSL or a minimal install without X11, images are saved as PNG
+//! files in the temp directory and opened with the system's default viewer, through `xdg-open` (or `wslview`),
+//! `open` on macOS, or `start` on Windows.
+//!
+//! To see which commands are used, and whether they can be found, call [`check_installation`].
+//!
+//! [detected]: ./struct.MagickCommands.html#method.detect
+//! [`check_installation`]: ./fn.check_installation.html
+//! [GraphicsMagick]: http://www.graphicsmagick.org/
+//! [`set_commands`]: ./fn.set_commands.html
 
 use std::{
+    env,
     fs::{self, File},
-    io,
-    process::Command,
-    process::{Child, Stdio},
+    io::{self, Write},
+    mem,
+    path::{Path, PathBuf},
+    process::{self, Child, Command, Output, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock, RwLock,
+    },
+    thread,
 };
 
-use crate::ppm::Ppm;
+use crate::{
+    color::PpmColor,
+    error::{GraphicsError, Result},
+    ppm::Ppm,
+};
+
+/// The ImageMagick commands used to convert and display images. See the [module documentation].
+///
+/// Every command is a program followed by the arguments that always come first, so `vec!["gm", "convert"]` runs
+/// `gm convert <args>`.
+///
+/// [module documentation]: ./index.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MagickCommands {
+    /// Command converting images, used by [`save`] and [`pipe_to_magick`].
+    ///
+    /// [`save`]: ../screen/trait.Screen.html#tymethod.save
+    /// [`pipe_to_magick`]: ./fn.pipe_to_magick.html
+    pub convert: Vec<String>,
+    /// Command displaying an image file in a window, used by [`display`].
+    ///
+    /// [`display`]: ../screen/trait.Screen.html#tymethod.display
+    pub display: Vec<String>,
+}
+
+impl MagickCommands {
+    /// `magick` and `imdisplay` on Windows, `convert` and `display` elsewhere.
+    pub fn platform_default() -> Self {
+        let (convert, display) = if cfg!(windows) {
+            ("magick", "imdisplay")
+        } else {
+            ("convert", "display")
+        };
+        MagickCommands {
+            convert: vec![convert.to_string()],
+            display: vec![display.to_string()],
+        }
+    }
+
+    /// The commands for the installed ImageMagick: `magick` and `magick display` (`imdisplay` on Windows) if
+    /// `magick -version` reports ImageMagick 7, and the [`platform_default`] otherwise.
+    ///
+    /// The check runs ImageMagick once, and its result is reused afterwards.
+    ///
+    /// [`platform_default`]: #method.platform_default
+    pub fn detect() -> Self {
+        static DETECTED: OnceLock<MagickCommands> = OnceLock::new();
+        DETECTED
+            .get_or_init(|| match version(&["magick".to_string()]) {
+                Ok(line) if is_im7(&line) => MagickCommands {
+                    convert: vec!["magick".to_string()],
+                    display: if cfg!(windows) {
+                        vec!["imdisplay".to_string()]
+                    } else {
+                        vec!["magick".to_string(), "display".to_string()]
+                    },
+                },
+                _ => Self::platform_default(),
+            })
+            .clone()
+    }
+
+    /// The [`detect`]ed commands, with commands replaced by the `GRAPHICS_MAGICK_CMD` and `GRAPHICS_DISPLAY_CMD`
+    /// environment variables when they are set. Their values are split on whitespace.
+    ///
+    /// [`detect`]: #method.detect
+    pub fn from_env() -> Self {
+        let mut commands = Self::detect();
+        let var = |name| {
+            env::var(name)
+                .ok()
+                .map(|v| v.split_whitespace().map(String::from).collect::<Vec<_>>())
+                .filter(|words| !words.is_empty())
+        };
+        if let Some(convert) = var("GRAPHICS_MAGICK_CMD") {
+            commands.convert = convert;
+        }
+        if let Some(display) = var("GRAPHICS_DISPLAY_CMD") {
+            commands.display = display;
+        }
+        commands
+    }
+}
+
+impl Default for MagickCommands {
+    fn default() -> Self {
+        Self::platform_default()
+    }
+}
+
+/// The commands in use, read from the environment the first time they're needed.
+fn config() -> &'static RwLock<MagickCommands> {
+    static COMMANDS: OnceLock<RwLock<MagickCommands>> = OnceLock::new();
+    COMMANDS.get_or_init(|| RwLock::new(MagickCommands::from_env()))
+}
+
+/// Use `commands` from now on, instead of the ones from the environment or the detected ones. Empty commands are
+/// replaced with the [`detect`]ed ones.
+///
+/// [`detect`]: ./struct.MagickCommands.html#method.detect
+pub fn set_commands(mut commands: MagickCommands) {
+    let default = MagickCommands::detect();
+    if commands.convert.is_empty() {
+        commands.convert = default.convert;
+    }
+    if commands.display.is_empty() {
+        commands.display = default.display;
+    }
+    *config().write().unwrap_or_else(|e| e.into_inner()) = commands;
+}
+
+/// The commands in use. See [`set_commands`].
+///
+/// [`set_commands`]: ./fn.set_commands.html
+pub fn commands() -> MagickCommands {
+    config().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// What [`check_installation`] found.
+///
+/// [`check_installation`]: ./fn.check_installation.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MagickInfo {
+    /// The first line of the version information, like `Version: ImageMagick 7.1.1-15 Q16-HDRI x86_64 ...`.
+    pub version: String,
+    /// The commands in use. See [`commands`].
+    ///
+    /// [`commands`]: ./fn.commands.html
+    pub commands: MagickCommands,
+    /// Where the program converting images is.
+    pub convert_path: PathBuf,
+    /// Where the program displaying images is, or `None` if it can't be found. Saving images works without it.
+    pub display_path: Option<PathBuf>,
+}
+
+/// Check that ImageMagick is installed, and find out which version it is and where it's installed.
+///
+/// This fails with [`GraphicsError::MagickNotFound`] if the program converting images can't be found, and with
+/// [`GraphicsError::MagickFailed`] if it can't even print its version. A missing display program isn't an error, since
+/// only [`display`] needs it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use graphics::magick;
+///
+/// match magick::check_installation() {
+///     Ok(info) => println!("{} at {}", info.version, info.convert_path.display()),
+///     Err(e) => eprintln!("{}", e),
+/// }
+/// ```
+///
+/// [`GraphicsError::MagickNotFound`]: ../error/enum.GraphicsError.html#variant.MagickNotFound
+/// [`GraphicsError::MagickFailed`]: ../error/enum.GraphicsError.html#variant.MagickFailed
+/// [`display`]: ../screen/trait.Screen.html#tymethod.display
+pub fn check_installation() -> Result<MagickInfo> {
+    let commands = commands();
+    let convert_path =
+        find_program(&commands.convert[0]).ok_or_else(|| GraphicsError::MagickNotFound {
+            command: commands.convert[0].clone(),
+        })?;
+    let version = version(&commands.convert)?;
+    let display_path = find_program(&commands.display[0]);
+    Ok(MagickInfo {
+        version,
+        commands,
+        convert_path,
+        display_path,
+    })
+}
+
+/// Where `program` is: itself if it's a path, or the first match in the `PATH` directories otherwise. On Windows,
+/// the usual executable extensions are tried as well.
+fn find_program(program: &str) -> Option<PathBuf> {
+    let extensions: &[&str] = if cfg!(windows) {
+        &["", ".exe", ".com", ".bat", ".cmd"]
+    } else {
+        &[""]
+    };
+    let find_in = |dir: &Path| {
+        extensions
+            .iter()
+            .map(|ext| dir.join(format!("{}{}", program, ext)))
+            .find(|candidate| candidate.is_file())
+    };
+
+    if Path::new(program).components().count() > 1 {
+        return find_in(Path::new(""));
+    }
+    env::split_paths(&env::var_os("PATH")?).find_map(|dir| find_in(&dir))
+}
+
+/// A [`Command`] running `words`: a program and its first arguments.
+///
+/// [`Command`]: https://doc.rust-lang.org/std/process/struct.Command.html
+fn command(words: &[String]) -> Command {
+    let mut cmd = Command::new(&words[0]);
+    cmd.args(&words[1..]);
+    cmd
+}
+
+/// The first line `words -version` prints.
+fn version(words: &[String]) -> Result<String> {
+    let child = spawn(
+        command(words)
+            .arg("-version")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )?;
+    let output = check(child.wait_with_output()?)?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Whether a `-version` line, like `Version: ImageMagick 7.1.1-15 Q16-HDRI x86_64`, is from ImageMagick 7 or later.
+fn is_im7(version: &str) -> bool {
+    version
+        .split_whitespace()
+        .skip_while(|&word| word != "ImageMagick")
+        .nth(1)
+        .and_then(|number| number.split('.').next()?.parse::<u32>().ok())
+        .is_some_and(|major| major >= 7)
+}
+
+/// The command converting images.
+fn convert_command() -> Command {
+    command(&commands().convert)
+}
+
+/// Spawn `cmd`, reporting a missing program as [`GraphicsError::MagickNotFound`].
+///
+/// [`GraphicsError::MagickNotFound`]: ../error/enum.GraphicsError.html#variant.MagickNotFound
+fn spawn(cmd: &mut Command) -> Result<Child> {
+    cmd.spawn().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => GraphicsError::MagickNotFound {
+            command: cmd.get_program().to_string_lossy().into_owned(),
+        },
+        _ => GraphicsError::Io(e),
+    })
+}
+
+/// Turn the output of a finished ImageMagick process into an error if it failed.
+fn check(output: Output) -> Result<Output> {
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(GraphicsError::MagickFailed {
+            status: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
 
 /// Subprocess (and run) `(magick) convert` with a piped stdin with the given `args`.
 ///
 /// This function will be very useful later on when we deal with animations
 /// (by piping all the image data to ImageMagick and letting it make a gif out of it).
-pub fn pipe_to_magick(args: &[&str]) -> io::Result<Child> {
-    Command::new(if cfg!(windows) { "magick" } else { "convert" })
-        .args(args)
-        .stdin(Stdio::piped())
-        .spawn()
+pub fn pipe_to_magick(args: &[&str]) -> Result<Child> {
+    spawn(convert_command().args(args).stdin(Stdio::piped()))
 }
 
-/// Write Ppm to a temporary file and display with ImageMagick.
+/// Run `(magick) convert` with `args`, give it `input` on stdin, and wait for it to finish.
+pub(crate) fn convert_with_input(args: &[&str], input: &[u8]) -> Result<()> {
+    run_with_input(convert_command().args(args), input)
+}
+
+/// Run `cmd`, give it `input` on stdin, and wait for it to finish.
+fn run_with_input(cmd: &mut Command, input: &[u8]) -> Result<()> {
+    let mut child = spawn(cmd.stdin(Stdio::piped()).stderr(Stdio::piped()))?;
+    // This command should have a stdin, so it's ok to unwrap
+    let written = child.stdin.take().unwrap().write_all(input);
+    check(child.wait_with_output()?)?;
+    // a failing ImageMagick usually closes stdin early, so only report write errors if it succeeded
+    Ok(written?)
+}
+
+/// Decode the image file at `path` with `(magick) convert`, and return it as a binary ppm.
 ///
-/// This method requires ImageMagick `display` to be installed as `imdisplay` on Windows and `display` on *nix.
-pub(crate) fn display_ppm(img: &Ppm) -> io::Result<()> {
-    let tmpfile_name = "tmp.ppm";
+/// This is how formats without a decoder in this crate, like png and jpg, are opened.
+pub(crate) fn read_as_ppm(path: &Path) -> Result<Vec<u8>> {
+    let child = spawn(
+        convert_command()
+            .arg(path)
+            .arg("ppm:-")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )?;
+    Ok(check(child.wait_with_output()?)?.stdout)
+}
+
+/// A file in the OS temp directory that is removed when this is dropped, even if displaying fails or panics.
+#[derive(Debug)]
+struct TempFile(PathBuf);
+
+impl TempFile {
+    /// A path unique to this process and call, so concurrent programs and threads don't overwrite each other's
+    /// files. The file isn't created.
+    fn new(extension: &str) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "graphics-{}-{}.{}",
+            process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed),
+            extension
+        );
+        TempFile(env::temp_dir().join(name))
+    }
 
-    img.write_binary_to_buf(&mut File::create(tmpfile_name)?)?;
+    /// Leave the file in place, for programs that may read it after this one exits.
+    fn keep(self) {
+        mem::forget(self);
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // the file may never have been created
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Display Ppm with ImageMagick.
+///
+/// The image is streamed to the display command as `ppm:-` on stdin, so nothing is written to disk. Viewers that
+/// can't read stdin, like `imdisplay`, or that fail to, get a file in the OS temp directory instead, which is removed
+/// afterwards.
+///
+/// This method requires ImageMagick `display` to be installed as `imdisplay` on Windows and `display` on *nix, unless
+/// another command is configured. See the [module documentation]. If the display command can't be found, the image
+/// is opened in the system's default viewer instead, with [`open_in_system_viewer`].
+///
+/// [module documentation]: ./index.html
+/// [`open_in_system_viewer`]: ./fn.open_in_system_viewer.html
+pub(crate) fn display_ppm<C: PpmColor>(img: &Ppm<C>) -> Result<()> {
+    let display = commands().display;
+    let result = if reads_stdin(&display[0]) {
+        match display_piped(img, &display) {
+            Err(GraphicsError::MagickFailed { .. }) => display_file(img, &display),
+            result => result,
+        }
+    } else {
+        display_file(img, &display)
+    };
+    match result {
+        Err(e @ GraphicsError::MagickNotFound { .. }) => {
+            check(open_in_system_viewer(img, e)?.wait_with_output()?).map(drop)
+        }
+        result => result,
+    }
+}
 
-    let mut cmd = Command::new(if cfg!(windows) {
-        "imdisplay"
+/// Programs opening a file in the default viewer, in the order they're tried.
+fn system_viewers() -> Vec<Vec<&'static str>> {
+    if cfg!(windows) {
+        vec![vec!["cmd", "/C", "start", ""]]
+    } else if cfg!(target_os = "macos") {
+        vec![vec!["open"]]
     } else {
-        "display"
-    });
+        // wslview opens files with the Windows default program from WSL
+        vec![vec!["xdg-open"], vec!["wslview"]]
+    }
+}
+
+/// Save the image as a PNG in the OS temp directory, and start the first of `xdg-open`, `wslview`, `open` (on macOS)
+/// or `start` (on Windows) that can be found on it, returning `not_found` if none can.
+///
+/// These programs usually exit before the viewer has read the file, so it's left in the temp directory.
+fn open_in_system_viewer<C: PpmColor>(img: &Ppm<C>, not_found: GraphicsError) -> Result<Child> {
+    let tmpfile = TempFile::new("png");
+    img.save_png(&tmpfile.0)?;
+    for viewer in system_viewers() {
+        let mut cmd = Command::new(viewer[0]);
+        cmd.args(&viewer[1..])
+            .arg(&tmpfile.0)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        match spawn(&mut cmd) {
+            Ok(child) => {
+                tmpfile.keep();
+                return Ok(child);
+            }
+            Err(GraphicsError::MagickNotFound { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Err(not_found)
+}
+
+/// Whether the display `program` can read an image from stdin.
+fn reads_stdin(program: &str) -> bool {
+    // split by hand, so Windows paths are recognized on every platform
+    let name = program
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(program)
+        .to_ascii_lowercase();
+    name != "imdisplay" && !name.starts_with("imdisplay.")
+}
+
+/// Stream the image to `display ppm:-`.
+fn display_piped<C: PpmColor>(img: &Ppm<C>, display: &[String]) -> Result<()> {
+    let mut ppm = Vec::new();
+    img.write_binary_nobuffer(&mut ppm)?;
+    run_with_input(command(display).arg("ppm:-"), &ppm)
+}
+
+/// Write the image to a temporary file and display that.
+fn display_file<C: PpmColor>(img: &Ppm<C>, display: &[String]) -> Result<()> {
+    let tmpfile = TempFile::new("ppm");
+
+    img.write_binary_to_buf(&mut File::create(&tmpfile.0)?)?;
+
+    let child = spawn(command(display).arg(&tmpfile.0).stderr(Stdio::piped()))?;
+    check(child.wait_with_output()?).map(drop)
+}
+
+/// A viewer window opened by [`display_async`], which the program doesn't wait for.
+///
+/// Call [`wait`] to pause until the window is closed. Dropping the handle, or calling [`detach`], leaves the window
+/// open. A temporary file made for the viewer is removed once it closes, or left in the OS temp directory if the
+/// program exits first.
+///
+/// [`display_async`]: ../ppm/struct.Ppm.html#method.display_async
+/// [`wait`]: #method.wait
+/// [`detach`]: #method.detach
+#[derive(Debug)]
+pub struct DisplayHandle {
+    // `None` once waited for or dropped
+    child: Option<Child>,
+    tmpfile: Option<TempFile>,
+}
+
+impl DisplayHandle {
+    /// Pause the current thread until the viewer window is closed.
+    pub fn wait(mut self) -> Result<()> {
+        let child = self.child.take().unwrap();
+        check(child.wait_with_output()?).map(drop)
+    }
+
+    /// Whether the viewer window was closed. Call [`wait`] afterwards to find out if the viewer failed.
+    ///
+    /// [`wait`]: #method.wait
+    pub fn is_finished(&mut self) -> Result<bool> {
+        Ok(self.child.as_mut().unwrap().try_wait()?.is_some())
+    }
+
+    /// Close the viewer window.
+    pub fn kill(mut self) -> Result<()> {
+        let mut child = self.child.take().unwrap();
+        let killed = child.kill();
+        child.wait()?;
+        Ok(killed?)
+    }
+
+    /// Let the viewer window stay open on its own. This is the same as dropping the handle.
+    pub fn detach(self) {}
+
+    /// The process id of the viewer.
+    pub fn id(&self) -> u32 {
+        self.child.as_ref().unwrap().id()
+    }
+}
+
+impl Drop for DisplayHandle {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let tmpfile = self.tmpfile.take();
+            // reap the viewer and remove its file once it's closed
+            thread::spawn(move || {
+                let _ = child.wait();
+                drop(tmpfile);
+            });
+        }
+    }
+}
+
+/// Open a viewer for the image without waiting for it to close. Like [`display_ppm`], the image is streamed on stdin
+/// when the viewer can read it, written to a temporary file otherwise, and opened in the system's default viewer if
+/// the display command can't be found.
+///
+/// [`display_ppm`]: ./fn.display_ppm.html
+pub(crate) fn display_ppm_async<C: PpmColor>(img: &Ppm<C>) -> Result<DisplayHandle> {
+    match spawn_display(img, &commands().display) {
+        Err(e @ GraphicsError::MagickNotFound { .. }) => Ok(DisplayHandle {
+            child: Some(open_in_system_viewer(img, e)?),
+            tmpfile: None,
+        }),
+        result => result,
+    }
+}
+
+/// Start `display` on the image.
+fn spawn_display<C: PpmColor>(img: &Ppm<C>, display: &[String]) -> Result<DisplayHandle> {
+    if reads_stdin(&display[0]) {
+        let mut ppm = Vec::new();
+        img.write_binary_nobuffer(&mut ppm)?;
+        let mut child = spawn(
+            command(display)
+                .arg("ppm:-")
+                .stdin(Stdio::piped())
+                .stderr(Stdio::piped()),
+        )?;
+        // the viewer reads the whole image before opening its window, so this doesn't wait for the window
+        let written = child.stdin.take().unwrap().write_all(&ppm);
+        match written {
+            Ok(()) => {
+                return Ok(DisplayHandle {
+                    child: Some(child),
+                    tmpfile: None,
+                })
+            }
+            // the viewer stopped reading, so it probably can't read stdin
+            Err(e) => match check(child.wait_with_output()?) {
+                Err(GraphicsError::MagickFailed { .. }) => {}
+                Err(other) => return Err(other),
+                Ok(_) => return Err(e.into()),
+            },
+        }
+    }
+
+    let tmpfile = TempFile::new("ppm");
+    img.write_binary_to_buf(&mut File::create(&tmpfile.0)?)?;
+    let child = spawn(command(display).arg(&tmpfile.0).stderr(Stdio::piped()))?;
+    Ok(DisplayHandle {
+        child: Some(child),
+        tmpfile: Some(tmpfile),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_commands() {
+        let original = commands();
+
+        set_commands(MagickCommands {
+            convert: vec!["gm".to_string(), "convert".to_string()],
+            display: vec![],
+        });
+        let current = commands();
+        assert_eq!(vec!["gm", "convert"], current.convert);
+        assert_eq!(MagickCommands::detect().display, current.display);
+
+        let cmd = command(&current.convert);
+        assert_eq!("gm", cmd.get_program());
+        assert_eq!(vec!["convert"], cmd.get_args().collect::<Vec<_>>());
+
+        set_commands(original);
+    }
+
+    #[test]
+    fn test_temp_file() {
+        let (a, b) = (TempFile::new("ppm"), TempFile::new("ppm"));
+        assert_ne!(a.0, b.0);
+        assert!(a.0.starts_with(env::temp_dir()));
+
+        let path = a.0.clone();
+        File::create(&path).unwrap();
+        drop(a);
+        assert!(!path.exists());
+    }
 
-    let mut display = cmd
-        // .arg("-flip")
-        .arg(tmpfile_name)
-        .spawn()
-        .unwrap();
+    #[test]
+    fn test_reads_stdin() {
+        assert!(reads_stdin("display"));
+        assert!(reads_stdin("magick"));
+        assert!(!reads_stdin("imdisplay"));
+        assert!(!reads_stdin(r"C:\Program Files\ImageMagick\IMDisplay.exe"));
+    }
 
-    let _result = display.wait().unwrap();
-    fs::remove_file(tmpfile_name)?;
+    #[test]
+    fn test_find_program() {
+        assert_eq!(None, find_program("graphics-no-such-program"));
+        #[cfg(unix)]
+        assert!(find_program("sh").is_some_and(|path| path.is_absolute()));
+    }
 
-    Ok(())
+    #[test]
+    fn test_is_im7() {
+        assert!(is_im7(
+            "Version: ImageMagick 7.1.1-15 Q16-HDRI x86_64 21298 https://imagemagick.org"
+        ));
+        assert!(!is_im7(
+            "Version: ImageMagick 6.9.11-60 Q16 x86_64 2021-01-25 https://imagemagick.org"
+        ));
+        assert!(!is_im7(
+            "GraphicsMagick 1.3.38 2022-03-26 Q16 http://www.GraphicsMagick.org/"
+        ));
+    }
 }
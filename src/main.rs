@@ -1,6 +1,6 @@
 use std::fs::File;
 
-use graphics::{prelude::*, turtle::Turtle};
+use graphics::{cache::RenderCache, prelude::*, turtle::Turtle};
 
 fn main() {
     let width = 500;
@@ -86,7 +86,10 @@ fn main() {
 
     img.write_ascii_to_buf(&mut File::create("ascii.ppm").expect("error creating ascii.ppm"))
         .expect("error writing to file");
-    img.save("img.png").expect("error saving file as png");
+
+    // Like img.save("img.png"), but skips encoding when the image didn't change since the last run
+    let cache = RenderCache::new("target/render-cache").expect("error creating render cache");
+    img.save_cached("img.png", &cache).expect("error saving file as png");
 
     // You can also add return type io::Result<()> to main(), and use ? where you see `expect`
 }
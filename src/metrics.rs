@@ -0,0 +1,207 @@
+//! Image quality metrics for comparing two images.
+//!
+//! These are useful to put a number on how much a setting (supersampling, dithering, ...) changes the output.
+//!
+//! - [`mse`] (mean squared error) is the average of the squared differences of all color channels, so `0` means
+//!   identical.
+//! - [`psnr`] (peak signal-to-noise ratio) is measured in decibels. Higher is better, identical images give infinity.
+//!   Above 40dB differences are hard to see.
+//! - [`ssim`] (structural similarity) is in `[-1, 1]`, and `1` means identical. It tracks perceived quality better than PSNR,
+//!   because it compares local structure instead of raw pixel errors. This is a simplified version working on luminance
+//!   over non-overlapping 8x8 windows, instead of the gaussian-weighted sliding window of the original paper.
+//!
+//! All functions return `None` if the images don't have the same dimensions.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{metrics, prelude::*};
+//!
+//! let mut a = Ppm::new();
+//! let mut b = Ppm::new();
+//! a.draw_line((0., 0., 0.), (499., 400., 0.), Rgb::WHITE);
+//! b.draw_line((0., 0., 0.), (499., 401., 0.), Rgb::WHITE);
+//!
+//! assert_eq!(Some(0.), metrics::mse(&a, &a));
+//! assert!(metrics::mse(&a, &b).unwrap() > 0.);
+//! assert_eq!(Some(f64::INFINITY), metrics::psnr(&a, &a));
+//! assert!(metrics::psnr(&a, &b).unwrap() > 20.);
+//! assert_eq!(Some(1.), metrics::ssim(&a, &a));
+//! assert!(metrics::ssim(&a, &b).unwrap() < 1.);
+//! ```
+//!
+//! For a picture of where two images differ, see [`Ppm::diff`].
+//!
+//! [`mse`]: ./fn.mse.html
+//! [`psnr`]: ./fn.psnr.html
+//! [`ssim`]: ./fn.ssim.html
+//! [`Ppm::diff`]: ../ppm/struct.Ppm.html#method.diff
+
+use crate::{color::Rgb, ppm::Ppm, screen::Screen};
+
+/// Side length of the square windows used by [`ssim`].
+///
+/// [`ssim`]: ./fn.ssim.html
+const SSIM_WINDOW: usize = 8;
+
+/// Mean squared error between `a` and `b`, over all color channels.
+///
+/// Channels are compared as they are, so both images should have the same color depth.
+pub fn mse(a: &Ppm, b: &Ppm) -> Option<f64> {
+    if !same_size(a, b) {
+        return None;
+    }
+
+    let (a_data, b_data) = (a.data(), b.data());
+    let mut sum = 0.;
+    for (p, q) in a_data.iter().zip(b_data.iter()) {
+        for (x, y) in channels(*p).iter().zip(channels(*q).iter()) {
+            sum += (x - y) * (x - y);
+        }
+    }
+    Some(sum / (a_data.len() * 3) as f64)
+}
+
+/// Peak signal-to-noise ratio between `a` and `b`, in decibels, over all color channels.
+///
+/// The peak value is the larger color depth of the two images.
+pub fn psnr(a: &Ppm, b: &Ppm) -> Option<f64> {
+    let mse = mse(a, b)?;
+    let peak = a.color_depth().max(b.color_depth()) as f64;
+    Some(10. * (peak * peak / mse).log10())
+}
+
+/// Simplified structural similarity index between `a` and `b`.
+///
+/// Images smaller than the window size are compared as a single window. When the size of the image isn't a multiple of
+/// the window size, the last row and column of windows overlap the ones before them, so that every pixel is compared.
+pub fn ssim(a: &Ppm, b: &Ppm) -> Option<f64> {
+    if !same_size(a, b) {
+        return None;
+    }
+
+    let (width, height) = (a.width(), a.height());
+    let luma_a: Vec<f64> = a.data().iter().map(|c| luma(*c)).collect();
+    let luma_b: Vec<f64> = b.data().iter().map(|c| luma(*c)).collect();
+
+    let peak = a.color_depth().max(b.color_depth()) as f64;
+    let c1 = (0.01 * peak).powi(2);
+    let c2 = (0.03 * peak).powi(2);
+
+    let window_w = SSIM_WINDOW.min(width);
+    let window_h = SSIM_WINDOW.min(height);
+
+    let mut total = 0.;
+    let mut windows = 0;
+    for y0 in window_starts(height, window_h) {
+        for x0 in window_starts(width, window_w) {
+            let indices =
+                (y0..y0 + window_h).flat_map(|y| (x0..x0 + window_w).map(move |x| y * width + x));
+
+            let n = (window_w * window_h) as f64;
+            let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab) = (0., 0., 0., 0., 0.);
+            for i in indices {
+                let (p, q) = (luma_a[i], luma_b[i]);
+                sum_a += p;
+                sum_b += q;
+                sum_aa += p * p;
+                sum_bb += q * q;
+                sum_ab += p * q;
+            }
+
+            let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+            let var_a = sum_aa / n - mean_a * mean_a;
+            let var_b = sum_bb / n - mean_b * mean_b;
+            let covar = sum_ab / n - mean_a * mean_b;
+
+            total += ((2. * mean_a * mean_b + c1) * (2. * covar + c2))
+                / ((mean_a * mean_a + mean_b * mean_b + c1) * (var_a + var_b + c2));
+            windows += 1;
+        }
+    }
+
+    Some(total / windows as f64)
+}
+
+/// Start of every window of size `window` along a side of size `size`, with the last window ending at `size`.
+fn window_starts(size: usize, window: usize) -> impl Iterator<Item = usize> {
+    let last = size - window;
+    (0..last).step_by(window).chain(std::iter::once(last))
+}
+
+fn same_size(a: &Ppm, b: &Ppm) -> bool {
+    a.width() == b.width() && a.height() == b.height()
+}
+
+fn channels(c: Rgb) -> [f64; 3] {
+    [c.red as f64, c.green as f64, c.blue as f64]
+}
+
+/// Rec. 601 luma.
+fn luma(c: Rgb) -> f64 {
+    0.299 * c.red as f64 + 0.587 * c.green as f64 + 0.114 * c.blue as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppm::PpmBuilder;
+
+    fn img(width: usize, height: usize, color: Rgb) -> Ppm {
+        let mut img = PpmBuilder::new(height, width, 255).build();
+        img.clear(color);
+        img
+    }
+
+    #[test]
+    fn test_different_sizes() {
+        let (a, b) = (img(10, 10, Rgb::BLACK), img(10, 11, Rgb::BLACK));
+        assert_eq!(None, mse(&a, &b));
+        assert_eq!(None, psnr(&a, &b));
+        assert_eq!(None, ssim(&a, &b));
+        assert_eq!(None, ssim(&a, &img(11, 10, Rgb::BLACK)));
+    }
+
+    #[test]
+    fn test_extremes() {
+        let (black, white) = (img(16, 16, Rgb::BLACK), img(16, 16, Rgb::WHITE));
+        assert_eq!(Some(255. * 255.), mse(&black, &white));
+        assert_eq!(Some(0.), psnr(&black, &white));
+        assert!(ssim(&black, &white).unwrap() < 0.01);
+
+        // symmetric
+        assert_eq!(mse(&white, &black), mse(&black, &white));
+        assert_eq!(ssim(&white, &black), ssim(&black, &white));
+    }
+
+    #[test]
+    fn test_single_pixel() {
+        let (a, mut b) = (img(1, 1, Rgb::BLACK), img(1, 1, Rgb::BLACK));
+        assert_eq!(Some(1.), ssim(&a, &b));
+        assert_eq!(Some(f64::INFINITY), psnr(&a, &b));
+        b.set(0, 0, Rgb::new(3, 0, 0));
+        assert_eq!(Some(3.), mse(&a, &b));
+        assert!(ssim(&a, &b).unwrap() < 1.);
+    }
+
+    #[test]
+    fn test_every_pixel_counts() {
+        // 10 is not a multiple of the window size, the last columns and rows are still compared
+        let a = img(10, 10, Rgb::GRAY);
+        for &(x, y) in [(9, 0), (0, 9), (9, 9), (8, 4)].iter() {
+            let mut b = img(10, 10, Rgb::GRAY);
+            b.set(x, y, Rgb::WHITE);
+            assert!(ssim(&a, &b).unwrap() < 1., "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn test_sizes() {
+        // smaller than, equal to and larger than the window
+        for &(width, height) in [(3, 20), (8, 8), (17, 5), (64, 9)].iter() {
+            let a = img(width, height, Rgb::GRAY);
+            assert_eq!(Some(1.), ssim(&a, &a), "{}x{}", width, height);
+            assert_eq!(Some(0.), mse(&a, &a));
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Stand-ins for the [`magick`] functions used by the rest of the crate, in builds without the `magick` feature. They
+//! don't run anything, and fail with [`GraphicsError::MagickDisabled`].
+//!
+//! [`magick`]: ../magick/index.html
+//! [`GraphicsError::MagickDisabled`]: ../error/enum.GraphicsError.html#variant.MagickDisabled
+
+use std::path::Path;
+
+use crate::{
+    color::PpmColor,
+    error::{GraphicsError, Result},
+    ppm::Ppm,
+};
+
+pub(crate) fn convert_with_input(_args: &[&str], _input: &[u8]) -> Result<()> {
+    Err(GraphicsError::MagickDisabled)
+}
+
+pub(crate) fn read_as_ppm(_path: &Path) -> Result<Vec<u8>> {
+    Err(GraphicsError::MagickDisabled)
+}
+
+pub(crate) fn display_ppm<C: PpmColor>(_img: &Ppm<C>) -> Result<()> {
+    Err(GraphicsError::MagickDisabled)
+}
@@ -0,0 +1,361 @@
+//! Dependency-free encoder for the [PNG] format, so images can be saved as PNG without ImageMagick.
+//!
+//! Use it through [`Ppm::save_png`] and [`Ppm::write_png_to_buf`], or just [`save`] a file with a `.png` extension.
+//!
+//! Image data is compressed with a small deflate implementation: LZ77 with hash chains, and the fixed Huffman codes.
+//! Files come out a bit larger than with zlib's best settings, but much smaller than a ppm.
+//!
+//! [PNG]: http://www.libpng.org/pub/png/spec/1.2/PNG-Contents.html
+//! [`Ppm::save_png`]: ../ppm/struct.Ppm.html#method.save_png
+//! [`Ppm::write_png_to_buf`]: ../ppm/struct.Ppm.html#method.write_png_to_buf
+//! [`save`]: ../screen/trait.Screen.html#tymethod.save
+
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+};
+
+use crate::{color::PpmColor, ppm::Ppm, screen::Screen};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Write `img` as an 8 bit truecolor PNG, with alpha if the color type has one.
+pub(crate) fn write_png<C: PpmColor, W: Write>(img: &Ppm<C>, writer: &mut W) -> io::Result<()> {
+    let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "image too large for PNG");
+    let width = u32::try_from(img.width()).map_err(|_| too_large())?;
+    let height = u32::try_from(img.height()).map_err(|_| too_large())?;
+    let (color_type, bytes_per_pixel) = if C::HAS_ALPHA { (6, 4) } else { (2, 3) };
+
+    writer.write_all(&SIGNATURE)?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // bit depth, color type, compression, filter, interlace
+    header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+    write_chunk(writer, b"IHDR", &header)?;
+
+    let pixels: Vec<u8> = img
+        .to_rgba8()
+        .iter()
+        .flat_map(|pixel| pixel[..bytes_per_pixel].to_vec())
+        .collect();
+    let filtered = filter(&pixels, img.width() * bytes_per_pixel, bytes_per_pixel);
+    write_chunk(writer, b"IDAT", &zlib(&filtered))?;
+
+    write_chunk(writer, b"IEND", &[])
+}
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data.iter()));
+    writer.write_all(&crc.to_be_bytes())
+}
+
+/// CRC-32 (ISO 3309), as used by PNG chunks.
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Filter every row of `pixels`, with the filter that gives the smallest sum of absolute values (as signed bytes), a
+/// heuristic from the PNG spec for what compresses best. Each row is prefixed with its filter type.
+fn filter(pixels: &[u8], row_size: usize, bpp: usize) -> Vec<u8> {
+    let rows = pixels.len().checked_div(row_size).unwrap_or(0);
+    let mut out = Vec::with_capacity(pixels.len() + rows);
+    let zeros = vec![0; row_size];
+    let mut candidate = vec![0; row_size];
+    let mut best = vec![0; row_size];
+
+    for y in 0..rows {
+        let row = &pixels[y * row_size..(y + 1) * row_size];
+        let prior = if y == 0 {
+            &zeros[..]
+        } else {
+            &pixels[(y - 1) * row_size..y * row_size]
+        };
+
+        let mut best_filter = 0;
+        let mut best_score = u64::MAX;
+        for filter_type in 0..5u8 {
+            for i in 0..row_size {
+                let a = if i >= bpp { row[i - bpp] } else { 0 };
+                let b = prior[i];
+                let c = if i >= bpp { prior[i - bpp] } else { 0 };
+                let predicted = match filter_type {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    _ => paeth(a, b, c),
+                };
+                candidate[i] = row[i].wrapping_sub(predicted);
+            }
+            let score = candidate
+                .iter()
+                .map(|&v| (v as i8).unsigned_abs() as u64)
+                .sum();
+            if score < best_score {
+                best_score = score;
+                best_filter = filter_type;
+                best.copy_from_slice(&candidate);
+            }
+        }
+        out.push(best_filter);
+        out.extend_from_slice(&best);
+    }
+    out
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Compress `data` into a zlib stream.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    // deflate, 32K window, no preset dictionary; the check bits make the header a multiple of 31
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+const WINDOW: usize = 1 << 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// How many earlier positions with the same hash are tried, trading speed for compression.
+const MAX_CHAIN: usize = 64;
+
+/// Base lengths of the length codes 257 to 285, and their extra bits.
+#[rustfmt::skip]
+const LENGTHS: [(u16, u8); 29] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0), (11, 1), (13, 1), (15, 1), (17, 1), (19, 2),
+    (23, 2), (27, 2), (31, 2), (35, 3), (43, 3), (51, 3), (59, 3), (67, 4), (83, 4), (99, 4), (115, 4), (131, 5),
+    (163, 5), (195, 5), (227, 5), (258, 0),
+];
+
+/// Base distances of the distance codes 0 to 29, and their extra bits.
+#[rustfmt::skip]
+const DISTANCES: [(u16, u8); 30] = [
+    (1, 0), (2, 0), (3, 0), (4, 0), (5, 1), (7, 1), (9, 2), (13, 2), (17, 3), (25, 3), (33, 4), (49, 4), (65, 5),
+    (97, 5), (129, 6), (193, 6), (257, 7), (385, 7), (513, 8), (769, 8), (1025, 9), (1537, 9), (2049, 10), (3073, 10),
+    (4097, 11), (6145, 11), (8193, 12), (12289, 12), (16385, 13), (24577, 13),
+];
+
+/// Compress `data` into a single deflate block, with the fixed Huffman codes.
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    // final block, fixed codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut chains = HashChains::new(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let (length, distance) = chains.longest_match(data, i);
+        if length >= MIN_MATCH {
+            bits.length(length, distance);
+            for j in i..i + length {
+                chains.insert(data, j);
+            }
+            i += length;
+        } else {
+            bits.literal(data[i] as u16);
+            chains.insert(data, i);
+            i += 1;
+        }
+    }
+    bits.literal(256);
+    bits.finish()
+}
+
+/// Earlier positions in the data, indexed by a hash of the 3 bytes starting there, to find matches quickly.
+struct HashChains {
+    /// Most recent position of each hash.
+    head: Vec<usize>,
+    /// For each position, the previous position with the same hash.
+    prev: Vec<usize>,
+}
+
+impl HashChains {
+    const NONE: usize = usize::MAX;
+
+    fn new(len: usize) -> Self {
+        HashChains {
+            head: vec![Self::NONE; WINDOW],
+            prev: vec![Self::NONE; len],
+        }
+    }
+
+    fn hash(data: &[u8], i: usize) -> usize {
+        ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize)
+            & (WINDOW - 1)
+    }
+
+    fn insert(&mut self, data: &[u8], i: usize) {
+        if i + MIN_MATCH <= data.len() {
+            let h = Self::hash(data, i);
+            self.prev[i] = self.head[h];
+            self.head[h] = i;
+        }
+    }
+
+    /// Length and distance of the longest earlier match for the data at `i`. The length is `0` if there's none.
+    fn longest_match(&self, data: &[u8], i: usize) -> (usize, usize) {
+        let (mut best_length, mut best_distance) = (0, 0);
+        if i + MIN_MATCH > data.len() {
+            return (best_length, best_distance);
+        }
+        let max_length = MAX_MATCH.min(data.len() - i);
+        let mut candidate = self.head[Self::hash(data, i)];
+        let mut chain = 0;
+        while candidate != Self::NONE && i - candidate <= WINDOW && chain < MAX_CHAIN {
+            let length = data[candidate..]
+                .iter()
+                .zip(data[i..i + max_length].iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            if length > best_length {
+                best_length = length;
+                best_distance = i - candidate;
+                if length == max_length {
+                    break;
+                }
+            }
+            candidate = self.prev[candidate];
+            chain += 1;
+        }
+        (best_length, best_distance)
+    }
+}
+
+/// Writes bits from the least significant one, the way deflate packs them.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    /// Write the `count` low bits of `value`, least significant first.
+    fn write(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Write a Huffman `code` of `length` bits, most significant first.
+    fn code(&mut self, code: u32, length: u32) {
+        let reversed = code.reverse_bits() >> (32 - length);
+        self.write(reversed, length);
+    }
+
+    /// Write a literal/length symbol with the fixed codes.
+    fn literal(&mut self, symbol: u16) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    /// Write a back reference of `length` bytes, `distance` bytes back.
+    fn length(&mut self, length: usize, distance: usize) {
+        let code = LENGTHS
+            .iter()
+            .rposition(|&(base, _)| base as usize <= length)
+            .unwrap();
+        let (base, extra) = LENGTHS[code];
+        self.literal(257 + code as u16);
+        self.write((length - base as usize) as u32, extra as u32);
+
+        let code = DISTANCES
+            .iter()
+            .rposition(|&(base, _)| base as usize <= distance)
+            .unwrap();
+        let (base, extra) = DISTANCES[code];
+        self.code(code as u32, 5);
+        self.write((distance - base as usize) as u32, extra as u32);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Rgb, ppm::PpmBuilder};
+
+    #[test]
+    fn test_write_png() {
+        let mut img = PpmBuilder::new(20, 20, 255).build();
+        img.draw_line((0., 0., 0.), (19., 19., 0.), Rgb::WHITE);
+        let mut buf = vec![];
+        img.write_png_to_buf(&mut buf).unwrap();
+
+        assert_eq!(&SIGNATURE, &buf[..8]);
+        assert_eq!(b"IHDR", &buf[12..16]);
+        // IEND, with its well-known CRC
+        assert_eq!(
+            &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82],
+            &buf[buf.len() - 12..]
+        );
+        // mostly black, so it should compress well
+        assert!(buf.len() < 20 * 20 * 3 / 2);
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789".iter()));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+    }
+}
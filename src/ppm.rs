@@ -16,11 +16,29 @@
 //! [builder pattern]: https://doc.rust-lang.org/1.0.0/style/ownership/builders.html
 
 use std::{
+    borrow::Cow,
     convert::TryFrom,
-    io::{self, prelude::*, BufWriter},
+    fs::File,
+    io::{self, prelude::*, BufReader, BufWriter},
+    mem,
+    ops::Range,
+    path::Path,
+    slice,
+    sync::Mutex,
+    thread,
 };
 
-use crate::{color::Rgb, magick, screen::Screen};
+use crate::{
+    blend::BlendMode,
+    bmp,
+    color::{Gamma, PpmColor, Rgb},
+    error::{self, GraphicsError},
+    magick, png, qoi,
+    raster::{Bresenham, Rasterizer},
+    screen::{SaveOptions, Screen},
+    tga,
+    util::{Float, Point, Vec3},
+};
 
 /// Builder for [`Ppm`].
 ///
@@ -29,7 +47,10 @@ use crate::{color::Rgb, magick, screen::Screen};
 /// # Examples
 ///
 /// ```rust
-/// use graphics::prelude::*;
+/// use graphics::{
+///     ppm::{Origin, WrapMode},
+///     prelude::*,
+/// };
 ///
 /// let mut img = PpmBuilder::new(500, 1000, 255)
 ///     .bg_color(Rgb::WHITE)
@@ -40,15 +61,18 @@ use crate::{color::Rgb, magick, screen::Screen};
 ///
 /// assert_eq!(1000, img.width());
 /// assert_eq!(500, img.height());
-/// assert_eq!(true, img.wrap_x);
-/// assert_eq!(false, img.wrap_y);
-/// assert_eq!(true, img.invert_y);
+/// assert_eq!(WrapMode::Wrap, img.wrap_x);
+/// assert_eq!(WrapMode::Ignore, img.wrap_y);
+/// assert_eq!(Origin::BottomLeft, img.origin);
 /// ```
 ///
 /// The multi-line version
 ///
 /// ```rust
-/// use graphics::prelude::*;
+/// use graphics::{
+///     ppm::{Origin, WrapMode},
+///     prelude::*,
+/// };
 ///
 /// let builder = PpmBuilder::new(400, 500, 255);
 ///
@@ -56,21 +80,26 @@ use crate::{color::Rgb, magick, screen::Screen};
 ///
 /// // ... some code
 ///
-/// let builder = builder.wrap_y(true);
-/// let builder = builder.invert_y(false);
+/// let builder = builder.wrap_y(WrapMode::Mirror);
+/// let builder = builder.origin(Origin::TopLeft);
 /// let mut img = builder.build();
 /// 
 /// // draw stuff...
 ///
-/// assert_eq!(true, img.wrap_y);
-/// assert_eq!(false, img.wrap_x);
-/// assert_eq!(false, img.invert_y);
+/// assert_eq!(WrapMode::Mirror, img.wrap_y);
+/// assert_eq!(WrapMode::Ignore, img.wrap_x);
+/// assert_eq!(Origin::TopLeft, img.origin);
 /// assert_eq!(500, img.width());
 /// assert_eq!(400, img.height());
 /// ```
 ///
+/// A [`Ppm`] can store any [`PpmColor`], like [`Rgb16`] for 16 bit images. See [`with_color_type`].
+///
 /// [`Ppm`]: ./struct.Ppm.html
-pub struct PpmBuilder {
+/// [`PpmColor`]: ../color/trait.PpmColor.html
+/// [`Rgb16`]: ../color/struct.Rgb16.html
+/// [`with_color_type`]: #method.with_color_type
+pub struct PpmBuilder<C = Rgb> {
     /// Height of the image to be built.
     height: usize,
     /// Width of the image to be built.
@@ -81,123 +110,814 @@ pub struct PpmBuilder {
     /// 
     /// [`data`]: ./struct.Ppm.html#structfield.data
     /// [`Ppm`]: ./struct.Ppm.html
-    data: Vec<Rgb>,
+    data: Vec<C>,
     /// Color to fill a Ppm on build. Not used if data is provided with [`with_data`].
     ///
     /// [`with_data`]: #method.with_data
-    pub bg_color: Rgb,
-    /// If true, x values outside image will be wrapped around. if false, the point will be ignored. Defaults to `false`.
-    pub wrap_x: bool,
-    /// If true, y values outside image will be wrapped around. If false, the point will be ignored. Defaults to `false`.
-    pub wrap_y: bool,
-    /// Whether y values will be inverted when plotting. Defaults to true, which puts the origin on the bottom left.
-    pub invert_y: bool,
+    pub bg_color: C,
+    /// What happens to x values outside the image. Defaults to [`WrapMode::Ignore`].
+    ///
+    /// [`WrapMode::Ignore`]: ./enum.WrapMode.html#variant.Ignore
+    pub wrap_x: WrapMode,
+    /// What happens to y values outside the image. Defaults to [`WrapMode::Ignore`].
+    ///
+    /// [`WrapMode::Ignore`]: ./enum.WrapMode.html#variant.Ignore
+    pub wrap_y: WrapMode,
+    /// Where (0, 0) is, and which way y goes. Defaults to [`Origin::BottomLeft`]. See [`origin`].
+    ///
+    /// [`Origin::BottomLeft`]: ./enum.Origin.html#variant.BottomLeft
+    /// [`origin`]: #method.origin
+    pub origin: Origin,
+    /// Algorithm used to rasterize shapes. Defaults to [`Bresenham`].
+    ///
+    /// [`Bresenham`]: ../raster/struct.Bresenham.html
+    pub rasterizer: &'static dyn Rasterizer,
+    /// Supersampling factor for anti-aliasing. Defaults to `1` (no supersampling). See [`supersample`].
+    ///
+    /// [`supersample`]: #method.supersample
+    pub supersample: usize,
+    /// Transfer function applied when writing the image. Defaults to [`Gamma::Linear`]. See [`gamma`].
+    ///
+    /// [`Gamma::Linear`]: ../color/enum.Gamma.html#variant.Linear
+    /// [`gamma`]: #method.gamma
+    pub gamma: Gamma,
+    /// How samples are compared with the z-buffer. Defaults to [`DepthTest::Greater`]. See [`depth_test`].
+    ///
+    /// [`DepthTest::Greater`]: ./enum.DepthTest.html#variant.Greater
+    /// [`depth_test`]: #method.depth_test
+    pub depth_test: DepthTest,
+    /// Tolerance of the depth test. Defaults to `0`. See [`depth_epsilon`].
+    ///
+    /// [`depth_epsilon`]: #method.depth_epsilon
+    pub depth_epsilon: Float,
+    /// Comments to write in the header of ppm files. See [`comment`].
+    ///
+    /// [`comment`]: #method.comment
+    comments: Vec<String>,
 }
 
 impl PpmBuilder {
     pub const DEFAULT_BG_COLOR: Rgb = Rgb::BLACK;
 
+    /// Most samples (pixels, times [`supersample`] squared) an image can have, to catch sizes that are obviously a
+    /// mistake before trying to allocate them. That's a 16384x16384 image.
+    ///
+    /// [`supersample`]: #method.supersample
+    pub const MAX_SAMPLES: usize = 1 << 28;
+
     /// Make a new PpmBuilder with default configurations.
     pub fn new(height: usize, width: usize, color_depth: u16) -> Self {
+        Self::with_color_type(height, width, color_depth)
+    }
+}
+
+impl<C: PpmColor> PpmBuilder<C> {
+    /// Make a new PpmBuilder for a [`Ppm`] storing colors of type `C`, with default configurations.
+    ///
+    /// [`new`] is the same, for [`Rgb`]. The type has to be given explicitly, like `PpmBuilder::<Rgb16>::with_color_type`.
+    /// See [`Rgb16`] for an example.
+    ///
+    /// [`Ppm`]: ./struct.Ppm.html
+    /// [`new`]: #method.new
+    /// [`Rgb`]: ../color/struct.Rgb.html
+    /// [`Rgb16`]: ../color/struct.Rgb16.html
+    pub fn with_color_type(height: usize, width: usize, color_depth: u16) -> Self {
         Self {
             height,
             width,
             color_depth,
-            wrap_x: false,
-            wrap_y: false,
-            invert_y: true,
+            wrap_x: WrapMode::Ignore,
+            wrap_y: WrapMode::Ignore,
+            origin: Origin::BottomLeft,
             data: vec![],
-            bg_color: Self::DEFAULT_BG_COLOR,
+            bg_color: C::from_rgb(PpmBuilder::DEFAULT_BG_COLOR),
+            rasterizer: &Bresenham,
+            supersample: 1,
+            gamma: Gamma::Linear,
+            depth_test: DepthTest::Greater,
+            depth_epsilon: 0.,
+            comments: vec![],
         }
     }
 
-    /// Set `wrap_x`.
+    /// Set [`wrap_x`], to a [`WrapMode`] or a `bool`: `true` is [`WrapMode::Wrap`], and `false` is
+    /// [`WrapMode::Ignore`].
     ///
-    /// [`wrap_x`]: #structfield.wrap_y
-    /// [`PpmBuilder`]: ./struct.PpmBuilder.html
-    pub fn wrap_x(mut self, to_wrap: bool) -> Self {
-        self.wrap_x = to_wrap;
+    /// # Examples
+    ///
+    /// A tile that repeats seamlessly, since every shape drawn over an edge continues on the other side:
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut tile = PpmBuilder::new(100, 100, 255).wrap_x(true).wrap_y(true).build();
+    /// tile.draw_circle(90., 50., 0., 20., Rgb::WHITE);
+    ///
+    /// assert_eq!(Some(Rgb::WHITE), tile.get(10, 50));
+    /// ```
+    ///
+    /// [`wrap_x`]: #structfield.wrap_x
+    /// [`WrapMode`]: ./enum.WrapMode.html
+    /// [`WrapMode::Wrap`]: ./enum.WrapMode.html#variant.Wrap
+    /// [`WrapMode::Ignore`]: ./enum.WrapMode.html#variant.Ignore
+    pub fn wrap_x<W: Into<WrapMode>>(mut self, mode: W) -> Self {
+        self.wrap_x = mode.into();
         self
     }
 
-    /// Set `wrap_y`.
+    /// Set [`wrap_y`]. See [`wrap_x`](#method.wrap_x).
     ///
     /// [`wrap_y`]: #structfield.wrap_y
-    /// [`PpmBuilder`]: ./struct.PpmBuilder.html
-    pub fn wrap_y(mut self, to_wrap: bool) -> Self {
-        self.wrap_y = to_wrap;
+    pub fn wrap_y<W: Into<WrapMode>>(mut self, mode: W) -> Self {
+        self.wrap_y = mode.into();
         self
     }
 
-    /// Set `invert_y`.
+    /// Set where (0, 0) is, and which way y goes.
     ///
-    /// [`invert_y`]: #structfield.invert_y
-    /// [`PpmBuilder`]: ./struct.PpmBuilder.html
-    pub fn invert_y(mut self, to_invert: bool) -> Self {
-        self.invert_y = to_invert;
+    /// # Examples
+    ///
+    /// A 1920x1080 wallpaper, with the usual math axes in the middle of the screen:
+    ///
+    /// ```
+    /// use graphics::{ppm::Origin, prelude::*};
+    ///
+    /// let mut img = PpmBuilder::new(1080, 1920, 255).origin(Origin::Center).build();
+    ///
+    /// img.draw_line((-960., 0., 0.), (959., 0., 0.), Rgb::WHITE);
+    /// img.draw_line((0., -540., 0.), (0., 539., 0.), Rgb::WHITE);
+    /// img.plot(100, 100, 0., Rgb::new(255, 0, 0));
+    ///
+    /// assert_eq!(Some(Rgb::WHITE), img.get(-960, 0));
+    /// assert_eq!(None, img.get(960, 0));
+    /// // the top right quadrant is in the top right of the file
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get(100, 100));
+    /// assert_eq!(Rgb::new(255, 0, 0), img.rows().nth(540 - 100 - 1).unwrap()[960 + 100]);
+    /// ```
+    pub fn origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Shorthand for [`origin`]: `true` is [`Origin::BottomLeft`], `false` is [`Origin::TopLeft`].
+    ///
+    /// [`origin`]: #method.origin
+    /// [`Origin::BottomLeft`]: ./enum.Origin.html#variant.BottomLeft
+    /// [`Origin::TopLeft`]: ./enum.Origin.html#variant.TopLeft
+    pub fn invert_y(self, to_invert: bool) -> Self {
+        self.origin(if to_invert {
+            Origin::BottomLeft
+        } else {
+            Origin::TopLeft
+        })
+    }
+
+    /// Set the [`Rasterizer`] used to draw shapes.
+    ///
+    /// See the [`raster`] module for more info.
+    ///
+    /// [`Rasterizer`]: ../raster/trait.Rasterizer.html
+    /// [`raster`]: ../raster/index.html
+    pub fn rasterizer(mut self, rasterizer: &'static dyn Rasterizer) -> Self {
+        self.rasterizer = rasterizer;
+        self
+    }
+
+    /// Anti-alias the whole image by rendering it at `n` times the resolution, and averaging every `n` by `n` block of
+    /// samples back into one pixel when the image is written, saved, or displayed.
+    ///
+    /// Drawing code doesn't need to change: coordinates, [`width`] and [`height`] stay the same. Lines and triangles
+    /// (and everything built on them, like polylines, arcs, and thick lines) are rasterized at the higher resolution, so
+    /// their edges come out smooth. Shapes plotted pixel by pixel, like [`draw_circle`] and [`fill_rect`], just fill whole
+    /// blocks of samples.
+    ///
+    /// This costs `n * n` times the memory and roughly that much time, so `2` to `4` is usually plenty.
+    /// A value of `0` is treated as `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = PpmBuilder::new(500, 500, 255).supersample(4).build();
+    ///
+    /// img.draw_line((0., 0., 0.), (499., 200., 0.), Rgb::WHITE);
+    ///
+    /// assert_eq!(500, img.width());
+    /// assert_eq!(4, img.supersample());
+    /// ```
+    ///
+    /// [`width`]: ../screen/trait.Screen.html#tymethod.width
+    /// [`height`]: ../screen/trait.Screen.html#tymethod.height
+    /// [`draw_circle`]: ../screen/trait.Screen.html#method.draw_circle
+    /// [`fill_rect`]: ../screen/trait.Screen.html#method.fill_rect
+    pub fn supersample(mut self, n: usize) -> Self {
+        self.supersample = n.max(1);
         self
     }
 
+    /// Treat colors drawn on the image as linear light values, and encode them with `gamma` when the image is written,
+    /// saved, or displayed.
+    ///
+    /// Everything that mixes colors, like [`supersample`], gradients and [`plot_f`], then blends in linear space, which
+    /// avoids the dark fringes and muddy mid-tones of blending encoded values. [`get_pixel`] still returns the linear
+    /// values that were drawn.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{color::Gamma, prelude::*};
+    ///
+    /// let mut img = PpmBuilder::new(1, 1, 255).gamma(Gamma::Srgb).build();
+    /// img.plot(0, 0, 0., Rgb::gray(128));
+    ///
+    /// let mut buf = vec![];
+    /// img.write_binary_to_buf(&mut buf).unwrap();
+    ///
+    /// assert_eq!(Some(Rgb::gray(128)), img.get_pixel(0, 0));
+    /// assert_eq!(&[188, 188, 188], &buf[buf.len() - 3..]);
+    /// ```
+    ///
+    /// [`supersample`]: #method.supersample
+    /// [`plot_f`]: ../screen/trait.Screen.html#method.plot_f
+    /// [`get_pixel`]: ../screen/trait.Screen.html#tymethod.get_pixel
+    pub fn gamma(mut self, gamma: Gamma) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Set how a new sample is compared with the z-buffer to decide whether it's drawn.
+    ///
+    /// # Examples
+    ///
+    /// Draw in painter's order, so whatever is drawn last is on top, whatever its depth:
+    ///
+    /// ```
+    /// use graphics::{ppm::DepthTest, prelude::*};
+    ///
+    /// let mut img = PpmBuilder::new(10, 10, 255).depth_test(DepthTest::Always).build();
+    /// img.plot(5, 5, 100., Rgb::WHITE);
+    /// img.plot(5, 5, 0., Rgb::new(255, 0, 0));
+    ///
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get(5, 5));
+    /// ```
+    pub fn depth_test(mut self, depth_test: DepthTest) -> Self {
+        self.depth_test = depth_test;
+        self
+    }
+
+    /// Set the tolerance of the depth test: how much closer a sample has to be to pass [`DepthTest::Greater`] or
+    /// [`DepthTest::Less`], and how much farther it can be and still pass [`DepthTest::GreaterEqual`] or
+    /// [`DepthTest::LessEqual`].
+    ///
+    /// Coplanar shapes, like a decal drawn over a wall, compute slightly different depths for the same pixel because of
+    /// rounding, so the winner flickers from pixel to pixel. Testing with [`DepthTest::GreaterEqual`] and a small epsilon
+    /// makes the one drawn last win everywhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{ppm::DepthTest, prelude::*};
+    ///
+    /// let mut img = PpmBuilder::new(10, 10, 255)
+    ///     .depth_test(DepthTest::GreaterEqual)
+    ///     .depth_epsilon(1e-3)
+    ///     .build();
+    /// img.plot(5, 5, 1., Rgb::WHITE);
+    /// img.plot(5, 5, 1. - 1e-6, Rgb::new(255, 0, 0));
+    ///
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get(5, 5));
+    /// ```
+    ///
+    /// [`DepthTest::Greater`]: ./enum.DepthTest.html#variant.Greater
+    /// [`DepthTest::Less`]: ./enum.DepthTest.html#variant.Less
+    /// [`DepthTest::GreaterEqual`]: ./enum.DepthTest.html#variant.GreaterEqual
+    /// [`DepthTest::LessEqual`]: ./enum.DepthTest.html#variant.LessEqual
+    pub fn depth_epsilon(mut self, epsilon: Float) -> Self {
+        self.depth_epsilon = epsilon;
+        self
+    }
+
+    /// Add a comment to write in the header of ppm files. See [`Ppm::add_comment`].
+    ///
+    /// [`Ppm::add_comment`]: ./struct.Ppm.html#method.add_comment
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comments.push(comment.to_string());
+        self
+    }
+
+    /// Start from the image file at `path`: the builder gets its width, height, color depth, pixels, and comments.
+    ///
+    /// Files are read like with [`Ppm::open`]. Other settings are the defaults, and can still be changed before
+    /// building, for example to draw on the image with a different [`origin`].
+    ///
+    /// # Examples
+    ///
+    /// Annotate a photo:
+    ///
+    /// ```no_run
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = PpmBuilder::from_image("photo.png")
+    ///     .expect("error reading photo.png")
+    ///     .build();
+    ///
+    /// img.draw_text(10, 10, "Look here", 2, Rgb::new(255, 0, 0));
+    /// img.save("annotated.png").expect("error saving annotated.png");
+    /// ```
+    ///
+    /// [`Ppm::open`]: ./struct.Ppm.html#method.open
+    /// [`origin`]: #method.origin
+    pub fn from_image<P: AsRef<Path>>(path: P) -> error::Result<Self> {
+        let img = Ppm::<C>::open(path)?;
+        let mut builder =
+            Self::with_color_type(img.height, img.width, img.color_depth).with_data(img.data);
+        builder.comments = img.comments;
+        Ok(builder)
+    }
+
+    /// Start from RGBA pixels, row by row from the top left, like the ones from [`to_rgba_bytes`]. The color depth is
+    /// `255`, and the alpha is dropped for color types without it.
+    ///
+    /// This is how images from other libraries get in, without this crate depending on them. For the [`image`] crate,
+    /// the `image` feature adds conversions from its buffers, and [`Ppm::load_texture`].
+    ///
+    /// Fails with [`GraphicsError::InvalidDimensions`] if `bytes` doesn't have `width * height * 4` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let bytes = [255, 0, 0, 255, 0, 0, 255, 255];
+    /// let img: Ppm = PpmBuilder::from_rgba_bytes(2, 1, &bytes).unwrap().build();
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get(0, 0));
+    /// assert_eq!(bytes.to_vec(), img.to_rgba_bytes());
+    /// ```
+    ///
+    /// [`to_rgba_bytes`]: ./struct.Ppm.html#method.to_rgba_bytes
+    /// [`image`]: https://crates.io/crates/image
+    /// [`Ppm::load_texture`]: ./struct.Ppm.html#method.load_texture
+    /// [`GraphicsError::InvalidDimensions`]: ../error/enum.GraphicsError.html#variant.InvalidDimensions
+    pub fn from_rgba_bytes(width: usize, height: usize, bytes: &[u8]) -> error::Result<Self> {
+        if width.checked_mul(height).and_then(|n| n.checked_mul(4)) != Some(bytes.len()) {
+            return Err(GraphicsError::InvalidDimensions(format!(
+                "{} bytes are not the RGBA pixels of a {}x{} image",
+                bytes.len(),
+                width,
+                height
+            )));
+        }
+        let data = bytes
+            .chunks(4)
+            .map(|p| C::from_channels([p[0] as u16, p[1] as u16, p[2] as u16]).with_alpha(p[3]))
+            .collect();
+        Ok(Self::with_color_type(height, width, 255).with_data(data))
+    }
+
     /// Set initial data.
     ///
     /// If initial image data is provided with this method, `bg_color` will not be used.
-    pub fn with_data(mut self, data: Vec<Rgb>) -> Self {
+    pub fn with_data(mut self, data: Vec<C>) -> Self {
         self.data = data;
         self
     }
 
     /// Set background color.
-    pub fn bg_color(mut self, bg_color: Rgb) -> Self {
+    pub fn bg_color(mut self, bg_color: C) -> Self {
         self.bg_color = bg_color;
         self
     }
 
     /// Build a [`Ppm`]. Always remember to call this method after configuring a [`PpmBuilder`].
     /// 
+    /// # Panics
+    ///
+    /// If the settings are invalid. See [`try_build`] for what's checked, and to handle the error instead.
+    ///
     /// [`Ppm`]: ./struct.Ppm.html
     /// [`PpmBuilder`]: ./struct.PpmBuilder.html
-    pub fn build(self) -> Ppm {
-        Ppm {
+    /// [`try_build`]: #method.try_build
+    pub fn build(self) -> Ppm<C> {
+        match self.try_build() {
+            Ok(img) => img,
+            Err(e) => panic!("invalid PpmBuilder settings: {}", e),
+        }
+    }
+
+    /// Build a [`Ppm`], or return a [`GraphicsError::InvalidDimensions`] if:
+    ///
+    /// - the width or height is `0`,
+    /// - the color depth is `0`,
+    /// - data was given with [`with_data`], but not `width * height` pixels of it,
+    /// - or the image would have more than [`MAX_SAMPLES`] samples.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// assert!(PpmBuilder::new(0, 500, 255).try_build().is_err());
+    ///
+    /// let err = PpmBuilder::new(2, 2, 255)
+    ///     .with_data(vec![Rgb::WHITE; 3])
+    ///     .try_build()
+    ///     .err()
+    ///     .unwrap();
+    /// assert_eq!("data has 3 pixels, but a 2x2 image needs 4", err.to_string());
+    /// ```
+    ///
+    /// [`Ppm`]: ./struct.Ppm.html
+    /// [`GraphicsError::InvalidDimensions`]: ../error/enum.GraphicsError.html#variant.InvalidDimensions
+    /// [`with_data`]: #method.with_data
+    /// [`MAX_SAMPLES`]: #associatedconstant.MAX_SAMPLES
+    pub fn try_build(self) -> error::Result<Ppm<C>> {
+        let invalid = GraphicsError::InvalidDimensions;
+        if self.width == 0 || self.height == 0 {
+            return Err(invalid(format!(
+                "a {}x{} image has no pixels",
+                self.width, self.height
+            )));
+        }
+        if self.color_depth == 0 {
+            return Err(invalid("color depth must be at least 1".to_string()));
+        }
+        let pixels = self.width.checked_mul(self.height);
+        if !self.data.is_empty() && pixels != Some(self.data.len()) {
+            return Err(invalid(format!(
+                "data has {} pixels, but a {}x{} image needs {}",
+                self.data.len(),
+                self.width,
+                self.height,
+                self.width.saturating_mul(self.height)
+            )));
+        }
+        let n = self.supersample.max(1);
+        let samples = pixels
+            .and_then(|pixels| pixels.checked_mul(n.checked_mul(n)?))
+            .filter(|&samples| samples <= PpmBuilder::MAX_SAMPLES)
+            .ok_or_else(|| {
+                invalid(format!(
+                    "a {}x{} image with {}x supersampling is too large",
+                    self.width, self.height, n
+                ))
+            })?;
+
+        let data = if self.data.is_empty() {
+            vec![self.bg_color; samples]
+        } else if n == 1 {
+            self.data
+        } else {
+            // repeat every pixel over its block of samples
+            let width = self.width;
+            (0..samples)
+                .map(|i| {
+                    let (x, y) = (i % (width * n) / n, i / (width * n) / n);
+                    self.data[y * width + x]
+                })
+                .collect()
+        };
+        Ok(Ppm {
             height: self.height,
             width: self.width,
             color_depth: self.color_depth,
             wrap_x: self.wrap_x,
             wrap_y: self.wrap_y,
-            invert_y: self.invert_y,
-            data: if self.data.is_empty() {
-                vec![self.bg_color; self.width * self.height]
-            } else {
-                self.data
-            },
-            zbuf: vec![f64::NEG_INFINITY; self.width * self.height],
+            origin: self.origin,
+            rasterizer: self.rasterizer,
+            supersample: n,
+            gamma: self.gamma,
+            clip: None,
+            data,
+            zbuf: vec![self.depth_test.far(); samples],
+            depth_test: self.depth_test,
+            depth_epsilon: self.depth_epsilon,
+            stencil: None,
+            stencil_mode: (StencilTest::Always, StencilOp::Keep, StencilOp::Keep),
+            comments: self.comments,
+            scratch: Mutex::new(vec![]),
+        })
+    }
+}
+
+/// How to compute pixels when resizing an image. See [`Ppm::resized`].
+///
+/// [`Ppm::resized`]: ./struct.Ppm.html#method.resized
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Filter {
+    /// Take the closest pixel. Keeps hard edges, which is what pixel art needs.
+    Nearest,
+    /// Interpolate between the 4 closest pixels. Smoother, but blurry when upscaling a lot, and it skips pixels when
+    /// shrinking to less than half the size.
+    Bilinear,
+}
+
+/// What happens to a coordinate outside a [`Ppm`], for each axis. See [`PpmBuilder::wrap_x`].
+///
+/// # Examples
+///
+/// Mirroring turns any drawing into a pattern that tiles without seams, since every edge matches the opposite one:
+///
+/// ```
+/// use graphics::{ppm::WrapMode, prelude::*};
+///
+/// let mut tile = PpmBuilder::new(100, 100, 255)
+///     .wrap_x(WrapMode::Mirror)
+///     .wrap_y(WrapMode::Mirror)
+///     .build();
+///
+/// // 4 copies of everything, one per quadrant
+/// tile.draw_line((0., 0., 0.), (200., 200., 0.), Rgb::WHITE);
+///
+/// assert_eq!(Some(Rgb::WHITE), tile.get(150, 150));
+/// assert_eq!(tile.get(20, 20), tile.get(179, 179));
+/// ```
+///
+/// [`Ppm`]: ./struct.Ppm.html
+/// [`PpmBuilder::wrap_x`]: ./struct.PpmBuilder.html#method.wrap_x
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum WrapMode {
+    /// Points outside the image are ignored. This is the default.
+    Ignore,
+    /// Points wrap around to the other side.
+    Wrap,
+    /// Points are moved to the closest edge.
+    Clamp,
+    /// Points are reflected back in, as if the image was mirrored across each edge.
+    Mirror,
+}
+
+impl WrapMode {
+    /// Map `value` into `[0, limit)`, or return `None` if it's ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::ppm::WrapMode;
+    ///
+    /// assert_eq!(None, WrapMode::Ignore.apply(12, 10));
+    /// assert_eq!(Some(2), WrapMode::Wrap.apply(12, 10));
+    /// assert_eq!(Some(9), WrapMode::Clamp.apply(12, 10));
+    /// assert_eq!(Some(7), WrapMode::Mirror.apply(12, 10));
+    /// assert_eq!(Some(0), WrapMode::Mirror.apply(-1, 10));
+    /// ```
+    pub fn apply(self, value: i64, limit: i64) -> Option<i64> {
+        if (0..limit).contains(&value) {
+            return Some(value);
+        }
+        if limit <= 0 {
+            return None;
+        }
+        match self {
+            WrapMode::Ignore => None,
+            WrapMode::Wrap => Some(wrap_index(value, limit)),
+            WrapMode::Clamp => Some(value.clamp(0, limit - 1)),
+            WrapMode::Mirror => {
+                let value = value.rem_euclid(2 * limit);
+                Some(if value < limit {
+                    value
+                } else {
+                    2 * limit - 1 - value
+                })
+            }
+        }
+    }
+}
+
+/// `true` is [`WrapMode::Wrap`], and `false` is [`WrapMode::Ignore`].
+///
+/// [`WrapMode::Wrap`]: ./enum.WrapMode.html#variant.Wrap
+/// [`WrapMode::Ignore`]: ./enum.WrapMode.html#variant.Ignore
+impl From<bool> for WrapMode {
+    fn from(wrap: bool) -> Self {
+        if wrap {
+            WrapMode::Wrap
+        } else {
+            WrapMode::Ignore
+        }
+    }
+}
+
+/// Where (0, 0) is on a [`Ppm`], and which way y goes. See [`PpmBuilder::origin`].
+///
+/// x always goes to the right.
+///
+/// [`Ppm`]: ./struct.Ppm.html
+/// [`PpmBuilder::origin`]: ./struct.PpmBuilder.html#method.origin
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Origin {
+    /// (0, 0) is the top left pixel, and y goes down, like in most image formats and GUI toolkits.
+    TopLeft,
+    /// (0, 0) is the bottom left pixel, and y goes up, like in math. This is the default.
+    BottomLeft,
+    /// (0, 0) is the pixel in the middle, and y goes up. x goes from `-(width / 2)` to `width - width / 2 - 1`, and the
+    /// same for y with the height.
+    ///
+    /// Code that reads a whole [`Screen`] pixel by pixel, like [`draw_image`], starts from (0, 0) and only sees part of
+    /// an image with this origin.
+    ///
+    /// [`Screen`]: ../screen/trait.Screen.html
+    /// [`draw_image`]: ../screen/trait.Screen.html#method.draw_image
+    Center,
+}
+
+impl Origin {
+    /// Whether y goes up.
+    pub fn y_up(self) -> bool {
+        self != Origin::TopLeft
+    }
+
+    /// The smallest pixel coordinates on a `width` by `height` image: `(0, 0)`, unless the origin is in the center.
+    pub(crate) fn corner(self, width: usize, height: usize) -> (i64, i64) {
+        match self {
+            Origin::TopLeft | Origin::BottomLeft => (0, 0),
+            Origin::Center => (-((width / 2) as i64), -((height / 2) as i64)),
+        }
+    }
+}
+
+/// How a new sample is compared with the depth already in the z-buffer, to decide whether it's drawn. See
+/// [`PpmBuilder::depth_test`].
+///
+/// [`PpmBuilder::depth_test`]: ./struct.PpmBuilder.html#method.depth_test
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DepthTest {
+    /// Draw everything, so whatever is drawn last is on top (the painter's algorithm). The z-buffer is still written.
+    Always,
+    /// Draw samples closer than what's there, where a larger z is closer. This is the default.
+    Greater,
+    /// Like `Greater`, but also draw samples at the same depth, so the last of several coplanar shapes wins.
+    GreaterEqual,
+    /// Draw samples closer than what's there, where a smaller z is closer.
+    Less,
+    /// Like `Less`, but also draw samples at the same depth.
+    LessEqual,
+}
+
+impl DepthTest {
+    /// Whether a sample at depth `z` passes the test against the `stored` depth, with a tolerance of `epsilon`. See
+    /// [`PpmBuilder::depth_epsilon`].
+    ///
+    /// [`PpmBuilder::depth_epsilon`]: ./struct.PpmBuilder.html#method.depth_epsilon
+    pub fn passes(self, z: Float, stored: Float, epsilon: Float) -> bool {
+        match self {
+            DepthTest::Always => true,
+            DepthTest::Greater => z > stored + epsilon,
+            DepthTest::GreaterEqual => z >= stored - epsilon,
+            DepthTest::Less => z < stored - epsilon,
+            DepthTest::LessEqual => z <= stored + epsilon,
+        }
+    }
+
+    /// Whether a smaller z is closer.
+    pub fn is_reversed(self) -> bool {
+        matches!(self, DepthTest::Less | DepthTest::LessEqual)
+    }
+
+    /// Depth of an empty z-buffer, farther than anything: negative infinity, or positive infinity if [`is_reversed`].
+    ///
+    /// [`is_reversed`]: #method.is_reversed
+    pub fn far(self) -> Float {
+        if self.is_reversed() {
+            Float::INFINITY
+        } else {
+            Float::NEG_INFINITY
+        }
+    }
+}
+
+/// Test against the stencil buffer that decides whether a sample is drawn. See [`Ppm::set_stencil`].
+///
+/// [`Ppm::set_stencil`]: ./struct.Ppm.html#method.set_stencil
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StencilTest {
+    /// Always pass.
+    Always,
+    /// Never pass. Useful to write a mask into the stencil buffer without drawing anything.
+    Never,
+    /// Pass where the stencil value is equal to the given value.
+    Equal(u8),
+    /// Pass where the stencil value is not equal to the given value.
+    NotEqual(u8),
+}
+
+impl StencilTest {
+    /// Whether a sample with a `stencil` value passes.
+    pub fn passes(self, stencil: u8) -> bool {
+        match self {
+            StencilTest::Always => true,
+            StencilTest::Never => false,
+            StencilTest::Equal(value) => stencil == value,
+            StencilTest::NotEqual(value) => stencil != value,
+        }
+    }
+}
+
+/// What happens to the stencil value of a sample. See [`Ppm::set_stencil`].
+///
+/// [`Ppm::set_stencil`]: ./struct.Ppm.html#method.set_stencil
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StencilOp {
+    /// Leave it as is.
+    Keep,
+    /// Set it to `0`.
+    Zero,
+    /// Set it to the given value.
+    Replace(u8),
+    /// Add `1`, stopping at `255`.
+    Increment,
+    /// Subtract `1`, stopping at `0`.
+    Decrement,
+}
+
+impl StencilOp {
+    /// The new stencil value of a sample that had `stencil`.
+    pub fn apply(self, stencil: u8) -> u8 {
+        match self {
+            StencilOp::Keep => stencil,
+            StencilOp::Zero => 0,
+            StencilOp::Replace(value) => value,
+            StencilOp::Increment => stencil.saturating_add(1),
+            StencilOp::Decrement => stencil.saturating_sub(1),
         }
     }
 }
 
-/// Represent a ppm image.
-pub struct Ppm {
+/// Represent a ppm image, with pixels of color type `C`.
+pub struct Ppm<C = Rgb> {
     /// Height of the image (max y value).
     height: usize,
     /// Width of the image (max x value).
     width: usize,
     /// Max value of color_depth is 2^16, per ppm spec.
     color_depth: u16, // max = 2^16
-    /// If true, x values outside image will be wrapped around. if `false`, the point will be ignored.
-    pub wrap_x: bool,
-    /// If true, y values outside image will be wrapped around. If `false`, the point will be ignored.
-    pub wrap_y: bool,
-    /// Whether y values will be inverted when plotting. Value of `true` will put origin on bottom left.
-    pub invert_y: bool,
+    /// What happens to x values outside the image. See [`WrapMode`].
+    ///
+    /// [`WrapMode`]: ./enum.WrapMode.html
+    pub wrap_x: WrapMode,
+    /// What happens to y values outside the image. See [`WrapMode`].
+    ///
+    /// [`WrapMode`]: ./enum.WrapMode.html
+    pub wrap_y: WrapMode,
+    /// Where (0, 0) is, and which way y goes. See [`PpmBuilder::origin`].
+    ///
+    /// [`PpmBuilder::origin`]: ./struct.PpmBuilder.html#method.origin
+    pub origin: Origin,
+    /// Algorithm used to rasterize shapes. See the [`raster`] module.
+    ///
+    /// [`raster`]: ../raster/index.html
+    pub rasterizer: &'static dyn Rasterizer,
+    /// Transfer function applied when writing the image. See [`PpmBuilder::gamma`].
+    ///
+    /// [`PpmBuilder::gamma`]: ./struct.PpmBuilder.html#method.gamma
+    pub gamma: Gamma,
     /// Image data.
     /// 
     /// Image data is a 2D array. However here we use a 1D array to represent it, because Vec in Vec in Rust isn't so great. [`index`] is
     /// used to get the appropriate index of `data` based on a (x, y) coordinate. 
     /// 
+    /// With [`supersample`] on, `data` holds all the samples, which are averaged into pixels on output.
+    ///
     /// [`index`]: #method.index
-    data: Vec<Rgb>,
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    data: Vec<C>,
+    /// Supersampling factor. Each pixel is made of `supersample * supersample` samples.
+    supersample: usize,
+    /// Clip rectangle `(x0, y0, x1, y1)` in pixel coordinates, end-exclusive. See [`set_clip`].
+    ///
+    /// [`set_clip`]: #method.set_clip
+    clip: Option<(i64, i64, i64, i64)>,
     /// Z-buffer (depth buffer).
-    #[allow(dead_code)]
-    zbuf: Vec<f64>,
+    zbuf: Vec<Float>,
+    /// How samples are compared with the z-buffer. See [`set_depth_test`].
+    ///
+    /// [`set_depth_test`]: #method.set_depth_test
+    depth_test: DepthTest,
+    /// Tolerance of the depth test. See [`PpmBuilder::depth_epsilon`].
+    ///
+    /// [`PpmBuilder::depth_epsilon`]: ./struct.PpmBuilder.html#method.depth_epsilon
+    pub depth_epsilon: Float,
+    /// Stencil buffer, one value per sample, only allocated once [`set_stencil`] is called.
+    ///
+    /// [`set_stencil`]: #method.set_stencil
+    stencil: Option<Vec<u8>>,
+    /// Stencil test, and what to do to the stencil value when a sample is drawn, and when it fails the stencil test.
+    stencil_mode: (StencilTest, StencilOp, StencilOp),
+    /// Comments written in the header of ppm files. See [`add_comment`].
+    ///
+    /// [`add_comment`]: #method.add_comment
+    comments: Vec<String>,
+    /// The file laid out by [`write_binary_nobuffer`], kept between writes so that writing every frame of an animation
+    /// doesn't allocate.
+    ///
+    /// [`write_binary_nobuffer`]: #method.write_binary_nobuffer
+    scratch: Mutex<Vec<u8>>,
+}
+
+impl Default for Ppm {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Ppm {
@@ -221,185 +941,2520 @@ impl Ppm {
     pub fn new() -> Self {
         PpmBuilder::new(500, 500, 255).build()
     }
+}
 
-    /// Returns `Some(index)` if `index` is in the bounds of `self.data`. Otherwise `None`.
+impl<C: PpmColor> Ppm<C> {
+    /// Return the max value of a color channel in this image.
+    pub fn color_depth(&self) -> u16 {
+        self.color_depth
+    }
+
+    /// Return the supersampling factor set with [`PpmBuilder::supersample`].
     ///
-    /// `None` is useful because you might want to ignore points that are outside of the visible space. Note that there's no `null` in Rust,
-    /// so this requires you to handle all cases.
+    /// [`PpmBuilder::supersample`]: ./struct.PpmBuilder.html#method.supersample
+    pub fn supersample(&self) -> usize {
+        self.supersample
+    }
+
+    /// Only allow drawing inside the `width` by `height` rectangle with its corner at (`x`, `y`), until [`reset_clip`].
+    ///
+    /// Coordinates are the same ones given to [`plot`], before wrapping. Every drawing method goes through the clip
+    /// rectangle, except [`clear`], which always clears the whole image. Setting a new clip rectangle replaces the old one.
+    /// A rectangle reaching past `i64::MAX` is cut off there.
+    ///
+    /// # Examples
+    ///
+    /// Split-screen comparison of two renders:
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// img.set_clip(0, 0, 250, 500);
+    /// img.fill_triangle((50., 50., 0.), (450., 50., 0.), (250., 450., 0.), Rgb::new(255, 0, 0));
+    ///
+    /// img.set_clip(250, 0, 250, 500);
+    /// img.fill_triangle((50., 50., 0.), (450., 50., 0.), (250., 450., 0.), Rgb::new(0, 0, 255));
+    ///
+    /// img.reset_clip();
+    /// img.draw_line((250., 0., 1.), (250., 499., 1.), Rgb::WHITE);
+    ///
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get_pixel(200, 100));
+    /// assert_eq!(Some(Rgb::new(0, 0, 255)), img.get_pixel(300, 100));
+    /// ```
+    ///
+    /// [`reset_clip`]: #method.reset_clip
+    /// [`plot`]: ../screen/trait.Screen.html#tymethod.plot
+    /// [`clear`]: ../screen/trait.Screen.html#tymethod.clear
+    pub fn set_clip(&mut self, x: i64, y: i64, width: usize, height: usize) {
+        let end =
+            |start: i64, size: usize| start.saturating_add(i64::try_from(size).unwrap_or(i64::MAX));
+        self.clip = Some((x, y, end(x, width), end(y, height)));
+    }
+
+    /// Remove the clip rectangle set with [`set_clip`], allowing drawing on the whole image again.
+    ///
+    /// [`set_clip`]: #method.set_clip
+    pub fn reset_clip(&mut self) {
+        self.clip = None;
+    }
+
+    /// The clip rectangle set with [`set_clip`], as `(x, y, width, height)`.
+    ///
+    /// [`set_clip`]: #method.set_clip
+    pub fn clip(&self) -> Option<(i64, i64, usize, usize)> {
+        // the end is never before the start, so the difference fits in a u64
+        let size = |start: i64, end: i64| end.wrapping_sub(start) as u64 as usize;
+        self.clip
+            .map(|(x0, y0, x1, y1)| (x0, y0, size(x0, x1), size(y0, y1)))
+    }
+
+    /// Add a comment to the image, like the author, the frame number, or the seed and settings used to render it.
+    ///
+    /// Comments are written in the header of ppm, pgm, pbm and pam files, and read back by [`from_reader`]. A comment
+    /// with several lines is written as several comments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = PpmBuilder::new(10, 10, 255).comment("author: me").build();
+    /// img.add_comment("seed: 42");
+    ///
+    /// let mut buf = vec![];
+    /// img.write_binary_to_buf(&mut buf).unwrap();
+    /// assert!(buf.starts_with(b"P6\n# author: me\n# seed: 42\n10 10 255\n"));
+    ///
+    /// let read = Ppm::<Rgb>::from_reader(&buf[..]).unwrap();
+    /// assert_eq!(&["author: me", "seed: 42"], read.comments());
+    /// ```
+    ///
+    /// [`from_reader`]: #method.from_reader
+    pub fn add_comment(&mut self, comment: &str) {
+        self.comments.push(comment.to_string());
+    }
+
+    /// The comments of this image. See [`add_comment`].
+    ///
+    /// [`add_comment`]: #method.add_comment
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Remove all comments.
+    pub fn clear_comments(&mut self) {
+        self.comments.clear();
+    }
+
+    /// Write every line of every comment as a header comment.
+    fn write_comments<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        for line in self.comments.iter().flat_map(|c| c.lines()) {
+            writeln!(writer, "# {}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Reset the z-buffer, without touching the pixels, so that anything drawn next goes over what's already there.
+    ///
+    /// Useful to draw a new layer, like a HUD or labels, over a finished 3D render. [`clear`] resets both.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    /// img.plot(10, 10, 100., Rgb::WHITE);
+    ///
+    /// img.clear_zbuf_only();
+    /// img.plot(10, 10, 0., Rgb::new(255, 0, 0));
+    ///
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get(10, 10));
+    /// ```
+    ///
+    /// [`clear`]: ../screen/trait.Screen.html#tymethod.clear
+    pub fn clear_zbuf_only(&mut self) {
+        self.zbuf.fill(self.depth_test.far());
+    }
+
+    /// The depth test set with [`PpmBuilder::depth_test`] or [`set_depth_test`].
+    ///
+    /// [`PpmBuilder::depth_test`]: ./struct.PpmBuilder.html#method.depth_test
+    /// [`set_depth_test`]: #method.set_depth_test
+    pub fn depth_test(&self) -> DepthTest {
+        self.depth_test
+    }
+
+    /// Change the depth test. This also clears the z-buffer (see [`clear_zbuf_only`]), since depths stored for one
+    /// direction of comparison don't mean anything for the other.
+    ///
+    /// Switching to [`DepthTest::Always`] to draw a 2D overlay on top of a 3D scene is a common use.
+    ///
+    /// [`clear_zbuf_only`]: #method.clear_zbuf_only
+    /// [`DepthTest::Always`]: ./enum.DepthTest.html#variant.Always
+    pub fn set_depth_test(&mut self, depth_test: DepthTest) {
+        self.depth_test = depth_test;
+        self.clear_zbuf_only();
+    }
+
+    /// Turn on the stencil buffer, and set how [`plot`] uses it.
+    ///
+    /// The stencil buffer holds a `u8` for every pixel (every sample with [`supersample`] on), starting at `0`. Every
+    /// plotted sample is first checked with `test`: if it fails, it isn't drawn, and `on_fail` is applied to its stencil
+    /// value. Otherwise, the depth test runs as usual, and if the sample is drawn, `on_pass` is applied. Samples that
+    /// fail the depth test keep their stencil value.
+    ///
+    /// Call this again to change the mode; the stencil values are kept. [`clear`] doesn't reset them, use
+    /// [`clear_stencil`].
+    ///
+    /// # Examples
+    ///
+    /// Only draw inside a window, to look at another scene through it:
+    ///
+    /// ```
+    /// use graphics::{
+    ///     ppm::{StencilOp, StencilTest},
+    ///     prelude::*,
+    /// };
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // write the mask without drawing anything
+    /// img.set_stencil(StencilTest::Never, StencilOp::Keep, StencilOp::Replace(1));
+    /// img.fill_rect((150., 150., 0.), 200., 200., Rgb::WHITE);
+    ///
+    /// // only draw inside the mask
+    /// img.set_stencil(StencilTest::Equal(1), StencilOp::Keep, StencilOp::Keep);
+    /// img.fill_triangle((0., 0., 0.), (499., 0., 0.), (250., 499., 0.), Rgb::new(0, 0, 255));
+    ///
+    /// assert_eq!(Some(Rgb::new(0, 0, 255)), img.get(250, 250));
+    /// assert_eq!(Some(Rgb::BLACK), img.get(10, 10));
+    /// assert_eq!(Some(1), img.stencil_at(250, 250));
+    /// ```
+    ///
+    /// An outline: draw a shape, marking it in the stencil, then a slightly bigger copy everywhere it isn't marked.
+    ///
+    /// ```
+    /// use graphics::{
+    ///     ppm::{StencilOp, StencilTest},
+    ///     prelude::*,
+    /// };
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// img.set_stencil(StencilTest::Always, StencilOp::Replace(1), StencilOp::Keep);
+    /// img.fill_triangle((100., 100., 0.), (400., 100., 0.), (250., 400., 0.), Rgb::new(255, 0, 0));
+    ///
+    /// img.set_stencil(StencilTest::NotEqual(1), StencilOp::Keep, StencilOp::Keep);
+    /// img.fill_triangle((90., 94., -1.), (410., 94., -1.), (250., 414., -1.), Rgb::WHITE);
+    ///
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get(250, 200));
+    /// assert_eq!(Some(Rgb::WHITE), img.get(250, 97));
+    /// ```
+    ///
+    /// [`plot`]: ../screen/trait.Screen.html#tymethod.plot
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    /// [`clear`]: ../screen/trait.Screen.html#tymethod.clear
+    /// [`clear_stencil`]: #method.clear_stencil
+    pub fn set_stencil(&mut self, test: StencilTest, on_pass: StencilOp, on_fail: StencilOp) {
+        if self.stencil.is_none() {
+            self.stencil = Some(vec![0; self.data.len()]);
+        }
+        self.stencil_mode = (test, on_pass, on_fail);
+    }
+
+    /// Turn off the stencil buffer set up with [`set_stencil`], freeing its memory.
+    ///
+    /// [`set_stencil`]: #method.set_stencil
+    pub fn disable_stencil(&mut self) {
+        self.stencil = None;
+        self.stencil_mode = (StencilTest::Always, StencilOp::Keep, StencilOp::Keep);
+    }
+
+    /// Set every value in the stencil buffer to `value`, if it's on.
+    pub fn clear_stencil(&mut self, value: u8) {
+        if let Some(stencil) = &mut self.stencil {
+            stencil.fill(value);
+        }
+    }
+
+    /// Stencil value of the pixel at (`x`, `y`), or `None` if it's outside the image or the stencil buffer is off.
+    ///
+    /// With [`supersample`] on, this is the value of the first sample of the pixel.
+    ///
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    pub fn stencil_at(&self, x: i64, y: i64) -> Option<u8> {
+        let n = self.supersample as i64;
+        let index = self.index(x * n, y * n)?;
+        self.stencil.as_ref().map(|stencil| stencil[index])
+    }
+
+    /// Depth of the pixel at (`x`, `y`) in the z-buffer, or `None` if it's outside the image. The same as
+    /// [`get_depth`].
+    ///
+    /// Pixels that nothing was drawn on have a depth of negative infinity, or positive infinity with a reversed
+    /// [`DepthTest`].
+    ///
+    /// [`DepthTest`]: ./enum.DepthTest.html
+    ///
+    /// [`get_depth`]: ../screen/trait.Screen.html#tymethod.get_depth
+    pub fn depth_at(&self, x: i64, y: i64) -> Option<Float> {
+        self.get_depth(x, y)
+    }
+
+    /// The smallest and largest depth of anything drawn on the image, or `None` if nothing was.
+    pub fn depth_range(&self) -> Option<(Float, Float)> {
+        self.zbuf
+            .iter()
+            .filter(|z| z.is_finite())
+            .fold(None, |range, &z| match range {
+                None => Some((z, z)),
+                Some((min, max)) => Some((min.min(z), max.max(z))),
+            })
+    }
+
+    /// Visualize the z-buffer as a grayscale image: the closest pixels are white, the farthest dark gray, and pixels
+    /// nothing was drawn on are black.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    /// img.fill_triangle((0., 0., -50.), (499., 0., -50.), (250., 499., 50.), Rgb::WHITE);
+    ///
+    /// assert_eq!(Some((-50., 50.)), img.depth_range().map(|(min, max)| (min.round(), max.round())));
+    ///
+    /// let depth = img.depth_image();
+    /// let (far, near) = (depth.get(250, 10).unwrap(), depth.get(250, 400).unwrap());
+    /// assert!(Rgb::BLACK.red < far.red && far.red < near.red);
+    /// assert_eq!(Some(Rgb::BLACK), depth.get(0, 499));
+    /// // depth.save("depth.png")
+    /// ```
+    pub fn depth_image(&self) -> Ppm {
+        const FAR: Float = 32.;
+
+        let mut img = PpmBuilder::new(self.height, self.width, 255)
+            .origin(self.origin)
+            .build();
+        let (min, max) = match self.depth_range() {
+            Some(range) => range,
+            None => return img,
+        };
+        let (left, bottom) = self.corner();
+        for y in bottom..bottom + self.height as i64 {
+            for x in left..left + self.width as i64 {
+                let z = match self.get_depth(x, y) {
+                    Some(z) if z.is_finite() => z,
+                    _ => continue,
+                };
+                let t = if max > min {
+                    (z - min) / (max - min)
+                } else {
+                    1.
+                };
+                let t = if self.depth_test.is_reversed() {
+                    1. - t
+                } else {
+                    t
+                };
+                img.set(x, y, Rgb::gray((FAR + t * (255. - FAR)).round() as u8));
+            }
+        }
+        img
+    }
+
+    /// Color of the pixel at (`x`, `y`), or `None` if it's outside the image. The same as [`get_pixel`].
+    ///
+    /// [`get_pixel`]: ../screen/trait.Screen.html#tymethod.get_pixel
+    pub fn get(&self, x: i64, y: i64) -> Option<C> {
+        self.get_pixel(x, y)
+    }
+
+    /// Set the pixel at (`x`, `y`) to `color`, without looking at or changing the z-buffer, and ignoring the clip
+    /// rectangle. Points outside the image are ignored (or wrapped, like [`plot`]).
+    ///
+    /// Useful for post-processing and tests, where plot's depth test gets in the way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    /// img.plot(10, 10, 5., Rgb::WHITE);
+    ///
+    /// // plot at a lower z would be hidden
+    /// img.set(10, 10, Rgb::new(255, 0, 0));
+    ///
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get(10, 10));
+    /// assert_eq!(Some(5.), img.get_depth(10, 10));
+    /// assert_eq!(None, img.get(-1, 10));
+    /// ```
+    ///
+    /// [`plot`]: ../screen/trait.Screen.html#tymethod.plot
+    pub fn set(&mut self, x: i64, y: i64, color: C) {
+        let n = self.supersample as i64;
+        for sy in y * n..(y + 1) * n {
+            for sx in x * n..(x + 1) * n {
+                if let Some(index) = self.index(sx, sy) {
+                    self.data[index] = color;
+                }
+            }
+        }
+    }
+
+    /// Iterate over the pixels of the image, row by row, in the order they are written to a file (starting from the
+    /// top left).
+    ///
+    /// With [`supersample`] on, this goes over every sample instead: `supersample * supersample` per pixel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    /// img.fill_rect((0., 0., 0.), 100., 100., Rgb::WHITE);
+    ///
+    /// assert_eq!(100 * 100, img.pixels().filter(|&&c| c == Rgb::WHITE).count());
+    /// ```
+    ///
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    pub fn pixels(&self) -> slice::Iter<'_, C> {
+        self.data.iter()
+    }
+
+    /// Iterate mutably over the pixels of the image, in the same order as [`pixels`]. This doesn't touch the z-buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = PpmBuilder::new(10, 10, 255).bg_color(Rgb::new(0, 100, 200)).build();
+    ///
+    /// // invert colors
+    /// for c in img.pixels_mut() {
+    ///     *c = Rgb::WHITE - *c;
+    /// }
+    ///
+    /// assert_eq!(Some(Rgb::new(255, 155, 55)), img.get(3, 4));
+    /// ```
+    ///
+    /// [`pixels`]: #method.pixels
+    pub fn pixels_mut(&mut self) -> slice::IterMut<'_, C> {
+        self.data.iter_mut()
+    }
+
+    /// Iterate over the rows of the image, starting from the top, each as a slice of pixels from left to right.
+    ///
+    /// With [`supersample`] on, these are rows of samples, [`width`] times `supersample` long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = PpmBuilder::new(4, 4, 255).build();
+    /// img.plot(0, 0, 0., Rgb::WHITE);
+    ///
+    /// assert_eq!(4, img.rows().count());
+    /// // the origin is on the bottom left
+    /// assert_eq!(Some(&Rgb::WHITE), img.rows().last().unwrap().first());
+    /// ```
+    ///
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    /// [`width`]: ../screen/trait.Screen.html#tymethod.width
+    pub fn rows(&self) -> slice::Chunks<'_, C> {
+        self.data.chunks((self.width * self.supersample).max(1))
+    }
+
+    /// Iterate mutably over the rows of the image, in the same order as [`rows`]. This doesn't touch the z-buffer.
+    ///
+    /// [`rows`]: #method.rows
+    pub fn rows_mut(&mut self) -> slice::ChunksMut<'_, C> {
+        self.data.chunks_mut((self.width * self.supersample).max(1))
+    }
+
+    /// Iterate over the pixels of the image with their coordinates, as `(x, y, &color)`. See [`enumerate_pixels_mut`].
+    ///
+    /// [`enumerate_pixels_mut`]: #method.enumerate_pixels_mut
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (i64, i64, &C)> + '_ {
+        let coords = self.coords();
+        self.data.iter().enumerate().map(move |(i, c)| {
+            let (x, y) = coords(i);
+            (x, y, c)
+        })
+    }
+
+    /// Iterate mutably over the pixels of the image with their coordinates, as `(x, y, &mut color)`. This doesn't touch
+    /// the z-buffer.
+    ///
+    /// Coordinates are the same ones [`plot`] takes, so they follow [`origin`]. With [`supersample`] on, every sample
+    /// is yielded with the coordinates of its pixel.
+    ///
+    /// # Examples
+    ///
+    /// A vignette, darkening the image away from the center:
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = PpmBuilder::new(500, 500, 255).bg_color(Rgb::WHITE).build();
+    ///
+    /// for (x, y, c) in img.enumerate_pixels_mut() {
+    ///     let d = ((x - 250).pow(2) + (y - 250).pow(2)) as f64;
+    ///     *c *= 1. - (d / (2. * 250f64.powi(2))).min(1.);
+    /// }
+    ///
+    /// assert_eq!(Some(Rgb::WHITE), img.get(250, 250));
+    /// assert_eq!(Some(Rgb::BLACK), img.get(0, 0));
+    /// ```
+    ///
+    /// [`plot`]: ../screen/trait.Screen.html#tymethod.plot
+    /// [`origin`]: #structfield.origin
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    pub fn enumerate_pixels_mut(&mut self) -> impl Iterator<Item = (i64, i64, &mut C)> + '_ {
+        let coords = self.coords();
+        self.data.iter_mut().enumerate().map(move |(i, c)| {
+            let (x, y) = coords(i);
+            (x, y, c)
+        })
+    }
+
+    /// Copy the `width` by `height` region with its corner at (`x`, `y`) into a new image, with pixel (`x`, `y`) at its
+    /// origin. The region is clipped to the image, so the result can be smaller than asked for.
+    ///
+    /// The new image has the same settings, except [`supersample`]: samples are averaged into pixels, and it's not
+    /// supersampled. Its z-buffer is empty.
+    ///
+    /// Fails with [`GraphicsError::InvalidDimensions`] if no part of the region is inside the image, since that leaves
+    /// no pixels.
+    ///
+    /// # Examples
+    ///
+    /// Cut a spritesheet into sprites:
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut sheet = PpmBuilder::new(64, 64, 255).build();
+    /// sheet.fill_rect((32., 32., 0.), 32., 32., Rgb::WHITE);
+    ///
+    /// let sprites: Vec<Ppm> = (0..4)
+    ///     .map(|i| sheet.crop(i % 2 * 32, i / 2 * 32, 32, 32).unwrap())
+    ///     .collect();
+    ///
+    /// assert_eq!(32, sprites[3].width());
+    /// assert!(sprites[3].pixels().all(|&c| c == Rgb::WHITE));
+    /// assert!(sprites[0].pixels().all(|&c| c == Rgb::BLACK));
+    ///
+    /// // clipped to the image
+    /// assert_eq!(14, sheet.crop(50, 50, 100, 100).unwrap().height());
+    /// assert!(sheet.crop(-100, 0, 50, 50).is_err());
+    /// ```
+    ///
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    /// [`GraphicsError::InvalidDimensions`]: ../error/enum.GraphicsError.html#variant.InvalidDimensions
+    pub fn crop(&self, x: i64, y: i64, width: usize, height: usize) -> error::Result<Ppm<C>> {
+        let (left, bottom) = self.corner();
+        let (right, top) = (left + self.width as i64, bottom + self.height as i64);
+        let end =
+            |start: i64, size: usize| start.saturating_add(i64::try_from(size).unwrap_or(i64::MAX));
+        let x0 = x.clamp(left, right);
+        let y0 = y.clamp(bottom, top);
+        let x1 = end(x, width).clamp(x0, right);
+        let y1 = end(y, height).clamp(y0, top);
+        if x0 == x1 || y0 == y1 {
+            return Err(GraphicsError::InvalidDimensions(format!(
+                "the {}x{} region at ({}, {}) is outside the image",
+                width, height, x, y
+            )));
+        }
+
+        let mut cropped = self
+            .builder_like((y1 - y0) as usize, (x1 - x0) as usize)
+            .build();
+        let (cropped_left, cropped_bottom) = cropped.corner();
+        for py in y0..y1 {
+            for px in x0..x1 {
+                if let Some(color) = self.get(px, py) {
+                    cropped.set(px - x0 + cropped_left, py - y0 + cropped_bottom, color);
+                }
+            }
+        }
+        Ok(cropped)
+    }
+
+    /// Scale the image to `width` by `height` pixels, using `filter` to compute the new pixels.
+    ///
+    /// The new image has the same settings, except [`supersample`], like with [`crop`].
+    ///
+    /// # Panics
+    ///
+    /// If `width` or `height` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{ppm::Filter, prelude::*};
+    ///
+    /// let mut img = PpmBuilder::new(2, 2, 255).build();
+    /// img.plot(1, 0, 0., Rgb::WHITE);
+    ///
+    /// let big = img.resized(100, 100, Filter::Nearest);
+    /// assert_eq!(Some(Rgb::WHITE), big.get(99, 0));
+    /// assert_eq!(Some(Rgb::BLACK), big.get(49, 0));
+    ///
+    /// let smooth = img.resized(100, 100, Filter::Bilinear);
+    /// let middle = smooth.get(50, 0).unwrap();
+    /// assert!(100 < middle.red && middle.red < 155);
+    /// ```
+    ///
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    /// [`crop`]: #method.crop
+    pub fn resized(&self, width: usize, height: usize, filter: Filter) -> Ppm<C> {
+        let mut resized = self.builder_like(height, width).build();
+        let (sx, sy) = (
+            self.width as f64 / width as f64,
+            self.height as f64 / height as f64,
+        );
+        // pixels are clamped to the edges, no matter the wrapping settings
+        let (left, bottom) = self.corner();
+        let pixel = |x: i64, y: i64| {
+            let x = x.clamp(0, self.width as i64 - 1);
+            let y = y.clamp(0, self.height as i64 - 1);
+            self.get(left + x, bottom + y).unwrap()
+        };
+        let (resized_left, resized_bottom) = resized.corner();
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                // position of the center of the new pixel, in old pixels
+                let (fx, fy) = ((x as f64 + 0.5) * sx - 0.5, (y as f64 + 0.5) * sy - 0.5);
+                let color = match filter {
+                    Filter::Nearest => pixel(fx.round() as i64, fy.round() as i64),
+                    Filter::Bilinear => {
+                        let (x0, y0) = (fx.floor(), fy.floor());
+                        let (tx, ty) = (fx - x0, fy - y0);
+                        let (x0, y0) = (x0 as i64, y0 as i64);
+                        let bottom = pixel(x0, y0).mix(pixel(x0 + 1, y0), tx);
+                        let top = pixel(x0, y0 + 1).mix(pixel(x0 + 1, y0 + 1), tx);
+                        bottom.mix(top, ty)
+                    }
+                };
+                resized.set(resized_left + x, resized_bottom + y, color);
+            }
+        }
+        resized
+    }
+
+    /// Rotate the image 90° clockwise, as it's seen in the written file.
+    ///
+    /// Like with [`crop`], the new image has the same settings except [`supersample`], and an empty z-buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = PpmBuilder::new(3, 3, 255).build();
+    /// // bottom left
+    /// img.plot(0, 0, 0., Rgb::WHITE);
+    ///
+    /// // top left
+    /// assert_eq!(Some(Rgb::WHITE), img.rotate90().get(0, 2));
+    /// // top right
+    /// assert_eq!(Some(Rgb::WHITE), img.rotate180().get(2, 2));
+    /// // bottom right
+    /// assert_eq!(Some(Rgb::WHITE), img.rotate270().get(2, 0));
+    ///
+    /// assert_eq!(Some(Rgb::WHITE), img.flip_horizontal().get(2, 0));
+    /// assert_eq!(Some(Rgb::WHITE), img.flip_vertical().get(0, 2));
+    /// ```
+    ///
+    /// [`crop`]: #method.crop
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    pub fn rotate90(&self) -> Ppm<C> {
+        let height = self.height;
+        self.remapped(self.height, self.width, |x, y| (y, height - 1 - x))
+    }
+
+    /// Rotate the image 180°. See [`rotate90`].
+    ///
+    /// [`rotate90`]: #method.rotate90
+    pub fn rotate180(&self) -> Ppm<C> {
+        let (width, height) = (self.width, self.height);
+        self.remapped(width, height, |x, y| (width - 1 - x, height - 1 - y))
+    }
+
+    /// Rotate the image 90° counterclockwise. See [`rotate90`].
+    ///
+    /// [`rotate90`]: #method.rotate90
+    pub fn rotate270(&self) -> Ppm<C> {
+        let width = self.width;
+        self.remapped(self.height, self.width, |x, y| (width - 1 - y, x))
+    }
+
+    /// Mirror the image left to right. See [`rotate90`].
+    ///
+    /// [`rotate90`]: #method.rotate90
+    pub fn flip_horizontal(&self) -> Ppm<C> {
+        let width = self.width;
+        self.remapped(self.width, self.height, |x, y| (width - 1 - x, y))
+    }
+
+    /// Mirror the image top to bottom. Handy to fix an image drawn with the wrong [`origin`]. See [`rotate90`].
+    ///
+    /// [`origin`]: #structfield.origin
+    /// [`rotate90`]: #method.rotate90
+    pub fn flip_vertical(&self) -> Ppm<C> {
+        let height = self.height;
+        self.remapped(self.width, self.height, |x, y| (x, height - 1 - y))
+    }
+
+    /// A new `width` by `height` image, where the pixel at (`x`, `y`) comes from the pixel at `source(x, y)` in this
+    /// image. Coordinates are in file order: from the top left, with y going down.
+    fn remapped<F>(&self, width: usize, height: usize, source: F) -> Ppm<C>
+    where
+        F: Fn(usize, usize) -> (usize, usize),
+    {
+        let pixels = self.data();
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = source(x, y);
+                data.push(pixels[sy * self.width + sx]);
+            }
+        }
+        self.builder_like(height, width).with_data(data).build()
+    }
+
+    /// Blend `layer` over this image with `mode`, with the layer's pixel (0, 0) at (`x`, `y`). Parts of the layer outside
+    /// this image are ignored.
+    ///
+    /// This doesn't touch the z-buffer. Channels are scaled by each image's [`color_depth`], so images of different
+    /// depths can be combined.
+    ///
+    /// # Examples
+    ///
+    /// Render the background and a glow separately, then combine them:
+    ///
+    /// ```
+    /// use graphics::{blend::BlendMode, prelude::*};
+    ///
+    /// let mut background = PpmBuilder::new(100, 100, 255).bg_color(Rgb::new(0, 0, 100)).build();
+    /// let glow = PpmBuilder::new(20, 20, 255).bg_color(Rgb::new(200, 200, 0)).build();
+    ///
+    /// background.composite(&glow, 40, 40, BlendMode::Add);
+    ///
+    /// assert_eq!(Some(Rgb::new(200, 200, 100)), background.get(50, 50));
+    /// assert_eq!(Some(Rgb::new(0, 0, 100)), background.get(10, 10));
+    /// ```
+    ///
+    /// [`color_depth`]: #method.color_depth
+    pub fn composite<D: PpmColor>(&mut self, layer: &Ppm<D>, x: i64, y: i64, mode: BlendMode) {
+        // one copy of the loop per mode, instead of matching the mode for every channel
+        match mode {
+            BlendMode::Normal => {
+                self.composite_with(layer, x, y, |b, t| BlendMode::Normal.apply(b, t))
+            }
+            BlendMode::Add => self.composite_with(layer, x, y, |b, t| BlendMode::Add.apply(b, t)),
+            BlendMode::Multiply => {
+                self.composite_with(layer, x, y, |b, t| BlendMode::Multiply.apply(b, t))
+            }
+            BlendMode::Screen => {
+                self.composite_with(layer, x, y, |b, t| BlendMode::Screen.apply(b, t))
+            }
+        }
+    }
+
+    fn composite_with<D, F>(&mut self, layer: &Ppm<D>, x: i64, y: i64, blend: F)
+    where
+        D: PpmColor,
+        F: Fn(f64, f64) -> f64,
+    {
+        let (max, layer_max) = (
+            self.color_depth.max(1) as f64,
+            layer.color_depth().max(1) as f64,
+        );
+        let blend_pixel = |under: C, top: D| {
+            let (bottom, top) = (under.channels(), top.channels());
+            let mut channels = [0; 3];
+            for (i, channel) in channels.iter_mut().enumerate() {
+                let blended = blend(bottom[i] as f64 / max, top[i] as f64 / layer_max);
+                *channel = (blended * max).round() as u16;
+            }
+            C::from_channels(channels).with_alpha(under.alpha())
+        };
+
+        let (left, bottom) = self.corner();
+        let (layer_left, layer_bottom) = layer.corner();
+        // the columns of the layer that land on the image
+        let first = (left - x).max(0);
+        let last = (left + self.width as i64 - x).min(layer.width() as i64);
+        if first >= last {
+            return;
+        }
+
+        if self.supersample != 1 {
+            for ly in 0..layer.height() as i64 {
+                for lx in first..last {
+                    let (px, py) = (x + lx, y + ly);
+                    if py < bottom || py >= bottom + self.height as i64 {
+                        continue;
+                    }
+                    if let (Some(under), Some(top)) = (
+                        self.get(px, py),
+                        layer.get(layer_left + lx, layer_bottom + ly),
+                    ) {
+                        self.set(px, py, blend_pixel(under, top));
+                    }
+                }
+            }
+            return;
+        }
+
+        // blend whole rows of pixels at a time, which is much faster than getting and setting each of them
+        let top_pixels = layer.data();
+        let count = (last - first) as usize;
+        for ly in 0..layer.height() as i64 {
+            let py = y + ly;
+            if py < bottom || py >= bottom + self.height as i64 {
+                continue;
+            }
+            let (column, row) = self.pixel_position(x + first, py).unwrap();
+            let (layer_column, layer_row) = layer
+                .pixel_position(layer_left + first, layer_bottom + ly)
+                .unwrap();
+            let start = row * self.width + column;
+            let layer_start = layer_row * layer.width() + layer_column;
+            for (under, &top) in self.data[start..start + count]
+                .iter_mut()
+                .zip(&top_pixels[layer_start..layer_start + count])
+            {
+                *under = blend_pixel(*under, top);
+            }
+        }
+    }
+
+    /// The absolute difference between this image and `other`, channel by channel: black where they match, and
+    /// brighter where they differ more. The result has the size and settings of this image, without supersampling.
+    ///
+    /// Images are lined up at their top left corners. Where `other` is smaller, this image is compared to black.
+    /// Channels of `other` are scaled to this image's [`color_depth`]. To put a single number on the difference, see
+    /// [`metrics`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut a = Ppm::new();
+    /// let mut b = Ppm::new();
+    /// a.draw_line((0., 0., 0.), (499., 400., 0.), Rgb::WHITE);
+    /// b.draw_line((0., 0., 0.), (499., 401., 0.), Rgb::new(255, 0, 0));
+    ///
+    /// let diff = a.diff(&b);
+    /// // the start of both lines differs only in green and blue
+    /// assert_eq!(Some(Rgb::new(0, 255, 255)), diff.get(0, 0));
+    /// assert_eq!(Some(Rgb::BLACK), diff.get(0, 499));
+    /// ```
+    ///
+    /// [`color_depth`]: #method.color_depth
+    /// [`metrics`]: ../metrics/index.html
+    pub fn diff<D: PpmColor>(&self, other: &Ppm<D>) -> Ppm<C> {
+        let (max, other_max) = (self.color_depth as u32, other.color_depth().max(1) as u32);
+        let (pixels, other_pixels) = (self.data(), other.data());
+        let mut data = Vec::with_capacity(self.width * self.height);
+        for (y, row) in pixels.chunks(self.width.max(1)).enumerate() {
+            for (x, color) in row.iter().enumerate() {
+                let theirs = if x < other.width() && y < other.height() {
+                    other_pixels[y * other.width() + x].channels()
+                } else {
+                    [0; 3]
+                };
+                let mine = color.channels();
+                let mut channels = [0; 3];
+                for (i, channel) in channels.iter_mut().enumerate() {
+                    let scaled = (theirs[i] as u32 * max + other_max / 2) / other_max;
+                    *channel = (mine[i] as u32).abs_diff(scaled) as u16;
+                }
+                data.push(C::from_channels(channels));
+            }
+        }
+        self.builder_like(self.height, self.width)
+            .with_data(data)
+            .build()
+    }
+
+    /// Fill every triangle in `triangles` with its color, in order. The result is the same as calling
+    /// [`fill_triangle`] on each one.
+    ///
+    /// With the `parallel` feature, the image is split into bands of rows, one per thread, and every thread fills the
+    /// parts of the triangles in its band. Bands don't share any pixels, so no locking is needed, and the depth test
+    /// and stencil work as usual. This pays off for frames with many triangles, like polygon meshes; for a few small
+    /// ones, starting the threads takes longer than filling. Without the feature, everything is filled on the current
+    /// thread.
+    ///
+    /// The threads are plain [`thread::scope`] threads from the standard library, so the feature doesn't pull in any
+    /// dependency like rayon.
+    ///
+    /// If [`wrap_y`] is on, a triangle can wrap around onto any row, so every band would have to rasterize every
+    /// triangle in full. Filling stays on the current thread in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // a fan of 36 triangles around the center
+    /// let around = |i: usize| {
+    ///     let angle = (i as f64 * 10.).to_radians();
+    ///     (250. + 200. * angle.cos(), 250. + 200. * angle.sin(), 0.)
+    /// };
+    /// let triangles: Vec<_> = (0..36)
+    ///     .map(|i| ([(250., 250., 0.), around(i), around(i + 1)], Rgb::new(i as u8 * 7, 100, 255)))
+    ///     .collect();
+    /// img.fill_triangles(&triangles);
+    ///
+    /// assert_eq!(Some(Rgb::new(0, 100, 255)), img.get_pixel(400, 260));
+    /// ```
+    ///
+    /// [`fill_triangle`]: ../screen/trait.Screen.html#method.fill_triangle
+    /// [`thread::scope`]: https://doc.rust-lang.org/std/thread/fn.scope.html
+    /// [`wrap_y`]: ./struct.PpmBuilder.html#method.wrap_y
+    pub fn fill_triangles<P: Into<Point> + Copy>(&mut self, triangles: &[([P; 3], C)])
+    where
+        C: Send + Sync,
+    {
+        let triangles: Vec<([Point; 3], C)> = triangles
+            .iter()
+            .map(|&(points, color)| (points.map(|p| self.to_samples(p.into())), color))
+            .collect();
+
+        self.fill_in_bands(&triangles, worker_threads());
+    }
+
+    /// Fill `triangles`, already in sample coordinates, in `count` bands of rows filled on separate threads.
+    fn fill_in_bands(&mut self, triangles: &[([Point; 3], C)], count: usize)
+    where
+        C: Send + Sync,
+    {
+        if self.data.is_empty() {
+            return;
+        }
+        let row = self.width * self.supersample;
+        let rows = self.height * self.supersample;
+        // wrapping triangles can cover any row, so bands wouldn't save any work
+        let count = if self.wrap_y != WrapMode::Ignore {
+            1
+        } else {
+            count
+        };
+        let band_len = rows.div_ceil(count.clamp(1, rows)) * row;
+
+        // move the buffers out, so the settings can be shared between threads while the bands are written
+        let mut data = mem::take(&mut self.data);
+        let mut zbuf = mem::take(&mut self.zbuf);
+        let mut stencil = self.stencil.take();
+        {
+            let this = &*self;
+            let mut stencil_bands = stencil.as_mut().map(|s| s.chunks_mut(band_len));
+            let mut bands: Vec<(usize, Samples<'_, C>)> = data
+                .chunks_mut(band_len)
+                .zip(zbuf.chunks_mut(band_len))
+                .enumerate()
+                .map(|(i, (data, zbuf))| {
+                    let stencil = stencil_bands.as_mut().and_then(|bands| bands.next());
+                    (
+                        i * band_len,
+                        Samples {
+                            data,
+                            zbuf,
+                            stencil,
+                        },
+                    )
+                })
+                .collect();
+
+            if bands.len() == 1 {
+                let (start, band) = bands.pop().unwrap();
+                this.fill_band(start, band, triangles);
+            } else {
+                thread::scope(|scope| {
+                    for (start, band) in bands {
+                        scope.spawn(move || this.fill_band(start, band, triangles));
+                    }
+                });
+            }
+        }
+        self.data = data;
+        self.zbuf = zbuf;
+        self.stencil = stencil;
+    }
+
+    /// Fill `triangles`, in pixel coordinates, one `size` by `size` tile of samples at a time, on `threads` threads.
+    /// See [`TileRenderer`].
+    ///
+    /// [`TileRenderer`]: ../tile/struct.TileRenderer.html
+    pub(crate) fn fill_tiled(&mut self, triangles: &[([Point; 3], C)], size: usize, threads: usize)
+    where
+        C: Send + Sync,
+    {
+        if self.data.is_empty() {
+            return;
+        }
+        let triangles: Vec<([Point; 3], C)> = triangles
+            .iter()
+            .map(|&(points, color)| (points.map(|p| self.to_samples(p)), color))
+            .collect();
+        let (row, rows) = (
+            self.width * self.supersample,
+            self.height * self.supersample,
+        );
+        let size = size.max(1);
+        let tiles_x = row.div_ceil(size);
+
+        // bin the triangles into the tiles they might cover, keeping their order
+        let mut bins = vec![Vec::new(); tiles_x * rows.div_ceil(size)];
+        for (i, (points, _)) in triangles.iter().enumerate() {
+            if let Some((columns, rows)) = self.stored_bounds(points) {
+                for ty in rows.start / size..rows.end.div_ceil(size) {
+                    for tx in columns.start / size..columns.end.div_ceil(size) {
+                        bins[ty * tiles_x + tx].push(i);
+                    }
+                }
+            }
+        }
+        let tiles = Tiles {
+            size,
+            columns: tiles_x,
+            bins,
+            triangles,
+        };
+
+        // move the buffers out, so the settings can be shared between threads while the tiles are written
+        let mut data = mem::take(&mut self.data);
+        let mut zbuf = mem::take(&mut self.zbuf);
+        let mut stencil = self.stencil.take();
+        {
+            let this = &*self;
+            let mut stencil_rows = stencil.as_mut().map(|s| s.chunks_mut(size * row));
+            let mut tile_rows = data
+                .chunks_mut(size * row)
+                .zip(zbuf.chunks_mut(size * row))
+                .enumerate()
+                .map(|(ty, (data, zbuf))| {
+                    let stencil = stencil_rows.as_mut().and_then(|rows| rows.next());
+                    (
+                        ty,
+                        Samples {
+                            data,
+                            zbuf,
+                            stencil,
+                        },
+                    )
+                });
+
+            let threads = threads.clamp(1, rows.div_ceil(size));
+            if threads == 1 {
+                for (ty, samples) in tile_rows {
+                    this.fill_tile_row(ty, samples, &tiles);
+                }
+            } else {
+                // threads take the next row of tiles when they are done, so they all finish around the same time
+                let queue = Mutex::new(&mut tile_rows);
+                thread::scope(|scope| {
+                    for _ in 0..threads {
+                        scope.spawn(|| loop {
+                            let next = queue.lock().unwrap().next();
+                            match next {
+                                Some((ty, samples)) => this.fill_tile_row(ty, samples, &tiles),
+                                None => break,
+                            }
+                        });
+                    }
+                });
+            }
+        }
+        self.data = data;
+        self.zbuf = zbuf;
+        self.stencil = stencil;
+    }
+
+    /// Fill the tiles in row `ty`, which has the samples `samples`. Every tile is copied to a small buffer which fits
+    /// in the cache, filled, and copied back.
+    fn fill_tile_row(&self, ty: usize, mut samples: Samples<'_, C>, tiles: &Tiles<C>) {
+        let row = self.width * self.supersample;
+        let height = samples.data.len() / row;
+        let first_row = ty * tiles.size;
+        let rows = self.plot_rows(first_row, first_row + height);
+        let n = self.supersample as i64;
+        let (left, bottom) = (self.corner().0 * n, self.corner().1 * n);
+        let total_height = (self.height * self.supersample) as i64;
+        let wraps = self.wrap_x != WrapMode::Ignore || self.wrap_y != WrapMode::Ignore;
+
+        let (mut data, mut zbuf) = (Vec::new(), Vec::new());
+        let mut stencil = samples.stencil.as_ref().map(|_| Vec::new());
+        for tx in 0..tiles.columns {
+            let bin = &tiles.bins[ty * tiles.columns + tx];
+            if bin.is_empty() {
+                continue;
+            }
+            let (first_column, last_column) = (tx * tiles.size, ((tx + 1) * tiles.size).min(row));
+            let width = last_column - first_column;
+            let tile_row = |r: usize| r * row + first_column..r * row + last_column;
+
+            data.clear();
+            zbuf.clear();
+            for r in 0..height {
+                data.extend_from_slice(&samples.data[tile_row(r)]);
+                zbuf.extend_from_slice(&samples.zbuf[tile_row(r)]);
+            }
+            if let (Some(tile), Some(all)) = (&mut stencil, &samples.stencil) {
+                tile.clear();
+                for r in 0..height {
+                    tile.extend_from_slice(&all[tile_row(r)]);
+                }
+            }
+
+            let mut tile = Samples {
+                data: &mut data,
+                zbuf: &mut zbuf,
+                stencil: stencil.as_deref_mut(),
+            };
+            // without wrapping, the rectangle given to the rasterizer can be cut to the clip rectangle, so every
+            // sample plotted is in the tile and can go there directly
+            let (mut columns, mut rows) =
+                (self.plot_columns(first_column, last_column), rows.clone());
+            if let (Some((x0, y0, x1, y1)), false) = (self.clip, wraps) {
+                columns = columns.start.max(x0 * n)..columns.end.min(x1 * n);
+                rows = rows.start.max(y0 * n)..rows.end.min(y1 * n);
+            }
+            let local = |x: i64, y: i64| {
+                let (r, c) = if wraps {
+                    let index = self.sample_index(x, y)?;
+                    (index / row, index % row)
+                } else {
+                    let y = y - bottom;
+                    let r = if self.origin.y_up() {
+                        total_height - 1 - y
+                    } else {
+                        y
+                    };
+                    (r as usize, (x - left) as usize)
+                };
+                let inside = (first_row..first_row + height).contains(&r)
+                    && (first_column..last_column).contains(&c);
+                inside.then(|| (r - first_row) * width + c - first_column)
+            };
+
+            for &i in bin {
+                let ([p0, p1, p2], color) = tiles.triangles[i];
+                let mut plot = |x, y, z| {
+                    if let Some(index) = local(x, y) {
+                        tile.write(
+                            index,
+                            z,
+                            color,
+                            self.depth_test,
+                            self.depth_epsilon,
+                            self.stencil_mode,
+                        );
+                    }
+                };
+                self.rasterizer.triangle_in_rect(
+                    p0,
+                    p1,
+                    p2,
+                    columns.clone(),
+                    rows.clone(),
+                    &mut plot,
+                );
+            }
+
+            for r in 0..height {
+                let range = r * width..(r + 1) * width;
+                samples.data[tile_row(r)].copy_from_slice(&data[range.clone()]);
+                samples.zbuf[tile_row(r)].copy_from_slice(&zbuf[range.clone()]);
+                if let (Some(tile), Some(all)) = (&stencil, &mut samples.stencil) {
+                    all[tile_row(r)].copy_from_slice(&tile[range]);
+                }
+            }
+        }
+    }
+
+    /// The stored columns and rows of samples the triangle `points`, in sample coordinates, might cover, or `None` if
+    /// it's outside the image.
+    fn stored_bounds(&self, points: &[Point; 3]) -> Option<(Range<usize>, Range<usize>)> {
+        let n = self.supersample as i64;
+        let (width, height) = (
+            (self.width * self.supersample) as i64,
+            (self.height * self.supersample) as i64,
+        );
+        let (left, bottom) = self.corner();
+        let min = |f: fn(&Point) -> Float| {
+            points
+                .iter()
+                .map(f)
+                .fold(Float::INFINITY, Float::min)
+                .ceil() as i64
+        };
+        let max = |f: fn(&Point) -> Float| {
+            points
+                .iter()
+                .map(f)
+                .fold(Float::NEG_INFINITY, Float::max)
+                .floor() as i64
+        };
+
+        let columns = if self.wrap_x != WrapMode::Ignore {
+            0..width
+        } else {
+            (min(|p| p.x) - left * n).max(0)..(max(|p| p.x) - left * n + 1).min(width)
+        };
+        let (low, high) = (min(|p| p.y) - bottom * n, max(|p| p.y) - bottom * n);
+        let rows = if self.wrap_y != WrapMode::Ignore {
+            0..height
+        } else if self.origin.y_up() {
+            (height - 1 - high).max(0)..(height - low).min(height)
+        } else {
+            low.max(0)..(high + 1).min(height)
+        };
+
+        if columns.is_empty() || rows.is_empty() {
+            return None;
+        }
+        Some((
+            columns.start as usize..columns.end as usize,
+            rows.start as usize..rows.end as usize,
+        ))
+    }
+
+    /// A builder for a `width` by `height` image with the same settings and comments as this one, except
+    /// supersampling.
+    fn builder_like(&self, height: usize, width: usize) -> PpmBuilder<C> {
+        let mut builder = PpmBuilder::with_color_type(height, width, self.color_depth)
+            .wrap_x(self.wrap_x)
+            .wrap_y(self.wrap_y)
+            .origin(self.origin)
+            .rasterizer(self.rasterizer)
+            .gamma(self.gamma)
+            .depth_test(self.depth_test)
+            .depth_epsilon(self.depth_epsilon);
+        builder.comments = self.comments.clone();
+        builder
+    }
+
+    /// A blank image with the same size and settings as this one, including supersampling. Used as a back buffer.
+    #[cfg(feature = "window")]
+    pub(crate) fn blank_like(&self) -> Ppm<C> {
+        self.builder_like(self.height, self.width)
+            .supersample(self.supersample)
+            .build()
+    }
+
+    /// A function mapping an index in [`data`] to the coordinates of its pixel.
+    ///
+    /// [`data`]: #structfield.data
+    fn coords(&self) -> impl Fn(usize) -> (i64, i64) {
+        let n = self.supersample;
+        let (row, rows) = (self.width * n, self.height * n);
+        let y_up = self.origin.y_up();
+        let (left, bottom) = self.corner();
+        move |i| {
+            let (x, y) = (i % row, i / row);
+            let y = if y_up { rows - y - 1 } else { y };
+            ((x / n) as i64 + left, (y / n) as i64 + bottom)
+        }
+    }
+
+    /// The smallest pixel coordinates on the image: `(0, 0)`, unless the [`origin`] is in the center.
+    ///
+    /// [`origin`]: #structfield.origin
+    fn corner(&self) -> (i64, i64) {
+        self.origin.corner(self.width, self.height)
+    }
+
+    /// [`Screen::clip_bounds`] in sample coordinates.
+    ///
+    /// [`Screen::clip_bounds`]: ../screen/trait.Screen.html#method.clip_bounds
+    fn sample_bounds(&self) -> (Range<i64>, Range<i64>) {
+        let n = self.supersample as i64;
+        let scale = |r: Range<i64>| r.start.saturating_mul(n)..r.end.saturating_mul(n);
+        let (columns, rows) = self.clip_bounds();
+        (scale(columns), scale(rows))
+    }
+
+    /// Image data, row by row, with supersamples averaged into pixels. See [`data`] for the layout.
+    ///
+    /// [`data`]: #structfield.data
+    pub(crate) fn data(&self) -> Cow<'_, [C]> {
+        let n = self.supersample;
+        if n == 1 {
+            return Cow::Borrowed(&self.data);
+        }
+
+        // box filter every n by n block: sum the rows of samples of a block first, then the columns, so the loops
+        // go over contiguous memory instead of jumping between rows for every pixel. u32 sums can't overflow: a
+        // channel is 16 bits at most, and there are fewer than 2^16 samples in a block.
+        let row = self.width * n;
+        if row == 0 {
+            return Cow::Owned(Vec::new());
+        }
+        let count = (n * n) as u32;
+        let mut columns = vec![[0u32; 4]; row];
+        let mut pixels = Vec::with_capacity(self.width * self.height);
+        for block_row in self.data.chunks_exact(row * n) {
+            columns.fill([0; 4]);
+            for samples in block_row.chunks_exact(row) {
+                for (sum, &color) in columns.iter_mut().zip(samples) {
+                    let [r, g, b] = color.channels();
+                    *sum = [
+                        sum[0] + r as u32,
+                        sum[1] + g as u32,
+                        sum[2] + b as u32,
+                        sum[3] + color.alpha() as u32,
+                    ];
+                }
+            }
+            pixels.extend(columns.chunks_exact(n).map(|block| {
+                let [r, g, b, a] = block
+                    .iter()
+                    .fold([0; 4], |[r, g, b, a], sum| {
+                        [r + sum[0], g + sum[1], b + sum[2], a + sum[3]]
+                    })
+                    .map(|sum| (sum + count / 2) / count);
+                C::from_channels([r as u16, g as u16, b as u16]).with_alpha(a as u8)
+            }));
+        }
+        Cow::Owned(pixels)
+    }
+
+    /// Image data as written to files: [`data`], encoded with [`gamma`].
+    ///
+    /// [`data`]: #method.data
+    /// [`gamma`]: #structfield.gamma
+    pub(crate) fn output_data(&self) -> Cow<'_, [C]> {
+        let data = self.data();
+        if self.gamma == Gamma::Linear {
+            return data;
+        }
+        let max = self.color_depth;
+        data.iter()
+            .map(|c| {
+                C::from_channels(self.gamma.encode_channels(c.channels(), max))
+                    .with_alpha(c.alpha())
+            })
+            .collect()
+    }
+
+    /// Map a point in pixel coordinates to the center of its block of samples.
+    fn to_samples(&self, p: Point) -> Point {
+        let n = self.supersample as Float;
+        Point::new(p.x * n + (n - 1.) / 2., p.y * n + (n - 1.) / 2., p.z)
+    }
+
+    /// Plot a single sample, in sample coordinates.
+    fn plot_sample(&mut self, x: i64, y: i64, z: Float, color: C) {
+        if let Some(index) = self.sample_index(x, y) {
+            let mut samples = Samples {
+                data: &mut self.data,
+                zbuf: &mut self.zbuf,
+                stencil: self.stencil.as_deref_mut(),
+            };
+            samples.write(
+                index,
+                z,
+                color,
+                self.depth_test,
+                self.depth_epsilon,
+                self.stencil_mode,
+            );
+        }
+    }
+
+    /// The index of a sample, in sample coordinates, or `None` if it's outside the image or the clip rectangle.
+    fn sample_index(&self, x: i64, y: i64) -> Option<usize> {
+        if let Some((x0, y0, x1, y1)) = self.clip {
+            let n = self.supersample as i64;
+            let (x, y) = (x.div_euclid(n), y.div_euclid(n));
+            if x < x0 || x >= x1 || y < y0 || y >= y1 {
+                return None;
+            }
+        }
+        self.index(x, y)
+    }
+
+    /// Fill `triangles`, already in sample coordinates, where they cover `band`, which starts at sample `start`.
+    fn fill_band(&self, start: usize, mut band: Samples<'_, C>, triangles: &[([Point; 3], C)]) {
+        let row = self.width * self.supersample;
+        let rows = self.plot_rows(start / row, (start + band.data.len()) / row);
+        let columns = self.plot_columns(0, row);
+        for &([p0, p1, p2], color) in triangles {
+            let mut plot = |x, y, z| {
+                let index = self.sample_index(x, y).and_then(|i| i.checked_sub(start));
+                if let Some(index) = index.filter(|&i| i < band.data.len()) {
+                    band.write(
+                        index,
+                        z,
+                        color,
+                        self.depth_test,
+                        self.depth_epsilon,
+                        self.stencil_mode,
+                    );
+                }
+            };
+            self.rasterizer
+                .triangle_in_rect(p0, p1, p2, columns.clone(), rows.clone(), &mut plot);
+        }
+    }
+
+    /// The y values of samples stored in rows `first..last`, counted from the top, or all of them if they wrap around.
+    fn plot_rows(&self, first: usize, last: usize) -> Range<i64> {
+        let height = (self.height * self.supersample) as i64;
+        let (first, last) = (first as i64, last as i64);
+        let bottom = self.corner().1 * self.supersample as i64;
+        if self.wrap_y != WrapMode::Ignore {
+            i64::MIN..i64::MAX
+        } else if self.origin.y_up() {
+            height - last + bottom..height - first + bottom
+        } else {
+            first + bottom..last + bottom
+        }
+    }
+
+    /// The x values of samples stored in columns `first..last`, or all of them if they wrap around.
+    fn plot_columns(&self, first: usize, last: usize) -> Range<i64> {
+        let left = self.corner().0 * self.supersample as i64;
+        if self.wrap_x != WrapMode::Ignore {
+            i64::MIN..i64::MAX
+        } else {
+            first as i64 + left..last as i64 + left
+        }
+    }
+
+    /// Returns `Some(index)` if `index` is in the bounds of `self.data`. Otherwise `None`.
+    ///
+    /// `x` and `y` are sample coordinates, which are the same as pixel coordinates unless [`supersample`] is on.
+    ///
+    /// `None` is useful because you might want to ignore points that are outside of the visible space. Note that there's no `null` in Rust,
+    /// so this requires you to handle all cases.
     ///
     /// This method is used in the `plot` function for [`Screen` impl].
     ///
-    /// [`Screen` impl]: #impl-Screen
-    fn index(&self, x: i64, y: i64) -> Option<usize> {
-        let (width, height) = (
-            i64::try_from(self.width).unwrap(),
-            i64::try_from(self.height).unwrap(),
-        );
+    /// [`Screen` impl]: #impl-Screen
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        let (width, height) = (
+            i64::try_from(self.width * self.supersample).unwrap(),
+            i64::try_from(self.height * self.supersample).unwrap(),
+        );
+
+        // move the origin to a corner, in samples
+        let (left, bottom) = self.corner();
+        let n = self.supersample as i64;
+        let (x, y) = (x - left * n, y - bottom * n);
+
+        let x = self.wrap_x.apply(x, width)?;
+        let y = self.wrap_y.apply(y, height)?;
+
+        // rows are stored from the top
+        let y = if self.origin.y_up() {
+            height - y - 1
+        } else {
+            y
+        };
+
+        // now we know that x and y are positive, we can cast without worry
+        usize::try_from(y * width + x).ok()
+    }
+
+    /// Where the pixel at (`x`, `y`) is in files: its column, and its row from the top.
+    pub(crate) fn pixel_position(&self, x: i64, y: i64) -> Option<(usize, usize)> {
+        let n = self.supersample;
+        let i = self.index(x * n as i64, y * n as i64)?;
+        let row = self.width * n;
+        Some((i % row / n, i / row / n))
+    }
+
+    /// Write ppm in binary format to the given `writer` without buffering.
+    ///
+    /// This is useful for writing image data to memory or if the `writer` is already buffered.
+    ///
+    /// The whole file is laid out in memory first, and handed to `writer` with a single `write_all`. The buffer it's laid
+    /// out in is kept with the image and reused by the next write, so writing the same image over and over, like every
+    /// frame of an animation, doesn't allocate.
+    pub fn write_binary_nobuffer<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        // another thread writing this image at the same time gets a buffer of its own
+        let (mut scratch, mut own) = (self.scratch.try_lock(), vec![]);
+        let bytes = match &mut scratch {
+            Ok(scratch) => &mut **scratch,
+            Err(_) => &mut own,
+        };
+        bytes.clear();
+        bytes.reserve(self.binary_len());
+        writeln!(bytes, "P6")?;
+        self.write_comments(bytes)?;
+        writeln!(bytes, "{} {} {}", self.width, self.height, self.color_depth)?;
+
+        let data = self.output_data();
+        if self.color_depth < 256 {
+            for t in data.iter() {
+                let [r, g, b] = t.channels();
+                bytes.extend_from_slice(&[r as u8, g as u8, b as u8]);
+            }
+        } else {
+            for t in data.iter() {
+                // content is in big endian, per ppm spec
+                let [r, g, b] = t.channels();
+                let ([r0, r1], [g0, g1], [b0, b1]) =
+                    (r.to_be_bytes(), g.to_be_bytes(), b.to_be_bytes());
+                bytes.extend_from_slice(&[r0, r1, g0, g1, b0, b1]);
+            }
+        }
+        writer.write_all(bytes)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Size of the binary ppm body, plus some room for the header.
+    fn binary_len(&self) -> usize {
+        let sample_size = if self.color_depth < 256 { 1 } else { 2 };
+        let header: usize = self.comments.iter().map(|c| c.len() + 3).sum::<usize>() + 32;
+        header + self.width * self.height * 3 * sample_size
+    }
+
+    /// Write ppm in binary format to the given `writer`.
+    ///
+    /// The `writer` will be buffered before being written to.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use graphics::prelude::*;
+    /// let mut img = Ppm::new();
+    ///     
+    /// let xmax = img.width() as f64;
+    /// let ymax = img.height() as f64;
+    /// let color = Rgb::WHITE;
+    ///
+    /// img.draw_line((xmax - 1., ymax - 1., 0.), (xmax / 2., 0., 0.), color);
+    ///
+    /// img.write_binary_to_buf(&mut File::create("binary.ppm").expect("error creating binary.ppm"))
+    ///     .expect("error writing to file");
+    /// ```
+    ///
+    /// Or, if you use `?` to handle errors:
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use std::io;
+    /// use graphics::prelude::*;
+    ///
+    /// fn main() -> io::Result<()> { // note that the return type has changed
+    ///     let mut img = Ppm::new();
+    ///     
+    ///     let xmax = img.width() as f64;
+    ///     let ymax = img.height() as f64;
+    ///     let color = Rgb::WHITE;
+    ///
+    ///     img.draw_line((xmax - 1., ymax - 1., 0.), (xmax / 2., 0., 0.), color);
+    ///     
+    ///     img.write_binary_to_buf(&mut File::create("binary.ppm")?)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn write_binary_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        self.write_binary_nobuffer(&mut BufWriter::new(writer))
+    }
 
-        if (!self.wrap_x && (x < 0 || x >= width)) || (!self.wrap_y && (y < 0 || y >= height)) {
-            // wrapping is disabled AND (x or y is out of bounds)
-            return None;
+    /// Write ppm in ascii format to the given `writer`.
+    ///
+    /// The `writer` will be buffered before being written to.
+    ///
+    /// See [`write_binary_to_buf`] for examples.
+    ///
+    /// [`write_binary_to_buf`]: #method.write_binary_to_buf
+    pub fn write_ascii_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        writeln!(buf, "P3")?;
+        self.write_comments(&mut buf)?;
+        writeln!(buf, "{} {} {}", self.width, self.height, self.color_depth)?;
+        let data = self.output_data();
+        write_ascii_samples(&mut buf, data.iter().flat_map(|t| t.channels()))?;
+        buf.flush()?;
+        Ok(())
+    }
+
+    /// Write the image as a grayscale pgm in binary format (P5), with the luminance of every pixel.
+    ///
+    /// The `writer` will be buffered before being written to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let img = PpmBuilder::new(1, 1, 255).bg_color(Rgb::new(255, 0, 0)).build();
+    /// let mut buf = vec![];
+    /// img.write_pgm_binary_to_buf(&mut buf).unwrap();
+    ///
+    /// assert_eq!(b"P5\n1 1 255\n\x4c", &buf[..]);
+    /// ```
+    pub fn write_pgm_binary_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        writeln!(buf, "P5")?;
+        self.write_comments(&mut buf)?;
+        writeln!(buf, "{} {} {}", self.width, self.height, self.color_depth)?;
+        for t in self.output_data().iter() {
+            let gray = luminance(t.channels());
+            if self.color_depth < 256 {
+                buf.write_all(&[gray as u8])?;
+            } else {
+                buf.write_all(&gray.to_be_bytes())?;
+            }
+        }
+        buf.flush()?;
+        Ok(())
+    }
+
+    /// Write the image as a grayscale pgm in ascii format (P2). See [`write_pgm_binary_to_buf`].
+    ///
+    /// [`write_pgm_binary_to_buf`]: #method.write_pgm_binary_to_buf
+    pub fn write_pgm_ascii_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        writeln!(buf, "P2")?;
+        self.write_comments(&mut buf)?;
+        writeln!(buf, "{} {} {}", self.width, self.height, self.color_depth)?;
+        let data = self.output_data();
+        write_ascii_samples(&mut buf, data.iter().map(|t| luminance(t.channels())))?;
+        buf.flush()?;
+        Ok(())
+    }
+
+    /// Write the image as a black and white pbm bitmap in binary format (P4). Pixels with a luminance below half the
+    /// color depth are black, the others white.
+    ///
+    /// To keep some shading, [dither] the image to black and white first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = PpmBuilder::new(2, 2, 255).bg_color(Rgb::WHITE).build();
+    /// // top left
+    /// img.plot(0, 1, 0., Rgb::gray(100));
+    /// let mut buf = vec![];
+    /// img.write_pbm_binary_to_buf(&mut buf).unwrap();
+    ///
+    /// // 1 is black, and every row is padded to a whole byte
+    /// assert_eq!(b"P4\n2 2\n\x80\x00", &buf[..]);
+    /// ```
+    ///
+    /// [dither]: ../dither/fn.to_palette.html
+    pub fn write_pbm_binary_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        writeln!(buf, "P4")?;
+        self.write_comments(&mut buf)?;
+        writeln!(buf, "{} {}", self.width, self.height)?;
+        for row in self.output_data().chunks(self.width.max(1)) {
+            for byte in row.chunks(8) {
+                let bits = byte
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, t)| self.is_black(**t))
+                    .fold(0u8, |bits, (i, _)| bits | (0x80 >> i));
+                buf.write_all(&[bits])?;
+            }
         }
+        buf.flush()?;
+        Ok(())
+    }
+
+    /// Write the image as a black and white pbm bitmap in ascii format (P1). See [`write_pbm_binary_to_buf`].
+    ///
+    /// [`write_pbm_binary_to_buf`]: #method.write_pbm_binary_to_buf
+    pub fn write_pbm_ascii_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        writeln!(buf, "P1")?;
+        self.write_comments(&mut buf)?;
+        writeln!(buf, "{} {}", self.width, self.height)?;
+        let data = self.output_data();
+        write_ascii_samples(&mut buf, data.iter().map(|t| self.is_black(*t) as u16))?;
+        buf.flush()?;
+        Ok(())
+    }
 
-        let x = wrap_index(x, width);
-        let y = wrap_index(y, height);
+    /// Whether `color` is written as black in a pbm.
+    fn is_black(&self, color: C) -> bool {
+        (luminance(color.channels()) as u32) * 2 < self.color_depth as u32
+    }
 
-        // invert y based on config
-        let y = if self.invert_y {
-            self.width as i64 - y - 1
+    /// Write the image as a [pam] file (P7), with an alpha channel if the color type has one, like [`Rgba`].
+    ///
+    /// The `writer` will be buffered before being written to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{color::Rgba, prelude::*};
+    ///
+    /// let mut layer = PpmBuilder::<Rgba>::with_color_type(2, 2, 255)
+    ///     .bg_color(Rgba::TRANSPARENT)
+    ///     .build();
+    /// layer.plot(0, 0, 0., Rgba::new(255, 0, 0, 128));
+    ///
+    /// let mut buf = vec![];
+    /// layer.write_pam_to_buf(&mut buf).unwrap();
+    ///
+    /// let header = "P7\nWIDTH 2\nHEIGHT 2\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n";
+    /// assert!(buf.starts_with(header.as_bytes()));
+    /// // bottom left is the first pixel of the last row
+    /// assert_eq!(&[255, 0, 0, 128], &buf[header.len() + 8..header.len() + 12]);
+    /// ```
+    ///
+    /// [pam]: http://netpbm.sourceforge.net/doc/pam.html
+    /// [`Rgba`]: ../color/struct.Rgba.html
+    pub fn write_pam_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        let (depth, tuple_type) = if C::HAS_ALPHA {
+            (4, "RGB_ALPHA")
         } else {
-            y
+            (3, "RGB")
         };
+        writeln!(buf, "P7")?;
+        self.write_comments(&mut buf)?;
+        writeln!(buf, "WIDTH {}", self.width)?;
+        writeln!(buf, "HEIGHT {}", self.height)?;
+        writeln!(buf, "DEPTH {}", depth)?;
+        writeln!(buf, "MAXVAL {}", self.color_depth)?;
+        writeln!(buf, "TUPLTYPE {}", tuple_type)?;
+        writeln!(buf, "ENDHDR")?;
 
-        // now we know that x and y are positive, we can cast without worry
-        usize::try_from(y * self.width as i64 + x).ok()
+        let max = self.color_depth as u32;
+        for t in self.output_data().iter() {
+            let [r, g, b] = t.channels();
+            let alpha = ((t.alpha() as u32 * max + 127) / 255) as u16;
+            let samples = [r, g, b, alpha];
+            for sample in samples[..depth].iter() {
+                if self.color_depth < 256 {
+                    buf.write_all(&[*sample as u8])?;
+                } else {
+                    buf.write_all(&sample.to_be_bytes())?;
+                }
+            }
+        }
+        buf.flush()?;
+        Ok(())
     }
 
-    /// Write ppm in binary format to the given `writer` without buffering.
+    /// Write the image as an uncompressed 24 bit [BMP] file, without needing ImageMagick.
     ///
-    /// This is useful for writing image data to memory or if the `writer` is already buffered.
-    pub fn write_binary_nobuffer<T: Write>(&self, writer: &mut T) -> io::Result<()> {
-        writeln!(writer, "P6")?;
-        writeln!(
-            writer,
-            "{} {} {}",
-            self.width, self.height, self.color_depth
-        )?;
-        if self.color_depth < 256 {
-            for t in self.data.iter() {
-                writer.write_all(&[t.red, t.green, t.blue])?;
+    /// Channels are scaled to 8 bits, and transparency is dropped. The `writer` will be buffered before being written to.
+    ///
+    /// [BMP]: https://en.wikipedia.org/wiki/BMP_file_format
+    pub fn write_bmp_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        bmp::write_bmp(self, &mut buf)?;
+        buf.flush()
+    }
+
+    /// Save the image as a BMP file at `path`, without needing ImageMagick. See [`write_bmp_to_buf`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    /// img.draw_line((0., 0., 0.), (499., 499., 0.), Rgb::WHITE);
+    /// img.save_bmp("line.bmp").expect("error saving line.bmp");
+    /// ```
+    ///
+    /// [`write_bmp_to_buf`]: #method.write_bmp_to_buf
+    pub fn save_bmp<P: AsRef<Path>>(&self, path: P) -> error::Result<()> {
+        Ok(self.write_bmp_to_buf(&mut File::create(path)?)?)
+    }
+
+    /// Write the image as a 24 bit [TGA] file (32 bit if the color type has an alpha channel, like [`Rgba`]), without
+    /// needing ImageMagick. With `rle`, runs of identical pixels are compressed.
+    ///
+    /// Channels are scaled to 8 bits. The `writer` will be buffered before being written to.
+    ///
+    /// [TGA]: https://en.wikipedia.org/wiki/Truevision_TGA
+    /// [`Rgba`]: ../color/struct.Rgba.html
+    pub fn write_tga_to_buf<T: Write>(&self, writer: &mut T, rle: bool) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        tga::write_tga(self, &mut buf, rle)?;
+        buf.flush()
+    }
+
+    /// Save the image as a run-length encoded TGA file at `path`, without needing ImageMagick. See
+    /// [`write_tga_to_buf`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    /// img.draw_line((0., 0., 0.), (499., 499., 0.), Rgb::WHITE);
+    /// img.save_tga("line.tga").expect("error saving line.tga");
+    /// ```
+    ///
+    /// [`write_tga_to_buf`]: #method.write_tga_to_buf
+    pub fn save_tga<P: AsRef<Path>>(&self, path: P) -> error::Result<()> {
+        Ok(self.write_tga_to_buf(&mut File::create(path)?, true)?)
+    }
+
+    /// Write the image as an 8 bit [PNG] file (with an alpha channel if the color type has one, like [`Rgba`]), without
+    /// needing ImageMagick.
+    ///
+    /// Channels are scaled to 8 bits. The `writer` will be buffered before being written to.
+    ///
+    /// [PNG]: http://www.libpng.org/pub/png/spec/1.2/PNG-Contents.html
+    /// [`Rgba`]: ../color/struct.Rgba.html
+    pub fn write_png_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        png::write_png(self, &mut buf)?;
+        buf.flush()
+    }
+
+    /// Save the image as a PNG file at `path`, without needing ImageMagick. See [`write_png_to_buf`].
+    ///
+    /// [`save`] already does this for paths ending in `.png`.
+    ///
+    /// [`write_png_to_buf`]: #method.write_png_to_buf
+    /// [`save`]: ../screen/trait.Screen.html#tymethod.save
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> error::Result<()> {
+        Ok(self.write_png_to_buf(&mut File::create(path)?)?)
+    }
+
+    /// Write the image as a [QOI] file (with an alpha channel if the color type has one, like [`Rgba`]), without
+    /// needing ImageMagick.
+    ///
+    /// Channels are scaled to 8 bits. The `writer` will be buffered before being written to.
+    ///
+    /// # Examples
+    ///
+    /// Store animation frames compactly, and load them back:
+    ///
+    /// ```no_run
+    /// use graphics::prelude::*;
+    ///
+    /// for frame in 0..100 {
+    ///     let mut img = Ppm::new();
+    ///     img.draw_circle(250., 250., 0., frame as f64 * 2., Rgb::WHITE);
+    ///     img.save(&format!("frame{:03}.qoi", frame)).expect("error saving frame");
+    /// }
+    ///
+    /// let first: Ppm = Ppm::open("frame000.qoi").expect("error loading frame");
+    /// ```
+    ///
+    /// [QOI]: https://qoiformat.org/qoi-specification.pdf
+    /// [`Rgba`]: ../color/struct.Rgba.html
+    pub fn write_qoi_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        qoi::write_qoi(self, &mut buf)?;
+        buf.flush()
+    }
+
+    /// Save the image as a QOI file at `path`, without needing ImageMagick. See [`write_qoi_to_buf`].
+    ///
+    /// [`save`] already does this for paths ending in `.qoi`.
+    ///
+    /// [`write_qoi_to_buf`]: #method.write_qoi_to_buf
+    /// [`save`]: ../screen/trait.Screen.html#tymethod.save
+    pub fn save_qoi<P: AsRef<Path>>(&self, path: P) -> error::Result<()> {
+        Ok(self.write_qoi_to_buf(&mut File::create(path)?)?)
+    }
+
+    /// Display the image with ImageMagick like [`display`], but without waiting for the window to be closed.
+    ///
+    /// # Examples
+    ///
+    /// Look at several images at once:
+    ///
+    /// ```no_run
+    /// use graphics::prelude::*;
+    ///
+    /// let mut windows = Vec::new();
+    /// for radius in &[50., 100., 150.] {
+    ///     let mut img = Ppm::new();
+    ///     img.draw_circle(250., 250., 0., *radius, Rgb::WHITE);
+    ///     windows.push(img.display_async().expect("error displaying image"));
+    /// }
+    /// for window in windows {
+    ///     window.wait().expect("error displaying image");
+    /// }
+    /// ```
+    ///
+    /// [`display`]: ../screen/trait.Screen.html#tymethod.display
+    #[cfg(feature = "magick")]
+    pub fn display_async(&self) -> error::Result<magick::DisplayHandle> {
+        magick::display_ppm_async(self)
+    }
+
+    /// Write the image as text for terminals with 24-bit color, two pixels per character: the upper one as the
+    /// foreground color of `▀`, and the lower one as its background color.
+    ///
+    /// Each pixel becomes one column, so use [`print_to_terminal`] to shrink the image to fit first.
+    ///
+    /// [`print_to_terminal`]: #method.print_to_terminal
+    pub fn write_ansi_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        let pixels = self.to_rgba8();
+        let rows: Vec<_> = pixels.chunks(self.width).collect();
+        for pair in rows.chunks(2) {
+            for (x, [r, g, b, _]) in pair[0].iter().enumerate() {
+                write!(buf, "\x1b[38;2;{};{};{}m", r, g, b)?;
+                match pair.get(1) {
+                    Some(lower) => {
+                        let [r, g, b, _] = lower[x];
+                        write!(buf, "\x1b[48;2;{};{};{}m▀", r, g, b)?;
+                    }
+                    // the last row of an odd height image, over the terminal's background
+                    None => write!(buf, "\x1b[49m▀")?,
+                }
             }
+            writeln!(buf, "\x1b[0m")?;
+        }
+        buf.flush()
+    }
+
+    /// Print the image to stdout with [`write_ansi_to_buf`], to look at it over SSH or in CI logs without an image
+    /// viewer.
+    ///
+    /// Images wider than the terminal are shrunk to fit, using the width in the `COLUMNS` environment variable, or
+    /// `80` columns if it isn't set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    /// img.draw_line((0., 0., 0.), (499., 499., 0.), Rgb::WHITE);
+    /// img.print_to_terminal().expect("error printing image");
+    /// ```
+    ///
+    /// [`write_ansi_to_buf`]: #method.write_ansi_to_buf
+    pub fn print_to_terminal(&self) -> io::Result<()> {
+        let columns = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|c| c.parse::<usize>().ok())
+            .filter(|&c| c > 0)
+            .unwrap_or(80);
+        let stdout = io::stdout();
+        if self.width > columns {
+            let height = (self.height * columns / self.width).max(1);
+            self.resized(columns, height, Filter::Bilinear)
+                .write_ansi_to_buf(&mut stdout.lock())
         } else {
-            for t in self.data.iter() {
-                // content is in big endian, per ppm spec
-                writer.write_all(&(t.red.to_be_bytes()))?;
-                writer.write_all(&(t.green.to_be_bytes()))?;
-                writer.write_all(&(t.blue.to_be_bytes()))?;
+            self.write_ansi_to_buf(&mut stdout.lock())
+        }
+    }
+
+    /// The pixels as RGBA bytes, row by row from the top left, with channels scaled to 8 bits. Colors without alpha are
+    /// opaque.
+    ///
+    /// This is the layout of a JavaScript canvas `ImageData`, so it's the way to show images in a web page from
+    /// WebAssembly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = PpmBuilder::new(2, 2, 255).build();
+    /// img.plot(0, 1, 0., Rgb::new(255, 128, 0));
+    ///
+    /// let bytes = img.to_rgba_bytes();
+    /// assert_eq!(2 * 2 * 4, bytes.len());
+    /// // y goes up, so (0, 1) is the top left
+    /// assert_eq!(&[255, 128, 0, 255], &bytes[..4]);
+    /// ```
+    ///
+    /// With `wasm-bindgen` and `web-sys`, the bytes can be drawn on a canvas:
+    ///
+    /// ```ignore
+    /// let bytes = img.to_rgba_bytes();
+    /// let data = ImageData::new_with_u8_clamped_array(Clamped(&bytes), img.width() as u32)?;
+    /// context.put_image_data(&data, 0., 0.)?;
+    /// ```
+    pub fn to_rgba_bytes(&self) -> Vec<u8> {
+        self.to_rgba8().into_iter().flatten().collect()
+    }
+
+    /// Pixels as written to files, from the top left, with channels and alpha scaled to 8 bits. Used by the encoders of
+    /// other formats.
+    pub(crate) fn to_rgba8(&self) -> Vec<[u8; 4]> {
+        let max = self.color_depth.max(1) as u32;
+        let scale = |c: u16| ((c as u32).min(max) * 255 / max) as u8;
+        self.output_data()
+            .iter()
+            .map(|t| {
+                let [r, g, b] = t.channels();
+                [scale(r), scale(g), scale(b), t.alpha()]
+            })
+            .collect()
+    }
+
+    /// Load an image file, picking the format from the extension:
+    ///
+    /// - `.qoi` is read with [`from_qoi_reader`],
+    /// - `.ppm`, `.pnm`, or no extension is read with [`from_reader`], as ascii (P3) or binary (P6) ppm,
+    /// - anything else, like `.png` or `.jpg`, is converted to ppm by ImageMagick first, so it needs to be installed.
+    ///
+    /// [`from_reader`]: #method.from_reader
+    /// [`from_qoi_reader`]: #method.from_qoi_reader
+    pub fn open<P: AsRef<Path>>(path: P) -> error::Result<Self> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("qoi") => Self::from_qoi_reader(BufReader::new(File::open(path)?)),
+            None | Some("ppm") | Some("pnm") => {
+                Self::from_reader(BufReader::new(File::open(path)?))
             }
+            _ => Self::from_reader(&magick::read_as_ppm(path)?[..]),
         }
-        writer.flush()?;
-        Ok(())
     }
 
-    /// Write ppm in binary format to the given `writer`.
+    /// Load a PNG or JPEG file with the [`image`] crate, to use as a texture or background, without ImageMagick. The
+    /// image is built with default settings, with the top row of the file at the top, and a color depth of `255`.
     ///
-    /// The `writer` will be buffered before being written to.
+    /// Fails with [`GraphicsError::Io`] if the file can't be read, and [`GraphicsError::Parse`] if it can't be decoded.
+    ///
+    /// This is only available with the `image` feature.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use std::fs::File;
     /// use graphics::prelude::*;
-    /// let mut img = Ppm::new();
-    ///     
-    /// let xmax = img.width() as f64;
-    /// let ymax = img.height() as f64;
-    /// let color = Rgb::WHITE;
-    ///
-    /// img.draw_line((xmax - 1., ymax - 1., 0.), (xmax / 2., 0., 0.), color);
     ///
-    /// img.write_binary_to_buf(&mut File::create("binary.ppm").expect("error creating binary.ppm"))
-    ///     .expect("error writing to file");
+    /// let photo: Ppm = Ppm::load_texture("photo.jpg").expect("error reading photo.jpg");
+    /// let mut img = Ppm::new();
+    /// img.draw_image(&photo, 0, 0, 0., None);
     /// ```
     ///
-    /// Or, if you use `?` to handle errors:
+    /// [`image`]: https://crates.io/crates/image
+    /// [`GraphicsError::Io`]: ../error/enum.GraphicsError.html#variant.Io
+    /// [`GraphicsError::Parse`]: ../error/enum.GraphicsError.html#variant.Parse
+    #[cfg(feature = "image")]
+    pub fn load_texture<P: AsRef<Path>>(path: P) -> error::Result<Self> {
+        crate::image_interop::load_texture(path.as_ref())
+    }
+
+    /// Decode a [QOI] image. Like with [`from_reader`], the image is built with default settings, with the first row
+    /// in the file at the top. The color depth is `255`.
     ///
-    /// ```no_run
-    /// use std::fs::File;
-    /// use std::io;
-    /// use graphics::prelude::*;
+    /// [QOI]: https://qoiformat.org/qoi-specification.pdf
+    /// [`from_reader`]: #method.from_reader
+    pub fn from_qoi_reader<R: Read>(mut reader: R) -> error::Result<Self> {
+        qoi::read_qoi(&mut reader)
+    }
+
+    /// Parse a ppm image, in ascii (P3) or binary (P6) format, with comments anywhere in the header.
     ///
-    /// fn main() -> io::Result<()> { // note that the return type has changed
-    ///     let mut img = Ppm::new();
-    ///     
-    ///     let xmax = img.width() as f64;
-    ///     let ymax = img.height() as f64;
-    ///     let color = Rgb::WHITE;
+    /// The image is built with default settings, and a color depth of the file's maxval. Pixels are placed so that the
+    /// image looks the same as the file: the first row in the file is the top row. Channels are read as they are, so
+    /// files with a maxval above `255` should be read into a [`Ppm<Rgb16>`], or they get clamped.
     ///
-    ///     img.draw_line((xmax - 1., ymax - 1., 0.), (xmax / 2., 0., 0.), color);
-    ///     
-    ///     img.write_binary_to_buf(&mut File::create("binary.ppm")?)?;
+    /// # Examples
     ///
-    ///     Ok(())
-    /// }
     /// ```
-    pub fn write_binary_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
-        self.write_binary_nobuffer(&mut BufWriter::new(writer))
-    }
-
-    /// Write ppm in ascii format to the given `writer`.
+    /// use graphics::prelude::*;
     ///
-    /// The `writer` will be buffered before being written to.
+    /// let file = b"P3\n# a comment\n2 2\n255\n255 0 0   0 0 255\n0 0 0   0 0 0\n";
+    /// let img = Ppm::<Rgb>::from_reader(&file[..]).unwrap();
     ///
-    /// See [`write_binary_to_buf`] for examples.
+    /// assert_eq!(2, img.width());
+    /// // the first row is the top one
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get(0, 1));
+    /// ```
     ///
-    /// [`write_binary_to_buf`]: #method.write_binary_to_buf
-    pub fn write_ascii_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
-        let mut buf = BufWriter::new(writer);
-        writeln!(buf, "P3")?;
-        writeln!(buf, "{} {} {}", self.width, self.height, self.color_depth)?;
-        for t in self.data.iter() {
-            writeln!(buf, "{} {} {}", t.red, t.green, t.blue)?;
+    /// [`Ppm<Rgb16>`]: ../color/struct.Rgb16.html
+    pub fn from_reader<R: Read>(mut reader: R) -> error::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        let mut parser = PnmParser::new(&bytes);
+
+        let binary = match parser.token()? {
+            b"P6" => true,
+            b"P3" => false,
+            _ => return Err(GraphicsError::parse("not a ppm file")),
+        };
+        let width = parser.number()?;
+        let height = parser.number()?;
+        let maxval = parser.number()?;
+        if maxval == 0 || maxval > u16::MAX as usize {
+            return Err(GraphicsError::parse("invalid maxval"));
+        }
+        let samples = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or_else(|| GraphicsError::parse("image too large"))?;
+
+        let channels = if binary {
+            parser.raster(samples, maxval > 255)?
+        } else {
+            (0..samples)
+                .map(|_| parser.number())
+                .collect::<error::Result<Vec<_>>>()?
+        };
+        if channels.iter().any(|&c| c > maxval) {
+            return Err(GraphicsError::parse("channel larger than maxval"));
+        }
+        let data = channels
+            .chunks(3)
+            .map(|c| C::from_channels([c[0] as u16, c[1] as u16, c[2] as u16]))
+            .collect();
+
+        let mut img = PpmBuilder::with_color_type(height, width, maxval as u16)
+            .with_data(data)
+            .try_build()?;
+        img.comments = parser.comments;
+        Ok(img)
+    }
+}
+
+/// Longest line allowed in ascii netpbm files.
+const ASCII_LINE_LENGTH: usize = 70;
+
+/// Write `samples` as decimal numbers separated by spaces, starting a new line before going over
+/// `ASCII_LINE_LENGTH` characters. Text is built in a buffer and written in large chunks.
+fn write_ascii_samples<T: Write>(
+    writer: &mut T,
+    samples: impl Iterator<Item = u16>,
+) -> io::Result<()> {
+    const CHUNK: usize = 1 << 16;
+    let mut out = Vec::with_capacity(CHUNK + ASCII_LINE_LENGTH);
+    let mut line_length = 0;
+    let mut digits = [0u8; 5];
+    for mut sample in samples {
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (sample % 10) as u8;
+            sample /= 10;
+            if sample == 0 {
+                break;
+            }
+        }
+        let token = &digits[start..];
+
+        if line_length > 0 {
+            if line_length + 1 + token.len() > ASCII_LINE_LENGTH {
+                out.push(b'\n');
+                line_length = 0;
+            } else {
+                out.push(b' ');
+                line_length += 1;
+            }
+        }
+        out.extend_from_slice(token);
+        line_length += token.len();
+
+        if out.len() >= CHUNK {
+            writer.write_all(&out)?;
+            out.clear();
+        }
+    }
+    out.push(b'\n');
+    writer.write_all(&out)
+}
+
+/// Rec. 601 luma of red, green and blue channels, rounded.
+fn luminance([red, green, blue]: [u16; 3]) -> u16 {
+    (0.299 * red as f64 + 0.587 * green as f64 + 0.114 * blue as f64).round() as u16
+}
+
+/// Reads the parts of a netpbm file.
+struct PnmParser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    /// Comments found so far, without the `#` and the space after it.
+    comments: Vec<String>,
+}
+
+impl<'a> PnmParser<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        PnmParser {
+            bytes,
+            position: 0,
+            comments: vec![],
         }
-        buf.flush()?;
-        Ok(())
     }
+
+    /// Next whitespace separated token, skipping comments (from `#` to the end of the line).
+    fn token(&mut self) -> error::Result<&'a [u8]> {
+        loop {
+            match self.bytes.get(self.position) {
+                Some(b'#') => {
+                    let start = self.position + 1;
+                    while !matches!(
+                        self.bytes.get(self.position),
+                        Some(b'\n') | Some(b'\r') | None
+                    ) {
+                        self.position += 1;
+                    }
+                    let comment = String::from_utf8_lossy(&self.bytes[start..self.position]);
+                    let comment = comment.strip_prefix(' ').unwrap_or(&comment);
+                    self.comments.push(comment.to_string());
+                }
+                Some(c) if c.is_ascii_whitespace() => self.position += 1,
+                Some(_) => break,
+                None => return Err(GraphicsError::parse("unexpected end of file")),
+            }
+        }
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|c| !c.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+        Ok(&self.bytes[start..self.position])
+    }
+
+    /// Next token, as a decimal number.
+    fn number(&mut self) -> error::Result<usize> {
+        let token = self.token()?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|t| t.parse().ok())
+            .ok_or_else(|| GraphicsError::parse("expected a number"))
+    }
+
+    /// `samples` binary samples, of 1 byte, or 2 big endian bytes if `wide`. Comes right after the single whitespace
+    /// character ending the header.
+    fn raster(&mut self, samples: usize, wide: bool) -> error::Result<Vec<usize>> {
+        let start = self.position + 1;
+        let size = if wide { 2 } else { 1 };
+        let raster = samples
+            .checked_mul(size)
+            .and_then(|len| start.checked_add(len))
+            .and_then(|end| self.bytes.get(start..end))
+            .ok_or_else(|| GraphicsError::parse("unexpected end of file"))?;
+        self.position = start + raster.len();
+        Ok(if wide {
+            raster
+                .chunks(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]) as usize)
+                .collect()
+        } else {
+            raster.iter().map(|&c| c as usize).collect()
+        })
+    }
+}
+
+/// Average of `colors`, rounded to the closest color. Used to box filter supersamples into pixels.
+fn average<C: PpmColor>(colors: impl Iterator<Item = C>) -> C {
+    let (mut sums, mut alpha, mut count) = ([0u64; 3], 0u64, 0);
+    for color in colors {
+        for (sum, channel) in sums.iter_mut().zip(color.channels().iter()) {
+            *sum += *channel as u64;
+        }
+        alpha += color.alpha() as u64;
+        count += 1;
+    }
+    let count = count.max(1);
+    C::from_channels([
+        ((sums[0] + count / 2) / count) as u16,
+        ((sums[1] + count / 2) / count) as u16,
+        ((sums[2] + count / 2) / count) as u16,
+    ])
+    .with_alpha(((alpha + count / 2) / count) as u8)
+}
+
+/// Triangles binned into the tiles of a [`TileRenderer`].
+///
+/// [`TileRenderer`]: ../tile/struct.TileRenderer.html
+struct Tiles<C> {
+    /// Width and height of a tile, in samples.
+    size: usize,
+    /// Number of tiles in a row.
+    columns: usize,
+    /// Indices of the triangles that might cover each tile, in order, row by row from the top left.
+    bins: Vec<Vec<usize>>,
+    /// Triangles in sample coordinates, with their colors.
+    triangles: Vec<([Point; 3], C)>,
+}
+
+/// How many threads to fill on: one per core with the `parallel` feature, otherwise just the current thread.
+pub(crate) fn worker_threads() -> usize {
+    #[cfg(feature = "parallel")]
+    return thread::available_parallelism().map_or(1, |n| n.get());
+    #[cfg(not(feature = "parallel"))]
+    1
 }
 
-/// Wraps an `index` to be an i64 in [0, index). Used in [`Ppm`]'s [`index`] method.
+/// Some of the samples of a [`Ppm`], with their depth and stencil values: all of them, a band of rows, or a tile.
+/// Filling in parallel gives every thread its own.
 ///
 /// [`Ppm`]: ./struct.Ppm.html
-/// [`index`]: ./struct.Ppm.html#method.index
+struct Samples<'a, C> {
+    data: &'a mut [C],
+    zbuf: &'a mut [Float],
+    stencil: Option<&'a mut [u8]>,
+}
+
+impl<C: Copy> Samples<'_, C> {
+    /// Draw the sample at `index`, if it passes the stencil and depth tests.
+    fn write(
+        &mut self,
+        index: usize,
+        z: Float,
+        color: C,
+        depth_test: DepthTest,
+        epsilon: Float,
+        (stencil_test, on_pass, on_fail): (StencilTest, StencilOp, StencilOp),
+    ) {
+        if let Some(stencil) = &mut self.stencil {
+            if !stencil_test.passes(stencil[index]) {
+                stencil[index] = on_fail.apply(stencil[index]);
+                return;
+            }
+        }
+        if depth_test.passes(z, self.zbuf[index], epsilon) {
+            self.data[index] = color;
+            self.zbuf[index] = z;
+            if let Some(stencil) = &mut self.stencil {
+                stencil[index] = on_pass.apply(stencil[index]);
+            }
+        }
+    }
+}
+
+/// Wraps an `index` to be an i64 in [0, index). Used by [`WrapMode::Wrap`].
+///
+/// [`WrapMode::Wrap`]: ./enum.WrapMode.html#variant.Wrap
 fn wrap_index(value: i64, limit: i64) -> i64 {
     ((value % limit) + limit) % limit
 }
 
-impl Screen for Ppm {
+impl<C: PpmColor> Screen<C> for Ppm<C> {
     /// Plot a point on this PPMImg at (`x`, `y`, `z`).
     ///
     /// `z` is used for depth-buffer. Will only plot if `z` if `z` > existing `z` in buffer.
-    fn plot(&mut self, x: i64, y: i64, z: f64, color: Rgb) {
-        if let Some(index) = self.index(x, y) {
-            if self.zbuf[index] < z {
-                self.data[index] = color;
-                self.zbuf[index] = z;
+    ///
+    /// With [`supersample`] on, this plots the whole block of samples making up the pixel.
+    ///
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    fn plot(&mut self, x: i64, y: i64, z: Float, color: C) {
+        let n = self.supersample as i64;
+        for sy in y * n..(y + 1) * n {
+            for sx in x * n..(x + 1) * n {
+                self.plot_sample(sx, sy, z, color);
             }
         }
     }
 
-    fn save(&self, file_path: &str) -> io::Result<()> {
-        let mut cmd = magick::pipe_to_magick(&vec!["ppm:-", file_path])?;
-
-        // This command should have a stdnin, so it's ok to unwrap
-        let mut stdin = cmd.stdin.take().unwrap();
-        self.write_binary_to_buf(&mut stdin)?;
+    /// Overwrite the pixel, see [`set`].
+    ///
+    /// [`set`]: #method.set
+    fn set_pixel(&mut self, x: i64, y: i64, color: C) {
+        self.set(x, y, color);
+    }
 
-        drop(stdin);
+    /// Draw a line, see [`Screen::draw_line`].
+    ///
+    /// With [`supersample`] on, the line is rasterized at the sample resolution, as a 1 pixel wide quad.
+    ///
+    /// [`Screen::draw_line`]: ../screen/trait.Screen.html#method.draw_line
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    fn draw_line<P0: Into<Point>, P1: Into<Point>>(&mut self, p0: P0, p1: P1, color: C) {
+        let rasterizer = self.rasterizer;
+        if self.supersample == 1 {
+            rasterizer.line(p0.into(), p1.into(), &mut |x, y, z| {
+                self.plot(x, y, z, color)
+            });
+            return;
+        }
 
-        let status = cmd.wait()?;
-        if status.success() {
-            Ok(())
+        let (p0, p1) = (self.to_samples(p0.into()), self.to_samples(p1.into()));
+        let direction = Vec3::new(p1.x - p0.x, p1.y - p0.y, 0.).normalize();
+        let direction = if direction == Vec3::ZERO {
+            Vec3::new(1., 0., 0.)
         } else {
-            Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Magick exited with non-zero status",
-            ))
+            direction
+        };
+        // half a pixel on every side, so the end pixels are fully covered like they are without supersampling
+        let half = self.supersample as Float / 2.;
+        let (along, across) = (
+            direction * half,
+            Vec3::new(-direction.y, direction.x, 0.) * half,
+        );
+        let (p0, p1) = (p0 - along, p1 + along);
+
+        let (a, b, c, d) = (p0 + across, p1 + across, p1 - across, p0 - across);
+        let (columns, rows) = self.sample_bounds();
+        let mut plot = |x, y, z| self.plot_sample(x, y, z, color);
+        rasterizer.triangle_in_rect(a, b, c, columns.clone(), rows.clone(), &mut plot);
+        rasterizer.triangle_in_rect(a, c, d, columns, rows, &mut plot);
+    }
+
+    /// Fill a triangle, see [`Screen::fill_triangle`].
+    ///
+    /// With [`supersample`] on, the triangle is rasterized at the sample resolution.
+    ///
+    /// [`Screen::fill_triangle`]: ../screen/trait.Screen.html#method.fill_triangle
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    fn fill_triangle<P0, P1, P2>(&mut self, p0: P0, p1: P1, p2: P2, color: C)
+    where
+        P0: Into<Point>,
+        P1: Into<Point>,
+        P2: Into<Point>,
+    {
+        let (p0, p1, p2) = (
+            self.to_samples(p0.into()),
+            self.to_samples(p1.into()),
+            self.to_samples(p2.into()),
+        );
+        let (rasterizer, (columns, rows)) = (self.rasterizer, self.sample_bounds());
+        rasterizer.triangle_in_rect(p0, p1, p2, columns, rows, &mut |x, y, z| {
+            self.plot_sample(x, y, z, color)
+        });
+    }
+
+    /// Save the image to `file_path`, in the format given by its extension.
+    ///
+    /// PNG, QOI, BMP, TGA and PPM files are written directly. Other formats are converted by ImageMagick, which has to be
+    /// installed.
+    fn save(&self, file_path: &str) -> error::Result<()> {
+        let extension = Path::new(file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("png") => return self.save_png(file_path),
+            Some("qoi") => return self.save_qoi(file_path),
+            Some("bmp") => return self.save_bmp(file_path),
+            Some("tga") => return self.save_tga(file_path),
+            Some("ppm") => return Ok(self.write_binary_to_buf(&mut File::create(file_path)?)?),
+            _ => (),
         }
+
+        let mut ppm = Vec::with_capacity(self.binary_len());
+        self.write_binary_nobuffer(&mut ppm)?;
+        magick::convert_with_input(&["ppm:-", file_path], &ppm)
+    }
+
+    /// Resize with [`resized`] and a bilinear filter, then save like [`save`]. Only the quality and compression
+    /// options need ImageMagick.
+    ///
+    /// [`resized`]: #method.resized
+    /// [`save`]: #method.save
+    fn save_with(&self, file_path: &str, options: &SaveOptions) -> error::Result<()> {
+        if let Some((width, height)) = options.resize {
+            let options = SaveOptions {
+                resize: None,
+                ..*options
+            };
+            return self
+                .resized(width, height, Filter::Bilinear)
+                .save_with(file_path, &options);
+        }
+        if options.quality.is_none() && options.compression.is_none() {
+            return self.save(file_path);
+        }
+
+        let mut ppm = Vec::with_capacity(self.binary_len());
+        self.write_binary_nobuffer(&mut ppm)?;
+        options.convert("ppm:-", file_path, &ppm)
     }
 
     fn write_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
         self.write_binary_nobuffer(writer)
     }
 
-    fn display(&self) -> io::Result<()> {
+    fn rasterizer(&self) -> &'static dyn Rasterizer {
+        self.rasterizer
+    }
+
+    /// The pixels on the image, in the coordinates of its [`origin`], inside the clip rectangle set with [`set_clip`].
+    /// Unbounded along the axes that wrap around.
+    ///
+    /// [`origin`]: #structfield.origin
+    /// [`set_clip`]: #method.set_clip
+    fn clip_bounds(&self) -> (Range<i64>, Range<i64>) {
+        let (left, bottom) = self.corner();
+        let (mut columns, mut rows) = (
+            left..left + self.width as i64,
+            bottom..bottom + self.height as i64,
+        );
+        if self.wrap_x != WrapMode::Ignore {
+            columns = i64::MIN..i64::MAX;
+        }
+        if self.wrap_y != WrapMode::Ignore {
+            rows = i64::MIN..i64::MAX;
+        }
+        if let Some((x0, y0, x1, y1)) = self.clip {
+            columns = columns.start.max(x0)..columns.end.min(x1);
+            rows = rows.start.max(y0)..rows.end.min(y1);
+        }
+        (columns, rows)
+    }
+
+    fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    fn display(&self) -> error::Result<()> {
         magick::display_ppm(self)
     }
 
-    fn clear(&mut self, color: Rgb) {
-        for d in self.data.iter_mut() {
-            *d = color;
+    fn clear(&mut self, color: C) {
+        self.data.fill(color);
+        self.clear_zbuf_only();
+    }
+
+    fn get_pixel(&self, x: i64, y: i64) -> Option<C> {
+        let n = self.supersample as i64;
+        if n == 1 {
+            return self.index(x, y).map(|i| self.data[i]);
+        }
+
+        let mut block = Vec::with_capacity((n * n) as usize);
+        for sy in y * n..(y + 1) * n {
+            for sx in x * n..(x + 1) * n {
+                block.push(self.data[self.index(sx, sy)?]);
+            }
+        }
+        Some(average(block.into_iter()))
+    }
+
+    /// With [`supersample`] on, this is the depth of the closest sample in the pixel.
+    ///
+    /// [`supersample`]: ./struct.PpmBuilder.html#method.supersample
+    fn get_depth(&self, x: i64, y: i64) -> Option<Float> {
+        let n = self.supersample as i64;
+        let mut depth = self.depth_test.far();
+        for sy in y * n..(y + 1) * n {
+            for sx in x * n..(x + 1) * n {
+                let z = self.zbuf[self.index(sx, sy)?];
+                depth = if self.depth_test.is_reversed() {
+                    depth.min(z)
+                } else {
+                    depth.max(z)
+                };
+            }
         }
-        self.zbuf = vec![f64::NEG_INFINITY; self.height * self.width];
+        Some(depth)
     }
 
     fn width(&self) -> usize {
@@ -414,6 +3469,8 @@ impl Screen for Ppm {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{color::Rgb16, random::Rng};
+    use proptest::prelude::*;
 
     fn old_wrap_index(value: i64, limit: i64) -> i64 {
         if value >= limit {
@@ -430,7 +3487,116 @@ mod tests {
         }
     }
     #[test]
-    #[ignore]
+    fn test_supersample() {
+        let mut img = PpmBuilder::new(10, 10, 255).supersample(4).build();
+        img.plot(1, 1, 0., Rgb::WHITE);
+        assert_eq!(Some(Rgb::WHITE), img.get_pixel(1, 1));
+
+        // a shallow line covers some pixels only partially
+        img.draw_line((0., 5., 0.), (9., 7., 0.), Rgb::WHITE);
+        let data = img.data();
+        assert_eq!(100, data.len());
+        assert!(data.iter().any(|c| c.red > 0 && c.red < 255));
+
+        // averaging all blocks at once agrees with averaging pixels one by one
+        let mut rng = Rng::new(3);
+        for &(n, width, height) in [(2, 7, 5), (3, 4, 6), (4, 1, 1)].iter() {
+            let mut img = PpmBuilder::new(height, width, 255).supersample(n).build();
+            for sample in img.data.iter_mut() {
+                *sample = rng.color();
+            }
+            let pixels: Vec<_> = (0..height as i64)
+                .rev()
+                .flat_map(|y| (0..width as i64).map(move |x| (x, y)))
+                .map(|(x, y)| img.get_pixel(x, y).unwrap())
+                .collect();
+            assert_eq!(pixels, img.data().into_owned());
+        }
+    }
+
+    #[test]
+    fn test_composite() {
+        let mut rng = Rng::new(5);
+        let mut layer = PpmBuilder::new(6, 9, 255).build();
+        for pixel in layer.data.iter_mut() {
+            *pixel = rng.color();
+        }
+        let modes = [
+            BlendMode::Normal,
+            BlendMode::Add,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+        ];
+        for &origin in [Origin::TopLeft, Origin::BottomLeft, Origin::Center].iter() {
+            for &supersample in [1, 2].iter() {
+                for &(x, y) in [(0, 0), (-3, 2), (4, -4), (12, 1), (-20, 0)].iter() {
+                    for &mode in modes.iter() {
+                        let build = || {
+                            let mut img = PpmBuilder::new(10, 14, 100)
+                                .origin(origin)
+                                .supersample(supersample)
+                                .build();
+                            img.fill_triangle(
+                                (-9., -9., 0.),
+                                (14., 0., 0.),
+                                (0., 9., 0.),
+                                Rgb::new(90, 20, 60),
+                            );
+                            img
+                        };
+                        let mut img = build();
+
+                        // blend pixel by pixel
+                        let mut expected = build();
+                        for ly in 0..6 {
+                            for lx in 0..9 {
+                                let (px, py) = (x + lx, y + ly);
+                                if let Some(under) = img.get(px, py) {
+                                    let top = layer.get(lx, ly).unwrap().channels();
+                                    let mut channels = under.channels();
+                                    for (channel, top) in channels.iter_mut().zip(top.iter()) {
+                                        let blended =
+                                            mode.apply(*channel as f64 / 100., *top as f64 / 255.);
+                                        *channel = (blended * 100.).round() as u16;
+                                    }
+                                    expected.set(px, py, Rgb::from_channels(channels));
+                                }
+                            }
+                        }
+                        img.composite(&layer, x, y, mode);
+                        assert_eq!(
+                            expected.data(),
+                            img.data(),
+                            "{:?} {} {:?} {:?}",
+                            origin,
+                            supersample,
+                            (x, y),
+                            mode
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_16_bit() {
+        let mut img = PpmBuilder::<Rgb16>::with_color_type(1, 2, 65535)
+            .invert_y(false)
+            .build();
+        img.plot(1, 0, 0., Rgb16::new(0x1234, 0xabcd, 0xffff));
+        let mut buf = vec![];
+        img.write_binary_nobuffer(&mut buf).unwrap();
+
+        let header = b"P6\n2 1 65535\n";
+        assert_eq!(&header[..], &buf[..header.len()]);
+        assert_eq!(
+            &[0, 0, 0, 0, 0, 0, 0x12, 0x34, 0xab, 0xcd, 0xff, 0xff][..],
+            &buf[header.len()..]
+        );
+    }
+
+    #[test]
     fn test_wrap_index() {
         let cases = vec![
             (1, 2),
@@ -445,4 +3611,353 @@ mod tests {
             assert_eq!(old_wrap_index(value, limit), wrap_index(value, limit))
         }
     }
+
+    /// A limit, and a value that is usually within a few limits of it, but can be anything.
+    fn value_and_limit() -> impl Strategy<Value = (i64, i64)> {
+        (1i64..1 << 20).prop_flat_map(|limit| {
+            (
+                prop_oneof![-3 * limit..3 * limit, any::<i64>()],
+                Just(limit),
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_wrap_index_properties((value, limit) in value_and_limit()) {
+            let wrapped = wrap_index(value, limit);
+            prop_assert!((0..limit).contains(&wrapped));
+            prop_assert_eq!(value.rem_euclid(limit), wrapped);
+            prop_assert_eq!(old_wrap_index(value, limit), wrapped);
+        }
+
+        #[test]
+        fn test_wrap_mode_properties(limit in 1i64..1000, value in -5000i64..5000) {
+            for &mode in [WrapMode::Wrap, WrapMode::Clamp, WrapMode::Mirror].iter() {
+                let mapped = mode.apply(value, limit).unwrap();
+                prop_assert!((0..limit).contains(&mapped), "{:?}", mode);
+                if (0..limit).contains(&value) {
+                    prop_assert_eq!(value, mapped);
+                }
+            }
+            prop_assert_eq!(
+                WrapMode::Mirror.apply(value, limit),
+                WrapMode::Mirror.apply(-1 - value, limit)
+            );
+            prop_assert_eq!(
+                WrapMode::Mirror.apply(value, limit),
+                WrapMode::Mirror.apply(value + 2 * limit, limit)
+            );
+            let clamped = WrapMode::Clamp.apply(value, limit).unwrap();
+            prop_assert!(clamped == value || clamped == 0 || clamped == limit - 1);
+            prop_assert_eq!(
+                value < 0 || value >= limit,
+                WrapMode::Ignore.apply(value, limit).is_none()
+            );
+        }
+
+        #[test]
+        fn test_clip_properties(
+            (x, y) in (-20i64..80, -20i64..60),
+            (width, height) in (0usize..60, 0usize..60),
+            [p0, p1, p2] in prop::array::uniform3((-50f64..130., -50f64..110.)),
+        ) {
+            let mut img = PpmBuilder::new(60, 80, 255).build();
+            img.set_clip(x, y, width, height);
+
+            let (p0, p1, p2) = ((p0.0, p0.1, 0.), (p1.0, p1.1, 0.), (p2.0, p2.1, 0.));
+            img.draw_line(p0, p1, Rgb::WHITE);
+            img.fill_triangle(p0, p1, p2, Rgb::WHITE);
+            img.draw_circle(p2.0, p2.1, 0., 30., Rgb::WHITE);
+
+            for (px, py, &color) in img.enumerate_pixels() {
+                if color != Rgb::BLACK {
+                    prop_assert!(px >= x && py >= y, "({}, {}) outside the clip", px, py);
+                    prop_assert!(px < x + width as i64 && py < y + height as i64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_crop_out_of_bounds() {
+        let mut img = PpmBuilder::new(10, 20, 255).origin(Origin::Center).build();
+        let (left, bottom) = img.corner();
+        img.set(left, bottom, Rgb::WHITE);
+
+        // partly outside: clipped to the image
+        let cropped = img.crop(left - 5, bottom - 5, 8, 8).unwrap();
+        assert_eq!((3, 3), (cropped.width(), cropped.height()));
+        assert_eq!(
+            Some(Rgb::WHITE),
+            cropped.get(cropped.corner().0, cropped.corner().1)
+        );
+        let cropped = img
+            .crop(left - 5, bottom + 8, usize::MAX, usize::MAX)
+            .unwrap();
+        assert_eq!((20, 2), (cropped.width(), cropped.height()));
+        let cropped = img.crop(-1000, -1000, usize::MAX, usize::MAX).unwrap();
+        assert_eq!((20, 10), (cropped.width(), cropped.height()));
+
+        // fully outside, or empty
+        for &(x, y, width, height) in [
+            (left - 10, bottom, 10, 10),
+            (left + 20, bottom, 10, 10),
+            (left, bottom - 100, 10, 10),
+            (left, bottom + 10, 10, 10),
+            (left, bottom, 0, 10),
+            (i64::MAX, i64::MAX, usize::MAX, usize::MAX),
+        ]
+        .iter()
+        {
+            assert!(img.crop(x, y, width, height).is_err(), "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn test_huge_clip() {
+        let mut img = PpmBuilder::new(10, 10, 255).build();
+        let size = i64::MAX as usize;
+        img.set_clip(5, 5, usize::MAX, usize::MAX);
+        assert_eq!(Some((5, 5, size - 5, size - 5)), img.clip());
+        img.clear(Rgb::BLACK);
+        img.fill_rect((0., 0., 0.), 10., 10., Rgb::WHITE);
+        assert_eq!(Some(Rgb::WHITE), img.get_pixel(9, 9));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(4, 4));
+
+        img.set_clip(-5, i64::MIN, usize::MAX, usize::MAX);
+        assert_eq!(Some((-5, i64::MIN, size, size)), img.clip());
+        img.set_clip(-5, -5, usize::MAX, usize::MAX);
+        img.fill_rect((0., 0., 0.), 10., 10., Rgb::WHITE);
+        assert_eq!(Some(Rgb::WHITE), img.get_pixel(4, 4));
+    }
+
+    #[test]
+    fn test_fill_triangles_matches_fill_triangle() {
+        let mut rng = Rng::new(6);
+        let settings: [fn() -> PpmBuilder; 4] = [
+            || PpmBuilder::new(37, 50, 255),
+            || PpmBuilder::new(37, 50, 255).supersample(2),
+            || {
+                PpmBuilder::new(37, 50, 255)
+                    .origin(Origin::Center)
+                    .wrap_y(WrapMode::Wrap)
+            },
+            || {
+                PpmBuilder::new(37, 50, 255)
+                    .origin(Origin::TopLeft)
+                    .depth_test(DepthTest::Less)
+            },
+        ];
+        for builder in settings.iter() {
+            let triangles: Vec<_> = (0..50)
+                .map(|_| {
+                    let mut point = || {
+                        (
+                            rng.range(-30., 80.),
+                            rng.range(-30., 70.),
+                            rng.range(-1., 1.),
+                        )
+                    };
+                    ([point(), point(), point()], rng.color())
+                })
+                .collect();
+
+            let mut one_by_one = builder().build();
+            let mut all = builder().build();
+            for img in [&mut one_by_one, &mut all].iter_mut() {
+                img.set_clip(-5, 3, 40, 30);
+                img.set_stencil(
+                    StencilTest::NotEqual(2),
+                    StencilOp::Increment,
+                    StencilOp::Keep,
+                );
+            }
+            for &([p0, p1, p2], color) in triangles.iter() {
+                one_by_one.fill_triangle(p0, p1, p2, color);
+            }
+            all.fill_triangles(&triangles);
+            let mut in_bands = builder().build();
+            in_bands.set_clip(-5, 3, 40, 30);
+            in_bands.set_stencil(
+                StencilTest::NotEqual(2),
+                StencilOp::Increment,
+                StencilOp::Keep,
+            );
+            let in_samples: Vec<_> = triangles
+                .iter()
+                .map(|&(points, color)| (points.map(|p| in_bands.to_samples(p.into())), color))
+                .collect();
+            in_bands.fill_in_bands(&in_samples, 4);
+
+            assert!(one_by_one.pixels().eq(all.pixels()));
+            assert_eq!(one_by_one.zbuf, all.zbuf);
+            assert_eq!(one_by_one.stencil, all.stencil);
+            assert!(one_by_one.pixels().eq(in_bands.pixels()));
+            assert_eq!(one_by_one.zbuf, in_bands.zbuf);
+            assert_eq!(one_by_one.stencil, in_bands.stencil);
+            assert!(one_by_one.pixels().any(|&c| c != Rgb::BLACK));
+        }
+    }
+
+    #[test]
+    fn test_read_write_round_trip() {
+        let mut img = PpmBuilder::new(3, 3, 255).build();
+        img.plot(0, 0, 0., Rgb::new(1, 2, 3));
+        img.plot(2, 1, 0., Rgb::WHITE);
+
+        for &binary in [true, false].iter() {
+            let mut buf = vec![];
+            if binary {
+                img.write_binary_to_buf(&mut buf).unwrap();
+            } else {
+                img.write_ascii_to_buf(&mut buf).unwrap();
+            }
+            let read = Ppm::<Rgb>::from_reader(&buf[..]).unwrap();
+            assert_eq!(img.data(), read.data());
+        }
+
+        let mut wide = PpmBuilder::<Rgb16>::with_color_type(2, 2, 1000).build();
+        wide.plot(1, 1, 0., Rgb16::new(1000, 500, 1));
+        let mut buf = vec![];
+        wide.write_binary_to_buf(&mut buf).unwrap();
+        let read = Ppm::<Rgb16>::from_reader(&buf[..]).unwrap();
+        assert_eq!(1000, read.color_depth());
+        assert_eq!(Some(Rgb16::new(1000, 500, 1)), read.get(1, 1));
+
+        assert!(Ppm::<Rgb>::from_reader(&b"P6 2 2 255\n\0\0"[..]).is_err());
+        assert!(Ppm::<Rgb>::from_reader(&b"P3 1 1 255 0 0 256"[..]).is_err());
+    }
+
+    /// Headers for images too large to exist, where the size of the raster overflows a usize.
+    #[test]
+    fn test_read_huge_header() {
+        let headers: [&[u8]; 4] = [
+            b"P6 4611686018427387904 1 65535\n\0\0",
+            b"P6 4611686018427387904 1 255\n\0\0",
+            b"P6 18446744073709551615 18446744073709551615 255\n",
+            b"P3 4611686018427387904 1 65535\n0 0 0",
+        ];
+        for header in headers.iter() {
+            assert!(Ppm::<Rgb16>::from_reader(*header).is_err());
+        }
+    }
+
+    #[test]
+    fn test_write_reuses_buffer() {
+        let mut img = PpmBuilder::new(20, 30, 255).build();
+        let (mut first, mut second) = (vec![], vec![]);
+        img.write_binary_nobuffer(&mut first).unwrap();
+        let buffer = img.scratch.lock().unwrap().as_ptr();
+
+        img.plot(3, 4, 0., Rgb::WHITE);
+        img.write_binary_nobuffer(&mut second).unwrap();
+        assert_eq!(buffer, img.scratch.lock().unwrap().as_ptr());
+        assert_eq!(first.len(), second.len());
+        assert_ne!(first, second);
+        let read = Ppm::<Rgb>::from_reader(&second[..]).unwrap();
+        assert_eq!(img.data(), read.data());
+
+        // still works while the buffer is in use
+        let _lock = img.scratch.lock().unwrap();
+        let mut third = vec![];
+        img.write_binary_nobuffer(&mut third).unwrap();
+        assert_eq!(second, third);
+    }
+
+    #[test]
+    fn test_ascii_line_length() {
+        let img = PpmBuilder::new(20, 20, 255)
+            .bg_color(Rgb::new(255, 10, 0))
+            .build();
+        let mut buf = vec![];
+        img.write_ascii_to_buf(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+
+        assert!(text.lines().all(|line| line.len() <= 70));
+        let samples: Vec<&str> = text.split_whitespace().skip(4).collect();
+        assert_eq!(20 * 20 * 3, samples.len());
+        assert_eq!(&["255", "10", "0"], &samples[..3]);
+    }
+
+    #[test]
+    fn test_reversed_depth_test() {
+        let mut img = PpmBuilder::new(4, 4, 255)
+            .depth_test(DepthTest::Less)
+            .supersample(2)
+            .build();
+        assert_eq!(Some(Float::INFINITY), img.get_depth(1, 1));
+
+        img.plot(1, 1, 5., Rgb::WHITE);
+        img.plot(1, 1, 10., Rgb::new(255, 0, 0));
+        assert_eq!(Some(Rgb::WHITE), img.get(1, 1));
+        assert_eq!(Some(5.), img.get_depth(1, 1));
+
+        img.set_depth_test(DepthTest::Greater);
+        assert_eq!(Some(Float::NEG_INFINITY), img.get_depth(1, 1));
+        img.plot(1, 1, 10., Rgb::new(255, 0, 0));
+        img.plot(1, 1, 5., Rgb::WHITE);
+        assert_eq!(Some(Rgb::new(255, 0, 0)), img.get(1, 1));
+    }
+
+    #[test]
+    fn test_non_square_origin() {
+        for &origin in [Origin::TopLeft, Origin::BottomLeft, Origin::Center].iter() {
+            let mut img = PpmBuilder::new(3, 5, 255).origin(origin).build();
+            let (left, bottom) = img.corner();
+            img.plot(left + 4, bottom, 0., Rgb::WHITE);
+            assert_eq!(Some(Rgb::WHITE), img.get(left + 4, bottom));
+            assert_eq!(None, img.get(left + 5, bottom));
+            assert_eq!(None, img.get(left, bottom + 3));
+
+            let row = if origin.y_up() { 2 } else { 0 };
+            assert_eq!(Rgb::WHITE, img.rows().nth(row).unwrap()[4]);
+
+            let cropped = img.crop(left + 3, bottom, 2, 2).unwrap();
+            assert_eq!(
+                Some(Rgb::WHITE),
+                cropped.get(cropped.corner().0 + 1, cropped.corner().1)
+            );
+            assert_eq!(
+                Some((left + 4, bottom, &Rgb::WHITE)),
+                img.enumerate_pixels().find(|&(_, _, &c)| c == Rgb::WHITE)
+            );
+        }
+    }
+
+    #[test]
+    fn test_builder_from_image() {
+        let mut img = PpmBuilder::new(4, 6, 255).comment("base").build();
+        img.plot(5, 0, 0., Rgb::WHITE);
+
+        for name in ["graphics_from_image.ppm", "graphics_from_image.qoi"].iter() {
+            let path = std::env::temp_dir().join(name);
+            img.save(path.to_str().unwrap()).unwrap();
+            let loaded = PpmBuilder::from_image(&path).unwrap().build();
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!((6, 4), (loaded.width(), loaded.height()));
+            assert_eq!(img.data(), loaded.data());
+        }
+
+        let missing = std::env::temp_dir().join("graphics_missing.ppm");
+        assert!(PpmBuilder::<Rgb>::from_image(missing).is_err());
+    }
+
+    #[test]
+    fn test_write_ansi() {
+        // 2 pixels wide and 3 tall, with y going up
+        let mut img = PpmBuilder::new(3, 2, 255).build();
+        img.plot(0, 2, 0., Rgb::new(255, 0, 0));
+        img.plot(1, 0, 0., Rgb::WHITE);
+
+        let mut out = Vec::new();
+        img.write_ansi_to_buf(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(2, lines.len());
+        assert!(lines[0].starts_with("\x1b[38;2;255;0;0m\x1b[48;2;0;0;0m▀"));
+        assert!(lines[1].starts_with("\x1b[38;2;0;0;0m\x1b[49m▀"));
+        assert!(lines[1].ends_with("\x1b[38;2;255;255;255m\x1b[49m▀\x1b[0m"));
+    }
 }
@@ -0,0 +1,217 @@
+//! Dependency-free encoder and decoder for the [QOI] format.
+//!
+//! QOI is lossless like PNG, compresses rendered images almost as well, and is much simpler and faster, which makes it a
+//! good fit for storing lots of animation frames.
+//!
+//! Use it through [`Ppm::save_qoi`], [`Ppm::write_qoi_to_buf`], [`Ppm::open`] and [`Ppm::from_qoi_reader`], or just
+//! [`save`] a file with a `.qoi` extension.
+//!
+//! [QOI]: https://qoiformat.org/qoi-specification.pdf
+//! [`Ppm::save_qoi`]: ../ppm/struct.Ppm.html#method.save_qoi
+//! [`Ppm::write_qoi_to_buf`]: ../ppm/struct.Ppm.html#method.write_qoi_to_buf
+//! [`Ppm::open`]: ../ppm/struct.Ppm.html#method.open
+//! [`Ppm::from_qoi_reader`]: ../ppm/struct.Ppm.html#method.from_qoi_reader
+//! [`save`]: ../screen/trait.Screen.html#tymethod.save
+
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Write},
+};
+
+use crate::{
+    color::PpmColor,
+    error::{self, GraphicsError},
+    ppm::{Ppm, PpmBuilder},
+    screen::Screen,
+};
+
+const MAGIC: &[u8; 4] = b"qoif";
+const END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xc0;
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+const MASK: u8 = 0xc0;
+
+/// Longest run a single `OP_RUN` can hold.
+const MAX_RUN: u8 = 62;
+
+fn hash([r, g, b, a]: [u8; 4]) -> usize {
+    (r as usize * 3 + g as usize * 5 + b as usize * 7 + a as usize * 11) % 64
+}
+
+/// Write `img` as a QOI image, with an alpha channel if the color type has one.
+pub(crate) fn write_qoi<C: PpmColor, W: Write>(img: &Ppm<C>, writer: &mut W) -> io::Result<()> {
+    let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "image too large for QOI");
+    let width = u32::try_from(img.width()).map_err(|_| too_large())?;
+    let height = u32::try_from(img.height()).map_err(|_| too_large())?;
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&width.to_be_bytes())?;
+    writer.write_all(&height.to_be_bytes())?;
+    // channels, and sRGB color space with linear alpha
+    writer.write_all(&[if C::HAS_ALPHA { 4 } else { 3 }, 0])?;
+
+    let pixels = img.to_rgba8();
+    let mut index = [[0u8; 4]; 64];
+    let mut previous = [0, 0, 0, 255];
+    let mut run = 0;
+    for (i, &pixel) in pixels.iter().enumerate() {
+        if pixel == previous {
+            run += 1;
+            if run == MAX_RUN || i == pixels.len() - 1 {
+                writer.write_all(&[OP_RUN | (run - 1)])?;
+                run = 0;
+            }
+            continue;
+        }
+        if run > 0 {
+            writer.write_all(&[OP_RUN | (run - 1)])?;
+            run = 0;
+        }
+
+        let slot = hash(pixel);
+        if index[slot] == pixel {
+            writer.write_all(&[OP_INDEX | slot as u8])?;
+        } else {
+            index[slot] = pixel;
+            let [r, g, b, a] = pixel;
+            if a == previous[3] {
+                let dr = r.wrapping_sub(previous[0]) as i8;
+                let dg = g.wrapping_sub(previous[1]) as i8;
+                let db = b.wrapping_sub(previous[2]) as i8;
+                let (dr_dg, db_dg) = (dr.wrapping_sub(dg), db.wrapping_sub(dg));
+                if (-2..=1).contains(&dr) && (-2..=1).contains(&dg) && (-2..=1).contains(&db) {
+                    let diff = ((dr + 2) << 4 | (dg + 2) << 2 | (db + 2)) as u8;
+                    writer.write_all(&[OP_DIFF | diff])?;
+                } else if (-32..=31).contains(&dg)
+                    && (-8..=7).contains(&dr_dg)
+                    && (-8..=7).contains(&db_dg)
+                {
+                    writer.write_all(&[
+                        OP_LUMA | (dg + 32) as u8,
+                        ((dr_dg + 8) << 4 | (db_dg + 8)) as u8,
+                    ])?;
+                } else {
+                    writer.write_all(&[OP_RGB, r, g, b])?;
+                }
+            } else {
+                writer.write_all(&[OP_RGBA, r, g, b, a])?;
+            }
+        }
+        previous = pixel;
+    }
+
+    writer.write_all(&END_MARKER)
+}
+
+/// Decode a QOI image into a [`Ppm`] with default settings and a color depth of `255`.
+pub(crate) fn read_qoi<C: PpmColor, R: Read>(reader: &mut R) -> error::Result<Ppm<C>> {
+    let mut header = [0; 14];
+    reader.read_exact(&mut header)?;
+    if &header[..4] != MAGIC {
+        return Err(GraphicsError::parse("not a qoi file"));
+    }
+    let width = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let height = u32::from_be_bytes([header[8], header[9], header[10], header[11]]) as usize;
+    let pixel_count = width
+        .checked_mul(height)
+        .ok_or_else(|| GraphicsError::parse("image too large"))?;
+
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    let mut bytes = bytes.into_iter();
+    let mut next = || {
+        bytes
+            .next()
+            .ok_or_else(|| GraphicsError::parse("unexpected end of file"))
+    };
+
+    let mut data = Vec::with_capacity(pixel_count.min(1 << 24));
+    let mut index = [[0u8; 4]; 64];
+    let mut pixel = [0, 0, 0, 255];
+    let mut run = 0;
+    while data.len() < pixel_count {
+        if run > 0 {
+            run -= 1;
+        } else {
+            let op = next()?;
+            match op {
+                OP_RGB => {
+                    pixel = [next()?, next()?, next()?, pixel[3]];
+                }
+                OP_RGBA => {
+                    pixel = [next()?, next()?, next()?, next()?];
+                }
+                _ => match op & MASK {
+                    OP_INDEX => pixel = index[op as usize],
+                    OP_DIFF => {
+                        pixel[0] = pixel[0].wrapping_add((op >> 4 & 3).wrapping_sub(2));
+                        pixel[1] = pixel[1].wrapping_add((op >> 2 & 3).wrapping_sub(2));
+                        pixel[2] = pixel[2].wrapping_add((op & 3).wrapping_sub(2));
+                    }
+                    OP_LUMA => {
+                        let dg = (op & 0x3f).wrapping_sub(32);
+                        let second = next()?;
+                        pixel[0] =
+                            pixel[0].wrapping_add(dg.wrapping_add((second >> 4).wrapping_sub(8)));
+                        pixel[1] = pixel[1].wrapping_add(dg);
+                        pixel[2] =
+                            pixel[2].wrapping_add(dg.wrapping_add((second & 0xf).wrapping_sub(8)));
+                    }
+                    _ => run = op & 0x3f,
+                },
+            }
+            index[hash(pixel)] = pixel;
+        }
+        let [r, g, b, a] = pixel;
+        data.push(C::from_channels([r as u16, g as u16, b as u16]).with_alpha(a));
+    }
+
+    PpmBuilder::with_color_type(height, width, 255)
+        .with_data(data)
+        .try_build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::{Rgb, Rgba},
+        gradient::Gradient,
+    };
+
+    #[test]
+    fn test_qoi_round_trip() {
+        let mut img = PpmBuilder::new(64, 64, 255).build();
+        img.fill_rect(
+            (0., 0., 0.),
+            64.,
+            64.,
+            &Gradient::linear((0., 0.), (64., 20.))
+                .stop(0., Rgb::new(255, 0, 0))
+                .stop(1., Rgb::new(0, 40, 255)),
+        );
+        img.draw_line((0., 0., 1.), (63., 30., 1.), Rgb::WHITE);
+        img.plot(5, 5, 2., Rgb::new(3, 200, 7));
+
+        let mut buf = vec![];
+        img.write_qoi_to_buf(&mut buf).unwrap();
+        assert!(buf.len() < 64 * 64 * 3);
+        let read = Ppm::<Rgb>::from_qoi_reader(&buf[..]).unwrap();
+        assert_eq!(img.data(), read.data());
+
+        let mut layer = PpmBuilder::<Rgba>::with_color_type(8, 8, 255)
+            .bg_color(Rgba::TRANSPARENT)
+            .build();
+        layer.plot(1, 2, 0., Rgba::new(10, 20, 30, 40));
+        let mut buf = vec![];
+        layer.write_qoi_to_buf(&mut buf).unwrap();
+        assert_eq!(4, buf[12]);
+        let read = Ppm::<Rgba>::from_qoi_reader(&buf[..]).unwrap();
+        assert_eq!(layer.data(), read.data());
+    }
+}
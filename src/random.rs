@@ -0,0 +1,255 @@
+//! A small seedable random number generator, for procedural art and stochastic effects.
+//!
+//! The same seed always gives the same sequence of numbers, on every platform, so an image made with an [`Rng`] comes
+//! out identical every time it's rendered. Change the seed to get a different image.
+//!
+//! This is not cryptographically secure. It's a [SplitMix64] generator: fast, tiny, and good enough for graphics.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{prelude::*, random::Rng};
+//!
+//! let mut img = Ppm::new();
+//! let mut rng = Rng::new(42);
+//!
+//! // 100 random stars
+//! for _ in 0..100 {
+//!     let p = rng.point_in_rect(0., 0., 500., 500.);
+//!     img.draw_circle(p.x as f64, p.y as f64, 0., 2., rng.color());
+//! }
+//!
+//! // same seed, same numbers
+//! assert_eq!(Rng::new(7).next_u64(), Rng::new(7).next_u64());
+//! ```
+//!
+//! [`Rng`]: ./struct.Rng.html
+//! [SplitMix64]: http://prng.di.unimi.it/splitmix64.c
+
+use crate::{
+    color::Rgb,
+    util::{Float, Point},
+};
+
+/// Seedable random number generator. See the [module documentation].
+///
+/// [module documentation]: ./index.html
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Make a generator from a `seed`.
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Make a generator seeded from the current time, for when reproducibility doesn't matter.
+    pub fn from_time() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Rng::new(nanos)
+    }
+
+    /// A random `u64`, with every value equally likely.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A random number in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        // the top 53 bits fill the mantissa exactly
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A random number in `[min, max)`.
+    pub fn range(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+
+    /// A random integer in `[min, max)`. Returns `min` if the range is empty.
+    pub fn range_int(&mut self, min: i64, max: i64) -> i64 {
+        if max <= min {
+            return min;
+        }
+        // the size of the range fits in a u64, even when it overflows an i64
+        let size = max.wrapping_sub(min) as u64;
+        min.wrapping_add((self.next_u64() % size) as i64)
+    }
+
+    /// `true` with a probability of `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    /// A random element of `items`, or `None` if it's empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.get(self.range_int(0, items.len() as i64) as usize)
+    }
+
+    /// A random color, with every channel uniform in `[0, 255]`.
+    pub fn color(&mut self) -> Rgb {
+        let bits = self.next_u64();
+        Rgb::new(bits as u8, (bits >> 8) as u8, (bits >> 16) as u8)
+    }
+
+    /// A color with a random hue, and the given `saturation` and `value` in `[0, 1]`. Keeping those fixed gives random
+    /// colors that still look good together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::random::Rng;
+    ///
+    /// let mut rng = Rng::new(1);
+    /// let (_, s, v) = rng.color_hsv(1., 1.).to_hsv();
+    /// assert_eq!((1., 1.), (s, v));
+    /// ```
+    pub fn color_hsv(&mut self, saturation: f64, value: f64) -> Rgb {
+        let hue = self.range(0., 360.);
+        Rgb::from_hsv(hue, saturation, value)
+    }
+
+    /// A random point in the `width` by `height` rectangle with its corner at (`x`, `y`), with a `z` of `0`.
+    pub fn point_in_rect(&mut self, x: Float, y: Float, width: Float, height: Float) -> Point {
+        Point::new(
+            x + width * self.next_f64() as Float,
+            y + height * self.next_f64() as Float,
+            0.,
+        )
+    }
+
+    /// `n * n` sample offsets in the unit square, one at a random position in each cell of an `n` by `n` grid.
+    ///
+    /// Jittered samples cover a pixel more evenly than purely random ones, and trade the regular aliasing patterns of a
+    /// plain grid for noise. Add them to a pixel's corner to sample it for anti-aliasing, soft shadows, and the like.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::random::Rng;
+    ///
+    /// let samples = Rng::new(0).jitter(4);
+    /// assert_eq!(16, samples.len());
+    /// assert!(samples.iter().all(|&(x, y)| 0. <= x && x < 1. && 0. <= y && y < 1.));
+    /// ```
+    pub fn jitter(&mut self, n: usize) -> Vec<(Float, Float)> {
+        let cell = 1. / n as Float;
+        let mut samples = Vec::with_capacity(n * n);
+        for i in 0..n {
+            for j in 0..n {
+                samples.push((
+                    (j as Float + self.next_f64() as Float) * cell,
+                    (i as Float + self.next_f64() as Float) * cell,
+                ));
+            }
+        }
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+        // 0 is a fine seed
+        let mut zero = Rng::new(0);
+        assert_ne!(zero.next_u64(), zero.next_u64());
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let f = rng.next_f64();
+            assert!((0. ..1.).contains(&f));
+            let r = rng.range(-3., 5.);
+            assert!((-3. ..5.).contains(&r));
+            let i = rng.range_int(-3, 5);
+            assert!((-3..5).contains(&i));
+        }
+    }
+
+    #[test]
+    fn test_degenerate_ranges() {
+        let mut rng = Rng::new(7);
+        assert_eq!(4., rng.range(4., 4.));
+        assert_eq!(4, rng.range_int(4, 4));
+        assert_eq!(4, rng.range_int(4, -4));
+        assert_eq!(i64::MAX - 1, rng.range_int(i64::MAX - 1, i64::MAX));
+        assert!(rng.range(f64::NAN, 1.).is_nan());
+
+        // ranges wider than an i64 don't overflow
+        for _ in 0..100 {
+            let i = rng.range_int(i64::MIN, i64::MAX);
+            assert!(i < i64::MAX);
+        }
+    }
+
+    #[test]
+    fn test_chance_and_choose() {
+        let mut rng = Rng::new(3);
+        assert!((0..100).all(|_| !rng.chance(0.)));
+        assert!((0..100).all(|_| rng.chance(1.)));
+        assert!((0..100).all(|_| !rng.chance(f64::NAN)));
+
+        assert_eq!(None, rng.choose::<u8>(&[]));
+        assert_eq!(Some(&5), rng.choose(&[5]));
+        let items = [1, 2, 3];
+        let mut seen = [false; 3];
+        for _ in 0..100 {
+            seen[*rng.choose(&items).unwrap() - 1] = true;
+        }
+        assert_eq!([true; 3], seen);
+    }
+
+    #[test]
+    fn test_point_in_rect() {
+        let mut rng = Rng::new(5);
+        for _ in 0..100 {
+            let p = rng.point_in_rect(10., 20., 5., 3.);
+            assert!(10. <= p.x && p.x < 15. && 20. <= p.y && p.y < 23.);
+            assert_eq!(0., p.z);
+        }
+        let p = rng.point_in_rect(10., 20., 0., 0.);
+        assert_eq!((10., 20.), (p.x, p.y));
+    }
+
+    #[test]
+    fn test_jitter() {
+        let mut rng = Rng::new(9);
+        assert!(rng.jitter(0).is_empty());
+        assert_eq!(1, rng.jitter(1).len());
+
+        // one sample in each cell of a 3 by 3 grid
+        let samples = rng.jitter(3);
+        for (i, &(x, y)) in samples.iter().enumerate() {
+            let (column, row) = ((x * 3.) as usize, (y * 3.) as usize);
+            assert_eq!(i, row * 3 + column);
+        }
+    }
+
+    #[test]
+    fn test_colors() {
+        let mut rng = Rng::new(11);
+        let colors: Vec<_> = (0..10).map(|_| rng.color()).collect();
+        assert!(colors.windows(2).any(|w| w[0] != w[1]));
+        assert_eq!(Rgb::BLACK, rng.color_hsv(1., 0.));
+        let (_, s, v) = rng.color_hsv(1., 1.).to_hsv();
+        assert_eq!((1., 1.), (s, v));
+    }
+}
@@ -0,0 +1,357 @@
+//! Rasterization algorithms, behind the [`Rasterizer`] trait.
+//!
+//! A rasterizer turns a shape (a line or a triangle) into the pixels that cover it. [`Screen`] asks its [`rasterizer`] for those
+//! pixels, and takes care of plotting them. So a new line algorithm can be plugged into any [`Screen`], and compared
+//! against the default one through the exact same drawing API:
+//!
+//! ```
+//! use std::time::Instant;
+//! use graphics::{prelude::*, raster::{Bresenham, Dda}};
+//!
+//! let mut bresenham = PpmBuilder::new(500, 500, 255).rasterizer(&Bresenham).build();
+//! let mut dda = PpmBuilder::new(500, 500, 255).rasterizer(&Dda).build();
+//!
+//! for img in [&mut bresenham, &mut dda].iter_mut() {
+//!     let start = Instant::now();
+//!     for i in 0..100 {
+//!         img.draw_line((0., 0., 0.), (499., i as f64 * 5., 0.), Rgb::WHITE);
+//!     }
+//!     println!("{:?}", start.elapsed());
+//! }
+//! ```
+//!
+//! # Writing Your Own
+//! Implement [`Rasterizer`] on a (usually empty) struct, and pass a reference to it to [`PpmBuilder::rasterizer`]:
+//!
+//! ```
+//! use graphics::{prelude::*, raster::Rasterizer, util::Float};
+//!
+//! /// Only plots the endpoints. Not very useful.
+//! struct Endpoints;
+//!
+//! impl Rasterizer for Endpoints {
+//!     fn line(&self, p0: Point, p1: Point, plot: &mut dyn FnMut(i64, i64, Float)) {
+//!         plot(p0.x as i64, p0.y as i64, p0.z);
+//!         plot(p1.x as i64, p1.y as i64, p1.z);
+//!     }
+//! }
+//!
+//! let mut img = PpmBuilder::new(500, 500, 255).rasterizer(&Endpoints).build();
+//! img.draw_line((0., 0., 0.), (499., 499., 0.), Rgb::WHITE);
+//! ```
+//!
+//! Triangles are still filled with the default half-space algorithm. To change that too, override
+//! [`Rasterizer::triangle_in_rect`], which the other triangle methods go through.
+//!
+//! Rasterizers are passed around as `&'static dyn Rasterizer`, so they should not hold any state. A reference to a unit
+//! struct, like `&Endpoints`, is automatically `'static`.
+//!
+//! [`Rasterizer`]: ./trait.Rasterizer.html
+//! [`Rasterizer::triangle_in_rect`]: ./trait.Rasterizer.html#method.triangle_in_rect
+//! [`Screen`]: ../screen/trait.Screen.html
+//! [`rasterizer`]: ../screen/trait.Screen.html#method.rasterizer
+//! [`PpmBuilder::rasterizer`]: ../ppm/struct.PpmBuilder.html#method.rasterizer
+
+use std::ops::Range;
+
+use crate::util::{Float, Point};
+
+/// An algorithm that decides which pixels make up a shape.
+///
+/// Rasterizers are shared between threads when filling in parallel, so they have to be `Sync`, which stateless ones
+/// are.
+pub trait Rasterizer: Sync {
+    /// Call `plot(x, y, z)` for every pixel on the line from `p0` to `p1`, both endpoints included.
+    ///
+    /// `z` should be interpolated from `p0.z` to `p1.z` along the line.
+    fn line(&self, p0: Point, p1: Point, plot: &mut dyn FnMut(i64, i64, Float));
+
+    /// Call `plot(x, y, z)` for every pixel inside the triangle `p0`, `p1`, `p2`.
+    ///
+    /// Nothing is clipped, so every pixel of a huge triangle gets visited. [`Screen`]s call [`triangle_in_rect`] with their
+    /// [`clip_bounds`] instead. The default implementation is [`triangle_in_rect`] over all pixels.
+    ///
+    /// [`Screen`]: ../screen/trait.Screen.html
+    /// [`clip_bounds`]: ../screen/trait.Screen.html#method.clip_bounds
+    /// [`triangle_in_rect`]: #method.triangle_in_rect
+    fn triangle(&self, p0: Point, p1: Point, p2: Point, plot: &mut dyn FnMut(i64, i64, Float)) {
+        self.triangle_in_rect(p0, p1, p2, i64::MIN..i64::MAX, i64::MIN..i64::MAX, plot);
+    }
+
+    /// Like [`triangle`], but only call `plot` for pixels with a `y` in `rows`. This is how filling is split across
+    /// threads by bands of rows.
+    ///
+    /// The default implementation is [`triangle_in_rect`] over all columns.
+    ///
+    /// [`triangle`]: #method.triangle
+    /// [`triangle_in_rect`]: #method.triangle_in_rect
+    fn triangle_rows(
+        &self,
+        p0: Point,
+        p1: Point,
+        p2: Point,
+        rows: Range<i64>,
+        plot: &mut dyn FnMut(i64, i64, Float),
+    ) {
+        self.triangle_in_rect(p0, p1, p2, i64::MIN..i64::MAX, rows, plot);
+    }
+
+    /// Call `plot(x, y, z)` for every pixel inside the triangle `p0`, `p1`, `p2` with an `x` in `columns` and a `y` in
+    /// `rows`. Screens clip triangles to their bounds with this, and the tiles of a [`TileRenderer`] are filled with it.
+    ///
+    /// The other triangle methods go through this one, so it is the one to override for a different triangle algorithm.
+    ///
+    /// `z` should be interpolated across the triangle. The default implementation is a half-space (barycentric) rasterizer:
+    /// every pixel in the bounding box of the triangle (cut down to `columns` and `rows`) is tested against the 3 edges, and
+    /// the edge functions double as barycentric weights for interpolating `z`.
+    ///
+    /// Pixels are sampled at integer coordinates, the same ones given to [`plot`]. A pixel exactly on an edge shared by two
+    /// triangles is only drawn by one of them (the [top-left rule]), so adjacent triangles neither overlap nor leave gaps.
+    /// Degenerate (zero area) triangles draw nothing.
+    ///
+    /// [`plot`]: ../screen/trait.Screen.html#tymethod.plot
+    /// [`TileRenderer`]: ../tile/struct.TileRenderer.html
+    /// [top-left rule]: https://en.wikipedia.org/wiki/Rasterisation#Triangle_rasterization
+    fn triangle_in_rect(
+        &self,
+        p0: Point,
+        p1: Point,
+        p2: Point,
+        columns: Range<i64>,
+        rows: Range<i64>,
+        plot: &mut dyn FnMut(i64, i64, Float),
+    ) {
+        half_space_triangle(p0, p1, p2, columns, rows, plot);
+    }
+}
+
+/// The default [`Rasterizer::triangle_in_rect`].
+///
+/// [`Rasterizer::triangle_in_rect`]: ./trait.Rasterizer.html#method.triangle_in_rect
+fn half_space_triangle(
+    p0: Point,
+    p1: Point,
+    p2: Point,
+    columns: Range<i64>,
+    rows: Range<i64>,
+    plot: &mut dyn FnMut(i64, i64, Float),
+) {
+    // make the triangle counter-clockwise, so the inside is on the left of every edge
+    let area = edge(p0, p1, p2);
+    if area == 0. {
+        return;
+    }
+    let (p1, p2, area) = if area < 0. {
+        (p2, p1, -area)
+    } else {
+        (p1, p2, area)
+    };
+
+    // pixels exactly on an edge belong to the triangle only if it is a top or a left edge
+    let bias = |a: Point, b: Point| {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        if dy < 0. || (dy == 0. && dx < 0.) {
+            0.
+        } else {
+            -Float::EPSILON
+        }
+    };
+    let (bias0, bias1, bias2) = (bias(p1, p2), bias(p2, p0), bias(p0, p1));
+
+    let min_x = (p0.x.min(p1.x).min(p2.x).ceil() as i64).max(columns.start);
+    let max_x = (p0.x.max(p1.x).max(p2.x).floor() as i64).min(columns.end.saturating_sub(1));
+    let min_y = (p0.y.min(p1.y).min(p2.y).ceil() as i64).max(rows.start);
+    let max_y = (p0.y.max(p1.y).max(p2.y).floor() as i64).min(rows.end.saturating_sub(1));
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let p = Point::new(x as Float, y as Float, 0.);
+            let (w0, w1, w2) = (edge(p1, p2, p), edge(p2, p0, p), edge(p0, p1, p));
+            if w0 + bias0 >= 0. && w1 + bias1 >= 0. && w2 + bias2 >= 0. {
+                let z = (w0 * p0.z + w1 * p1.z + w2 * p2.z) / area;
+                plot(x, y, z);
+            }
+        }
+    }
+}
+
+/// Edge function: twice the signed area of the triangle `a`, `b`, `p`. Positive if `p` is on the left of `a -> b`.
+fn edge(a: Point, b: Point, p: Point) -> Float {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// [Bresenham's line algorithm], generalized to work in all octants. This is the default rasterizer, and the reference to check
+/// your own line algorithm against.
+///
+/// Endpoints are rounded to the nearest pixel, and only integer arithmetic is used to walk the line.
+///
+/// [Bresenham's line algorithm]: https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Bresenham;
+
+impl Rasterizer for Bresenham {
+    fn line(&self, p0: Point, p1: Point, plot: &mut dyn FnMut(i64, i64, Float)) {
+        let (mut x, mut y) = (p0.x.round() as i64, p0.y.round() as i64);
+        let (x1, y1) = (p1.x.round() as i64, p1.y.round() as i64);
+
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let sx = if x < x1 { 1 } else { -1 };
+        let sy = if y < y1 { 1 } else { -1 };
+
+        let steps = dx.max(-dy);
+        let dz = if steps == 0 {
+            0.
+        } else {
+            (p1.z - p0.z) / steps as Float
+        };
+        let mut z = p0.z;
+
+        let mut err = dx + dy;
+        loop {
+            plot(x, y, z);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+            z += dz;
+        }
+    }
+}
+
+/// The [digital differential analyzer] line algorithm.
+///
+/// Steps one pixel at a time along the major axis, and adds the slope to the minor axis in floating point.
+/// Simpler than [`Bresenham`], but has to round at every step.
+///
+/// [digital differential analyzer]: https://en.wikipedia.org/wiki/Digital_differential_analyzer_(graphics_algorithm)
+/// [`Bresenham`]: ./struct.Bresenham.html
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Dda;
+
+impl Rasterizer for Dda {
+    fn line(&self, p0: Point, p1: Point, plot: &mut dyn FnMut(i64, i64, Float)) {
+        let (x0, y0) = (p0.x.round(), p0.y.round());
+        let (x1, y1) = (p1.x.round(), p1.y.round());
+
+        let steps = (x1 - x0).abs().max((y1 - y0).abs());
+        if steps == 0. {
+            plot(x0 as i64, y0 as i64, p0.z);
+            return;
+        }
+
+        let (dx, dy, dz) = ((x1 - x0) / steps, (y1 - y0) / steps, (p1.z - p0.z) / steps);
+        for i in 0..=steps as i64 {
+            let i = i as Float;
+            plot(
+                (x0 + dx * i).round() as i64,
+                (y0 + dy * i).round() as i64,
+                p0.z + dz * i,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn pixels(
+        rasterizer: &dyn Rasterizer,
+        p0: (f64, f64, f64),
+        p1: (f64, f64, f64),
+    ) -> Vec<(i64, i64)> {
+        let mut pixels = vec![];
+        rasterizer.line(p0.into(), p1.into(), &mut |x, y, _| pixels.push((x, y)));
+        pixels
+    }
+
+    #[test]
+    fn test_adjacent_triangles_do_not_overlap() {
+        let corners = [(0., 0., 0.), (10., 0., 0.), (10., 10., 0.), (0., 10., 0.)];
+        let mut count = std::collections::HashMap::new();
+        let mut plot = |x, y, _| *count.entry((x, y)).or_insert(0) += 1;
+        // a square split along its diagonal, in both windings
+        Bresenham.triangle(
+            corners[0].into(),
+            corners[1].into(),
+            corners[2].into(),
+            &mut plot,
+        );
+        Bresenham.triangle(
+            corners[0].into(),
+            corners[3].into(),
+            corners[2].into(),
+            &mut plot,
+        );
+
+        assert!(count.values().all(|&c| c == 1));
+        // right and top edges of the square are excluded
+        assert_eq!(100, count.len());
+    }
+
+    #[test]
+    fn test_lines_in_all_octants() {
+        let ends = [
+            (10., 3.),
+            (3., 10.),
+            (-3., 10.),
+            (-10., 3.),
+            (-10., -3.),
+            (-3., -10.),
+            (3., -10.),
+            (10., -3.),
+            (0., 0.),
+        ];
+        for rasterizer in [&Bresenham as &dyn Rasterizer, &Dda].iter() {
+            for &(x, y) in ends.iter() {
+                let line = pixels(*rasterizer, (0., 0., 0.), (x, y, 0.));
+                assert_eq!(Some(&(0, 0)), line.first());
+                assert_eq!(Some(&(x as i64, y as i64)), line.last());
+                // one pixel per step along the major axis, without gaps
+                assert_eq!((x.abs().max(y.abs()) + 1.) as usize, line.len());
+                for pair in line.windows(2) {
+                    assert!(
+                        (pair[0].0 - pair[1].0).abs() <= 1 && (pair[0].1 - pair[1].1).abs() <= 1
+                    );
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_random_lines(
+            (x0, y0) in (-1000i64..1000, -1000i64..1000),
+            (x1, y1) in (-1000i64..1000, -1000i64..1000),
+        ) {
+            for rasterizer in [&Bresenham as &dyn Rasterizer, &Dda].iter() {
+                let line = pixels(
+                    *rasterizer,
+                    (x0 as f64, y0 as f64, 0.),
+                    (x1 as f64, y1 as f64, 0.),
+                );
+                prop_assert_eq!(Some(&(x0, y0)), line.first());
+                prop_assert_eq!(Some(&(x1, y1)), line.last());
+                prop_assert_eq!(
+                    ((x1 - x0).abs().max((y1 - y0).abs()) + 1) as usize,
+                    line.len()
+                );
+                for pair in line.windows(2) {
+                    prop_assert!(
+                        (pair[0].0 - pair[1].0).abs() <= 1 && (pair[0].1 - pair[1].1).abs() <= 1
+                    );
+                }
+            }
+        }
+    }
+}
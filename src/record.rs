@@ -0,0 +1,527 @@
+//! Record drawing as a list of commands, to replay it on any other [`Screen`].
+//!
+//! [`RecordingScreen`] draws into a [`Ppm`] like usual, and also keeps a [`Recording`] of every call. Lines, circles,
+//! filled triangles, text, plots and clears are recorded as they are; other shapes are recorded as the lines, triangles
+//! and plots they are drawn with. A recording can be:
+//!
+//! - replayed onto another screen, like a bigger [`Ppm`] with [`replay_scaled`], an [`HtmlCanvas`], or a window,
+//! - saved to a text file with [`write_to_buf`], one command per line, and read back with [`from_reader`].
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{prelude::*, record::RecordingScreen};
+//!
+//! let mut screen = RecordingScreen::new(Ppm::new());
+//! screen.draw_line((0., 0., 0.), (499., 499., 0.), Rgb::WHITE);
+//! screen.draw_circle(250., 250., 0., 100., Rgb::new(255, 0, 0));
+//!
+//! // the same drawing, at twice the resolution
+//! let mut big = PpmBuilder::new(1000, 1000, 255).build();
+//! screen.recording().replay_scaled(&mut big, 2.);
+//! assert_eq!(Some(Rgb::WHITE), big.get_pixel(998, 998));
+//!
+//! // and through a file
+//! let mut file = Vec::new();
+//! screen.recording().write_to_buf(&mut file).unwrap();
+//! let loaded = graphics::record::Recording::<Rgb>::from_reader(&file[..]).unwrap();
+//! assert_eq!(screen.recording(), &loaded);
+//! ```
+//!
+//! [`Screen`]: ../screen/trait.Screen.html
+//! [`RecordingScreen`]: ./struct.RecordingScreen.html
+//! [`Ppm`]: ../ppm/struct.Ppm.html
+//! [`Recording`]: ./struct.Recording.html
+//! [`replay_scaled`]: ./struct.Recording.html#method.replay_scaled
+//! [`HtmlCanvas`]: ../html/struct.HtmlCanvas.html
+//! [`write_to_buf`]: ./struct.Recording.html#method.write_to_buf
+//! [`from_reader`]: ./struct.Recording.html#method.from_reader
+
+use std::{
+    convert::TryFrom,
+    fmt,
+    io::{self, BufRead, Write},
+    ops::Range,
+};
+
+use crate::{
+    color::{PpmColor, Rgb},
+    error::{self, GraphicsError},
+    ppm::{Origin, Ppm},
+    raster::Rasterizer,
+    screen::{SaveOptions, Screen},
+    util::{Float, Point},
+};
+
+/// One recorded drawing call. See [`Recording`].
+///
+/// [`Recording`]: ./struct.Recording.html
+#[derive(Clone, PartialEq, Debug)]
+pub enum DrawCommand<C> {
+    /// [`clear`](../screen/trait.Screen.html#tymethod.clear)
+    Clear(C),
+    /// [`plot`](../screen/trait.Screen.html#tymethod.plot)
+    Plot { x: i64, y: i64, z: Float, color: C },
+    /// [`draw_line`](../screen/trait.Screen.html#method.draw_line)
+    Line { p0: Point, p1: Point, color: C },
+    /// [`draw_circle`](../screen/trait.Screen.html#method.draw_circle)
+    Circle {
+        cx: f64,
+        cy: f64,
+        z: Float,
+        r: f64,
+        color: C,
+    },
+    /// [`fill_triangle`](../screen/trait.Screen.html#method.fill_triangle)
+    Triangle { points: [Point; 3], color: C },
+    /// [`draw_text`](../screen/trait.Screen.html#method.draw_text), with the built-in font.
+    Text {
+        x: i64,
+        y: i64,
+        text: String,
+        scale: usize,
+        color: C,
+    },
+}
+
+impl<C: PpmColor> DrawCommand<C> {
+    /// Draw this on `screen`, with x, y, and sizes multiplied by `scale`.
+    fn draw<S: Screen<C>>(&self, screen: &mut S, scale: f64) {
+        let s = scale as Float;
+        let point = |p: Point| Point::new(p.x * s, p.y * s, p.z);
+        let pixel = |v: i64| (v as f64 * scale).round() as i64;
+        match self {
+            DrawCommand::Clear(color) => screen.clear(*color),
+            DrawCommand::Plot { x, y, z, color } => screen.plot(pixel(*x), pixel(*y), *z, *color),
+            DrawCommand::Line { p0, p1, color } => screen.draw_line(point(*p0), point(*p1), *color),
+            DrawCommand::Circle {
+                cx,
+                cy,
+                z,
+                r,
+                color,
+            } => screen.draw_circle(cx * scale, cy * scale, *z, r * scale, *color),
+            DrawCommand::Triangle { points, color } => {
+                screen.fill_triangle(point(points[0]), point(points[1]), point(points[2]), *color)
+            }
+            DrawCommand::Text {
+                x,
+                y,
+                text,
+                scale: text_scale,
+                color,
+            } => {
+                let text_scale = ((*text_scale as f64 * scale).round() as usize).max(1);
+                screen.draw_text(pixel(*x), pixel(*y), text, text_scale, *color)
+            }
+        }
+    }
+
+    /// Parse a command written by its `Display` implementation.
+    fn parse(line: &str) -> error::Result<Self> {
+        let invalid = || GraphicsError::Parse(format!("invalid drawing command: {}", line));
+        let mut words = line.split(' ');
+        let name = words.next().ok_or_else(invalid)?;
+        let count = match name {
+            "clear" => 0,
+            "plot" => 3,
+            "line" => 6,
+            "circle" => 4,
+            "triangle" => 9,
+            "text" => 3,
+            _ => return Err(invalid()),
+        };
+        let numbers = words
+            .by_ref()
+            .take(count)
+            .map(|w| w.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        let channels = words
+            .by_ref()
+            .take(4)
+            .map(|w| w.parse::<u16>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        if numbers.len() != count || channels.len() != 4 {
+            return Err(invalid());
+        }
+        let color = C::from_channels([channels[0], channels[1], channels[2]])
+            .with_alpha(u8::try_from(channels[3]).map_err(|_| invalid())?);
+        let n = |i: usize| numbers[i] as Float;
+        let point = |i: usize| Point::new(n(i), n(i + 1), n(i + 2));
+
+        let command = match name {
+            "clear" => DrawCommand::Clear(color),
+            "plot" => DrawCommand::Plot {
+                x: numbers[0] as i64,
+                y: numbers[1] as i64,
+                z: n(2),
+                color,
+            },
+            "line" => DrawCommand::Line {
+                p0: point(0),
+                p1: point(3),
+                color,
+            },
+            "circle" => DrawCommand::Circle {
+                cx: numbers[0],
+                cy: numbers[1],
+                z: n(2),
+                r: numbers[3],
+                color,
+            },
+            "triangle" => DrawCommand::Triangle {
+                points: [point(0), point(3), point(6)],
+                color,
+            },
+            _ => DrawCommand::Text {
+                x: numbers[0] as i64,
+                y: numbers[1] as i64,
+                scale: numbers[2] as usize,
+                text: unescape(&words.collect::<Vec<_>>().join(" ")),
+                color,
+            },
+        };
+        Ok(command)
+    }
+}
+
+/// One command per line: its name, its numbers, the color as 4 channels (the last one alpha), and for text, the text
+/// with `\` and newlines escaped.
+impl<C: PpmColor> fmt::Display for DrawCommand<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let color = |f: &mut fmt::Formatter<'_>, c: &C| {
+            let [r, g, b] = c.channels();
+            write!(f, " {} {} {} {}", r, g, b, c.alpha())
+        };
+        let point = |f: &mut fmt::Formatter<'_>, p: &Point| write!(f, " {} {} {}", p.x, p.y, p.z);
+        match self {
+            DrawCommand::Clear(c) => {
+                write!(f, "clear")?;
+                color(f, c)
+            }
+            DrawCommand::Plot { x, y, z, color: c } => {
+                write!(f, "plot {} {} {}", x, y, z)?;
+                color(f, c)
+            }
+            DrawCommand::Line { p0, p1, color: c } => {
+                write!(f, "line")?;
+                point(f, p0)?;
+                point(f, p1)?;
+                color(f, c)
+            }
+            DrawCommand::Circle {
+                cx,
+                cy,
+                z,
+                r,
+                color: c,
+            } => {
+                write!(f, "circle {} {} {} {}", cx, cy, z, r)?;
+                color(f, c)
+            }
+            DrawCommand::Triangle { points, color: c } => {
+                write!(f, "triangle")?;
+                for p in points {
+                    point(f, p)?;
+                }
+                color(f, c)
+            }
+            DrawCommand::Text {
+                x,
+                y,
+                text,
+                scale,
+                color: c,
+            } => {
+                write!(f, "text {} {} {}", x, y, scale)?;
+                color(f, c)?;
+                write!(f, " {}", escape(text))
+            }
+        }
+    }
+}
+
+/// Escape `\` and newlines, so `text` fits on one line.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Undo [`escape`].
+///
+/// [`escape`]: ./fn.escape.html
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Drawing commands recorded by a [`RecordingScreen`], on a screen of a given size. See the [module documentation].
+///
+/// [`RecordingScreen`]: ./struct.RecordingScreen.html
+/// [module documentation]: ./index.html
+#[derive(Clone, PartialEq, Debug)]
+pub struct Recording<C = Rgb> {
+    /// Width of the recorded screen.
+    pub width: usize,
+    /// Height of the recorded screen.
+    pub height: usize,
+    /// The commands, in the order they were drawn.
+    pub commands: Vec<DrawCommand<C>>,
+}
+
+impl<C: PpmColor> Recording<C> {
+    /// Draw all commands on `screen`.
+    pub fn replay<S: Screen<C>>(&self, screen: &mut S) {
+        self.replay_scaled(screen, 1.);
+    }
+
+    /// Draw all commands on `screen`, with coordinates and sizes multiplied by `scale`, to render at another
+    /// resolution. Shapes are drawn again at the new size, but single plots stay single pixels.
+    pub fn replay_scaled<S: Screen<C>>(&self, screen: &mut S, scale: f64) {
+        for command in &self.commands {
+            command.draw(screen, scale);
+        }
+    }
+
+    /// Write the recording as text: a `size <width> <height>` line, then one command per line.
+    pub fn write_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        let mut buf = io::BufWriter::new(writer);
+        writeln!(buf, "size {} {}", self.width, self.height)?;
+        for command in &self.commands {
+            writeln!(buf, "{}", command)?;
+        }
+        buf.flush()
+    }
+
+    /// Read a recording written by [`write_to_buf`].
+    ///
+    /// [`write_to_buf`]: #method.write_to_buf
+    pub fn from_reader<R: BufRead>(reader: R) -> error::Result<Self> {
+        let mut lines = reader.lines();
+        let header = lines
+            .next()
+            .ok_or_else(|| GraphicsError::parse("empty recording"))??;
+        let size: Vec<usize> = header
+            .strip_prefix("size ")
+            .map(|size| size.split(' ').filter_map(|n| n.parse().ok()).collect())
+            .unwrap_or_default();
+        if size.len() != 2 {
+            return Err(GraphicsError::parse(
+                "recording doesn't start with its size",
+            ));
+        }
+
+        let mut commands = Vec::new();
+        for line in lines {
+            let line = line?;
+            if !line.is_empty() {
+                commands.push(DrawCommand::parse(&line)?);
+            }
+        }
+        Ok(Recording {
+            width: size[0],
+            height: size[1],
+            commands,
+        })
+    }
+}
+
+/// A [`Ppm`] that records what's drawn on it. See the [module documentation].
+///
+/// [`Ppm`]: ../ppm/struct.Ppm.html
+/// [module documentation]: ./index.html
+pub struct RecordingScreen<C: PpmColor = Rgb> {
+    img: Ppm<C>,
+    recording: Recording<C>,
+}
+
+impl<C: PpmColor> RecordingScreen<C> {
+    /// Record drawing on `img`. What's already on it isn't recorded.
+    pub fn new(img: Ppm<C>) -> Self {
+        let recording = Recording {
+            width: img.width(),
+            height: img.height(),
+            commands: Vec::new(),
+        };
+        RecordingScreen { img, recording }
+    }
+
+    /// What was recorded so far.
+    pub fn recording(&self) -> &Recording<C> {
+        &self.recording
+    }
+
+    /// Stop recording, and keep the recording.
+    pub fn into_recording(self) -> Recording<C> {
+        self.recording
+    }
+
+    /// The image drawn so far.
+    pub fn image(&self) -> &Ppm<C> {
+        &self.img
+    }
+
+    /// Stop recording, and keep the image.
+    pub fn into_image(self) -> Ppm<C> {
+        self.img
+    }
+}
+
+impl<C: PpmColor> Screen<C> for RecordingScreen<C> {
+    fn plot(&mut self, x: i64, y: i64, z: Float, color: C) {
+        self.recording
+            .commands
+            .push(DrawCommand::Plot { x, y, z, color });
+        self.img.plot(x, y, z, color);
+    }
+
+    fn save(&self, file_path: &str) -> error::Result<()> {
+        self.img.save(file_path)
+    }
+
+    fn save_with(&self, file_path: &str, options: &SaveOptions) -> error::Result<()> {
+        self.img.save_with(file_path, options)
+    }
+
+    fn width(&self) -> usize {
+        self.img.width()
+    }
+
+    fn height(&self) -> usize {
+        self.img.height()
+    }
+
+    fn write_to_buf<T: Write>(&self, writer: &mut T) -> io::Result<()> {
+        self.img.write_to_buf(writer)
+    }
+
+    fn display(&self) -> error::Result<()> {
+        self.img.display()
+    }
+
+    fn clear(&mut self, color: C) {
+        self.recording.commands.push(DrawCommand::Clear(color));
+        self.img.clear(color);
+    }
+
+    fn get_pixel(&self, x: i64, y: i64) -> Option<C> {
+        self.img.get_pixel(x, y)
+    }
+
+    fn get_depth(&self, x: i64, y: i64) -> Option<Float> {
+        self.img.get_depth(x, y)
+    }
+
+    fn rasterizer(&self) -> &'static dyn Rasterizer {
+        self.img.rasterizer()
+    }
+
+    fn clip_bounds(&self) -> (Range<i64>, Range<i64>) {
+        self.img.clip_bounds()
+    }
+
+    fn origin(&self) -> Origin {
+        self.img.origin()
+    }
+
+    fn draw_line<P0: Into<Point>, P1: Into<Point>>(&mut self, p0: P0, p1: P1, color: C) {
+        let (p0, p1) = (p0.into(), p1.into());
+        self.recording
+            .commands
+            .push(DrawCommand::Line { p0, p1, color });
+        self.img.draw_line(p0, p1, color);
+    }
+
+    fn draw_circle(&mut self, cx: f64, cy: f64, z: Float, r: f64, color: C) {
+        self.recording.commands.push(DrawCommand::Circle {
+            cx,
+            cy,
+            z,
+            r,
+            color,
+        });
+        self.img.draw_circle(cx, cy, z, r, color);
+    }
+
+    fn fill_triangle<P0, P1, P2>(&mut self, p0: P0, p1: P1, p2: P2, color: C)
+    where
+        P0: Into<Point>,
+        P1: Into<Point>,
+        P2: Into<Point>,
+    {
+        let points = [p0.into(), p1.into(), p2.into()];
+        self.recording
+            .commands
+            .push(DrawCommand::Triangle { points, color });
+        self.img
+            .fill_triangle(points[0], points[1], points[2], color);
+    }
+
+    fn draw_text(&mut self, x: i64, y: i64, text: &str, scale: usize, color: C) {
+        self.recording.commands.push(DrawCommand::Text {
+            x,
+            y,
+            text: text.to_string(),
+            scale,
+            color,
+        });
+        self.img.draw_text(x, y, text, scale, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Rgba, ppm::PpmBuilder};
+
+    #[test]
+    fn test_round_trip() {
+        let mut screen =
+            RecordingScreen::<Rgba>::new(PpmBuilder::with_color_type(50, 60, 255).build());
+        screen.clear(Rgba::new(1, 2, 3, 4));
+        screen.plot(1, 2, 0.5, Rgba::new(255, 0, 0, 255));
+        screen.fill_triangle(
+            (0., 0., 0.),
+            (10., 0., 0.),
+            (0., 10.25, -1.),
+            Rgba::new(0, 255, 0, 128),
+        );
+        screen.draw_text(5, 5, "two\nlines \\ ok", 2, Rgba::new(0, 0, 255, 255));
+
+        let mut text = Vec::new();
+        screen.recording().write_to_buf(&mut text).unwrap();
+        let loaded = Recording::<Rgba>::from_reader(&text[..]).unwrap();
+        assert_eq!(screen.recording(), &loaded);
+        assert_eq!((60, 50), (loaded.width, loaded.height));
+
+        assert!(Recording::<Rgb>::from_reader(&b"size 1 1\nsquare 1 2 3"[..]).is_err());
+        assert!(Recording::<Rgb>::from_reader(&b"plot 1 2 3 0 0 0 255"[..]).is_err());
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut screen = RecordingScreen::new(Ppm::new());
+        screen.draw_rect((10., 10., 0.), 100., 50., Rgb::WHITE);
+        screen.fill_polygon(
+            &[(200., 200., 0.), (300., 200., 0.), (250., 300., 0.)],
+            Rgb::new(255, 0, 0),
+        );
+
+        let mut copy = Ppm::new();
+        screen.recording().replay(&mut copy);
+        assert!(screen.image().pixels().eq(copy.pixels()));
+    }
+}
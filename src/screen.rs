@@ -6,17 +6,85 @@
 //!
 //! [`Screen`]: ./trait.Screen.html
 
-use std::io;
+use std::{io, ops::Range};
 
-use crate::{color::Rgb, util};
+use crate::{
+    cache::RenderCache,
+    color::{Color, Rgb, Rgba},
+    error,
+    fill::Fill,
+    font::Font,
+    magick,
+    ppm::Origin,
+    raster::{Bresenham, Rasterizer},
+    util::{self, Float, Point, Vec3},
+};
 
 /// A trait for image buffers.
 ///
 /// This trait requires basic functions to be implemented, and will provide default implementation for drawing lines, shapes, etc.
 /// This is also where most of the drawing algorithms for this graphics course will be implemented.
-pub trait Screen {
+///
+/// A screen stores pixels of color type `C`, which defaults to [`Rgb`]. Any type implementing [`Color`] works, so a
+/// grayscale mask (`Screen<u8>`) or a floating point buffer (`Screen<f32>`) gets all the same drawing methods:
+///
+/// ```
+/// use graphics::{prelude::*, util::Float};
+///
+/// /// A grayscale mask, without depth buffer.
+/// struct Mask {
+///     size: usize,
+///     data: Vec<u8>,
+/// }
+///
+/// impl Screen<u8> for Mask {
+///     fn plot(&mut self, x: i64, y: i64, _z: Float, color: u8) {
+///         if x >= 0 && y >= 0 && (x as usize) < self.size && (y as usize) < self.size {
+///             self.data[y as usize * self.size + x as usize] = color;
+///         }
+///     }
+///     fn get_pixel(&self, x: i64, y: i64) -> Option<u8> {
+///         if x >= 0 && y >= 0 && (x as usize) < self.size && (y as usize) < self.size {
+///             Some(self.data[y as usize * self.size + x as usize])
+///         } else {
+///             None
+///         }
+///     }
+///     fn get_depth(&self, x: i64, y: i64) -> Option<Float> {
+///         self.get_pixel(x, y).map(|_| 0.)
+///     }
+///     fn clear(&mut self, color: u8) {
+///         self.data.iter_mut().for_each(|d| *d = color);
+///     }
+///     fn width(&self) -> usize {
+///         self.size
+///     }
+///     fn height(&self) -> usize {
+///         self.size
+///     }
+///     fn write_to_buf<T: std::io::Write>(&self, writer: &mut T) -> std::io::Result<()> {
+///         writeln!(writer, "P5\n{} {}\n255", self.size, self.size)?;
+///         writer.write_all(&self.data)
+///     }
+///     fn save(&self, _file_path: &str) -> graphics::error::Result<()> {
+///         unimplemented!()
+///     }
+///     fn display(&self) -> graphics::error::Result<()> {
+///         unimplemented!()
+///     }
+/// }
+///
+/// let mut mask = Mask { size: 100, data: vec![0; 100 * 100] };
+/// mask.draw_circle(50., 50., 0., 30., 255);
+/// mask.flood_fill(50, 50, 255);
+/// assert_eq!(Some(255), mask.get_pixel(50, 50));
+/// ```
+///
+/// [`Rgb`]: ../color/struct.Rgb.html
+/// [`Color`]: ../color/trait.Color.html
+pub trait Screen<C: Color = Rgb> {
     /// Plot a point on the screen at (`x`, `y`, `z`).
-    fn plot(&mut self, x: i64, y: i64, z: f64, color: Rgb);
+    fn plot(&mut self, x: i64, y: i64, z: Float, color: C);
 
     /// Save image to `file_path`.
     /// 
@@ -33,7 +101,61 @@ pub trait Screen {
     /// }
     /// ppm.save("img.png").expect("error saving ppm as png");
     /// ```
-    fn save(&self, file_path: &str) -> io::Result<()>;
+    fn save(&self, file_path: &str) -> error::Result<()>;
+
+    /// Save image to `file_path`, like [`save`], with format-specific [`SaveOptions`].
+    ///
+    /// Without options this is the same as [`save`]. Otherwise, the image data from [`write_to_buf`] is converted by
+    /// ImageMagick with the matching flags, so it has to be installed. Implementations may handle some options
+    /// themselves; [`Ppm`] resizes without ImageMagick.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use graphics::{prelude::*, screen::SaveOptions};
+    ///
+    /// let mut img = Ppm::new();
+    /// img.draw_circle(250., 250., 0., 100., Rgb::WHITE);
+    ///
+    /// img.save_with("small.jpg", &SaveOptions::new().quality(60).resize(100, 100))
+    ///     .expect("error saving jpg");
+    /// img.save_with("smallest.png", &SaveOptions::new().compression(9))
+    ///     .expect("error saving png");
+    /// ```
+    ///
+    /// [`save`]: #tymethod.save
+    /// [`SaveOptions`]: ./struct.SaveOptions.html
+    /// [`write_to_buf`]: #tymethod.write_to_buf
+    /// [`Ppm`]: ../ppm/struct.Ppm.html
+    fn save_with(&self, file_path: &str, options: &SaveOptions) -> error::Result<()> {
+        if *options == SaveOptions::default() {
+            return self.save(file_path);
+        }
+        let mut pnm = Vec::new();
+        self.write_to_buf(&mut pnm)?;
+        options.convert("pnm:-", file_path, &pnm)
+    }
+
+    /// Save image to `file_path`, like [`save`], unless `cache` already has the same image saved the same way, in which case
+    /// that file is copied instead. See [`RenderCache::save`].
+    ///
+    /// Returns `true` if the output came from the cache.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use graphics::{cache::RenderCache, prelude::*};
+    ///
+    /// let mut img = Ppm::new();
+    /// img.draw_circle(250., 250., 0., 100., Rgb::WHITE);
+    ///
+    /// let cache = RenderCache::new("target/render-cache").expect("error creating cache");
+    /// img.save_cached("img.png", &cache).expect("error saving png");
+    /// ```
+    ///
+    /// [`save`]: #tymethod.save
+    /// [`RenderCache::save`]: ../cache/struct.RenderCache.html#method.save
+    fn save_cached(&self, file_path: &str, cache: &RenderCache) -> error::Result<bool> {
+        cache.save(self, file_path)
+    }
 
     /// Return the width of the screen.
     fn width(&self) -> usize;
@@ -65,8 +187,7 @@ pub trait Screen {
     /// img.display().expect("error while displaying ppm");
     /// ```
     /// [`Ppm`]: ../ppm/struct.Ppm.html
-    fn display(&self) -> io::Result<()>;
-
+    fn display(&self) -> error::Result<()>;
 
     /// Clear the screen (fill with `color`) and reset configurations like z-buffer.
     /// 
@@ -95,10 +216,197 @@ pub trait Screen {
     /// 
     /// img.display();
     /// ```
-    fn clear(&mut self, color: Rgb);
+    fn clear(&mut self, color: C);
+
+    /// Read back the color of the pixel at (`x`, `y`), in the same coordinates given to [`plot`].
+    ///
+    /// Returns `None` if the point is outside of the screen (and not wrapped back in). This is what flood fills, blending,
+    /// post-processing and tests comparing images are built on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    /// img.plot(10, 20, 0., Rgb::WHITE);
+    ///
+    /// assert_eq!(Some(Rgb::WHITE), img.get_pixel(10, 20));
+    /// assert_eq!(Some(Rgb::BLACK), img.get_pixel(20, 10));
+    /// assert_eq!(None, img.get_pixel(-1, 500));
+    /// ```
+    ///
+    /// [`plot`]: #tymethod.plot
+    fn get_pixel(&self, x: i64, y: i64) -> Option<C>;
+
+    /// Read back the depth (`z`) of the pixel at (`x`, `y`), in the same coordinates given to [`plot`].
+    ///
+    /// Pixels that were never drawn on (since the last [`clear`]) have a depth of negative infinity. Returns `None` if the
+    /// point is outside of the screen (and not wrapped back in).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{prelude::*, util::Float};
+    ///
+    /// let mut img = Ppm::new();
+    /// img.plot(10, 20, 5., Rgb::WHITE);
+    /// // behind the first plot, so it doesn't show
+    /// img.plot(10, 20, 1., Rgb::BLACK);
+    ///
+    /// assert_eq!(Some(5.), img.get_depth(10, 20));
+    /// assert_eq!(Some(Float::NEG_INFINITY), img.get_depth(20, 10));
+    /// ```
+    ///
+    /// [`plot`]: #tymethod.plot
+    /// [`clear`]: #tymethod.clear
+    fn get_depth(&self, x: i64, y: i64) -> Option<Float>;
 
     // Default methods -----
 
+    /// The [`Rasterizer`] used to turn shapes into pixels. Defaults to [`Bresenham`].
+    ///
+    /// Override this to plug in a different line algorithm. [`Ppm`] lets you choose it through [`PpmBuilder::rasterizer`].
+    ///
+    /// [`Rasterizer`]: ../raster/trait.Rasterizer.html
+    /// [`Bresenham`]: ../raster/struct.Bresenham.html
+    /// [`Ppm`]: ../ppm/struct.Ppm.html
+    /// [`PpmBuilder::rasterizer`]: ../ppm/struct.PpmBuilder.html#method.rasterizer
+    fn rasterizer(&self) -> &'static dyn Rasterizer {
+        &Bresenham
+    }
+
+    /// The x and y values (end exclusive) where [`plot`] can change anything. Filled shapes are clipped to these before
+    /// they are rasterized, so a huge triangle or rectangle only costs as much as the part of it on screen.
+    ///
+    /// Defaults to `0..width` and `0..height`. Screens with other coordinates, or that wrap points around, should
+    /// override it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let img = PpmBuilder::new(200, 300, 255).build();
+    /// assert_eq!((0..300, 0..200), img.clip_bounds());
+    /// ```
+    ///
+    /// [`plot`]: #tymethod.plot
+    fn clip_bounds(&self) -> (Range<i64>, Range<i64>) {
+        (0..self.width() as i64, 0..self.height() as i64)
+    }
+
+    /// Where (0, 0) is on the screen, and which way y goes, for code that exports drawings to other formats, like
+    /// [`Turtle::write_svg`].
+    ///
+    /// Defaults to [`Origin::BottomLeft`]. [`Ppm`] returns its [`origin`].
+    ///
+    /// [`Turtle::write_svg`]: ../turtle/struct.Turtle.html#method.write_svg
+    /// [`Origin::BottomLeft`]: ../ppm/enum.Origin.html#variant.BottomLeft
+    /// [`Ppm`]: ../ppm/struct.Ppm.html
+    /// [`origin`]: ../ppm/struct.Ppm.html#structfield.origin
+    fn origin(&self) -> Origin {
+        Origin::BottomLeft
+    }
+
+    /// Overwrite the pixel at (`x`, `y`) with `color`, skipping the depth test and leaving the depth alone.
+    ///
+    /// This is for recoloring what is already on screen, like [`flood_fill`] does, where going through [`plot`] would be
+    /// rejected by the z-buffer. The default implementation plots at `z = +inf`, which passes the usual depth test; screens
+    /// with a z-buffer should override it to write the pixel directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    /// img.plot(10, 10, 5., Rgb::WHITE);
+    /// img.set_pixel(10, 10, Rgb::new(255, 0, 0));
+    ///
+    /// assert_eq!(Some(Rgb::new(255, 0, 0)), img.get_pixel(10, 10));
+    /// assert_eq!(Some(5.), img.get_depth(10, 10));
+    /// ```
+    ///
+    /// [`plot`]: #tymethod.plot
+    /// [`flood_fill`]: #method.flood_fill
+    fn set_pixel(&mut self, x: i64, y: i64, color: C) {
+        self.plot(x, y, Float::INFINITY, color);
+    }
+
+    /// Plot a point at fractional coordinates (`x`, `y`), spreading it over the (up to 4) pixels it covers.
+    ///
+    /// Pixel centers are at integer coordinates. A point that falls between pixel centers is split among its neighbors, each
+    /// getting a share of `color` proportional to how close the point is, blended over the pixel's current color.
+    /// Sampling a curve densely with `plot_f` gives smoother results than rounding every sample with [`plot`].
+    ///
+    /// Each blended pixel goes through [`plot`], so the usual depth test applies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // exactly between 2 pixels
+    /// img.plot_f(10.5, 10., 0., Rgb::WHITE);
+    /// assert_eq!(Some(Rgb::gray(128)), img.get_pixel(10, 10));
+    /// assert_eq!(Some(Rgb::gray(128)), img.get_pixel(11, 10));
+    /// ```
+    ///
+    /// [`plot`]: #tymethod.plot
+    fn plot_f(&mut self, x: f64, y: f64, z: Float, color: C) {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let covered = [
+            (x0, y0, (1. - fx) * (1. - fy)),
+            (x0 + 1, y0, fx * (1. - fy)),
+            (x0, y0 + 1, (1. - fx) * fy),
+            (x0 + 1, y0 + 1, fx * fy),
+        ];
+        for &(x, y, weight) in covered.iter() {
+            if weight <= 0. {
+                continue;
+            }
+            if let Some(bg) = self.get_pixel(x, y) {
+                self.plot(x, y, z, bg.mix(color, weight));
+            }
+        }
+    }
+
+    /// Plot a translucent `color` at (`x`, `y`), composited over the pixel's current color ("source-over").
+    ///
+    /// The result goes through [`plot`], so the usual depth test applies: the blended pixel only shows if `z` is in front
+    /// of what's already there. Fully transparent colors don't plot anything. To use translucent colors with all the
+    /// drawing methods, see [`Blended`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{color::Rgba, prelude::*};
+    ///
+    /// let mut img = Ppm::new();
+    /// img.plot(10, 10, 0., Rgb::new(0, 0, 255));
+    /// img.plot_blended(10, 10, 1., Rgba::new(255, 0, 0, 128));
+    ///
+    /// assert_eq!(Some(Rgb::new(128, 0, 127)), img.get_pixel(10, 10));
+    /// ```
+    ///
+    /// [`plot`]: #tymethod.plot
+    /// [`Blended`]: ../blend/struct.Blended.html
+    fn plot_blended(&mut self, x: i64, y: i64, z: Float, color: Rgba) {
+        if color.alpha == 0 {
+            return;
+        }
+        if let Some(bg) = self.get_pixel(x, y) {
+            let blended = bg.mix(C::from_rgb(color.rgb()), color.alpha as f64 / 255.);
+            self.plot(x, y, z, blended);
+        }
+    }
+
     /// Draw a line from `p0 (x, y, z)` to `p1 (x, y z)`, with the given `color`.
     ///
     ///
@@ -115,45 +423,1522 @@ pub trait Screen {
     ///
     /// # Implementation
     ///
-    /// This method implements a line algorithm, and calls [`plot`] to to draw a single point on the image.
+    /// This method asks the [`rasterizer`] for the pixels on the line, and calls [`plot`] to draw each of them on the image.
+    /// By default, that is [Bresenham's line algorithm][Wikipedia page], generalized to work in all octants.
+    /// Endpoints are rounded to the nearest pixel.
     ///
-    /// `color` should be directly passed down to [`plot`], so all pixels of a line have the same color.
-    /// (You can definitely change that behavior if you want to do something cool!)
+    /// `color` is directly passed down to [`plot`], so all pixels of a line have the same color.
     ///
-    /// The `z` coordinates will be used later. For now, just ignore them and use `0.` for `z` when [`plot`]ting.
+    /// `z` is linearly interpolated from `p0` to `p1` along the major axis, so the z-buffer can do its job.
     ///
-    /// The method accepts 2 tuples of length 3. If you don't know how to work with them, you can learn about them on [Rust By Example].
-    /// The 2 tuples are meant to represent (x, y, z), in that order. Same goes for the tuple in [`plot`].
+    /// The method accepts anything that converts into a [`Point`], including tuples of length 3. If you don't know how to work with
+    /// tuples, you can learn about them on [Rust By Example]. The tuples are meant to represent (x, y, z), in that order.
     ///
-    /// If you need a refresher on the line algorithm, check out the [Wikipedia page].
-    ///
-    /// [`plot`]: #method.plot
+    /// [`plot`]: #tymethod.plot
+    /// [`rasterizer`]: #method.rasterizer
+    /// [`Point`]: ../util/struct.Point.html
     /// [Rust By Example]: https://doc.rust-lang.org/stable/rust-by-example/primitives/tuples.html
     /// [Wikipedia page]: https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm
-    fn draw_line(&mut self, p0: (f64, f64, f64), p1: (f64, f64, f64), color: Rgb) {
-        todo!("draw_line")
+    fn draw_line<P0: Into<Point>, P1: Into<Point>>(&mut self, p0: P0, p1: P1, color: C) {
+        let rasterizer = self.rasterizer();
+        rasterizer.line(p0.into(), p1.into(), &mut |x, y, z| {
+            self.plot(x, y, z, color)
+        });
+    }
+
+    /// Draw a line from `p0` to `p1` that is `width` pixels thick.
+    ///
+    /// The thick line is the rectangle around the line from `p0` to `p1`, filled as 2 triangles with [`fill_triangle`], so `z` is
+    /// interpolated along it just like in [`draw_line`]. The ends are cut flat at `p0` and `p1`.
+    /// Widths of 1 or less fall back to [`draw_line`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// img.draw_line_width((50., 50., 0.), (450., 300., 0.), 8., Rgb::WHITE);
+    /// ```
+    ///
+    /// [`draw_line`]: #method.draw_line
+    /// [`fill_triangle`]: #method.fill_triangle
+    fn draw_line_width<P0: Into<Point>, P1: Into<Point>>(
+        &mut self,
+        p0: P0,
+        p1: P1,
+        width: f64,
+        color: C,
+    ) {
+        let (p0, p1) = (p0.into(), p1.into());
+        if width <= 1. {
+            self.draw_line(p0, p1, color);
+            return;
+        }
+
+        let direction = Vec3::new(p1.x - p0.x, p1.y - p0.y, 0.).normalize();
+        // a single point is drawn as a square
+        let direction = if direction == Vec3::ZERO {
+            Vec3::new(1., 0., 0.)
+        } else {
+            direction
+        };
+        let offset = Vec3::new(-direction.y, direction.x, 0.) * (width as Float / 2.);
+
+        let (a, b, c, d) = (p0 + offset, p1 + offset, p1 - offset, p0 - offset);
+        self.fill_triangle(a, b, c, color);
+        self.fill_triangle(a, c, d, color);
     }
 
     /// Draw a line from (x, y, z) with a certain magnitude and angle, on the same z-plane as the point.
     ///
     /// Angle goes counter-clockwise from x axis.
     ///
-    /// Returns the other endpoint of the line `(x1, y1, z)` as a tuple.
+    /// Returns the other endpoint of the line `(x1, y1, z)` as a tuple, which converts into a [`Point`] if needed. The
+    /// tuple holds [`Float`]s, which are `f64` unless the `f32` feature is on.
     ///
     /// This function requires [`draw_line`] to be properly implemented.
     ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// let (x, y, z) = img.draw_line_degrees((100., 100., 0.), 90., 50., Rgb::WHITE);
+    /// assert!((x - 100.).abs() < 1e-9 && y == 150. && z == 0.);
+    /// assert_eq!(Some(Rgb::WHITE), img.get_pixel(100, 125));
+    /// ```
+    ///
     /// [`draw_line`]: #method.draw_line
-    fn draw_line_degrees(
+    /// [`Point`]: ../util/struct.Point.html
+    /// [`Float`]: ../util/type.Float.html
+    fn draw_line_degrees<P: Into<Point>>(
         &mut self,
-        p0: (f64, f64, f64),
+        p0: P,
         angle_degrees: f64,
         mag: f64,
-        color: Rgb,
-    ) -> (f64, f64, f64) {
+        color: C,
+    ) -> (Float, Float, Float) {
+        let p0 = p0.into();
         let (dx, dy) = util::polar_to_xy(mag, angle_degrees);
-        let p1 = (p0.0 + dx, p0.1 + dy, p0.2);
+        let p1 = p0.translated(dx as Float, dy as Float, 0.);
 
         self.draw_line(p0, p1, color);
-        p1
+        p1.into()
+    }
+
+    /// Draw a circle centered at (`cx`, `cy`) with radius `r`, on the plane at depth `z`.
+    ///
+    /// This uses the integer [midpoint circle algorithm], which only computes one eighth of the circle and mirrors it to the
+    /// other 7 octants. It is a lot faster than connecting many points on the circle with [`draw_line`].
+    ///
+    /// The center and radius are rounded to the nearest pixel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // a target
+    /// for r in (10..250).step_by(20) {
+    ///     img.draw_circle(250., 250., 0., r as f64, Rgb::WHITE);
+    /// }
+    /// ```
+    ///
+    /// [midpoint circle algorithm]: https://en.wikipedia.org/wiki/Midpoint_circle_algorithm
+    /// [`draw_line`]: #method.draw_line
+    fn draw_circle(&mut self, cx: f64, cy: f64, z: Float, r: f64, color: C) {
+        let (cx, cy, r) = (cx.round() as i64, cy.round() as i64, r.round() as i64);
+
+        let (mut x, mut y) = (r, 0);
+        let mut d = 1 - r;
+        while x >= y {
+            for &(dx, dy) in &[(x, y), (y, x)] {
+                self.plot(cx + dx, cy + dy, z, color);
+                self.plot(cx - dx, cy + dy, z, color);
+                self.plot(cx + dx, cy - dy, z, color);
+                self.plot(cx - dx, cy - dy, z, color);
+            }
+
+            y += 1;
+            if d < 0 {
+                d += 2 * y + 1;
+            } else {
+                x -= 1;
+                d += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// Draw an ellipse centered at (`cx`, `cy`) on the plane `z = 0`.
+    ///
+    /// `a` is the radius along the x axis and `b` is the radius along the y axis, before the ellipse is rotated
+    /// counter-clockwise by `rotation_degrees` around its center.
+    ///
+    /// Axis-aligned ellipses (rotation of a multiple of 90 degrees) are drawn with the integer [midpoint ellipse algorithm].
+    /// Rotated ones are approximated by a polygon, with sides short enough to look smooth.
+    ///
+    /// A radius of `0` flattens the ellipse into a line, `2 * a` or `2 * b` long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // a flower
+    /// for i in 0..6 {
+    ///     img.draw_ellipse(250., 250., 200., 50., i as f64 * 30., Rgb::new(255, 100, 200));
+    /// }
+    /// ```
+    ///
+    /// [midpoint ellipse algorithm]: https://www.geeksforgeeks.org/midpoint-ellipse-drawing-algorithm/
+    fn draw_ellipse(&mut self, cx: f64, cy: f64, a: f64, b: f64, rotation_degrees: f64, color: C) {
+        let quarter_turns = rotation_degrees / 90.;
+        if quarter_turns.fract() == 0. {
+            // axis-aligned: swap the radii if the ellipse is turned sideways
+            let (a, b) = if quarter_turns.rem_euclid(2.) == 0. {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            let (cx, cy) = (cx.round() as i64, cy.round() as i64);
+            let (a, b) = (a.round().abs(), b.round().abs());
+            if a == 0. || b == 0. {
+                // a flat ellipse is a line (or a point), where the algorithm below would only find the ends
+                let (cx, cy) = (cx as Float, cy as Float);
+                let (a, b) = (a as Float, b as Float);
+                self.draw_line((cx - a, cy - b, 0.), (cx + a, cy + b, 0.), color);
+                return;
+            }
+            let (a2, b2) = (a * a, b * b);
+
+            let mut plot4 = |x: i64, y: i64| {
+                self.plot(cx + x, cy + y, 0., color);
+                self.plot(cx - x, cy + y, 0., color);
+                self.plot(cx + x, cy - y, 0., color);
+                self.plot(cx - x, cy - y, 0., color);
+            };
+
+            // region 1: slope is shallower than -1, step in x
+            let (mut x, mut y) = (0., b);
+            let (mut dx, mut dy) = (0., 2. * a2 * y);
+            let mut d = b2 - a2 * b + a2 / 4.;
+            while dx < dy {
+                plot4(x as i64, y as i64);
+                x += 1.;
+                dx += 2. * b2;
+                if d < 0. {
+                    d += dx + b2;
+                } else {
+                    y -= 1.;
+                    dy -= 2. * a2;
+                    d += dx - dy + b2;
+                }
+            }
+
+            // region 2: slope is steeper than -1, step in y
+            let mut d = b2 * (x + 0.5) * (x + 0.5) + a2 * (y - 1.) * (y - 1.) - a2 * b2;
+            while y >= 0. {
+                plot4(x as i64, y as i64);
+                y -= 1.;
+                dy -= 2. * a2;
+                if d > 0. {
+                    d += a2 - dy;
+                } else {
+                    x += 1.;
+                    dx += 2. * b2;
+                    d += dx - dy + a2;
+                }
+            }
+        } else {
+            let (sin, cos) = rotation_degrees.to_radians().sin_cos();
+            // Ramanujan's approximation of the perimeter, aiming for sides of about 2 pixels
+            let h = ((a - b) / (a + b)).powi(2);
+            let perimeter =
+                std::f64::consts::PI * (a + b) * (1. + 3. * h / (10. + (4. - 3. * h).sqrt()));
+            let sides = ((perimeter / 2.).ceil() as usize).max(8);
+
+            let point = |i: usize| {
+                let (ty, tx) = (std::f64::consts::TAU * i as f64 / sides as f64).sin_cos();
+                let (x, y) = (a * tx, b * ty);
+                (cx + x * cos - y * sin, cy + x * sin + y * cos, 0.)
+            };
+            for i in 0..sides {
+                self.draw_line(point(i), point(i + 1), color);
+            }
+        }
+    }
+
+    /// Draw an arc of the circle centered at `center` with radius `r`, from angle `start_degrees` to `end_degrees`.
+    ///
+    /// Angles go counter-clockwise from the x axis, like in [`draw_line_degrees`]. The arc is swept from the start angle to
+    /// the end angle, so its direction depends on the order of the angles:
+    /// - If `end_degrees > start_degrees`, the arc goes counter-clockwise. Ranges wrapping around 0, like `350.` to `370.`,
+    ///   work as expected.
+    /// - If `end_degrees < start_degrees`, the arc goes clockwise, so `350.` to `10.` is the long way around.
+    ///
+    /// Sweeps larger than a full turn are capped at a full circle.
+    ///
+    /// The arc lies on the plane of `center`, and is made of segments about 2 pixels long drawn with [`draw_line`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // a smiley face
+    /// img.draw_circle(250., 250., 0., 200., Rgb::WHITE);
+    /// img.draw_circle(180., 320., 0., 20., Rgb::WHITE);
+    /// img.draw_circle(320., 320., 0., 20., Rgb::WHITE);
+    /// img.draw_arc((250., 250., 0.), 120., 200., 340., Rgb::WHITE);
+    /// ```
+    ///
+    /// [`draw_line`]: #method.draw_line
+    /// [`draw_line_degrees`]: #method.draw_line_degrees
+    fn draw_arc<P: Into<Point>>(
+        &mut self,
+        center: P,
+        r: f64,
+        start_degrees: f64,
+        end_degrees: f64,
+        color: C,
+    ) {
+        let center = center.into();
+        let sweep = (end_degrees - start_degrees).clamp(-360., 360.);
+        let steps = ((sweep.abs().to_radians() * r / 2.).ceil() as usize).max(1);
+
+        let point = |i: usize| {
+            let angle = start_degrees + sweep * i as f64 / steps as f64;
+            let (dx, dy) = util::polar_to_xy(r, angle);
+            center.translated(dx as Float, dy as Float, 0.)
+        };
+
+        let mut p0 = point(0);
+        for i in 1..=steps {
+            let p1 = point(i);
+            self.draw_line(p0, p1, color);
+            p0 = p1;
+        }
+    }
+
+    /// Draw the cubic [Bézier curve] starting at `p0` and ending at `p3`, with control points `p1` and `p2`.
+    ///
+    /// The curve is sampled at `steps + 1` evenly spaced values of its parameter, and consecutive samples are joined with
+    /// [`draw_line`]. More steps make a smoother curve. `z` is part of the curve, like `x` and `y`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // an "S"
+    /// img.draw_bezier((100., 100., 0.), (500., 100., 0.), (0., 400., 0.), (400., 400., 0.), 50, Rgb::WHITE);
+    /// assert_eq!(Some(Rgb::WHITE), img.get_pixel(100, 100));
+    /// assert_eq!(Some(Rgb::WHITE), img.get_pixel(400, 400));
+    /// ```
+    ///
+    /// [Bézier curve]: https://en.wikipedia.org/wiki/B%C3%A9zier_curve
+    /// [`draw_line`]: #method.draw_line
+    fn draw_bezier<P: Into<Point>>(&mut self, p0: P, p1: P, p2: P, p3: P, steps: usize, color: C) {
+        let (p0, p1, p2, p3) = (p0.into(), p1.into(), p2.into(), p3.into());
+        // polynomial coefficients: p(t) = a t^3 + b t^2 + c t + p0
+        let a = (p3 - p0) + (p1 - p2) * 3.;
+        let b = ((p0 - p1) + (p2 - p1)) * 3.;
+        let c = (p1 - p0) * 3.;
+        draw_cubic(self, p0, a, b, c, steps, color);
+    }
+
+    /// Draw the cubic [Hermite curve] from `p0` to `p1`, leaving `p0` with tangent `r0` and arriving at `p1` with tangent
+    /// `r1`.
+    ///
+    /// Sampled like [`draw_bezier`]. Longer tangents pull the curve further in their direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // a hill: leave going up, arrive going down
+    /// img.draw_hermite((50., 100., 0.), (450., 100., 0.), (0., 600., 0.), (0., -600., 0.), 50, Rgb::WHITE);
+    /// ```
+    ///
+    /// [Hermite curve]: https://en.wikipedia.org/wiki/Cubic_Hermite_spline
+    /// [`draw_bezier`]: #method.draw_bezier
+    fn draw_hermite<P, V>(&mut self, p0: P, p1: P, r0: V, r1: V, steps: usize, color: C)
+    where
+        P: Into<Point>,
+        V: Into<Vec3>,
+    {
+        let (p0, p1, r0, r1) = (p0.into(), p1.into(), r0.into(), r1.into());
+        // polynomial coefficients: p(t) = a t^3 + b t^2 + c t + p0
+        let a = (p0 - p1) * 2. + r0 + r1;
+        let b = (p1 - p0) * 3. - r0 * 2. - r1;
+        draw_cubic(self, p0, a, b, r0, steps, color);
+    }
+
+    /// Draw the outline of a `width` by `height` rectangle, with one corner at `corner`.
+    ///
+    /// The rectangle extends from `corner` towards positive x and y (negative `width` or `height` flip that direction), and
+    /// covers the same pixels as the border of [`fill_rect`] with the same arguments. Everything is on the plane of `corner`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// img.fill_rect((100., 100., 0.), 300., 200., Rgb::new(0, 0, 255));
+    /// img.draw_rect((100., 100., 1.), 300., 200., Rgb::WHITE);
+    /// ```
+    ///
+    /// [`fill_rect`]: #method.fill_rect
+    fn draw_rect<P: Into<Point>>(&mut self, corner: P, width: f64, height: f64, color: C) {
+        let corner = corner.into();
+        if let Some((x0, y0, x1, y1)) = rect_bounds(corner, width, height) {
+            let point = |x: i64, y: i64| Point::new(x as Float, y as Float, corner.z);
+            let (x1, y1) = (x1 - 1, y1 - 1);
+            self.draw_line(point(x0, y0), point(x1, y0), color);
+            self.draw_line(point(x1, y0), point(x1, y1), color);
+            self.draw_line(point(x1, y1), point(x0, y1), color);
+            self.draw_line(point(x0, y1), point(x0, y0), color);
+        }
+    }
+
+    /// Fill a `width` by `height` rectangle, with one corner at `corner`.
+    ///
+    /// The rectangle covers pixels from `corner` (rounded) up to, but not including, `corner + (width, height)`.
+    /// Pixels are plotted row by row, which is a lot faster than drawing a line for every row.
+    ///
+    /// `fill` is anything that converts into a [`Fill`]: a color, a [`Gradient`] or a pattern. See [`draw_rect`] for an
+    /// example.
+    ///
+    /// [`Fill`]: ../fill/enum.Fill.html
+    /// [`Gradient`]: ../gradient/struct.Gradient.html
+    /// [`draw_rect`]: #method.draw_rect
+    fn fill_rect<'a, P, F>(&mut self, corner: P, width: f64, height: f64, fill: F)
+    where
+        P: Into<Point>,
+        F: Into<Fill<'a, C>>,
+    {
+        let (corner, fill) = (corner.into(), fill.into());
+        if let Some((x0, y0, x1, y1)) = rect_bounds(corner, width, height) {
+            let (columns, rows) = self.clip_bounds();
+            for y in y0.max(rows.start)..y1.min(rows.end) {
+                for x in x0.max(columns.start)..x1.min(columns.end) {
+                    self.plot(x, y, corner.z, fill.color_at(x, y));
+                }
+            }
+        }
+    }
+
+    /// Draw the outline of a `width` by `height` rectangle with corners rounded to `radius`, with one corner at `corner`.
+    ///
+    /// Covers the same area as [`draw_rect`], with quarter circles drawn by [`draw_arc`] in the corners. The radius is capped
+    /// at half the shorter side, so a large radius gives a stadium (or circle) shape. A radius of `0` is the same as
+    /// [`draw_rect`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // a button
+    /// img.fill_round_rect((150., 200., 0.), 200., 60., 15., Rgb::new(50, 100, 200));
+    /// img.draw_round_rect((150., 200., 1.), 200., 60., 15., Rgb::WHITE);
+    /// img.draw_text(215, 225, "Click", 2, Rgb::WHITE);
+    ///
+    /// assert_eq!(Some(Rgb::BLACK), img.get_pixel(151, 201));
+    /// assert_eq!(Some(Rgb::new(50, 100, 200)), img.get_pixel(170, 210));
+    /// ```
+    ///
+    /// [`draw_rect`]: #method.draw_rect
+    /// [`draw_arc`]: #method.draw_arc
+    fn draw_round_rect<P: Into<Point>>(
+        &mut self,
+        corner: P,
+        width: f64,
+        height: f64,
+        radius: f64,
+        color: C,
+    ) {
+        let corner = corner.into();
+        let (x0, y0, x1, y1) = match rect_bounds(corner, width, height) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let (left, bottom, right, top) = (x0 as f64, y0 as f64, (x1 - 1) as f64, (y1 - 1) as f64);
+        let r = radius.clamp(0., (right - left).min(top - bottom) / 2.);
+        if r < 0.5 {
+            self.draw_rect(corner, width, height, color);
+            return;
+        }
+
+        let point = |x: f64, y: f64| Point::new(x as Float, y as Float, corner.z);
+        self.draw_line(point(left + r, bottom), point(right - r, bottom), color);
+        self.draw_line(point(right, bottom + r), point(right, top - r), color);
+        self.draw_line(point(right - r, top), point(left + r, top), color);
+        self.draw_line(point(left, top - r), point(left, bottom + r), color);
+
+        self.draw_arc(point(right - r, bottom + r), r, 270., 360., color);
+        self.draw_arc(point(right - r, top - r), r, 0., 90., color);
+        self.draw_arc(point(left + r, top - r), r, 90., 180., color);
+        self.draw_arc(point(left + r, bottom + r), r, 180., 270., color);
+    }
+
+    /// Fill a `width` by `height` rectangle with corners rounded to `radius`, with one corner at `corner`.
+    ///
+    /// Covers the same area as [`fill_rect`] minus the rounded corners, and takes the same kinds of [`Fill`]. See
+    /// [`draw_round_rect`] for an example.
+    ///
+    /// [`fill_rect`]: #method.fill_rect
+    /// [`Fill`]: ../fill/enum.Fill.html
+    /// [`draw_round_rect`]: #method.draw_round_rect
+    fn fill_round_rect<'a, P, F>(
+        &mut self,
+        corner: P,
+        width: f64,
+        height: f64,
+        radius: f64,
+        fill: F,
+    ) where
+        P: Into<Point>,
+        F: Into<Fill<'a, C>>,
+    {
+        let corner = corner.into();
+        let (x0, y0, x1, y1) = match rect_bounds(corner, width, height) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let (left, bottom, right, top) = (x0 as f64, y0 as f64, x1 as f64, y1 as f64);
+        let r = radius.clamp(0., (right - left).min(top - bottom) / 2.);
+
+        // outline of the shape, counter-clockwise from the bottom right corner
+        let steps = ((r * std::f64::consts::FRAC_PI_2 / 2.).ceil() as usize).max(1);
+        let mut outline = Vec::with_capacity(4 * (steps + 1));
+        let centers = [
+            (right - r, bottom + r),
+            (right - r, top - r),
+            (left + r, top - r),
+            (left + r, bottom + r),
+        ];
+        for (i, &(cx, cy)) in centers.iter().enumerate() {
+            for step in 0..=steps {
+                let angle = 270. + 90. * (i as f64 + step as f64 / steps as f64);
+                let (dx, dy) = util::polar_to_xy(r, angle);
+                outline.push(Point::new((cx + dx) as Float, (cy + dy) as Float, corner.z));
+            }
+        }
+        self.fill_polygon(&outline, fill);
+    }
+
+    /// Draw lines connecting consecutive `points`. If `closed` is `true`, the last point is also connected back to the first.
+    ///
+    /// Accepts a slice of anything that converts into a [`Point`], such as `(x, y, z)` tuples.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // a 5 pointed star
+    /// let star: Vec<(f64, f64, f64)> = (0..5)
+    ///     .map(|i| {
+    ///         let angle = (90. + 144. * i as f64).to_radians();
+    ///         (250. + 200. * angle.cos(), 250. + 200. * angle.sin(), 0.)
+    ///     })
+    ///     .collect();
+    /// img.draw_polyline(&star, true, Rgb::new(255, 200, 0));
+    ///
+    /// // a zig-zag
+    /// img.draw_polyline(&[(0., 0., 0.), (50., 50., 0.), (100., 0., 0.)], false, Rgb::WHITE);
+    /// ```
+    ///
+    /// [`Point`]: ../util/struct.Point.html
+    fn draw_polyline<P: Into<Point> + Copy>(&mut self, points: &[P], closed: bool, color: C) {
+        for pair in points.windows(2) {
+            self.draw_line(pair[0], pair[1], color);
+        }
+        if closed && points.len() > 2 {
+            self.draw_line(points[points.len() - 1], points[0], color);
+        }
+    }
+
+    /// Fill the triangle `p0`, `p1`, `p2` with `color`.
+    ///
+    /// The pixels come from the [`rasterizer`]'s [`triangle`] method, which by default is a half-space (barycentric)
+    /// rasterizer. `z` is interpolated across the triangle, so filled triangles are depth tested against each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// img.fill_triangle((50., 50., 0.), (450., 50., 0.), (250., 400., 0.), Rgb::new(255, 0, 0));
+    /// // in front of the red triangle on the right, behind it on the left
+    /// img.fill_triangle((50., 300., -10.), (450., 300., 10.), (250., 0., 0.), Rgb::new(0, 0, 255));
+    /// ```
+    ///
+    /// [`rasterizer`]: #method.rasterizer
+    /// [`triangle`]: ../raster/trait.Rasterizer.html#method.triangle
+    fn fill_triangle<P0, P1, P2>(&mut self, p0: P0, p1: P1, p2: P2, color: C)
+    where
+        P0: Into<Point>,
+        P1: Into<Point>,
+        P2: Into<Point>,
+    {
+        let (rasterizer, (columns, rows)) = (self.rasterizer(), self.clip_bounds());
+        rasterizer.triangle_in_rect(
+            p0.into(),
+            p1.into(),
+            p2.into(),
+            columns,
+            rows,
+            &mut |x, y, z| self.plot(x, y, z, color),
+        );
+    }
+
+    /// Fill the polygon with vertices `points` with a color, [`Gradient`] or pattern (see [`Fill`]).
+    ///
+    /// The polygon is closed automatically, and can be concave or self-intersecting: a pixel is inside if a ray from it
+    /// crosses the outline an odd number of times (the even-odd rule). Like [`fill_triangle`], pixels are sampled at integer
+    /// coordinates, and `z` is interpolated from the vertices, which makes sense as long as they are on the same plane.
+    /// Nothing is drawn if a vertex has a NaN or infinite `x` or `y`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// // an arrow
+    /// let arrow = [
+    ///     (100., 200., 0.),
+    ///     (300., 200., 0.),
+    ///     (300., 100., 0.),
+    ///     (450., 250., 0.),
+    ///     (300., 400., 0.),
+    ///     (300., 300., 0.),
+    ///     (100., 300., 0.),
+    /// ];
+    /// img.fill_polygon(&arrow, Rgb::new(0, 150, 0));
+    /// assert_eq!(Some(Rgb::new(0, 150, 0)), img.get_pixel(400, 250));
+    /// assert_eq!(Some(Rgb::BLACK), img.get_pixel(200, 150));
+    /// ```
+    ///
+    /// [`Gradient`]: ../gradient/struct.Gradient.html
+    /// [`Fill`]: ../fill/enum.Fill.html
+    /// [`fill_triangle`]: #method.fill_triangle
+    fn fill_polygon<'a, P, F>(&mut self, points: &[P], fill: F)
+    where
+        P: Into<Point> + Copy,
+        F: Into<Fill<'a, C>>,
+    {
+        let fill = fill.into();
+        fill_polygon_with(self, points, |x, y| fill.color_at(x, y));
+    }
+
+    /// Flood fill the area around (`x`, `y`) with `fill_color`.
+    ///
+    /// Every pixel connected to (`x`, `y`) (horizontally or vertically) that has the same color as (`x`, `y`) gets filled, like
+    /// the paint bucket in an image editor. Pixels are written with [`set_pixel`], so the z-buffer doesn't get in the way.
+    ///
+    /// The fill stays inside [`clip_bounds`]. Along axes that wrap around, it covers the image once, without going around
+    /// its edges.
+    ///
+    /// An explicit stack is used instead of recursion, so large regions can't overflow the call stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// img.draw_circle(250., 250., 0., 100., Rgb::WHITE);
+    /// img.flood_fill(250, 250, Rgb::new(0, 0, 255));
+    ///
+    /// assert_eq!(Some(Rgb::new(0, 0, 255)), img.get_pixel(250, 250));
+    /// assert_eq!(Some(Rgb::WHITE), img.get_pixel(350, 250));
+    /// assert_eq!(Some(Rgb::BLACK), img.get_pixel(0, 0));
+    /// ```
+    ///
+    /// [`set_pixel`]: #method.set_pixel
+    /// [`clip_bounds`]: #method.clip_bounds
+    fn flood_fill(&mut self, x: i64, y: i64, fill_color: C) {
+        if let Some(target) = self.get_pixel(x, y) {
+            fill_region(self, x, y, fill_color, |c| c == target);
+        }
+    }
+
+    /// Fill the area around (`x`, `y`) with `fill_color`, up to pixels of `boundary_color`.
+    ///
+    /// Unlike [`flood_fill`], the area doesn't have to be one color: everything connected to (`x`, `y`) that isn't
+    /// `boundary_color` gets filled. Like [`flood_fill`], pixels are written with [`set_pixel`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// img.draw_circle(250., 250., 0., 100., Rgb::WHITE);
+    /// // a line inside the circle doesn't stop the fill
+    /// img.draw_line((200., 250., 0.), (300., 250., 0.), Rgb::new(255, 0, 0));
+    /// img.boundary_fill(250, 200, Rgb::new(0, 0, 255), Rgb::WHITE);
+    ///
+    /// assert_eq!(Some(Rgb::new(0, 0, 255)), img.get_pixel(250, 300));
+    /// assert_eq!(Some(Rgb::BLACK), img.get_pixel(0, 0));
+    /// ```
+    ///
+    /// [`flood_fill`]: #method.flood_fill
+    /// [`set_pixel`]: #method.set_pixel
+    fn boundary_fill(&mut self, x: i64, y: i64, fill_color: C, boundary_color: C) {
+        fill_region(self, x, y, fill_color, |c| c != boundary_color);
+    }
+
+    /// Draw `text` with the built-in 5x7 [`Font`], with each font pixel drawn as a `scale` by `scale` square.
+    ///
+    /// (`x`, `y`) is the bottom left corner of the first line of text. Lines are separated by `'\n'`, and go downwards.
+    /// Characters missing from the font are drawn as `'?'`. Text is drawn at `z = 0`.
+    ///
+    /// Use [`Font::text_size`] to measure text, e.g. to center it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{font::Font, prelude::*};
+    ///
+    /// let mut img = Ppm::new();
+    ///
+    /// for frame in 0..3 {
+    ///     img.clear(Rgb::BLACK);
+    ///     img.draw_text(10, 10, &format!("frame {}", frame), 2, Rgb::WHITE);
+    ///     // img.save(&format!("frame{}.png", frame));
+    /// }
+    ///
+    /// // a centered title
+    /// let (w, h) = Font::builtin().text_size("Hello!", 4);
+    /// img.draw_text(250 - w as i64 / 2, 250 - h as i64 / 2, "Hello!", 4, Rgb::WHITE);
+    /// ```
+    ///
+    /// [`Font`]: ../font/struct.Font.html
+    /// [`Font::text_size`]: ../font/struct.Font.html#method.text_size
+    fn draw_text(&mut self, x: i64, y: i64, text: &str, scale: usize, color: C) {
+        self.draw_text_with(Font::builtin(), x, y, text, scale, color);
+    }
+
+    /// Same as [`draw_text`], but with any [`Font`], e.g. one loaded from a BDF or PSF file with [`Font::open`].
+    ///
+    /// Characters missing from `font` are drawn as [`Font::REPLACEMENT`], or skipped if the font doesn't have it either.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use graphics::{font::Font, prelude::*};
+    ///
+    /// let font = Font::open("/usr/share/consolefonts/Lat2-Terminus16.psf").expect("error loading font");
+    /// let mut img = Ppm::new();
+    /// img.draw_text_with(&font, 10, 10, "Hello!", 2, Rgb::WHITE);
+    /// ```
+    ///
+    /// [`draw_text`]: #method.draw_text
+    /// [`Font`]: ../font/struct.Font.html
+    /// [`Font::open`]: ../font/struct.Font.html#method.open
+    /// [`Font::REPLACEMENT`]: ../font/struct.Font.html#associatedconstant.REPLACEMENT
+    fn draw_text_with(&mut self, font: &Font, x: i64, y: i64, text: &str, scale: usize, color: C) {
+        let (width, height, scale) = (font.width() as i64, font.height() as i64, scale as i64);
+
+        for (line_index, line) in text.split('\n').enumerate() {
+            let line_y = y - line_index as i64 * (height + 1) * scale;
+            for (char_index, c) in line.chars().enumerate() {
+                let cell_x = x + char_index as i64 * (width + 1) * scale;
+                let rows = match font.glyph(c) {
+                    Some(rows) => rows,
+                    None => continue,
+                };
+                for row in 0..height {
+                    for col in 0..width {
+                        if !font.is_set(rows, col as usize, row as usize) {
+                            continue;
+                        }
+                        let (px, py) = (cell_x + col * scale, line_y + (height - 1 - row) * scale);
+                        for dy in 0..scale {
+                            for dx in 0..scale {
+                                self.plot(px + dx, py + dy, 0., color);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copy `image` onto this screen, with its pixel (0, 0) at (`x`, `y`).
+    ///
+    /// Pixels of `image` that are exactly the `transparent` color (a chroma key) are skipped, so sprites can be drawn
+    /// over a background. Pixels falling outside of this screen are clipped by [`plot`].
+    ///
+    /// The whole image is drawn at depth `z`, so to go on top of something already drawn it needs a larger `z`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::prelude::*;
+    ///
+    /// // render the static background once
+    /// let mut background = Ppm::new();
+    /// background.fill_rect((0., 0., 0.), 500., 250., Rgb::new(0, 0, 255));
+    ///
+    /// let mut sprite = PpmBuilder::new(20, 20, 255).bg_color(Rgb::BLACK).build();
+    /// sprite.draw_circle(10., 10., 0., 8., Rgb::WHITE);
+    ///
+    /// let mut img = Ppm::new();
+    /// for frame in 0..3 {
+    ///     img.clear(Rgb::BLACK);
+    ///     img.draw_image(&background, 0, 0, 0., None);
+    ///     img.draw_image(&sprite, frame * 20, 100, 1., Some(Rgb::BLACK));
+    ///     // img.save(&format!("frame{}.png", frame));
+    /// }
+    /// assert_eq!(Some(Rgb::WHITE), img.get_pixel(40 + 10, 100 + 2));
+    /// assert_eq!(Some(Rgb::new(0, 0, 255)), img.get_pixel(40 + 10, 100 + 10));
+    /// ```
+    ///
+    /// [`plot`]: #tymethod.plot
+    fn draw_image<S: Screen<C>>(
+        &mut self,
+        image: &S,
+        x: i64,
+        y: i64,
+        z: Float,
+        transparent: Option<C>,
+    ) {
+        for iy in 0..image.height() as i64 {
+            for ix in 0..image.width() as i64 {
+                match image.get_pixel(ix, iy) {
+                    Some(color) if Some(color) != transparent => {
+                        self.plot(x + ix, y + iy, z, color)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Options for [`save_with`]. Options that don't apply to the format being saved are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::screen::SaveOptions;
+///
+/// let options = SaveOptions::new().quality(0).compression(20);
+/// assert_eq!(Some(1), options.quality);
+/// assert_eq!(Some(9), options.compression);
+/// assert_eq!(None, options.resize);
+/// ```
+///
+/// [`save_with`]: ./trait.Screen.html#method.save_with
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SaveOptions {
+    /// Quality of lossy formats like JPEG and WebP, from `1` (smallest file) to `100` (best image).
+    pub quality: Option<u8>,
+    /// PNG compression level, from `0` (fastest) to `9` (smallest file).
+    pub compression: Option<u8>,
+    /// Width and height to scale the image to, ignoring its aspect ratio.
+    pub resize: Option<(usize, usize)>,
+}
+
+impl SaveOptions {
+    /// No options, which saves like [`save`].
+    ///
+    /// [`save`]: ./trait.Screen.html#tymethod.save
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the quality of lossy formats, clamped to `1..=100`.
+    pub fn quality(mut self, quality: u8) -> Self {
+        self.quality = Some(quality.clamp(1, 100));
+        self
+    }
+
+    /// Set the PNG compression level, clamped to `0..=9`.
+    pub fn compression(mut self, level: u8) -> Self {
+        self.compression = Some(level.min(9));
+        self
+    }
+
+    /// Scale the image to `width` by `height`.
+    pub fn resize(mut self, width: usize, height: usize) -> Self {
+        self.resize = Some((width, height));
+        self
+    }
+
+    /// The ImageMagick flags for these options.
+    pub(crate) fn magick_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some((width, height)) = self.resize {
+            args.extend(vec![
+                "-resize".to_string(),
+                format!("{}x{}!", width, height),
+            ]);
+        }
+        if let Some(quality) = self.quality {
+            args.extend(vec![
+                "-quality".to_string(),
+                quality.clamp(1, 100).to_string(),
+            ]);
+        }
+        if let Some(level) = self.compression {
+            args.extend(vec![
+                "-define".to_string(),
+                format!("png:compression-level={}", level.min(9)),
+            ]);
+        }
+        args
+    }
+
+    /// Convert `data`, read by ImageMagick as `input`, to `file_path` with these options.
+    pub(crate) fn convert(&self, input: &str, file_path: &str, data: &[u8]) -> error::Result<()> {
+        let options = self.magick_args();
+        let mut args = vec![input];
+        args.extend(options.iter().map(String::as_str));
+        args.push(file_path);
+        magick::convert_with_input(&args, data)
+    }
+}
+
+/// Draw the curve `p(t) = a t^3 + b t^2 + c t + p0` for `t` from 0 to 1, as `steps` lines.
+fn draw_cubic<S, C>(screen: &mut S, p0: Point, a: Vec3, b: Vec3, c: Vec3, steps: usize, color: C)
+where
+    S: Screen<C> + ?Sized,
+    C: Color,
+{
+    let steps = steps.max(1);
+    let mut previous = p0;
+    for i in 1..=steps {
+        let t = i as Float / steps as Float;
+        let next = p0 + ((a * t + b) * t + c) * t;
+        screen.draw_line(previous, next, color);
+        previous = next;
+    }
+}
+
+/// Scanline fill of the polygon `points` with the even-odd rule, taking the color of each pixel from `shade`.
+fn fill_polygon_with<S, C, P, F>(screen: &mut S, points: &[P], mut shade: F)
+where
+    S: Screen<C> + ?Sized,
+    C: Color,
+    P: Into<Point> + Copy,
+    F: FnMut(i64, i64) -> C,
+{
+    let points: Vec<Point> = points.iter().map(|&p| p.into()).collect();
+    if points.len() < 3 || !points.iter().all(|p| p.x.is_finite() && p.y.is_finite()) {
+        return;
+    }
+    let (columns, rows) = screen.clip_bounds();
+    let min_y = (points
+        .iter()
+        .map(|p| p.y)
+        .fold(Float::INFINITY, Float::min)
+        .ceil() as i64)
+        .max(rows.start);
+    let max_y = (points
+        .iter()
+        .map(|p| p.y)
+        .fold(Float::NEG_INFINITY, Float::max)
+        .floor() as i64)
+        .min(rows.end.saturating_sub(1));
+
+    let mut crossings: Vec<(Float, Float)> = vec![];
+    for y in min_y..=max_y {
+        let scan_y = y as Float;
+        crossings.clear();
+        for (i, &a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            // half-open, so a vertex shared by two edges is only counted once
+            if (a.y <= scan_y) != (b.y <= scan_y) {
+                let t = (scan_y - a.y) / (b.y - a.y);
+                crossings.push((a.x + (b.x - a.x) * t, a.z + (b.z - a.z) * t));
+            }
+        }
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        for span in crossings.chunks_exact(2) {
+            let ((xa, za), (xb, zb)) = (span[0], span[1]);
+            let (x0, x1) = (xa.ceil() as i64, xb.ceil() as i64);
+            for x in x0.max(columns.start)..x1.min(columns.end) {
+                let z = if xb == xa {
+                    za
+                } else {
+                    za + (zb - za) * (x as Float - xa) / (xb - xa)
+                };
+                screen.plot(x, y, z, shade(x, y));
+            }
+        }
+    }
+}
+
+/// Fill the 4-connected region around (`x`, `y`) made of pixels that are `inside`, visiting each pixel at most once.
+fn fill_region<S, C, F>(screen: &mut S, x: i64, y: i64, fill_color: C, inside: F)
+where
+    S: Screen<C> + ?Sized,
+    C: Color,
+    F: Fn(C) -> bool,
+{
+    let (width, height) = (screen.width(), screen.height());
+    let (left, bottom) = screen.origin().corner(width, height);
+    let (columns, rows) = screen.clip_bounds();
+    let (columns, rows) = (
+        region_bounds(columns, left, width),
+        region_bounds(rows, bottom, height),
+    );
+    let stride = (columns.end - columns.start) as usize;
+    let mut visited = vec![false; stride * (rows.end - rows.start) as usize];
+
+    let mut stack = vec![(x, y)];
+    while let Some((x, y)) = stack.pop() {
+        if !columns.contains(&x) || !rows.contains(&y) {
+            continue;
+        }
+        let index = (y - rows.start) as usize * stride + (x - columns.start) as usize;
+        if visited[index] {
+            continue;
+        }
+        visited[index] = true;
+
+        if screen.get_pixel(x, y).is_some_and(&inside) {
+            screen.set_pixel(x, y, fill_color);
+            stack.extend_from_slice(&[(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]);
+        }
+    }
+}
+
+/// The coordinates [`fill_region`] visits along an axis: `bounds` from [`Screen::clip_bounds`], or the `size` pixels of
+/// the image from `start` if `bounds` are wider than that, which happens along axes that wrap around.
+///
+/// [`fill_region`]: ./fn.fill_region.html
+/// [`Screen::clip_bounds`]: ./trait.Screen.html#method.clip_bounds
+fn region_bounds(bounds: Range<i64>, start: i64, size: usize) -> Range<i64> {
+    // the end is after the start, so the difference fits in a u64
+    let span = if bounds.is_empty() {
+        0
+    } else {
+        bounds.end.wrapping_sub(bounds.start) as u64
+    };
+    if span > size as u64 {
+        start..start + size as i64
+    } else {
+        bounds
+    }
+}
+
+/// Pixel bounds `(x0, y0, x1, y1)` (end exclusive) of a rectangle, or `None` if it is empty.
+fn rect_bounds(corner: Point, width: f64, height: f64) -> Option<(i64, i64, i64, i64)> {
+    let (x, y) = (corner.x, corner.y);
+    let (xa, xb) = (x.round() as i64, (x + width as Float).round() as i64);
+    let (ya, yb) = (y.round() as i64, (y + height as Float).round() as i64);
+    let (x0, x1) = (xa.min(xb), xa.max(xb));
+    let (y0, y1) = (ya.min(yb), ya.max(yb));
+    if x0 == x1 || y0 == y1 {
+        None
+    } else {
+        Some((x0, y0, x1, y1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        font::Font,
+        gradient::Gradient,
+        ppm::{Origin, Ppm, PpmBuilder, WrapMode},
+        testing::MockScreen,
+    };
+
+    const RED: Rgb = Rgb::new(255, 0, 0);
+    const BLUE: Rgb = Rgb::new(0, 0, 255);
+
+    fn img(width: usize, height: usize) -> Ppm {
+        PpmBuilder::new(height, width, 255).build()
+    }
+
+    #[test]
+    fn test_flood_fill_recolors() {
+        // the rectangle is at z = 0, so plotting over it at z = 0 would fail the depth test
+        let mut img = img(100, 100);
+        img.fill_rect((0., 0., 0.), 100., 100., RED);
+        img.flood_fill(50, 50, BLUE);
+        assert_eq!(Some(BLUE), img.get_pixel(50, 50));
+        assert_eq!(Some(BLUE), img.get_pixel(0, 0));
+        assert_eq!(Some(BLUE), img.get_pixel(99, 99));
+        assert_eq!(Some(0.), img.get_depth(50, 50));
+    }
+
+    #[test]
+    fn test_flood_fill_stops_at_other_colors() {
+        let mut img = img(20, 20);
+        img.draw_line((10., 0., 0.), (10., 19., 0.), RED);
+        img.flood_fill(0, 0, BLUE);
+        assert_eq!(Some(BLUE), img.get_pixel(9, 10));
+        assert_eq!(Some(RED), img.get_pixel(10, 10));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(11, 10));
+
+        // same color and outside the image are both no-ops
+        img.flood_fill(0, 0, BLUE);
+        img.flood_fill(-1, 5, RED);
+        img.flood_fill(20, 5, RED);
+        assert_eq!(Some(BLUE), img.get_pixel(0, 0));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(19, 19));
+    }
+
+    #[test]
+    fn test_boundary_fill() {
+        let mut img = img(20, 20);
+        img.draw_rect((5., 5., 0.), 10., 10., Rgb::WHITE);
+        // different colors inside the boundary all get filled
+        img.plot(8, 8, 0., RED);
+        img.boundary_fill(10, 10, BLUE, Rgb::WHITE);
+        assert_eq!(Some(BLUE), img.get_pixel(8, 8));
+        assert_eq!(Some(BLUE), img.get_pixel(10, 10));
+        assert_eq!(Some(Rgb::WHITE), img.get_pixel(5, 10));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(2, 2));
+
+        // recoloring what is already there at z = 0
+        img.boundary_fill(10, 10, RED, Rgb::WHITE);
+        assert_eq!(Some(RED), img.get_pixel(10, 10));
+
+        // starting on the boundary fills nothing
+        img.boundary_fill(5, 10, RED, Rgb::WHITE);
+        assert_eq!(Some(Rgb::WHITE), img.get_pixel(5, 10));
+    }
+
+    #[test]
+    fn test_fill_centered() {
+        let mut img = PpmBuilder::new(20, 30, 255).origin(Origin::Center).build();
+        img.draw_line((0., -10., 0.), (0., 9., 0.), Rgb::WHITE);
+        img.flood_fill(-5, -5, RED);
+        assert_eq!(Some(RED), img.get_pixel(-15, -10));
+        assert_eq!(Some(RED), img.get_pixel(-1, 9));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(1, 0));
+
+        img.boundary_fill(3, -2, BLUE, Rgb::WHITE);
+        assert_eq!(Some(BLUE), img.get_pixel(14, 9));
+        assert_eq!(Some(BLUE), img.get_pixel(1, -10));
+        assert_eq!(Some(RED), img.get_pixel(-1, 0));
+        let colors = [RED, BLUE, Rgb::WHITE];
+        assert!(img.pixels().all(|c| colors.contains(c)));
+    }
+
+    #[test]
+    fn test_fill_clipped() {
+        let mut img = PpmBuilder::new(20, 20, 255).origin(Origin::Center).build();
+        img.set_clip(-5, -5, 10, 10);
+        img.flood_fill(0, 0, RED);
+        assert_eq!(Some(RED), img.get_pixel(-5, -5));
+        assert_eq!(Some(RED), img.get_pixel(4, 4));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(5, 0));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(-6, 0));
+        assert_eq!(100, img.pixels().filter(|&&c| c == RED).count());
+
+        // outside the clip rectangle
+        img.flood_fill(8, 8, BLUE);
+        assert!(img.pixels().all(|&c| c != BLUE));
+    }
+
+    #[test]
+    fn test_fill_wrapping() {
+        for &mode in [WrapMode::Wrap, WrapMode::Clamp, WrapMode::Mirror].iter() {
+            let mut img = PpmBuilder::new(10, 20, 255)
+                .origin(Origin::Center)
+                .wrap_x(mode)
+                .wrap_y(mode)
+                .build();
+            img.flood_fill(3, 3, RED);
+            assert!(img.pixels().all(|&c| c == RED), "{:?}", mode);
+
+            // from a corner, the region doesn't go around the edges
+            img.boundary_fill(-10, -5, BLUE, Rgb::WHITE);
+            assert!(img.pixels().all(|&c| c == BLUE), "{:?}", mode);
+        }
+    }
+
+    fn mock() -> MockScreen {
+        MockScreen::new(100, 100)
+    }
+
+    #[test]
+    fn test_circle() {
+        let mut screen = mock();
+        screen.draw_circle(50., 50., 2., 10., RED);
+        assert_eq!(Some((40, 40, 60, 60)), screen.bounding_box());
+        for &(x, y) in [(60, 50), (50, 60), (40, 50), (50, 40)].iter() {
+            assert!(screen.contains_point(x, y), "({}, {})", x, y);
+        }
+        assert!(!screen.contains_point(50, 50));
+        assert!(screen.plots().iter().all(|&(_, _, z, _)| z == 2.));
+
+        // every pixel is at the right distance, up to rounding
+        for &(x, y, _, _) in screen.plots() {
+            let distance = (((x - 50).pow(2) + (y - 50).pow(2)) as f64).sqrt();
+            assert!((distance - 10.).abs() < 1., "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn test_circle_degenerate() {
+        let mut screen = mock();
+        screen.draw_circle(50., 50., 0., 0., RED);
+        assert_eq!(1, screen.unique_count());
+        assert!(screen.contains_point(50, 50));
+
+        // a radius that rounds to 0
+        screen.reset();
+        screen.draw_circle(50.4, 49.6, 0., 0.3, RED);
+        assert_eq!(Some((50, 50, 50, 50)), screen.bounding_box());
+
+        screen.reset();
+        screen.draw_circle(50., 50., 0., -5., RED);
+        assert_eq!(0, screen.count());
+
+        // NaN doesn't hang or panic
+        screen.reset();
+        screen.draw_circle(f64::NAN, 50., 0., f64::NAN, RED);
+        assert!(screen.count() <= 8);
+    }
+
+    #[test]
+    fn test_circle_clipped() {
+        let mut img = img(20, 20);
+        img.draw_circle(0., 0., 0., 10., RED);
+        assert_eq!(Some(RED), img.get_pixel(10, 0));
+        assert_eq!(Some(RED), img.get_pixel(0, 10));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn test_arc() {
+        // a quarter counter-clockwise from the x axis
+        let mut screen = mock();
+        screen.draw_arc((50., 50., 0.), 20., 0., 90., RED);
+        assert_eq!(Some((50, 50, 70, 70)), screen.bounding_box());
+        assert!(screen.contains_point(70, 50));
+        assert!(screen.contains_point(50, 70));
+
+        // the same quarter the other way around is the other 3 quarters
+        screen.reset();
+        screen.draw_arc((50., 50., 0.), 20., 0., -270., RED);
+        assert_eq!(Some((30, 30, 70, 70)), screen.bounding_box());
+        assert!(!screen.contains_point(64, 64));
+
+        // wrapping around 0
+        screen.reset();
+        screen.draw_arc((50., 50., 0.), 20., 350., 370., RED);
+        let (x0, y0, x1, y1) = screen.bounding_box().unwrap();
+        assert!(x0 >= 69 && x1 == 70 && y0 >= 46 && y1 <= 54);
+    }
+
+    #[test]
+    fn test_arc_degenerate() {
+        // more than a full turn is a full circle, drawn once
+        let mut full = mock();
+        full.draw_arc((50., 50., 0.), 20., 0., 360., RED);
+        let mut more = mock();
+        more.draw_arc((50., 50., 0.), 20., 0., 1000., RED);
+        assert_eq!(full.plots(), more.plots());
+
+        // no sweep or no radius is a point
+        let mut screen = mock();
+        screen.draw_arc((50., 50., 0.), 20., 30., 30., RED);
+        assert_eq!(1, screen.unique_count());
+        screen.reset();
+        screen.draw_arc((50., 50., 0.), 0., 0., 180., RED);
+        assert_eq!(Some((50, 50, 50, 50)), screen.bounding_box());
+
+        screen.reset();
+        screen.draw_arc((50., 50., 0.), f64::NAN, 0., 90., RED);
+        screen.draw_arc((50., 50., 0.), 20., f64::NAN, 90., RED);
+    }
+
+    #[test]
+    fn test_rect() {
+        let mut screen = mock();
+        screen.draw_rect((10., 20., 0.), 5., 3., RED);
+        assert_eq!(Some((10, 20, 14, 22)), screen.bounding_box());
+        // the whole border, and nothing inside:
+        // 5 pixels at the top and bottom, 1 more on each side
+        assert_eq!(12, screen.unique_count());
+        assert!(!screen.contains_point(12, 21));
+
+        // negative sizes go the other way
+        let mut flipped = mock();
+        flipped.draw_rect((15., 23., 0.), -5., -3., RED);
+        assert_eq!(screen.bounding_box(), flipped.bounding_box());
+        assert_eq!(screen.unique_count(), flipped.unique_count());
+    }
+
+    #[test]
+    fn test_rect_degenerate() {
+        let mut screen = mock();
+        screen.draw_rect((10., 10., 0.), 0., 5., RED);
+        screen.draw_rect((10., 10., 0.), 5., 0., RED);
+        screen.draw_rect((10., 10., 0.), 0.2, 0.2, RED);
+        assert_eq!(0, screen.count());
+
+        let mut img = img(10, 10);
+        img.fill_rect((0., 0., 0.), 0., 5., RED);
+        assert!(img.pixels().all(|&c| c == Rgb::BLACK));
+
+        // a 1 by 1 rectangle is a single pixel
+        screen.draw_rect((10., 10., 0.), 1., 1., RED);
+        assert_eq!(Some((10, 10, 10, 10)), screen.bounding_box());
+    }
+
+    #[test]
+    fn test_rect_clipped() {
+        let mut img = img(10, 10);
+        img.fill_rect((-5., 5., 0.), 10., 10., RED);
+        assert_eq!(Some(RED), img.get_pixel(0, 9));
+        assert_eq!(Some(RED), img.get_pixel(4, 5));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(5, 5));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(0, 4));
+
+        img.draw_rect((-5., -5., 0.), 10., 10., BLUE);
+        assert_eq!(Some(BLUE), img.get_pixel(4, 0));
+        assert_eq!(Some(BLUE), img.get_pixel(0, 4));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(3, 3));
+    }
+
+    #[test]
+    fn test_polyline() {
+        let points = [(0., 0., 0.), (10., 0., 0.), (10., 10., 0.)];
+        let mut open = mock();
+        open.draw_polyline(&points, false, RED);
+        assert!(open.contains_point(10, 5));
+        assert!(!open.contains_point(5, 5));
+
+        let mut closed = mock();
+        closed.draw_polyline(&points, true, RED);
+        assert!(closed.contains_point(5, 5));
+        assert_eq!(open.unique_count() + 9, closed.unique_count());
+    }
+
+    #[test]
+    fn test_polyline_degenerate() {
+        let mut screen = mock();
+        screen.draw_polyline::<(f64, f64, f64)>(&[], true, RED);
+        screen.draw_polyline(&[(5., 5., 0.)], true, RED);
+        assert_eq!(0, screen.count());
+
+        // 2 points are a single line, even closed
+        screen.draw_polyline(&[(0., 0., 0.), (10., 0., 0.)], true, RED);
+        assert_eq!(11, screen.count());
+    }
+
+    #[test]
+    fn test_bezier() {
+        let mut screen = mock();
+        let (p0, p3) = ((10., 10., 0.), (90., 10., 0.));
+        screen.draw_bezier(p0, (10., 90., 0.), (90., 90., 0.), p3, 20, RED);
+        assert!(screen.contains_point(10, 10));
+        assert!(screen.contains_point(90, 10));
+        // the curve peaks at 3/4 of the height of the control points
+        let (_, _, _, top) = screen.bounding_box().unwrap();
+        assert_eq!(70, top);
+        assert!(screen.contains_point(50, 70));
+    }
+
+    #[test]
+    fn test_bezier_degenerate() {
+        // 0 steps is the same as 1: a line from start to end
+        let points = [
+            (10., 10., 0.),
+            (10., 90., 0.),
+            (90., 90., 0.),
+            (90., 10., 0.),
+        ];
+        let mut zero = mock();
+        zero.draw_bezier(points[0], points[1], points[2], points[3], 0, RED);
+        let mut line = mock();
+        line.draw_line(points[0], points[3], RED);
+        assert_eq!(line.plots(), zero.plots());
+
+        // all points the same
+        let mut screen = mock();
+        let p = (20., 20., 0.);
+        screen.draw_bezier(p, p, p, p, 10, RED);
+        assert_eq!(1, screen.unique_count());
+
+        screen.draw_bezier(p, (f64::NAN, 0., 0.), p, p, 10, RED);
+    }
+
+    #[test]
+    fn test_text() {
+        let font = Font::builtin();
+        let (width, height) = font.text_size("Hi", 2);
+
+        let mut screen = mock();
+        screen.draw_text(10, 20, "Hi", 2, RED);
+        let (x0, y0, x1, y1) = screen.bounding_box().unwrap();
+        assert!(x0 >= 10 && y0 >= 20);
+        assert!(x1 < 10 + width as i64 && y1 < 20 + height as i64);
+
+        // every pixel of the font is a 2 by 2 block
+        let mut small = mock();
+        small.draw_text(10, 20, "Hi", 1, RED);
+        assert_eq!(4 * small.unique_count(), screen.unique_count());
+
+        // lines go down
+        screen.reset();
+        screen.draw_text(10, 50, "H\nH", 1, RED);
+        let (_, y0, _, y1) = screen.bounding_box().unwrap();
+        assert!(y0 < 50 - font.height() as i64 && y1 >= 50);
+    }
+
+    #[test]
+    fn test_text_degenerate() {
+        let mut screen = mock();
+        screen.draw_text(10, 10, "", 2, RED);
+        screen.draw_text(10, 10, "  ", 2, RED);
+        screen.draw_text(10, 10, "Hi", 0, RED);
+        assert_eq!(0, screen.count());
+
+        // clipped at the edges of the image
+        let mut img = img(10, 10);
+        img.draw_text(-3, -3, "MM", 3, RED);
+        assert!(img.pixels().any(|&c| c == RED));
+        img.draw_text(i64::MAX / 4, 0, "M", 1, RED);
+    }
+
+    /// Ellipses with a radius of 0 are lines, in any rotation.
+    #[test]
+    fn test_flat_ellipses() {
+        for &rotation in [0., 90., 180.].iter() {
+            let mut screen = MockScreen::<Rgb>::new(100, 100);
+            screen.draw_ellipse(50., 50., 20., 0., rotation, RED);
+            let (horizontal, vertical) = ((30, 50, 70, 50), (50, 30, 50, 70));
+            let expected = if rotation == 90. {
+                vertical
+            } else {
+                horizontal
+            };
+            assert_eq!(Some(expected), screen.bounding_box());
+            assert_eq!(41, screen.count());
+
+            let mut screen = MockScreen::<Rgb>::new(100, 100);
+            screen.draw_ellipse(50., 50., 0., 20., rotation, RED);
+            let expected = if rotation == 90. {
+                horizontal
+            } else {
+                vertical
+            };
+            assert_eq!(Some(expected), screen.bounding_box());
+            assert_eq!(41, screen.count());
+        }
+
+        // rotated ones are polygons, going back and forth along the line
+        let mut screen = MockScreen::<Rgb>::new(100, 100);
+        screen.draw_ellipse(50., 50., 20., 0., 45., RED);
+        assert_eq!(Some((36, 36, 64, 64)), screen.bounding_box());
+        assert!(screen.contains_point(50, 50));
+
+        let mut screen = MockScreen::<Rgb>::new(100, 100);
+        screen.draw_ellipse(50., 50., 0., 0., 0., RED);
+        assert_eq!(Some((50, 50, 50, 50)), screen.bounding_box());
+        assert_eq!(1, screen.count());
+    }
+
+    #[test]
+    fn test_polygon_with_bad_vertices() {
+        let mut img = img(10, 10);
+        let gradient = Gradient::linear((0., 0.), (10., 0.)).stop(0., RED);
+        for &bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY].iter() {
+            let polygons = [
+                [(0., 0., 0.), (9., 0., 0.), (bad, 9., 0.)],
+                [(0., 0., 0.), (9., bad, 0.), (0., 9., 0.)],
+            ];
+            for polygon in polygons.iter() {
+                img.fill_polygon(polygon, RED);
+                img.fill_polygon(polygon, &gradient);
+            }
+        }
+        assert!(img.pixels().all(|&c| c == Rgb::BLACK));
+
+        // a NaN z is only a bad depth
+        img.fill_polygon(&[(0., 0., 0.), (9., 0., f64::NAN), (0., 9., 0.)], RED);
+    }
+
+    /// Shapes far bigger than the image. Without clipping, these would visit about 10^18 pixels and never finish.
+    #[test]
+    fn test_huge_shapes_are_clipped() {
+        let mut img = img(20, 10);
+        img.fill_triangle((-1e9, -1e9, 0.), (1e9, -1e9, 0.), (0., 1e9, 0.), RED);
+        assert!(img.pixels().all(|&c| c == RED));
+
+        img.fill_rect((-1e9, -1e9, 1.), 2e9, 2e9, BLUE);
+        assert!(img.pixels().all(|&c| c == BLUE));
+
+        img.fill_polygon(
+            &[
+                (-1e9, -1e9, 2.),
+                (1e9, -1e9, 2.),
+                (1e9, 1e9, 2.),
+                (-1e9, 1e9, 2.),
+            ],
+            RED,
+        );
+        assert!(img.pixels().all(|&c| c == RED));
+    }
+
+    #[test]
+    fn test_clip_bounds() {
+        let mut img = PpmBuilder::new(10, 20, 255).origin(Origin::Center).build();
+        assert_eq!((-10..10, -5..5), img.clip_bounds());
+        img.set_clip(-2, -1, 4, 100);
+        assert_eq!((-2..2, -1..5), img.clip_bounds());
+
+        // filling respects the clip rectangle, and the edges of the image
+        img.fill_triangle((-1e9, -1e9, 0.), (1e9, -1e9, 0.), (0., 1e9, 0.), RED);
+        assert_eq!(Some(RED), img.get_pixel(-2, -1));
+        assert_eq!(Some(RED), img.get_pixel(1, 4));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(2, 0));
+        assert_eq!(Some(Rgb::BLACK), img.get_pixel(0, -2));
+
+        let img = PpmBuilder::new(10, 20, 255).wrap_x(WrapMode::Wrap).build();
+        assert_eq!((i64::MIN..i64::MAX, 0..10), img.clip_bounds());
+
+        let img = PpmBuilder::new(10, 20, 255).supersample(2).build();
+        assert_eq!((0..20, 0..10), img.clip_bounds());
+    }
+
+    #[test]
+    fn test_supersampled_triangle_is_clipped() {
+        let mut img = PpmBuilder::new(10, 10, 255).supersample(4).build();
+        img.fill_triangle((-1e9, -1e9, 0.), (1e9, -1e9, 0.), (0., 1e9, 0.), RED);
+        assert!(img.data().iter().all(|&c| c == RED));
     }
 }
@@ -0,0 +1,352 @@
+//! Helpers for testing drawing code.
+//!
+//! - [`MockScreen`] is a [`Screen`] that only remembers what was plotted on it, so an implementation of [`draw_line`]
+//!   or a curve can be checked point by point in a unit test, without writing image files.
+//! - [`assert_image_matches`] compares a whole render against a reference image stored with the tests, to catch
+//!   regressions.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{prelude::*, testing::MockScreen};
+//!
+//! let mut screen = MockScreen::new(100, 100);
+//! screen.draw_line((10., 10., 0.), (20., 15., 0.), Rgb::WHITE);
+//!
+//! assert!(screen.contains_point(10, 10));
+//! assert!(screen.contains_point(20, 15));
+//! // one point per column of a shallow line
+//! assert_eq!(11, screen.count());
+//! assert_eq!(Some((10, 10, 20, 15)), screen.bounding_box());
+//! ```
+//!
+//! [`MockScreen`]: ./struct.MockScreen.html
+//! [`assert_image_matches`]: ./fn.assert_image_matches.html
+//! [`Screen`]: ../screen/trait.Screen.html
+//! [`draw_line`]: ../screen/trait.Screen.html#method.draw_line
+
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    color::{Color, PpmColor, Rgb},
+    error,
+    ppm::{Ppm, PpmBuilder},
+    screen::Screen,
+    util::Float,
+};
+
+/// Environment variable that makes [`assert_image_matches`] save the images as the new references, instead of
+/// comparing them.
+///
+/// [`assert_image_matches`]: ./fn.assert_image_matches.html
+pub const UPDATE_REFERENCES_VAR: &str = "GRAPHICS_UPDATE_REFERENCES";
+
+/// A [`Screen`] recording every plot, for unit tests. See the [module documentation].
+///
+/// Plots are recorded with the coordinates given to [`plot`], even outside of the screen, so clipping can be tested
+/// too. Saving and displaying do nothing.
+///
+/// [`Screen`]: ../screen/trait.Screen.html
+/// [`plot`]: ../screen/trait.Screen.html#tymethod.plot
+/// [module documentation]: ./index.html
+#[derive(Clone, Debug)]
+pub struct MockScreen<C: Color = Rgb> {
+    width: usize,
+    height: usize,
+    background: C,
+    plots: Vec<(i64, i64, Float, C)>,
+    /// The last color and depth plotted at each point.
+    pixels: HashMap<(i64, i64), (C, Float)>,
+}
+
+impl<C: Color> MockScreen<C> {
+    /// A `width` by `height` screen, cleared to black.
+    pub fn new(width: usize, height: usize) -> Self {
+        MockScreen {
+            width,
+            height,
+            background: C::from_rgb(Rgb::BLACK),
+            plots: Vec::new(),
+            pixels: HashMap::new(),
+        }
+    }
+
+    /// Every plot as (`x`, `y`, `z`, `color`), in order.
+    pub fn plots(&self) -> &[(i64, i64, Float, C)] {
+        &self.plots
+    }
+
+    /// The number of plots, counting points plotted more than once every time.
+    pub fn count(&self) -> usize {
+        self.plots.len()
+    }
+
+    /// The number of different points plotted.
+    pub fn unique_count(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// Whether (`x`, `y`) was plotted.
+    pub fn contains_point(&self, x: i64, y: i64) -> bool {
+        self.pixels.contains_key(&(x, y))
+    }
+
+    /// The last color plotted at (`x`, `y`).
+    pub fn color_at(&self, x: i64, y: i64) -> Option<C> {
+        self.pixels.get(&(x, y)).map(|&(color, _)| color)
+    }
+
+    /// The smallest and largest plotted coordinates, as `(min_x, min_y, max_x, max_y)`, or `None` if nothing was
+    /// plotted.
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        self.pixels.keys().fold(None, |bounds, &(x, y)| {
+            Some(match bounds {
+                None => (x, y, x, y),
+                Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            })
+        })
+    }
+
+    /// Forget everything plotted, without clearing.
+    pub fn reset(&mut self) {
+        self.plots.clear();
+        self.pixels.clear();
+    }
+
+    fn in_bounds(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+}
+
+impl<C: Color> Screen<C> for MockScreen<C> {
+    fn plot(&mut self, x: i64, y: i64, z: Float, color: C) {
+        self.plots.push((x, y, z, color));
+        self.pixels.insert((x, y), (color, z));
+    }
+
+    fn save(&self, _file_path: &str) -> error::Result<()> {
+        Ok(())
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn write_to_buf<T: io::Write>(&self, _writer: &mut T) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn display(&self) -> error::Result<()> {
+        Ok(())
+    }
+
+    /// Fill with `color`, and forget everything plotted.
+    fn clear(&mut self, color: C) {
+        self.background = color;
+        self.reset();
+    }
+
+    fn get_pixel(&self, x: i64, y: i64) -> Option<C> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        Some(self.color_at(x, y).unwrap_or(self.background))
+    }
+
+    /// Unbounded, so shapes are never clipped before they are plotted, and clipping can be tested.
+    fn clip_bounds(&self) -> (Range<i64>, Range<i64>) {
+        (i64::MIN..i64::MAX, i64::MIN..i64::MAX)
+    }
+
+    fn get_depth(&self, x: i64, y: i64) -> Option<Float> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        Some(
+            self.pixels
+                .get(&(x, y))
+                .map_or(Float::NEG_INFINITY, |&(_, z)| z),
+        )
+    }
+}
+
+/// Panic unless `img` looks exactly like the reference image at `reference`, a ppm file.
+///
+/// Relative paths are from the current directory, which is the package root under `cargo test`. Run the tests with
+/// the [`GRAPHICS_UPDATE_REFERENCES`] environment variable set to save the images as the references instead, the first
+/// time or after an intended change:
+///
+/// ```text
+/// GRAPHICS_UPDATE_REFERENCES=1 cargo test
+/// ```
+///
+/// When the images don't match, the actual image and a diff are saved in `target/golden` (or the `CARGO_TARGET_DIR`)
+/// as `name.actual.ppm` and `name.diff.ppm`. In the diff, pixels that differ are red, over a darkened copy of the
+/// actual image.
+///
+/// # Examples
+///
+/// ```no_run
+/// use graphics::{prelude::*, testing::assert_image_matches};
+///
+/// let mut img = Ppm::new();
+/// img.draw_circle(250., 250., 0., 100., Rgb::WHITE);
+/// assert_image_matches(&img, "references/circle.ppm");
+/// ```
+///
+/// [`GRAPHICS_UPDATE_REFERENCES`]: ./constant.UPDATE_REFERENCES_VAR.html
+pub fn assert_image_matches<C: PpmColor, P: AsRef<Path>>(img: &Ppm<C>, reference: P) {
+    let reference = reference.as_ref();
+    if env::var_os(UPDATE_REFERENCES_VAR).is_some_and(|v| !v.is_empty()) {
+        if let Some(dir) = reference.parent() {
+            fs::create_dir_all(dir).expect("error creating reference directory");
+        }
+        img.write_binary_to_buf(
+            &mut fs::File::create(reference).expect("error creating reference image"),
+        )
+        .expect("error writing reference image");
+        return;
+    }
+
+    let expected = Ppm::<C>::open(reference).unwrap_or_else(|e| {
+        panic!(
+            "error reading reference image {}: {}\nrun with {}=1 to create it",
+            reference.display(),
+            e,
+            UPDATE_REFERENCES_VAR
+        )
+    });
+
+    let (actual_data, expected_data) = (img.data(), expected.data());
+    let same_size = img.width() == expected.width() && img.height() == expected.height();
+    let differs: Vec<bool> = if same_size {
+        actual_data
+            .iter()
+            .zip(expected_data.iter())
+            .map(|(a, e)| a.channels() != e.channels())
+            .collect()
+    } else {
+        vec![true; actual_data.len()]
+    };
+    let count = differs.iter().filter(|&&d| d).count();
+    if count == 0 {
+        return;
+    }
+
+    let name = reference
+        .file_stem()
+        .map_or("image".into(), |s| s.to_string_lossy());
+    let dir = env::var_os("CARGO_TARGET_DIR")
+        .map_or_else(|| PathBuf::from("target"), PathBuf::from)
+        .join("golden");
+    let actual_path = dir.join(format!("{}.actual.ppm", name));
+    let diff_path = dir.join(format!("{}.diff.ppm", name));
+    let saved = fs::create_dir_all(&dir).and_then(|_| {
+        img.write_binary_to_buf(&mut fs::File::create(&actual_path)?)?;
+        diff_image(img, &differs).write_binary_to_buf(&mut fs::File::create(&diff_path)?)
+    });
+
+    let size = |img: &Ppm<C>| format!("{}x{}", img.width(), img.height());
+    let mut message = if same_size {
+        format!(
+            "image doesn't match {}: {} of {} pixels differ",
+            reference.display(),
+            count,
+            differs.len()
+        )
+    } else {
+        format!(
+            "image doesn't match {}: size is {} instead of {}",
+            reference.display(),
+            size(img),
+            size(&expected)
+        )
+    };
+    match saved {
+        Ok(()) => {
+            message += &format!(
+                "\nsaved {} and {}",
+                actual_path.display(),
+                diff_path.display()
+            )
+        }
+        Err(e) => message += &format!("\nerror saving the actual image: {}", e),
+    }
+    panic!(
+        "{}\nrun with {}=1 to accept the new image",
+        message, UPDATE_REFERENCES_VAR
+    );
+}
+
+/// `img` darkened, with pixels where `differs` is true in red.
+fn diff_image<C: PpmColor>(img: &Ppm<C>, differs: &[bool]) -> Ppm {
+    let max = img.color_depth().max(1) as u32;
+    let data = img
+        .data()
+        .iter()
+        .zip(differs)
+        .map(|(color, &differs)| {
+            if differs {
+                return Rgb::new(255, 0, 0);
+            }
+            let [r, g, b] = color.channels();
+            let gray = ((r as u32 + g as u32 + b as u32).min(3 * max) * 85 / max / 3) as u8;
+            Rgb::new(gray, gray, gray)
+        })
+        .collect();
+    PpmBuilder::new(img.height(), img.width(), 255)
+        .with_data(data)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_screen() {
+        let mut screen = MockScreen::new(10, 10);
+        screen.plot(-1, 3, 0., Rgb::WHITE);
+        screen.plot(2, 3, 1., Rgb::WHITE);
+        screen.plot(2, 3, 2., Rgb::new(255, 0, 0));
+
+        assert_eq!((3, 2), (screen.count(), screen.unique_count()));
+        assert_eq!(Some(Rgb::new(255, 0, 0)), screen.color_at(2, 3));
+        assert_eq!(Some(2.), screen.get_depth(2, 3));
+        assert_eq!(None, screen.get_pixel(-1, 3));
+        assert_eq!(Some(Rgb::BLACK), screen.get_pixel(5, 5));
+        assert_eq!(Some((-1, 3, 2, 3)), screen.bounding_box());
+
+        screen.clear(Rgb::WHITE);
+        assert_eq!(None, screen.bounding_box());
+        assert_eq!(Some(Rgb::WHITE), screen.get_pixel(2, 3));
+    }
+
+    #[test]
+    fn test_assert_image_matches() {
+        let mut img = Ppm::new();
+        img.draw_line((0., 0., 0.), (499., 300., 0.), Rgb::WHITE);
+        let reference = env::temp_dir().join(format!("graphics-golden-{}.ppm", std::process::id()));
+        img.write_binary_to_buf(&mut fs::File::create(&reference).unwrap())
+            .unwrap();
+
+        assert_image_matches(&img, &reference);
+
+        img.plot(10, 200, 0., Rgb::WHITE);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            assert_image_matches(&img, &reference)
+        }));
+        fs::remove_file(&reference).unwrap();
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("1 of 250000 pixels differ"), "{}", message);
+    }
+}
@@ -0,0 +1,119 @@
+//! Dependency-free encoder for the [TGA] format, uncompressed or run-length encoded.
+//!
+//! Use it through [`Ppm::save_tga`] and [`Ppm::write_tga_to_buf`].
+//!
+//! [TGA]: https://en.wikipedia.org/wiki/Truevision_TGA
+//! [`Ppm::save_tga`]: ../ppm/struct.Ppm.html#method.save_tga
+//! [`Ppm::write_tga_to_buf`]: ../ppm/struct.Ppm.html#method.write_tga_to_buf
+
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+};
+
+use crate::{color::PpmColor, ppm::Ppm, screen::Screen};
+
+/// Longest run (or raw packet) a TGA packet can hold.
+const MAX_PACKET: usize = 128;
+
+/// Write `img` as a true color TGA, with 8 bits of alpha if the color type has one. Rows go from the top down.
+pub(crate) fn write_tga<C: PpmColor, W: Write>(
+    img: &Ppm<C>,
+    writer: &mut W,
+    rle: bool,
+) -> io::Result<()> {
+    let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "image too large for TGA");
+    let width = u16::try_from(img.width()).map_err(|_| too_large())?;
+    let height = u16::try_from(img.height()).map_err(|_| too_large())?;
+    let (bits, alpha_bits) = if C::HAS_ALPHA { (32, 8) } else { (24, 0) };
+
+    writer.write_all(&[
+        0,                        // no image ID
+        0,                        // no color map
+        if rle { 10 } else { 2 }, // true color, compressed or not
+    ])?;
+    writer.write_all(&[0; 5])?; // color map specification
+    writer.write_all(&0u16.to_le_bytes())?; // x origin
+    writer.write_all(&0u16.to_le_bytes())?; // y origin
+    writer.write_all(&width.to_le_bytes())?;
+    writer.write_all(&height.to_le_bytes())?;
+    // bit 5 of the descriptor puts the first row at the top
+    writer.write_all(&[bits, 0x20 | alpha_bits])?;
+
+    let pixels: Vec<Vec<u8>> = img
+        .to_rgba8()
+        .iter()
+        .map(|&[r, g, b, a]| {
+            if C::HAS_ALPHA {
+                vec![b, g, r, a]
+            } else {
+                vec![b, g, r]
+            }
+        })
+        .collect();
+
+    if !rle {
+        for pixel in pixels.iter() {
+            writer.write_all(pixel)?;
+        }
+        return Ok(());
+    }
+
+    // packets can't span rows
+    for row in pixels.chunks(img.width().max(1)) {
+        let mut i = 0;
+        while i < row.len() {
+            let run = row[i..]
+                .iter()
+                .take(MAX_PACKET)
+                .take_while(|p| **p == row[i])
+                .count();
+            if run > 1 {
+                writer.write_all(&[0x80 | (run - 1) as u8])?;
+                writer.write_all(&row[i])?;
+                i += run;
+            } else {
+                // raw packet, up to the next run of at least 2
+                let mut end = i + 1;
+                while end < row.len() && end - i < MAX_PACKET && row[end] != row[end - 1] {
+                    end += 1;
+                }
+                if end < row.len() && end - i > 1 && row[end] == row[end - 1] {
+                    end -= 1;
+                }
+                writer.write_all(&[(end - i - 1) as u8])?;
+                for pixel in row[i..end].iter() {
+                    writer.write_all(pixel)?;
+                }
+                i = end;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Rgb, ppm::PpmBuilder};
+
+    #[test]
+    fn test_write_tga_rle() {
+        let mut img = PpmBuilder::new(4, 4, 255).build();
+        // top row: red, black, black, black
+        img.plot(0, 3, 0., Rgb::new(255, 0, 0));
+
+        let mut raw = vec![];
+        img.write_tga_to_buf(&mut raw, false).unwrap();
+        assert_eq!(18 + 4 * 4 * 3, raw.len());
+        assert_eq!(&[0, 0, 255], &raw[18..21]);
+
+        let mut rle = vec![];
+        img.write_tga_to_buf(&mut rle, true).unwrap();
+        assert_eq!(&raw[..2], &rle[..2]);
+        assert_eq!(10, rle[2]);
+        // a raw packet of 1 red pixel, a run of 3 black ones, then a run of 4 for every other row
+        assert_eq!(&[0, 0, 0, 255, 0x82, 0, 0, 0, 0x83, 0, 0, 0], &rle[18..30]);
+        assert_eq!(18 + 8 + 3 * 4, rle.len());
+    }
+}
@@ -0,0 +1,195 @@
+//! Tile-based filling, for very large images.
+//!
+//! At 4K or 8K, a single row of samples with its z-buffer doesn't fit in the CPU cache anymore, and filling a frame is
+//! limited by memory bandwidth more than by arithmetic. A [`TileRenderer`] collects triangles first, then splits the
+//! image into square tiles and sorts ("bins") the triangles into the tiles they cover. Every tile is copied to a small
+//! buffer, filled with its triangles only, and copied back, so all the work on a tile happens in the cache.
+//!
+//! With the `parallel` feature, rows of tiles are filled on separate threads. Either way, the image comes out exactly
+//! the same as with [`fill_triangle`] on every triangle, in order.
+//!
+//! Images which wrap around ([`WrapMode`]) can't be binned, so every tile gets every triangle, which is slow.
+//!
+//! # Examples
+//!
+//! ```
+//! use graphics::{prelude::*, tile::TileRenderer};
+//!
+//! let mut img = PpmBuilder::new(2160, 3840, 255).build();
+//! let mut tiles = TileRenderer::new();
+//!
+//! // a checkerboard of 2 triangles per square
+//! for y in 0..54 {
+//!     for x in 0..96 {
+//!         let (x0, y0) = (x as f64 * 40., y as f64 * 40.);
+//!         let color = if (x + y) % 2 == 0 { Rgb::WHITE } else { Rgb::new(200, 0, 0) };
+//!         tiles.fill_triangle((x0, y0, 0.), (x0 + 40., y0, 0.), (x0, y0 + 40., 0.), color);
+//!         tiles.fill_triangle((x0 + 40., y0, 0.), (x0 + 40., y0 + 40., 0.), (x0, y0 + 40., 0.), color);
+//!     }
+//! }
+//! tiles.render(&mut img);
+//!
+//! assert_eq!(Some(Rgb::WHITE), img.get_pixel(10, 10));
+//! assert_eq!(Some(Rgb::new(200, 0, 0)), img.get_pixel(50, 10));
+//! ```
+//!
+//! [`TileRenderer`]: ./struct.TileRenderer.html
+//! [`fill_triangle`]: ../screen/trait.Screen.html#method.fill_triangle
+//! [`WrapMode`]: ../ppm/enum.WrapMode.html
+
+use crate::{
+    color::{PpmColor, Rgb},
+    ppm::{self, Ppm},
+    util::Point,
+};
+
+/// Triangles to fill into an image one tile at a time. See the [module documentation].
+///
+/// [module documentation]: ./index.html
+#[derive(Clone, Debug)]
+pub struct TileRenderer<C = Rgb> {
+    tile_size: usize,
+    triangles: Vec<([Point; 3], C)>,
+}
+
+impl<C: PpmColor + Send + Sync> TileRenderer<C> {
+    /// The default width and height of a tile, in samples. A 64 by 64 tile with its z-buffer fits in the L2 cache.
+    pub const DEFAULT_TILE_SIZE: usize = 64;
+
+    /// A renderer with tiles of [`DEFAULT_TILE_SIZE`] samples.
+    ///
+    /// [`DEFAULT_TILE_SIZE`]: #associatedconstant.DEFAULT_TILE_SIZE
+    pub fn new() -> Self {
+        Self::with_tile_size(Self::DEFAULT_TILE_SIZE)
+    }
+
+    /// A renderer with `tile_size` by `tile_size` tiles, in samples. A tile size of `0` is taken as `1`.
+    pub fn with_tile_size(tile_size: usize) -> Self {
+        TileRenderer {
+            tile_size: tile_size.max(1),
+            triangles: Vec::new(),
+        }
+    }
+
+    /// Width and height of a tile, in samples.
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    /// Add a triangle to fill, like [`Screen::fill_triangle`].
+    ///
+    /// [`Screen::fill_triangle`]: ../screen/trait.Screen.html#method.fill_triangle
+    pub fn fill_triangle<P0, P1, P2>(&mut self, p0: P0, p1: P1, p2: P2, color: C)
+    where
+        P0: Into<Point>,
+        P1: Into<Point>,
+        P2: Into<Point>,
+    {
+        self.triangles
+            .push(([p0.into(), p1.into(), p2.into()], color));
+    }
+
+    /// Add every triangle in `triangles`, with its color.
+    pub fn fill_triangles<P: Into<Point> + Copy>(&mut self, triangles: &[([P; 3], C)]) {
+        self.triangles.extend(
+            triangles
+                .iter()
+                .map(|&(points, color)| (points.map(Into::into), color)),
+        );
+    }
+
+    /// How many triangles were added.
+    pub fn len(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Whether no triangle was added.
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// Remove all the triangles, to start the next frame.
+    pub fn clear(&mut self) {
+        self.triangles.clear();
+    }
+
+    /// Fill all the triangles into `img`, over what's already there. The triangles are kept, so the same ones can be
+    /// rendered into another image.
+    pub fn render(&self, img: &mut Ppm<C>) {
+        img.fill_tiled(&self.triangles, self.tile_size, ppm::worker_threads());
+    }
+}
+
+impl<C: PpmColor + Send + Sync> Default for TileRenderer<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ppm::{DepthTest, Origin, PpmBuilder, StencilOp, StencilTest, WrapMode},
+        random::Rng,
+        screen::Screen,
+    };
+
+    #[test]
+    fn test_matches_fill_triangle() {
+        let mut rng = Rng::new(7);
+        let settings: [fn() -> PpmBuilder; 4] = [
+            || PpmBuilder::new(37, 50, 255),
+            || PpmBuilder::new(37, 50, 255).supersample(3),
+            || {
+                PpmBuilder::new(37, 50, 255)
+                    .origin(Origin::Center)
+                    .wrap_x(WrapMode::Mirror)
+            },
+            || {
+                PpmBuilder::new(37, 50, 255)
+                    .origin(Origin::TopLeft)
+                    .depth_test(DepthTest::Less)
+            },
+        ];
+        for builder in settings.iter() {
+            let mut tiles = TileRenderer::with_tile_size(8);
+            for _ in 0..50 {
+                let mut point = || {
+                    (
+                        rng.range(-30., 80.),
+                        rng.range(-30., 70.),
+                        rng.range(-1., 1.),
+                    )
+                };
+                tiles.fill_triangle(point(), point(), point(), rng.color());
+            }
+
+            let mut one_by_one = builder().build();
+            let mut tiled = builder().build();
+            let mut threaded = builder().build();
+            for img in [&mut one_by_one, &mut tiled, &mut threaded].iter_mut() {
+                img.set_clip(-5, 3, 40, 30);
+                img.set_stencil(
+                    StencilTest::NotEqual(2),
+                    StencilOp::Increment,
+                    StencilOp::Keep,
+                );
+            }
+            for &([p0, p1, p2], color) in tiles.triangles.iter() {
+                one_by_one.fill_triangle(p0, p1, p2, color);
+            }
+            tiles.render(&mut tiled);
+            threaded.fill_tiled(&tiles.triangles, tiles.tile_size, 3);
+
+            assert!(one_by_one.pixels().any(|&c| c != Rgb::BLACK));
+            for img in [&tiled, &threaded].iter() {
+                assert!(one_by_one.pixels().eq(img.pixels()));
+                for (x, y, _) in one_by_one.enumerate_pixels() {
+                    assert_eq!(one_by_one.get_depth(x, y), img.get_depth(x, y));
+                    assert_eq!(one_by_one.stencil_at(x, y), img.stencil_at(x, y));
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
 //! Contains the `Turtle` struct.
 
-use crate::{prelude::*, util::polar_to_xy};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{colormap::ColorMap, error, magick, ppm::Origin, prelude::*, util::polar_to_xy};
 
 /// A simple turtle drawer on the plane Z = 0 (no depth).
 ///
@@ -37,6 +43,21 @@ use crate::{prelude::*, util::polar_to_xy};
 /// // img.save("circle.png");
 /// ```
 ///
+/// Lines drawn with a wider pen still show up when the image is shrunk into a thumbnail:
+///
+/// ```
+/// use graphics::{prelude::*, turtle::Turtle};
+///
+/// let mut turtle = Turtle::new(Ppm::new(), 100., 100., Rgb::WHITE);
+/// turtle.pen_down = true;
+/// turtle.pen_width = 5.;
+/// turtle.forward(200.);
+///
+/// let img = turtle.get_screen();
+/// assert_eq!(Some(Rgb::WHITE), img.get(200, 102));
+/// assert_eq!(Some(Rgb::BLACK), img.get(200, 104));
+/// ```
+///
 /// # Ownership
 /// When making a [`Turtle`], the [`Screen`] will be owned by the turtle. If you want to work with the [`Screen`] directly, you need to drop
 /// the turtle.
@@ -67,7 +88,136 @@ pub struct Turtle<T: Screen> {
     pub pen_down: bool,
     /// The color to draw with.
     pub fg_color: Rgb,
+    /// Width of the lines drawn, in pixels. `1.` by default. Wider lines are drawn with [`draw_line_width`].
+    ///
+    /// [`draw_line_width`]: ../screen/trait.Screen.html#method.draw_line_width
+    pub pen_width: f64,
+    /// The color to fill shapes with, between [`begin_fill`] and [`end_fill`]. Starts as the color given to
+    /// [`new`].
+    ///
+    /// [`begin_fill`]: #method.begin_fill
+    /// [`end_fill`]: #method.end_fill
+    /// [`new`]: #method.new
+    pub fill_color: Rgb,
+    /// If `true`, lines drawn are also recorded, to get them back with [`path`].
+    ///
+    /// [`path`]: #method.path
+    pub record_path: bool,
     img: T,
+    /// States saved with `push`.
+    stack: Vec<State>,
+    /// Where the turtle started, for `home`.
+    home: (f64, f64),
+    /// Set with `cycle_colors`.
+    cycle: Option<ColorCycle>,
+    /// The shape being recorded since `begin_fill`.
+    fill: Option<FillPath>,
+    /// Lines recorded with `record_path`.
+    path: Vec<Stroke>,
+    /// Capture a frame every this many steps, or never if `0`.
+    capture_every: usize,
+    /// Steps since the last frame.
+    steps: usize,
+    /// Captured frames, as written by `Screen::write_to_buf`.
+    frames: Vec<Vec<u8>>,
+}
+
+/// Connected lines drawn by a [`Turtle`] with the same color and width, recorded with [`record_path`].
+///
+/// [`Turtle`]: ./struct.Turtle.html
+/// [`record_path`]: ./struct.Turtle.html#structfield.record_path
+#[derive(Clone, PartialEq, Debug)]
+pub struct Stroke {
+    /// The points the lines go through, in order.
+    pub points: Vec<(f64, f64)>,
+    /// The color the lines were drawn with.
+    pub color: Rgb,
+    /// The width the lines were drawn with.
+    pub width: f64,
+}
+
+/// What makes the pen go through the colors of a [`ColorMap`], with [`Turtle::cycle_colors`].
+///
+/// [`ColorMap`]: ../colormap/struct.ColorMap.html
+/// [`Turtle::cycle_colors`]: ./struct.Turtle.html#method.cycle_colors
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CycleBy {
+    /// The distance travelled, in pixels.
+    Distance,
+    /// The number of moves: every call to [`forward`], [`move_to`], [`arc`] or [`home`] is one step.
+    ///
+    /// [`forward`]: ./struct.Turtle.html#method.forward
+    /// [`move_to`]: ./struct.Turtle.html#method.move_to
+    /// [`arc`]: ./struct.Turtle.html#method.arc
+    /// [`home`]: ./struct.Turtle.html#method.home
+    Steps,
+}
+
+/// A shape for [`Turtle::stamp`].
+///
+/// [`Turtle::stamp`]: ./struct.Turtle.html#method.stamp
+#[derive(Clone, PartialEq, Debug)]
+pub enum Shape {
+    /// A triangle pointing forward, 12 pixels long and 10 wide, centered on the turtle.
+    Triangle,
+    /// An arrowhead pointing forward, 14 pixels long and 12 wide, with a notch in the back.
+    Arrow,
+    /// Any polygon, with vertices given as (`forward`, `left`) in pixels from the turtle: a turtle facing right at
+    /// (0, 0) would stamp them at (`forward`, `left`).
+    Custom(Vec<(f64, f64)>),
+}
+
+impl Shape {
+    /// The vertices of the shape, as (`forward`, `left`) from the turtle.
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        match self {
+            Shape::Triangle => vec![(8., 0.), (-4., 5.), (-4., -5.)],
+            Shape::Arrow => vec![(8., 0.), (-6., 6.), (-2., 0.), (-6., -6.)],
+            Shape::Custom(points) => points.clone(),
+        }
+    }
+}
+
+/// Where the turtle went since [`Turtle::begin_fill`], and the lines it drew on the way.
+///
+/// [`Turtle::begin_fill`]: ./struct.Turtle.html#method.begin_fill
+#[derive(Clone, Debug, Default)]
+struct FillPath {
+    points: Vec<(f64, f64, f64)>,
+    /// Lines drawn on the way, drawn again over the fill.
+    lines: Vec<Line>,
+}
+
+/// A line drawn by the turtle.
+#[derive(Clone, Copy, Debug)]
+struct Line {
+    start: (f64, f64, f64),
+    end: (f64, f64, f64),
+    width: f64,
+    color: Rgb,
+}
+
+/// The pen's trip through a color map.
+#[derive(Clone, Debug)]
+struct ColorCycle {
+    map: ColorMap,
+    by: CycleBy,
+    period: f64,
+    /// Distance or steps so far.
+    progress: f64,
+}
+
+/// Everything [`Turtle::push`] saves.
+///
+/// [`Turtle::push`]: ./struct.Turtle.html#method.push
+#[derive(Clone, Copy, Debug)]
+struct State {
+    x: f64,
+    y: f64,
+    direction: f64,
+    pen_down: bool,
+    fg_color: Rgb,
+    pen_width: f64,
 }
 
 impl<T: Screen> Turtle<T> {
@@ -82,20 +232,26 @@ impl<T: Screen> Turtle<T> {
             pen_down: false,
             img: screen,
             fg_color,
+            pen_width: 1.,
+            stack: Vec::new(),
+            home: (x, y),
+            cycle: None,
+            fill: None,
+            fill_color: fg_color,
+            record_path: false,
+            path: Vec::new(),
+            capture_every: 0,
+            steps: 0,
+            frames: Vec::new(),
         }
     }
 
     /// Move forward, draw if `pen_down` is true.
     pub fn forward(&mut self, steps: f64) {
         let (x0, y0) = (self.x, self.y);
-        let (dx, dy) = polar_to_xy(steps.into(), self.direction);
-        let (x1, y1) = (x0 + dx, y0 + dy);
-        if self.pen_down {
-            self.img
-                .draw_line((x0, y0, 0.), (x1, y1, 0.), self.fg_color);
-        }
-        self.x = x1;
-        self.y = y1;
+        let (dx, dy) = polar_to_xy(steps, self.direction);
+        self.travel(x0 + dx, y0 + dy);
+        self.count_step();
     }
 
     /// Turn right `angle_deg` degrees without changing location.
@@ -109,13 +265,613 @@ impl<T: Screen> Turtle<T> {
     }
 
     /// Set position to (x, y), draw a line to the point if `pen_down` is true.
+    ///
+    /// To move without drawing no matter what `pen_down` is, use [`teleport`].
+    ///
+    /// [`teleport`]: #method.teleport
     pub fn move_to(&mut self, x: f64, y: f64) {
+        self.travel(x, y);
+        self.count_step();
+    }
+
+    /// Set position to (x, y) without drawing, even if `pen_down` is true.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{prelude::*, turtle::Turtle};
+    ///
+    /// let mut turtle = Turtle::new(Ppm::new(), 0., 0., Rgb::WHITE);
+    /// turtle.pen_down = true;
+    ///
+    /// turtle.teleport(100., 100.);
+    /// turtle.set_heading(90.);
+    /// turtle.forward(50.);
+    /// assert_eq!((100., 150.), turtle.position());
+    ///
+    /// let img = turtle.get_screen();
+    /// assert_eq!(Some(Rgb::BLACK), img.get(50, 50));
+    /// assert_eq!(Some(Rgb::WHITE), img.get(100, 120));
+    /// ```
+    pub fn teleport(&mut self, x: f64, y: f64) {
+        self.x = x;
+        self.y = y;
+        self.visit();
+    }
+
+    /// Go back to where the turtle was made, facing right. Draws a line there if `pen_down` is true, like
+    /// [`move_to`].
+    ///
+    /// [`move_to`]: #method.move_to
+    pub fn home(&mut self) {
+        let (x, y) = self.home;
+        self.move_to(x, y);
+        self.direction = 0.;
+    }
+
+    /// Face `angle_deg` degrees, counterclockwise from the right, no matter where the turtle was facing.
+    pub fn set_heading(&mut self, angle_deg: f64) {
+        self.direction = angle_deg % 360.;
+    }
+
+    /// The current position (x, y).
+    pub fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    /// The current direction in degrees. Same as the [`direction`] field.
+    ///
+    /// [`direction`]: #structfield.direction
+    pub fn heading(&self) -> f64 {
+        self.direction
+    }
+
+    /// Move along an arc of a circle with the given `radius`, turning `extent_degrees` on the way, and draw it if
+    /// `pen_down` is true.
+    ///
+    /// Like in Python's turtle, the center of the circle is `radius` to the turtle's left, so a positive `radius`
+    /// goes counterclockwise, and a negative one goes clockwise with the center on the right. A negative
+    /// `extent_degrees` goes backwards. The turtle ends up on the circle, facing along it, and the arc is drawn as
+    /// segments about 2 pixels long, which looks smooth.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{prelude::*, turtle::Turtle};
+    ///
+    /// let mut turtle = Turtle::new(Ppm::new(), 250., 250., Rgb::WHITE);
+    /// turtle.pen_down = true;
+    ///
+    /// // a quarter circle around (250, 350), ending at its right, facing up
+    /// turtle.arc(100., 90.);
+    /// turtle.forward(20.);
+    ///
+    /// let img = turtle.get_screen();
+    /// assert_eq!(Some(Rgb::WHITE), img.get(350, 350));
+    /// assert_eq!(Some(Rgb::WHITE), img.get(350, 365));
+    /// // a curve, not a straight line
+    /// assert_eq!(Some(Rgb::BLACK), img.get(300, 300));
+    /// ```
+    pub fn arc(&mut self, radius: f64, extent_degrees: f64) {
+        // going clockwise around a center on the right turns the other way
+        let turn = if radius < 0. {
+            -extent_degrees
+        } else {
+            extent_degrees
+        };
+        let start = self.direction + 90.;
+        let (dx, dy) = polar_to_xy(radius, start);
+        let (cx, cy) = (self.x + dx, self.y + dy);
+
+        let length = (radius * extent_degrees).abs().to_radians();
+        let steps = (length / 2.).ceil().clamp(1., 100_000.) as usize;
+        for step in 1..=steps {
+            let (dx, dy) = polar_to_xy(radius, start + turn * step as f64 / steps as f64);
+            self.travel(cx - dx, cy - dy);
+        }
+        self.count_step();
+        self.direction = (self.direction + turn) % 360.;
+    }
+
+    /// Draw a whole circle with the given `radius`, coming back to the same position and direction. See [`arc`].
+    ///
+    /// [`arc`]: #method.arc
+    pub fn circle(&mut self, radius: f64) {
+        self.arc(radius, 360.);
+    }
+
+    /// Save the position, direction, pen and color, to come back to them with [`pop`].
+    ///
+    /// Saved states make a stack: every `pop` restores the state saved by the latest `push` that wasn't popped yet.
+    /// This is how to draw branching shapes like trees and L-systems, where the turtle draws a branch, then goes back
+    /// to where the branch started.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{prelude::*, turtle::Turtle};
+    ///
+    /// let mut turtle = Turtle::new(Ppm::new(), 250., 0., Rgb::WHITE);
+    /// turtle.pen_down = true;
+    /// turtle.direction = 90.;
+    /// turtle.forward(100.);
+    ///
+    /// // two branches from the top of the trunk
+    /// for &angle in [45., -45.].iter() {
+    ///     turtle.push();
+    ///     turtle.turn_left(angle);
+    ///     turtle.fg_color = Rgb::new(0, 255, 0);
+    ///     turtle.forward(50.);
+    ///     turtle.pop();
+    /// }
+    /// // back at the top of the trunk, facing up, in white
+    /// turtle.forward(10.);
+    ///
+    /// let img = turtle.get_screen();
+    /// assert_eq!(Some(Rgb::WHITE), img.get(250, 105));
+    /// assert_eq!(Some(Rgb::new(0, 255, 0)), img.get(215, 135));
+    /// assert_eq!(Some(Rgb::new(0, 255, 0)), img.get(285, 135));
+    /// ```
+    ///
+    /// [`pop`]: #method.pop
+    pub fn push(&mut self) {
+        self.stack.push(State {
+            x: self.x,
+            y: self.y,
+            direction: self.direction,
+            pen_down: self.pen_down,
+            fg_color: self.fg_color,
+            pen_width: self.pen_width,
+        });
+    }
+
+    /// Go back to the state saved by the latest [`push`], without drawing.
+    ///
+    /// Returns `false`, and does nothing, if there is no saved state left.
+    ///
+    /// [`push`]: #method.push
+    pub fn pop(&mut self) -> bool {
+        let state = match self.stack.pop() {
+            Some(state) => state,
+            None => return false,
+        };
+        self.x = state.x;
+        self.y = state.y;
+        self.visit();
+        self.direction = state.direction;
+        self.pen_down = state.pen_down;
+        self.fg_color = state.fg_color;
+        self.pen_width = state.pen_width;
+        true
+    }
+
+    /// Draw `shape` filled with [`fg_color`] at the turtle's position, turned to face its direction, whether
+    /// `pen_down` is true or not. Shows which way the turtle is going, or stamps copies of a shape around.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{
+    ///     prelude::*,
+    ///     turtle::{Shape, Turtle},
+    /// };
+    ///
+    /// let mut turtle = Turtle::new(Ppm::new(), 100., 100., Rgb::WHITE);
+    /// turtle.set_heading(90.);
+    /// turtle.stamp(&Shape::Triangle);
+    ///
+    /// // a 40 by 20 rectangle in front of the turtle
+    /// turtle.teleport(300., 100.);
+    /// turtle.stamp(&Shape::Custom(vec![(0., -10.), (40., -10.), (40., 10.), (0., 10.)]));
+    ///
+    /// let img = turtle.get_screen();
+    /// // the triangle points up
+    /// assert_eq!(Some(Rgb::WHITE), img.get(100, 106));
+    /// assert_eq!(Some(Rgb::BLACK), img.get(100, 94));
+    /// assert_eq!(Some(Rgb::WHITE), img.get(300, 135));
+    /// assert_eq!(Some(Rgb::BLACK), img.get(315, 100));
+    /// ```
+    ///
+    /// [`fg_color`]: #structfield.fg_color
+    pub fn stamp(&mut self, shape: &Shape) {
+        let (forward_x, forward_y) = polar_to_xy(1., self.direction);
+        let points: Vec<_> = shape
+            .points()
+            .iter()
+            .map(|&(forward, left)| {
+                (
+                    self.x + forward * forward_x - left * forward_y,
+                    self.y + forward * forward_y + left * forward_x,
+                    0.,
+                )
+            })
+            .collect();
+        self.img.fill_polygon(&points, self.fg_color);
+    }
+
+    /// Start recording a shape to fill with [`end_fill`], from the current position.
+    ///
+    /// Like in Python's turtle, every place the turtle goes until [`end_fill`] is a vertex of the shape, whether
+    /// `pen_down` is true or not. Calling `begin_fill` again starts over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{prelude::*, turtle::Turtle};
+    ///
+    /// let mut turtle = Turtle::new(Ppm::new(), 100., 100., Rgb::WHITE);
+    /// turtle.pen_down = true;
+    /// turtle.fill_color = Rgb::new(255, 200, 0);
+    ///
+    /// // a star, which is concave
+    /// turtle.begin_fill();
+    /// for _ in 0..5 {
+    ///     turtle.forward(200.);
+    ///     turtle.turn_left(144.);
+    /// }
+    /// turtle.end_fill();
+    ///
+    /// let img = turtle.get_screen();
+    /// // the outline is drawn over the fill
+    /// assert_eq!(Some(Rgb::WHITE), img.get(150, 100));
+    /// assert_eq!(Some(Rgb::new(255, 200, 0)), img.get(150, 90));
+    /// ```
+    ///
+    /// [`end_fill`]: #method.end_fill
+    pub fn begin_fill(&mut self) {
+        self.fill = Some(FillPath::default());
+        self.visit();
+    }
+
+    /// Fill the shape recorded since [`begin_fill`] with [`fill_color`], and stop recording.
+    ///
+    /// The shape is closed automatically, and can be concave or cross itself (see [`fill_polygon`]). Lines drawn
+    /// since [`begin_fill`] are drawn again on top, so the outline stays visible. Does nothing if `begin_fill` wasn't
+    /// called.
+    ///
+    /// [`begin_fill`]: #method.begin_fill
+    /// [`fill_color`]: #structfield.fill_color
+    /// [`fill_polygon`]: ../screen/trait.Screen.html#method.fill_polygon
+    pub fn end_fill(&mut self) {
+        let fill = match self.fill.take() {
+            Some(fill) => fill,
+            None => return,
+        };
+        if fill.points.len() >= 3 {
+            self.img.fill_polygon(&fill.points, self.fill_color);
+        }
+        for line in fill.lines {
+            self.img
+                .draw_line_width(line.start, line.end, line.width, line.color);
+        }
+    }
+
+    /// Whether a shape is being recorded, between [`begin_fill`] and [`end_fill`].
+    ///
+    /// [`begin_fill`]: #method.begin_fill
+    /// [`end_fill`]: #method.end_fill
+    pub fn filling(&self) -> bool {
+        self.fill.is_some()
+    }
+
+    /// Make the pen go through the colors of `map` as the turtle moves, instead of staying [`fg_color`].
+    ///
+    /// The pen goes through the whole map every `period` pixels or steps, depending on `by`, then starts over. Every
+    /// line is drawn in the color at its start, so long lines are one color: draw with short moves (or [`arc`]s) for
+    /// smooth gradients. [`fg_color`] is kept up to date with the color of the latest line. A `period` of zero or
+    /// less stays on the first color.
+    ///
+    /// # Examples
+    ///
+    /// A rainbow spiral:
+    ///
+    /// ```
+    /// use graphics::{
+    ///     colormap::ColorMap,
+    ///     prelude::*,
+    ///     turtle::{CycleBy, Turtle},
+    /// };
+    ///
+    /// let mut turtle = Turtle::new(Ppm::new(), 250., 250., Rgb::WHITE);
+    /// turtle.pen_down = true;
+    /// turtle.cycle_colors(ColorMap::rainbow(), CycleBy::Steps, 100.);
+    ///
+    /// assert_eq!(Rgb::RED, turtle.fg_color);
+    /// for i in 0..200 {
+    ///     turtle.forward(i as f64);
+    ///     turtle.turn_left(59.);
+    /// }
+    /// // starting over after 100 steps
+    /// assert_eq!(ColorMap::rainbow().get(0.99), turtle.fg_color);
+    /// ```
+    ///
+    /// [`fg_color`]: #structfield.fg_color
+    /// [`arc`]: #method.arc
+    pub fn cycle_colors(&mut self, map: ColorMap, by: CycleBy, period: f64) {
+        self.fg_color = map.get(0.);
+        self.cycle = Some(ColorCycle {
+            map,
+            by,
+            period,
+            progress: 0.,
+        });
+    }
+
+    /// Stop cycling colors, and keep drawing in the current [`fg_color`].
+    ///
+    /// [`fg_color`]: #structfield.fg_color
+    pub fn stop_cycling(&mut self) {
+        self.cycle = None;
+    }
+
+    /// Go to (`x`, `y`), drawing a line there if `pen_down` is true.
+    fn travel(&mut self, x: f64, y: f64) {
+        if let Some(cycle) = &mut self.cycle {
+            self.fg_color = cycle.map.get((cycle.progress / cycle.period).fract());
+            if cycle.by == CycleBy::Distance {
+                cycle.progress += (x - self.x).hypot(y - self.y);
+            }
+        }
         if self.pen_down {
+            let (start, end) = ((self.x, self.y, 0.), (x, y, 0.));
             self.img
-                .draw_line((self.x, self.y, 0.), (x, y, 0.), self.fg_color);
+                .draw_line_width(start, end, self.pen_width, self.fg_color);
+            if let Some(fill) = &mut self.fill {
+                fill.lines.push(Line {
+                    start,
+                    end,
+                    width: self.pen_width,
+                    color: self.fg_color,
+                });
+            }
+            if self.record_path {
+                self.record(x, y);
+            }
         }
         self.x = x;
         self.y = y;
+        self.visit();
+    }
+
+    /// Record a line from the current position to (`x`, `y`), continuing the last stroke if it ends here.
+    fn record(&mut self, x: f64, y: f64) {
+        let (start, color, width) = ((self.x, self.y), self.fg_color, self.pen_width);
+        match self.path.last_mut() {
+            Some(stroke)
+                if stroke.points.last() == Some(&start)
+                    && stroke.color == color
+                    && stroke.width == width =>
+            {
+                stroke.points.push((x, y))
+            }
+            _ => self.path.push(Stroke {
+                points: vec![start, (x, y)],
+                color,
+                width,
+            }),
+        }
+    }
+
+    /// Add the current position to the shape being filled, if any.
+    fn visit(&mut self) {
+        if let Some(fill) = &mut self.fill {
+            fill.points.push((self.x, self.y, 0.));
+        }
+    }
+
+    /// Count a move for [`CycleBy::Steps`] and [`capture_every`].
+    ///
+    /// [`CycleBy::Steps`]: ./enum.CycleBy.html#variant.Steps
+    /// [`capture_every`]: #method.capture_every
+    fn count_step(&mut self) {
+        if let Some(cycle) = &mut self.cycle {
+            if cycle.by == CycleBy::Steps {
+                cycle.progress += 1.;
+            }
+        }
+        if self.capture_every > 0 {
+            self.steps += 1;
+            if self.steps >= self.capture_every {
+                self.capture_frame();
+            }
+        }
+    }
+
+    /// The lines drawn while [`record_path`] was `true`, as strokes of connected lines. A new stroke starts
+    /// where the turtle moved with the pen up, or changed color or width.
+    ///
+    /// The path can be drawn again on another screen, at another scale, or used as the vertices of polygons. See
+    /// [`write_svg`] to save it as a vector image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{prelude::*, turtle::Turtle};
+    ///
+    /// let mut turtle = Turtle::new(Ppm::new(), 10., 10., Rgb::WHITE);
+    /// turtle.record_path = true;
+    /// turtle.pen_down = true;
+    /// for _ in 0..4 {
+    ///     turtle.forward(20.);
+    ///     turtle.turn_left(90.);
+    /// }
+    /// turtle.pen_down = false;
+    /// turtle.forward(100.);
+    ///
+    /// let path = turtle.path();
+    /// assert_eq!(1, path.len());
+    /// assert_eq!(5, path[0].points.len());
+    ///
+    /// // draw the same walk 5 times bigger on a larger image
+    /// let mut big = PpmBuilder::new(2500, 2500, 255).build();
+    /// let points: Vec<_> = path[0].points.iter().map(|&(x, y)| (x * 5., y * 5., 0.)).collect();
+    /// big.fill_polygon(&points, Rgb::WHITE);
+    /// ```
+    ///
+    /// [`record_path`]: #structfield.record_path
+    /// [`write_svg`]: #method.write_svg
+    pub fn path(&self) -> &[Stroke] {
+        &self.path
+    }
+
+    /// Forget the recorded [`path`].
+    ///
+    /// [`path`]: #method.path
+    pub fn clear_path(&mut self) {
+        self.path.clear();
+    }
+
+    /// Write the recorded [`path`] to `writer` as an SVG image the size of the screen, with a line for each stroke.
+    ///
+    /// The SVG uses the same coordinates as the screen's [`origin`], so the image looks the same as the screen. The
+    /// background is left transparent.
+    ///
+    /// The `writer` will be buffered before being written to.
+    ///
+    /// [`path`]: #method.path
+    /// [`origin`]: ../screen/trait.Screen.html#method.origin
+    pub fn write_svg<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buf = BufWriter::new(writer);
+        let (width, height) = (self.img.width(), self.img.height());
+        writeln!(
+            buf,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height
+        )?;
+        // SVG has y going down from the top left, so move (0, 0) and flip the y axis to go up if needed
+        let transform = match self.img.origin() {
+            Origin::TopLeft => String::new(),
+            Origin::BottomLeft => format!(r#" transform="translate(0 {}) scale(1 -1)""#, height),
+            Origin::Center => format!(
+                r#" transform="translate({} {}) scale(1 -1)""#,
+                width / 2,
+                height - height / 2
+            ),
+        };
+        writeln!(
+            buf,
+            r#"<g{} fill="none" stroke-linecap="round" stroke-linejoin="round">"#,
+            transform
+        )?;
+        for stroke in &self.path {
+            write!(buf, r#"<polyline points=""#)?;
+            for (i, (x, y)) in stroke.points.iter().enumerate() {
+                let separator = if i == 0 { "" } else { " " };
+                write!(buf, "{}{},{}", separator, x, y)?;
+            }
+            writeln!(
+                buf,
+                r#"" stroke="rgb({},{},{})" stroke-width="{}"/>"#,
+                stroke.color.red, stroke.color.green, stroke.color.blue, stroke.width
+            )?;
+        }
+        writeln!(buf, "</g>\n</svg>")?;
+        buf.flush()
+    }
+
+    /// Save the recorded [`path`] as an SVG image at `path`. See [`write_svg`].
+    ///
+    /// [`path`]: #method.path
+    /// [`write_svg`]: #method.write_svg
+    pub fn save_svg<P: AsRef<Path>>(&self, path: P) -> error::Result<()> {
+        Ok(self.write_svg(&mut File::create(path)?)?)
+    }
+
+    /// Capture a frame of the screen every `steps` moves, to make an animation of the drawing with [`save_gif`].
+    /// `0` stops capturing. Every call to [`forward`], [`move_to`], [`arc`] or [`home`] is a step.
+    ///
+    /// Frames are kept in memory, uncompressed, so capture sparingly for long drawings: a 500 by 500 frame takes
+    /// 750 kB.
+    ///
+    /// # Examples
+    ///
+    /// Watch an L-system being drawn:
+    ///
+    /// ```no_run
+    /// use graphics::{lsystem::LSystem, prelude::*, turtle::Turtle};
+    ///
+    /// let dragon = LSystem::new("FX").rule('X', "X+YF+").rule('Y', "-FX-Y");
+    /// let mut turtle = Turtle::new(Ppm::new(), 300., 200., Rgb::WHITE);
+    /// turtle.capture_every(50);
+    /// dragon.draw(&mut turtle, 10, 4.);
+    ///
+    /// turtle.save_gif("dragon.gif", 4).expect("error saving dragon.gif");
+    /// ```
+    ///
+    /// [`save_gif`]: #method.save_gif
+    /// [`forward`]: #method.forward
+    /// [`move_to`]: #method.move_to
+    /// [`arc`]: #method.arc
+    /// [`home`]: #method.home
+    pub fn capture_every(&mut self, steps: usize) {
+        self.capture_every = steps;
+        self.steps = 0;
+    }
+
+    /// Capture a frame of the screen now, for [`save_gif`].
+    ///
+    /// [`save_gif`]: #method.save_gif
+    pub fn capture_frame(&mut self) {
+        self.steps = 0;
+        let mut frame = Vec::new();
+        // writing to memory only fails if the screen can't write itself, then there is no frame to keep
+        if self.img.write_to_buf(&mut frame).is_ok() {
+            self.frames.push(frame);
+        }
+    }
+
+    /// The frames captured so far, as images.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use graphics::{prelude::*, turtle::Turtle};
+    ///
+    /// let mut turtle = Turtle::new(Ppm::new(), 0., 0., Rgb::WHITE);
+    /// turtle.pen_down = true;
+    /// turtle.capture_every(2);
+    /// for _ in 0..5 {
+    ///     turtle.forward(10.);
+    /// }
+    ///
+    /// let frames = turtle.frames().unwrap();
+    /// assert_eq!(2, frames.len());
+    /// // the first frame has the first 2 lines only
+    /// assert_eq!(Some(Rgb::WHITE), frames[0].get(19, 0));
+    /// assert_eq!(Some(Rgb::BLACK), frames[0].get(21, 0));
+    /// ```
+    pub fn frames(&self) -> error::Result<Vec<Ppm>> {
+        self.frames
+            .iter()
+            .map(|frame| Ppm::from_reader(&frame[..]))
+            .collect()
+    }
+
+    /// Forget the captured frames.
+    pub fn clear_frames(&mut self) {
+        self.frames.clear();
+        self.steps = 0;
+    }
+
+    /// Save the captured frames, followed by the finished drawing, as an animated GIF that loops forever, with
+    /// `delay` hundredths of a second between frames.
+    ///
+    /// This requires ImageMagick to make the GIF.
+    pub fn save_gif(&self, file_path: &str, delay: u32) -> error::Result<()> {
+        let mut input: Vec<u8> = self.frames.concat();
+        self.img.write_to_buf(&mut input)?;
+        magick::convert_with_input(
+            &[
+                "-delay",
+                &delay.to_string(),
+                "-loop",
+                "0",
+                "ppm:-",
+                file_path,
+            ],
+            &input,
+        )
     }
 
     /// Get the inner [`Screen`] (T) instance.
@@ -128,3 +884,123 @@ impl<T: Screen> Turtle<T> {
         self.img
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: (f64, f64, f64), turtle: &Turtle<Ppm>) {
+        let actual = (turtle.x, turtle.y, turtle.direction);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(
+            close(expected.0, actual.0)
+                && close(expected.1, actual.1)
+                && close(expected.2, actual.2),
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_arc() {
+        let mut turtle = Turtle::new(Ppm::new(), 250., 250., Rgb::WHITE);
+        turtle.direction = 30.;
+        turtle.circle(80.);
+        assert_close((250., 250., 30.), &turtle);
+
+        turtle.direction = 0.;
+        turtle.arc(100., 90.);
+        assert_close((350., 350., 90.), &turtle);
+
+        // clockwise, with the center on the right
+        turtle.arc(-100., 90.);
+        assert_close((450., 450., 0.), &turtle);
+
+        // backwards
+        turtle.arc(100., -90.);
+        assert_close((350., 550., -90.), &turtle);
+    }
+
+    #[test]
+    fn test_home() {
+        let mut turtle = Turtle::new(Ppm::new(), 20., 30., Rgb::WHITE);
+        turtle.set_heading(400.);
+        assert_eq!(40., turtle.heading());
+        turtle.forward(100.);
+
+        turtle.pen_down = true;
+        turtle.home();
+        assert_close((20., 30., 0.), &turtle);
+        // the way back was drawn
+        assert_eq!(Some(Rgb::WHITE), turtle.img.get(40, 47));
+    }
+
+    #[test]
+    fn test_cycle_by_distance() {
+        let mut turtle = Turtle::new(Ppm::new(), 0., 0., Rgb::WHITE);
+        turtle.pen_down = true;
+        turtle.cycle_colors(ColorMap::grayscale(), CycleBy::Distance, 100.);
+        turtle.forward(50.);
+        turtle.forward(25.);
+        assert_eq!(Rgb::gray(128), turtle.fg_color);
+        // teleporting isn't travelling
+        turtle.teleport(0., 100.);
+        turtle.move_to(0., 125.);
+        turtle.move_to(0., 150.);
+        assert_eq!(Rgb::BLACK, turtle.fg_color);
+        assert_eq!(Some(Rgb::BLACK), turtle.img.get(0, 140));
+        assert_eq!(Some(Rgb::gray(191)), turtle.img.get(0, 110));
+
+        turtle.stop_cycling();
+        turtle.forward(10.);
+        assert_eq!(Rgb::BLACK, turtle.fg_color);
+    }
+
+    #[test]
+    fn test_write_svg() {
+        let mut turtle = Turtle::new(PpmBuilder::new(50, 100, 255).build(), 0., 0., Rgb::WHITE);
+        turtle.pen_down = true;
+        turtle.record_path = true;
+        turtle.move_to(10., 20.);
+        turtle.move_to(30., 20.);
+        turtle.teleport(0., 40.);
+        turtle.fg_color = Rgb::new(255, 0, 0);
+        turtle.pen_width = 3.;
+        turtle.move_to(5., 40.);
+
+        let mut svg = Vec::new();
+        turtle.write_svg(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains(r#"width="100" height="50""#), "{}", svg);
+        assert!(svg.contains(r#"translate(0 50) scale(1 -1)"#), "{}", svg);
+
+        // the transform follows the origin of the screen
+        let with_origin = |origin| {
+            let img = PpmBuilder::new(50, 100, 255).origin(origin).build();
+            let mut svg = Vec::new();
+            Turtle::new(img, 0., 0., Rgb::WHITE)
+                .write_svg(&mut svg)
+                .unwrap();
+            String::from_utf8(svg).unwrap()
+        };
+        assert!(with_origin(Origin::TopLeft).contains(r#"<g fill="none""#));
+        assert!(
+            with_origin(Origin::Center).contains(r#"<g transform="translate(50 25) scale(1 -1)""#)
+        );
+        assert!(
+            svg.contains(
+                r#"<polyline points="0,0 10,20 30,20" stroke="rgb(255,255,255)" stroke-width="1"/>"#
+            ),
+            "{}",
+            svg
+        );
+        assert!(
+            svg.contains(
+                r#"<polyline points="0,40 5,40" stroke="rgb(255,0,0)" stroke-width="3"/>"#
+            ),
+            "{}",
+            svg
+        );
+    }
+}
@@ -1,7 +1,320 @@
 //! Functions that can be used across modules but don't fall into other categories.
 
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Floating point type used for geometry ([`Vec3`], [`Point`]) and the z-buffer.
+///
+/// This is `f64` by default. Enable the `f32` feature to switch to `f32`, which halves the memory used (and moved around) by
+/// the z-buffer and vertex data. Tuples of `f64` can still be converted into [`Vec3`] and [`Point`] either way.
+///
+/// # Warning
+///
+/// Unlike other features, `f32` isn't additive: it changes the types in the public API, so crates written against `f64`
+/// break when it is on, and Cargo turns it on for every crate in the build once any of them asks for it. **Only the final
+/// binary should enable `f32`.** Libraries built on this crate should write `Float`, never `f64`, for z values and the
+/// fields of [`Vec3`] and [`Point`], so they compile either way.
+///
+/// [`Vec3`]: ./struct.Vec3.html
+/// [`Point`]: ./struct.Point.html
+#[cfg(not(feature = "f32"))]
+pub type Float = f64;
+
+/// Floating point type used for geometry ([`Vec3`], [`Point`]) and the z-buffer.
+///
+/// This is `f32` because the `f32` feature is enabled. **Only the final binary should enable it**, see the
+/// [crate documentation](../index.html#features).
+///
+/// [`Vec3`]: ./struct.Vec3.html
+/// [`Point`]: ./struct.Point.html
+#[cfg(feature = "f32")]
+pub type Float = f32;
+
 /// Calculate `(x, y)` from `mag` and `angle_deg`.
 pub(crate) fn polar_to_xy(mag: f64, angle_degrees: f64) -> (f64, f64) {
     let (dy, dx) = angle_degrees.to_radians().sin_cos();
     (dx * mag, dy * mag)
 }
+
+/// A 3D vector, for lighting, surface normals, backface culling, and the like.
+///
+/// Common operators (`+`, `-`, `*` and `/` by a scalar) are overloaded, so vector math reads like it does on paper.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::util::Vec3;
+///
+/// let x = Vec3::new(1., 0., 0.);
+/// let y = Vec3::new(0., 1., 0.);
+///
+/// assert_eq!(0., x.dot(y));
+/// assert_eq!(Vec3::new(0., 0., 1.), x.cross(y));
+/// assert_eq!(Vec3::new(2., 2., 0.), (x + y) * 2.);
+/// assert_eq!(1., Vec3::new(3., 4., 0.).normalize().length());
+///
+/// // tuples convert both ways
+/// let v: Vec3 = (1., 2., 3.).into();
+/// assert_eq!((1., 2., 3.), v.into());
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Vec3 {
+    pub x: Float,
+    pub y: Float,
+    pub z: Float,
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3::new(0., 0., 0.);
+
+    /// Function constructor for [`Vec3`].
+    ///
+    /// [`Vec3`]: ./struct.Vec3.html
+    pub const fn new(x: Float, y: Float, z: Float) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    /// Dot product.
+    pub fn dot(self, other: Vec3) -> Float {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Cross product, following the right-hand rule.
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    /// Length (magnitude) of the vector.
+    pub fn length(self) -> Float {
+        self.dot(self).sqrt()
+    }
+
+    /// Unit vector pointing in the same direction. A zero vector stays zero instead of becoming `NaN`s.
+    pub fn normalize(self) -> Vec3 {
+        let len = self.length();
+        if len == 0. {
+            self
+        } else {
+            self / len
+        }
+    }
+}
+
+impl From<(f64, f64, f64)> for Vec3 {
+    fn from((x, y, z): (f64, f64, f64)) -> Self {
+        Vec3::new(x as Float, y as Float, z as Float)
+    }
+}
+
+#[cfg(feature = "f32")]
+impl From<(f32, f32, f32)> for Vec3 {
+    fn from((x, y, z): (f32, f32, f32)) -> Self {
+        Vec3 { x, y, z }
+    }
+}
+
+impl From<Vec3> for (Float, Float, Float) {
+    fn from(v: Vec3) -> Self {
+        (v.x, v.y, v.z)
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, rhs: Vec3) -> Vec3 {
+        Vec3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, rhs: Vec3) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, rhs: Vec3) -> Vec3 {
+        Vec3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, rhs: Vec3) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Mul<Float> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Float) -> Vec3 {
+        Vec3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Mul<Vec3> for Float {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        rhs * self
+    }
+}
+
+impl MulAssign<Float> for Vec3 {
+    fn mul_assign(&mut self, rhs: Float) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div<Float> for Vec3 {
+    type Output = Vec3;
+
+    fn div(self, rhs: Float) -> Vec3 {
+        Vec3::new(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
+}
+
+/// A point in 3D space (x, y, z).
+///
+/// Drawing functions accept anything that is `Into<Point>`, so `(x, y, z)` tuples can be used wherever a [`Point`] is expected.
+///
+/// Subtracting two points gives the [`Vec3`] between them, and a [`Vec3`] can be added to a point to move it.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::prelude::*;
+///
+/// let p = Point::new(1., 2., 3.);
+///
+/// assert_eq!(Point::from((1., 2., 3.)), p);
+/// assert_eq!(Point::new(2., 2., 3.), p.translated(1., 0., 0.));
+/// assert_eq!(Vec3::new(1., 0., 0.), p.translated(1., 0., 0.) - p);
+/// assert_eq!(5., Point::new(0., 0., 0.).distance(Point::new(3., 4., 0.)));
+///
+/// let mut img = Ppm::new();
+/// // tuples and points can be mixed
+/// img.draw_line(p, (100., 100., 0.), Rgb::WHITE);
+/// ```
+///
+/// [`Point`]: ./struct.Point.html
+/// [`Vec3`]: ./struct.Vec3.html
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Point {
+    pub x: Float,
+    pub y: Float,
+    pub z: Float,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point::new(0., 0., 0.);
+
+    /// Function constructor for [`Point`].
+    ///
+    /// [`Point`]: ./struct.Point.html
+    pub const fn new(x: Float, y: Float, z: Float) -> Self {
+        Point { x, y, z }
+    }
+
+    /// Return this point moved by (`dx`, `dy`, `dz`).
+    pub fn translated(self, dx: Float, dy: Float, dz: Float) -> Point {
+        Point::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    /// Euclidean distance to `other`.
+    pub fn distance(self, other: Point) -> Float {
+        (other - self).length()
+    }
+
+    /// Linearly interpolate between `self` (`t = 0`) and `other` (`t = 1`).
+    pub fn lerp(self, other: Point, t: Float) -> Point {
+        self + (other - self) * t
+    }
+}
+
+impl From<(f64, f64, f64)> for Point {
+    fn from((x, y, z): (f64, f64, f64)) -> Self {
+        Point::new(x as Float, y as Float, z as Float)
+    }
+}
+
+#[cfg(feature = "f32")]
+impl From<(f32, f32, f32)> for Point {
+    fn from((x, y, z): (f32, f32, f32)) -> Self {
+        Point { x, y, z }
+    }
+}
+
+impl From<Point> for (Float, Float, Float) {
+    fn from(p: Point) -> Self {
+        (p.x, p.y, p.z)
+    }
+}
+
+impl From<Vec3> for Point {
+    fn from(v: Vec3) -> Self {
+        Point::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Point> for Vec3 {
+    fn from(p: Point) -> Self {
+        Vec3::new(p.x, p.y, p.z)
+    }
+}
+
+impl Sub for Point {
+    type Output = Vec3;
+
+    fn sub(self, rhs: Point) -> Vec3 {
+        Vec3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Add<Vec3> for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Vec3) -> Point {
+        self.translated(rhs.x, rhs.y, rhs.z)
+    }
+}
+
+impl Sub<Vec3> for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Vec3) -> Point {
+        self + -rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_polar_to_xy_round_trip(mag in 1e-3..1e4, angle in -720. ..720.) {
+            let (x, y) = polar_to_xy(mag, angle);
+
+            prop_assert!((x.hypot(y) - mag).abs() <= 1e-9 * mag);
+            let back = y.atan2(x).to_degrees();
+            let turn = (back - angle + 180.).rem_euclid(360.) - 180.;
+            prop_assert!(turn.abs() < 1e-6, "came back as {}", back);
+        }
+    }
+}
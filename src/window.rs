@@ -0,0 +1,754 @@
+//! A [`Screen`] shown live in a window, so drawing can be watched as it happens.
+//!
+//! [`WindowScreen`] draws into a [`Ppm`] and shows its pixels in a window, opened with [minifb] by
+//! [`WindowScreen::open`]. Every plot is shown, up to [`update_interval`] apart, so a line algorithm can be watched
+//! pixel by pixel with an interval of `0`. Windows from other libraries, like [softbuffer], can be used instead by
+//! implementing [`Window`] for them.
+//!
+//! This module is only available with the `window` feature, which adds a dependency on minifb:
+//!
+//! ```toml
+//! [dependencies]
+//! graphics = { path = "..", features = ["window"] }
+//! ```
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use graphics::{prelude::*, window::WindowScreen};
+//!
+//! let mut screen = WindowScreen::open(Ppm::new(), "graphics").expect("error opening window");
+//! screen.draw_line((0., 0., 0.), (499., 250., 0.), Rgb::WHITE);
+//! screen.wait_until_closed();
+//! ```
+//!
+//! [`Screen`]: ../screen/trait.Screen.html
+//! [`WindowScreen`]: ./struct.WindowScreen.html
+//! [`WindowScreen::open`]: ./struct.WindowScreen.html#method.open
+//! [`Ppm`]: ../ppm/struct.Ppm.html
+//! [`Window`]: ./trait.Window.html
+//! [`update_interval`]: ./struct.WindowScreen.html#method.update_interval
+//! [minifb]: https://crates.io/crates/minifb
+//! [softbuffer]: https://crates.io/crates/softbuffer
+
+use std::{
+    io, mem,
+    ops::Range,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    color::{PpmColor, Rgb},
+    error::{self, GraphicsError},
+    ppm::{Filter, Origin, Ppm},
+    raster::Rasterizer,
+    screen::{SaveOptions, Screen},
+    util::{Float, Point, Vec3},
+};
+
+/// A window that can show an image. See the [module documentation].
+///
+/// [module documentation]: ./index.html
+pub trait Window {
+    /// Show `pixels`: `width * height` colors as `0x00RRGGBB`, row by row from the top left.
+    ///
+    /// This is also a good place to handle the window's events, since it's called regularly.
+    fn update(&mut self, pixels: &[u32], width: usize, height: usize);
+
+    /// Whether the window is still open.
+    fn is_open(&self) -> bool;
+
+    /// Whether `key` is held down. Windows that don't report keys can leave this out.
+    fn is_key_down(&self, key: Key) -> bool {
+        let _ = key;
+        false
+    }
+
+    /// The mouse, if it's over the window. Windows that don't report the mouse can leave this out.
+    fn mouse(&self) -> Option<Mouse> {
+        None
+    }
+}
+
+/// Keys used by the interactive controls, like [`OrbitCamera::handle_input`].
+///
+/// [`OrbitCamera::handle_input`]: ./struct.OrbitCamera.html#method.handle_input
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Key {
+    Left,
+    Right,
+    Up,
+    Down,
+    W,
+    A,
+    S,
+    D,
+    Q,
+    E,
+    Space,
+}
+
+/// The state of the mouse over a [`Window`].
+///
+/// [`Window`]: ./trait.Window.html
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Mouse {
+    /// Position in pixels, from the top left of the window.
+    pub x: Float,
+    pub y: Float,
+    /// Whether the left button is held down.
+    pub left: bool,
+    /// Whether the right button is held down.
+    pub right: bool,
+    /// How far the wheel was scrolled since the last update, positive away from the user.
+    pub scroll: Float,
+}
+
+/// A [`Window`] opened with [minifb].
+///
+/// [`Window`]: ./trait.Window.html
+/// [minifb]: https://crates.io/crates/minifb
+pub struct MinifbWindow {
+    window: minifb::Window,
+}
+
+impl MinifbWindow {
+    /// Open a window titled `title`, `width` by `height` pixels.
+    ///
+    /// Fails with [`GraphicsError::Window`] if it can't be opened, for example when there is no display.
+    ///
+    /// [`GraphicsError::Window`]: ../error/enum.GraphicsError.html#variant.Window
+    pub fn open(title: &str, width: usize, height: usize) -> error::Result<Self> {
+        let mut window =
+            minifb::Window::new(title, width, height, minifb::WindowOptions::default())
+                .map_err(|e| GraphicsError::Window(e.to_string()))?;
+        // WindowScreen already waits between updates
+        window.set_target_fps(0);
+        Ok(MinifbWindow { window })
+    }
+
+    /// The minifb window, for what [`Window`] doesn't cover, like setting the title.
+    ///
+    /// [`Window`]: ./trait.Window.html
+    pub fn inner(&self) -> &minifb::Window {
+        &self.window
+    }
+
+    /// The minifb window, for what [`Window`] doesn't cover, like setting the title.
+    ///
+    /// [`Window`]: ./trait.Window.html
+    pub fn inner_mut(&mut self) -> &mut minifb::Window {
+        &mut self.window
+    }
+}
+
+impl Window for MinifbWindow {
+    fn update(&mut self, pixels: &[u32], width: usize, height: usize) {
+        // this only fails if `pixels` is smaller than `width * height`, then there is nothing to show
+        if self
+            .window
+            .update_with_buffer(pixels, width, height)
+            .is_err()
+        {
+            self.window.update();
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        let key = match key {
+            Key::Left => minifb::Key::Left,
+            Key::Right => minifb::Key::Right,
+            Key::Up => minifb::Key::Up,
+            Key::Down => minifb::Key::Down,
+            Key::W => minifb::Key::W,
+            Key::A => minifb::Key::A,
+            Key::S => minifb::Key::S,
+            Key::D => minifb::Key::D,
+            Key::Q => minifb::Key::Q,
+            Key::E => minifb::Key::E,
+            Key::Space => minifb::Key::Space,
+        };
+        self.window.is_key_down(key)
+    }
+
+    // the casts are only needed without the f32 feature
+    #[allow(clippy::unnecessary_cast)]
+    fn mouse(&self) -> Option<Mouse> {
+        let (x, y) = self.window.get_mouse_pos(minifb::MouseMode::Discard)?;
+        let scroll = self.window.get_scroll_wheel().map_or(0., |(_, y)| y);
+        Some(Mouse {
+            x: x as Float,
+            y: y as Float,
+            left: self.window.get_mouse_down(minifb::MouseButton::Left),
+            right: self.window.get_mouse_down(minifb::MouseButton::Right),
+            scroll: scroll as Float,
+        })
+    }
+}
+
+/// A [`Ppm`] shown in a [`Window`], updated as it's drawn on. See the [module documentation].
+///
+/// [`Ppm`]: ../ppm/struct.Ppm.html
+/// [`Window`]: ./trait.Window.html
+/// [module documentation]: ./index.html
+pub struct WindowScreen<W, C: PpmColor = Rgb> {
+    img: Ppm<C>,
+    window: W,
+    update_interval: Duration,
+    last_update: Instant,
+}
+
+impl<W: Window, C: PpmColor> WindowScreen<W, C> {
+    /// Show `img` in `window`.
+    pub fn new(img: Ppm<C>, window: W) -> Self {
+        let mut screen = WindowScreen {
+            img,
+            window,
+            update_interval: Duration::from_secs(1) / 60,
+            last_update: Instant::now(),
+        };
+        screen.refresh();
+        screen
+    }
+
+    /// Set the shortest time between window updates while drawing. The default is a 60th of a second; `0` shows
+    /// every single plot, which is slow, but handy to watch how a shape is rasterized.
+    pub fn update_interval(mut self, interval: Duration) -> Self {
+        self.update_interval = interval;
+        self
+    }
+
+    /// Show the image in the window now.
+    pub fn refresh(&mut self) {
+        let pixels: Vec<u32> = self
+            .img
+            .to_rgba8()
+            .iter()
+            .map(|&[r, g, b, _]| u32::from_be_bytes([0, r, g, b]))
+            .collect();
+        self.window
+            .update(&pixels, self.img.width(), self.img.height());
+        self.last_update = Instant::now();
+    }
+
+    /// Show the image if the window wasn't updated for the [`update_interval`].
+    ///
+    /// [`update_interval`]: #method.update_interval
+    fn refresh_if_due(&mut self) {
+        if self.last_update.elapsed() >= self.update_interval {
+            self.refresh();
+        }
+    }
+
+    /// Whether the window is still open.
+    pub fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    /// Keep showing the image until the window is closed.
+    pub fn wait_until_closed(&mut self) {
+        let interval = self.update_interval.max(Duration::from_millis(1));
+        while self.window.is_open() {
+            self.refresh();
+            thread::sleep(interval);
+        }
+    }
+
+    /// Draw a 3D scene, and draw it again whenever `camera` is moved with the keyboard or mouse, until the window is
+    /// closed. See [`OrbitCamera::handle_input`] for the controls.
+    ///
+    /// Every time, the image is cleared to `bg_color`, and `draw` is called with the image and the camera, whose
+    /// [`project`] turns the scene's points into screen coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use graphics::{prelude::*, window::OrbitCamera};
+    ///
+    /// // the 12 edges of a cube
+    /// let corners: Vec<Point> = (0..8)
+    ///     .map(|i| Point::new((i & 1) as f64 * 100., (i >> 1 & 1) as f64 * 100., (i >> 2) as f64 * 100.))
+    ///     .collect();
+    /// let edges: Vec<(usize, usize)> = (0..8)
+    ///     .flat_map(|i| (0..3).map(move |bit| (i, i | 1 << bit)).filter(|&(i, j)| i != j))
+    ///     .collect();
+    ///
+    /// let mut camera = OrbitCamera::new(Point::new(50., 50., 50.), 400.);
+    /// screen.run_scene(&mut camera, Rgb::BLACK, |img, camera| {
+    ///     for &(i, j) in &edges {
+    ///         let (w, h) = (img.width(), img.height());
+    ///         if let (Some(p0), Some(p1)) = (camera.project(corners[i], w, h), camera.project(corners[j], w, h)) {
+    ///             img.draw_line(p0, p1, Rgb::WHITE);
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// [`OrbitCamera::handle_input`]: ./struct.OrbitCamera.html#method.handle_input
+    /// [`project`]: ./struct.OrbitCamera.html#method.project
+    pub fn run_scene<F>(&mut self, camera: &mut OrbitCamera, bg_color: C, mut draw: F)
+    where
+        F: FnMut(&mut Ppm<C>, &OrbitCamera),
+    {
+        let interval = self.update_interval.max(Duration::from_millis(1));
+        let mut changed = true;
+        while self.window.is_open() {
+            if changed {
+                self.img.clear(bg_color);
+                draw(&mut self.img, camera);
+            }
+            self.refresh();
+            thread::sleep(interval);
+            changed = camera.handle_input(&self.window);
+        }
+    }
+
+    /// Play an animation of `frames` frames at `fps` frames per second, looping until the window is closed.
+    ///
+    /// Frames are rendered on the fly: `render` is called with a cleared back buffer and the frame number, and the
+    /// finished frame replaces the one in the window at once, so half-drawn frames never show up. Each frame is only
+    /// rendered when it's shown, so stepping back renders it again.
+    ///
+    /// - `Space` pauses and resumes.
+    /// - The left and right arrow keys step one frame back and forward, and pause.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use graphics::prelude::*;
+    ///
+    /// screen.play(120, 30., Rgb::BLACK, |img, frame| {
+    ///     img.draw_circle(250., 250., 0., frame as f64 * 2., Rgb::WHITE);
+    /// });
+    /// ```
+    pub fn play<F>(&mut self, frames: usize, fps: Float, bg_color: C, mut render: F)
+    where
+        F: FnMut(&mut Ppm<C>, usize),
+    {
+        if frames == 0 {
+            return;
+        }
+        // the cast is only needed with the f32 feature
+        #[allow(clippy::unnecessary_cast)]
+        let frame_time = Duration::from_secs_f64(1. / (fps as f64).max(1e-3));
+        let mut back = self.img.blank_like();
+        let mut keys = KeyPresses::default();
+        let (mut frame, mut shown, mut playing) = (0, None, true);
+
+        while self.window.is_open() {
+            let start = Instant::now();
+            if shown != Some(frame) {
+                back.clear(bg_color);
+                render(&mut back, frame);
+                mem::swap(&mut self.img, &mut back);
+                shown = Some(frame);
+            }
+            self.refresh();
+
+            keys.update(&self.window);
+            if keys.pressed(Key::Space) {
+                playing = !playing;
+            }
+            if keys.pressed(Key::Right) {
+                playing = false;
+                frame = (frame + 1) % frames;
+            } else if keys.pressed(Key::Left) {
+                playing = false;
+                frame = (frame + frames - 1) % frames;
+            } else if playing {
+                frame = (frame + 1) % frames;
+            }
+            thread::sleep(frame_time.saturating_sub(start.elapsed()));
+        }
+    }
+
+    /// Play frames that are already rendered at `fps` frames per second, with the same controls as [`play`].
+    ///
+    /// [`play`]: #method.play
+    pub fn play_frames(&mut self, frames: &[Ppm<C>], fps: Float) {
+        self.play(frames.len(), fps, C::from_rgb(Rgb::BLACK), |img, frame| {
+            // scaled to the window's size
+            *img = frames[frame].resized(img.width(), img.height(), Filter::Nearest);
+        });
+    }
+
+    /// The image being drawn on.
+    pub fn image(&self) -> &Ppm<C> {
+        &self.img
+    }
+
+    /// The image being drawn on. Changes show up in the window on the next [`refresh`].
+    ///
+    /// [`refresh`]: #method.refresh
+    pub fn image_mut(&mut self) -> &mut Ppm<C> {
+        &mut self.img
+    }
+
+    /// The window the image is shown in.
+    pub fn window(&self) -> &W {
+        &self.window
+    }
+
+    /// The window the image is shown in.
+    pub fn window_mut(&mut self) -> &mut W {
+        &mut self.window
+    }
+
+    /// Close the screen, keeping the image.
+    pub fn into_image(self) -> Ppm<C> {
+        self.img
+    }
+}
+
+impl<C: PpmColor> WindowScreen<MinifbWindow, C> {
+    /// Show `img` in a new window titled `title`, the size of the image.
+    ///
+    /// Fails with [`GraphicsError::Window`] if the window can't be opened, for example when there is no display.
+    ///
+    /// [`GraphicsError::Window`]: ../error/enum.GraphicsError.html#variant.Window
+    pub fn open(img: Ppm<C>, title: &str) -> error::Result<Self> {
+        let window = MinifbWindow::open(title, img.width(), img.height())?;
+        Ok(WindowScreen::new(img, window))
+    }
+}
+
+impl<W: Window, C: PpmColor> Screen<C> for WindowScreen<W, C> {
+    fn plot(&mut self, x: i64, y: i64, z: Float, color: C) {
+        self.img.plot(x, y, z, color);
+        self.refresh_if_due();
+    }
+
+    fn set_pixel(&mut self, x: i64, y: i64, color: C) {
+        self.img.set(x, y, color);
+        self.refresh_if_due();
+    }
+
+    fn save(&self, file_path: &str) -> error::Result<()> {
+        self.img.save(file_path)
+    }
+
+    fn save_with(&self, file_path: &str, options: &SaveOptions) -> error::Result<()> {
+        self.img.save_with(file_path, options)
+    }
+
+    fn width(&self) -> usize {
+        self.img.width()
+    }
+
+    fn height(&self) -> usize {
+        self.img.height()
+    }
+
+    fn write_to_buf<T: io::Write>(&self, writer: &mut T) -> io::Result<()> {
+        self.img.write_to_buf(writer)
+    }
+
+    /// Open the image in ImageMagick, like [`Ppm::display`], besides the window.
+    ///
+    /// [`Ppm::display`]: ../ppm/struct.Ppm.html#method.display
+    fn display(&self) -> error::Result<()> {
+        self.img.display()
+    }
+
+    fn clear(&mut self, color: C) {
+        self.img.clear(color);
+        self.refresh_if_due();
+    }
+
+    fn get_pixel(&self, x: i64, y: i64) -> Option<C> {
+        self.img.get_pixel(x, y)
+    }
+
+    fn get_depth(&self, x: i64, y: i64) -> Option<Float> {
+        self.img.get_depth(x, y)
+    }
+
+    fn rasterizer(&self) -> &'static dyn Rasterizer {
+        self.img.rasterizer()
+    }
+
+    fn clip_bounds(&self) -> (Range<i64>, Range<i64>) {
+        self.img.clip_bounds()
+    }
+
+    fn origin(&self) -> Origin {
+        self.img.origin()
+    }
+}
+
+/// A camera looking at a `target` point from `distance` away, that can be orbited around it, panned, and zoomed.
+///
+/// At a `yaw` and `pitch` of `0`, the camera looks down the negative z axis, with y up, like the default screen
+/// coordinates. Angles are in degrees.
+///
+/// # Examples
+///
+/// ```
+/// use graphics::{prelude::*, window::OrbitCamera};
+///
+/// let camera = OrbitCamera::new(Point::new(0., 0., 0.), 100.);
+/// // the target ends up in the middle of the screen
+/// let center = camera.project(Point::new(0., 0., 0.), 500, 500).unwrap();
+/// assert_eq!((250., 250.), (center.x, center.y));
+/// // and points behind the camera aren't drawn
+/// assert_eq!(None, camera.project(Point::new(0., 0., 200.), 500, 500));
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct OrbitCamera {
+    /// The point looked at, which the camera orbits around.
+    pub target: Point,
+    /// Distance from the camera to the `target`.
+    pub distance: Float,
+    /// Rotation around the y axis, in degrees.
+    pub yaw: Float,
+    /// Rotation up from the xz plane, in degrees, between `-89` and `89`.
+    pub pitch: Float,
+    /// Vertical field of view, in degrees.
+    pub fov: Float,
+    last_mouse: Option<Mouse>,
+}
+
+impl OrbitCamera {
+    /// How many degrees the arrow keys orbit per update.
+    const KEY_ORBIT: Float = 2.;
+    /// How many degrees a pixel of mouse movement orbits.
+    const MOUSE_ORBIT: Float = 0.5;
+
+    /// A camera looking at `target` from `distance` away along the positive z axis, with a 60 degree field of view.
+    pub fn new(target: Point, distance: Float) -> Self {
+        OrbitCamera {
+            target,
+            distance,
+            yaw: 0.,
+            pitch: 0.,
+            fov: 60.,
+            last_mouse: None,
+        }
+    }
+
+    /// Rotate the camera around the `target`, by `yaw` degrees to the right and `pitch` degrees up.
+    pub fn orbit(&mut self, yaw: Float, pitch: Float) {
+        self.yaw = (self.yaw + yaw) % 360.;
+        self.pitch = (self.pitch + pitch).clamp(-89., 89.);
+    }
+
+    /// Move the camera and the `target` by `dx` to the right and `dy` up, relative to the view and scaled by the
+    /// `distance`, so panning feels the same at every zoom.
+    pub fn pan(&mut self, dx: Float, dy: Float) {
+        let (right, up, _) = self.axes();
+        self.target = self.target + (right * dx + up * dy) * (self.distance / 100.);
+    }
+
+    /// Multiply the `distance` by `factor`; below `1` zooms in.
+    pub fn zoom(&mut self, factor: Float) {
+        self.distance = (self.distance * factor).max(1e-3);
+    }
+
+    /// Where the camera is.
+    pub fn eye(&self) -> Point {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        let offset = Vec3::new(
+            pitch.cos() * yaw.sin(),
+            pitch.sin(),
+            pitch.cos() * yaw.cos(),
+        );
+        self.target + offset * self.distance
+    }
+
+    /// The right, up and forward unit vectors of the view.
+    fn axes(&self) -> (Vec3, Vec3, Vec3) {
+        let forward = (self.target - self.eye()).normalize();
+        let right = forward.cross(Vec3::new(0., 1., 0.)).normalize();
+        (right, right.cross(forward), forward)
+    }
+
+    /// Project `p` onto a `width` by `height` screen, with y up. The z of the result is the negated distance in
+    /// front of the camera, so closer points have bigger z, as the depth buffer expects.
+    ///
+    /// Returns `None` for points behind (or right at) the camera.
+    pub fn project(&self, p: Point, width: usize, height: usize) -> Option<Point> {
+        let (right, up, forward) = self.axes();
+        let v = p - self.eye();
+        let depth = v.dot(forward);
+        if depth <= 1e-3 {
+            return None;
+        }
+        let focal = height as Float / 2. / (self.fov.to_radians() / 2.).tan();
+        Some(Point::new(
+            width as Float / 2. + v.dot(right) * focal / depth,
+            height as Float / 2. + v.dot(up) * focal / depth,
+            -depth,
+        ))
+    }
+
+    /// Move the camera with the keyboard and mouse of `window`, and return whether it moved.
+    ///
+    /// - The arrow keys, or dragging with the left mouse button, orbit around the `target`.
+    /// - `W`, `A`, `S`, `D`, or dragging with the right mouse button, pan.
+    /// - `Q` and `E`, or the mouse wheel, zoom in and out.
+    pub fn handle_input<W: Window>(&mut self, window: &W) -> bool {
+        let before = (self.target, self.distance, self.yaw, self.pitch);
+        let key = |k| if window.is_key_down(k) { 1. } else { 0. };
+
+        self.orbit(
+            (key(Key::Right) - key(Key::Left)) * Self::KEY_ORBIT,
+            (key(Key::Up) - key(Key::Down)) * Self::KEY_ORBIT,
+        );
+        self.pan(key(Key::D) - key(Key::A), key(Key::W) - key(Key::S));
+        self.zoom(1. + (key(Key::E) - key(Key::Q)) * 0.02);
+
+        let mouse = window.mouse();
+        if let (Some(last), Some(now)) = (self.last_mouse, mouse) {
+            let (dx, dy) = (now.x - last.x, now.y - last.y);
+            if now.left {
+                // window y goes down
+                self.orbit(-dx * Self::MOUSE_ORBIT, dy * Self::MOUSE_ORBIT);
+            } else if now.right {
+                self.pan(-dx / 5., dy / 5.);
+            }
+        }
+        if let Some(now) = mouse {
+            self.zoom(Float::powf(0.9, now.scroll));
+        }
+        self.last_mouse = mouse;
+
+        before != (self.target, self.distance, self.yaw, self.pitch)
+    }
+}
+
+/// Tracks which keys went down since the last update, since [`Window`] only says which are held.
+///
+/// [`Window`]: ./trait.Window.html
+#[derive(Default)]
+struct KeyPresses {
+    down: Vec<Key>,
+    pressed: Vec<Key>,
+}
+
+impl KeyPresses {
+    fn update<W: Window>(&mut self, window: &W) {
+        let down: Vec<Key> = [Key::Left, Key::Right, Key::Space]
+            .iter()
+            .copied()
+            .filter(|&k| window.is_key_down(k))
+            .collect();
+        self.pressed = down
+            .iter()
+            .copied()
+            .filter(|k| !self.down.contains(k))
+            .collect();
+        self.down = down;
+    }
+
+    fn pressed(&self, key: Key) -> bool {
+        self.pressed.contains(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppm::PpmBuilder;
+
+    /// Remembers the last image it was given.
+    #[derive(Default)]
+    struct Recorder {
+        updates: usize,
+        pixels: Vec<u32>,
+    }
+
+    impl Window for Recorder {
+        fn update(&mut self, pixels: &[u32], _width: usize, _height: usize) {
+            self.updates += 1;
+            self.pixels = pixels.to_vec();
+        }
+        fn is_open(&self) -> bool {
+            true
+        }
+    }
+
+    /// Holds down the given keys.
+    struct Keys(Vec<Key>);
+
+    impl Window for Keys {
+        fn update(&mut self, _pixels: &[u32], _width: usize, _height: usize) {}
+        fn is_open(&self) -> bool {
+            true
+        }
+        fn is_key_down(&self, key: Key) -> bool {
+            self.0.contains(&key)
+        }
+    }
+
+    #[test]
+    fn test_orbit_camera() {
+        let mut camera = OrbitCamera::new(Point::new(0., 0., 0.), 100.);
+        assert!(!camera.handle_input(&Keys(vec![])));
+
+        // orbiting right moves the camera towards +x, still looking at the target
+        assert!(camera.handle_input(&Keys(vec![Key::Right])));
+        assert!(camera.eye().x > 0.);
+        let center = camera.project(camera.target, 500, 500).unwrap();
+        assert!((center.x - 250.).abs() < 1e-3 && (center.y - 250.).abs() < 1e-3);
+
+        camera.handle_input(&Keys(vec![Key::W]));
+        assert!(camera.target.y > 0.);
+
+        camera.yaw = 0.;
+        camera.handle_input(&Keys(vec![Key::Q]));
+        assert!(camera.distance < 100.);
+        camera.pitch = 88.;
+        camera.handle_input(&Keys(vec![Key::Up]));
+        assert_eq!(89., camera.pitch);
+    }
+
+    /// Closes after as many updates as it has key presses, holding down the keys for each update.
+    struct Script {
+        updates: usize,
+        keys: Vec<Vec<Key>>,
+    }
+
+    impl Window for Script {
+        fn update(&mut self, _pixels: &[u32], _width: usize, _height: usize) {
+            self.updates += 1;
+        }
+        fn is_open(&self) -> bool {
+            self.updates < self.keys.len()
+        }
+        fn is_key_down(&self, key: Key) -> bool {
+            self.keys[self.updates - 1].contains(&key)
+        }
+    }
+
+    #[test]
+    fn test_play() {
+        use Key::*;
+        let keys = vec![vec![], vec![Space], vec![Space], vec![], vec![Left], vec![]];
+        let window = Script { updates: 0, keys };
+        let mut screen = WindowScreen::new(PpmBuilder::new(2, 2, 255).build(), window);
+        screen.window_mut().updates = 0;
+
+        let mut rendered = Vec::new();
+        screen.play(5, 1000., Rgb::BLACK, |_, frame| rendered.push(frame));
+        // paused on frame 1 with space, then stepped back
+        assert_eq!(vec![0, 1, 0], rendered);
+    }
+
+    #[test]
+    fn test_window_screen() {
+        let img = PpmBuilder::new(2, 3, 255).build();
+        let mut screen =
+            WindowScreen::new(img, Recorder::default()).update_interval(Duration::from_secs(0));
+        assert_eq!(1, screen.window().updates);
+
+        // y goes up, so (2, 0) is the end of the last row
+        screen.plot(2, 0, 0., Rgb::new(255, 128, 0));
+        assert_eq!(2, screen.window().updates);
+        assert_eq!(0x00ff8000, screen.window().pixels[5]);
+    }
+}